@@ -4,13 +4,16 @@
 //! `Arc<Cache>`. Lookups mutate LRU recency state, so both operations
 //! require mutable access.
 
+use std::hash::Hash;
+use std::mem::size_of;
 use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use bitcoin::Txid;
 use lru::LruCache;
 use tokio::sync::Mutex;
 
-use crate::types::{TxNode, TxOutput};
+use crate::types::{TxInput, TxNode, TxOutput};
 
 // ==============================================================================
 // Default Capacity
@@ -22,6 +25,127 @@ const DEFAULT_TX_CAPACITY: usize = 20_000;
 /// Default maximum number of cached prevout entries.
 const DEFAULT_PREVOUT_CAPACITY: usize = 100_000;
 
+// ==============================================================================
+// Cache Weight
+// ==============================================================================
+
+/// Estimated heap footprint of a cached value, used by [`Cache::with_byte_budget`]
+/// to bound memory use directly instead of guessing at an entry count. Values
+/// like `TxNode` vary enormously in size (a tx with thousands of inputs dwarfs
+/// a 1-in/1-out tx), so a count-based limit gives wildly unpredictable memory
+/// use; this lets eviction track actual bytes instead.
+trait CacheWeight {
+    /// Estimated bytes this value occupies, including its own stack size and
+    /// any heap allocations it owns. Doesn't need to be exact — just close
+    /// enough that `max_bytes` roughly bounds real memory use.
+    fn cache_weight(&self) -> usize;
+}
+
+impl CacheWeight for TxNode {
+    fn cache_weight(&self) -> usize {
+        size_of::<TxNode>()
+            + self.inputs.len() * size_of::<TxInput>()
+            + self
+                .outputs
+                .iter()
+                .map(|o| size_of::<TxOutput>() + o.script_pub_key.len())
+                .sum::<usize>()
+    }
+}
+
+impl CacheWeight for TxOutput {
+    fn cache_weight(&self) -> usize {
+        size_of::<TxOutput>() + self.script_pub_key.len()
+    }
+}
+
+// ==============================================================================
+// Bounded Cache
+// ==============================================================================
+
+/// Either count-bounded (plain LRU eviction on entry count) or byte-bounded
+/// (entries evicted least-recently-used-first until estimated memory usage
+/// is back under `max_bytes`), selected via [`Cache::with_capacity`] vs.
+/// [`Cache::with_byte_budget`].
+enum BoundedCache<K: Hash + Eq, V> {
+    Entries(LruCache<K, V>),
+    Bytes {
+        entries: LruCache<K, V>,
+        max_bytes: usize,
+        bytes_used: usize,
+    },
+}
+
+impl<K: Hash + Eq + Clone + PartialEq, V: CacheWeight> BoundedCache<K, V> {
+    fn entries(capacity: NonZeroUsize) -> Self {
+        Self::Entries(LruCache::new(capacity))
+    }
+
+    fn bytes(max_bytes: usize) -> Self {
+        Self::Bytes {
+            entries: LruCache::unbounded(),
+            max_bytes,
+            bytes_used: 0,
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        match self {
+            Self::Entries(entries) => entries.get(key),
+            Self::Bytes { entries, .. } => entries.get(key),
+        }
+    }
+
+    /// Insert `key`/`value`, evicting least-recently-used entries first.
+    /// Returns the number of *other* entries evicted to make room (a
+    /// replaced value for the same key doesn't count as an eviction).
+    fn put(&mut self, key: K, value: V) -> usize {
+        match self {
+            Self::Entries(entries) => match entries.push(key.clone(), value) {
+                Some((evicted_key, _)) if evicted_key != key => 1,
+                _ => 0,
+            },
+            Self::Bytes {
+                entries,
+                max_bytes,
+                bytes_used,
+            } => {
+                let weight = value.cache_weight();
+                let mut evicted = 0;
+                if let Some((evicted_key, old)) = entries.push(key.clone(), value) {
+                    *bytes_used = bytes_used.saturating_sub(old.cache_weight());
+                    if evicted_key != key {
+                        evicted += 1;
+                    }
+                }
+                *bytes_used += weight;
+                while *bytes_used > *max_bytes {
+                    let Some((_, evicted_value)) = entries.pop_lru() else {
+                        break;
+                    };
+                    *bytes_used = bytes_used.saturating_sub(evicted_value.cache_weight());
+                    evicted += 1;
+                }
+                evicted
+            }
+        }
+    }
+}
+
+// ==============================================================================
+// Cache Stats
+// ==============================================================================
+
+/// Hit/miss/eviction counts for one of [`Cache`]'s two underlying caches,
+/// so operators can size `max_bytes`/capacity against real memory use
+/// rather than guessing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
 // ==============================================================================
 // Cache
 // ==============================================================================
@@ -33,8 +157,14 @@ const DEFAULT_PREVOUT_CAPACITY: usize = 100_000;
 /// Mutex and not RwLock is needed since LRU reads update recency tracking.
 /// Entries are evicted in least-recently-used order when the cache is full.
 pub struct Cache {
-    transactions: Mutex<LruCache<Txid, TxNode>>,
-    prevouts: Mutex<LruCache<(Txid, u32), TxOutput>>,
+    transactions: Mutex<BoundedCache<Txid, TxNode>>,
+    prevouts: Mutex<BoundedCache<(Txid, u32), TxOutput>>,
+    tx_hits: AtomicU64,
+    tx_misses: AtomicU64,
+    tx_evictions: AtomicU64,
+    prevout_hits: AtomicU64,
+    prevout_misses: AtomicU64,
+    prevout_evictions: AtomicU64,
 }
 
 impl Cache {
@@ -43,15 +173,40 @@ impl Cache {
         Self::with_capacity(DEFAULT_TX_CAPACITY, DEFAULT_PREVOUT_CAPACITY)
     }
 
-    /// Create a cache with explicit capacities. Both values must be > 0.
+    /// Create a cache with explicit entry-count capacities. Both values must be > 0.
     pub fn with_capacity(tx_cap: usize, prevout_cap: usize) -> Self {
-        Self {
-            transactions: Mutex::new(LruCache::new(
-                NonZeroUsize::new(tx_cap).expect("tx capacity must be > 0"),
-            )),
-            prevouts: Mutex::new(LruCache::new(
+        Self::new_with(
+            BoundedCache::entries(NonZeroUsize::new(tx_cap).expect("tx capacity must be > 0")),
+            BoundedCache::entries(
                 NonZeroUsize::new(prevout_cap).expect("prevout capacity must be > 0"),
-            )),
+            ),
+        )
+    }
+
+    /// Create a cache bounded by estimated memory use instead of entry
+    /// count: entries are evicted least-recently-used-first until usage is
+    /// back under `tx_bytes`/`prevout_bytes`. See [`CacheWeight`] for how a
+    /// value's footprint is estimated.
+    pub fn with_byte_budget(tx_bytes: usize, prevout_bytes: usize) -> Self {
+        Self::new_with(
+            BoundedCache::bytes(tx_bytes),
+            BoundedCache::bytes(prevout_bytes),
+        )
+    }
+
+    fn new_with(
+        transactions: BoundedCache<Txid, TxNode>,
+        prevouts: BoundedCache<(Txid, u32), TxOutput>,
+    ) -> Self {
+        Self {
+            transactions: Mutex::new(transactions),
+            prevouts: Mutex::new(prevouts),
+            tx_hits: AtomicU64::new(0),
+            tx_misses: AtomicU64::new(0),
+            tx_evictions: AtomicU64::new(0),
+            prevout_hits: AtomicU64::new(0),
+            prevout_misses: AtomicU64::new(0),
+            prevout_evictions: AtomicU64::new(0),
         }
     }
 
@@ -59,24 +214,58 @@ impl Cache {
     ///
     /// Takes a mutex lock because LRU `get` updates recency tracking.
     pub async fn get_tx(&self, txid: &Txid) -> Option<TxNode> {
-        self.transactions.lock().await.get(txid).cloned()
+        let hit = self.transactions.lock().await.get(txid).cloned();
+        if hit.is_some() {
+            self.tx_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.tx_misses.fetch_add(1, Ordering::Relaxed);
+        }
+        hit
     }
 
     /// Insert a decoded transaction into the cache.
     pub async fn insert_tx(&self, txid: Txid, node: TxNode) {
-        self.transactions.lock().await.put(txid, node);
+        let evicted = self.transactions.lock().await.put(txid, node);
+        self.tx_evictions
+            .fetch_add(evicted as u64, Ordering::Relaxed);
     }
 
     /// Look up cached prevout info for a specific outpoint.
     ///
     /// Takes a mutex lock because LRU `get` updates recency tracking.
     pub async fn get_prevout(&self, txid: &Txid, vout: u32) -> Option<TxOutput> {
-        self.prevouts.lock().await.get(&(*txid, vout)).cloned()
+        let hit = self.prevouts.lock().await.get(&(*txid, vout)).cloned();
+        if hit.is_some() {
+            self.prevout_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.prevout_misses.fetch_add(1, Ordering::Relaxed);
+        }
+        hit
     }
 
     /// Cache resolved prevout data for a specific outpoint.
     pub async fn insert_prevout(&self, txid: Txid, vout: u32, info: TxOutput) {
-        self.prevouts.lock().await.put((txid, vout), info);
+        let evicted = self.prevouts.lock().await.put((txid, vout), info);
+        self.prevout_evictions
+            .fetch_add(evicted as u64, Ordering::Relaxed);
+    }
+
+    /// Hit/miss/eviction counters for the transaction cache.
+    pub fn tx_stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.tx_hits.load(Ordering::Relaxed),
+            misses: self.tx_misses.load(Ordering::Relaxed),
+            evictions: self.tx_evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Hit/miss/eviction counters for the prevout cache.
+    pub fn prevout_stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.prevout_hits.load(Ordering::Relaxed),
+            misses: self.prevout_misses.load(Ordering::Relaxed),
+            evictions: self.prevout_evictions.load(Ordering::Relaxed),
+        }
     }
 }
 
@@ -145,4 +334,41 @@ mod tests {
         // Different vout should miss.
         assert!(cache.get_prevout(&txid, 1).await.is_none());
     }
+
+    #[tokio::test]
+    async fn byte_budget_evicts_lru_entry_once_over_budget() {
+        let node = make_tx_node(vec![], vec![make_output(1000)], 100);
+        let one_entry_budget = node.cache_weight() + size_of::<TxNode>();
+        let cache = Cache::with_byte_budget(one_entry_budget, one_entry_budget);
+
+        let txid_a = txid_from_byte(1);
+        let txid_b = txid_from_byte(2);
+        cache.insert_tx(txid_a, node.clone()).await;
+        cache.insert_tx(txid_b, node.clone()).await;
+
+        assert!(
+            cache.get_tx(&txid_a).await.is_none(),
+            "oldest should be evicted once the byte budget is exceeded"
+        );
+        assert!(cache.get_tx(&txid_b).await.is_some());
+        assert_eq!(cache.tx_stats().evictions, 1);
+    }
+
+    #[tokio::test]
+    async fn stats_track_hits_misses_and_evictions() {
+        let cache = Cache::with_capacity(1, 1);
+        let txid_a = txid_from_byte(1);
+        let txid_b = txid_from_byte(2);
+        let node = make_tx_node(vec![], vec![make_output(1000)], 100);
+
+        cache.insert_tx(txid_a, node.clone()).await;
+        let _ = cache.get_tx(&txid_a).await;
+        let _ = cache.get_tx(&txid_b).await;
+        cache.insert_tx(txid_b, node).await;
+
+        let stats = cache.tx_stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.evictions, 1);
+    }
 }
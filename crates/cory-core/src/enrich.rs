@@ -3,10 +3,24 @@
 //! Provides script classification, fee/feerate computation, RBF signaling
 //! detection, and locktime interpretation.
 
-use bitcoin::{Amount, Script};
+use bitcoin::psbt::{Input as PsbtInput, Psbt};
+use bitcoin::script::Instruction;
+use bitcoin::{Amount, Network, Script, ScriptBuf, TxOut};
 use serde::{Deserialize, Serialize};
 
-use crate::types::{ScriptType, TxNode};
+use crate::error::CoreError;
+use crate::types::{ScriptType, TxInput, TxNode, TxOutput};
+
+/// Derive the address a script would resolve to on `network`, or `None` for
+/// scripts with no derivable address (e.g. `OP_RETURN`).
+fn address_from_script(
+    script: &ScriptBuf,
+    network: Network,
+) -> Option<bitcoin::Address<bitcoin::address::NetworkUnchecked>> {
+    bitcoin::Address::from_script(script.as_script(), network)
+        .ok()
+        .map(|addr| addr.as_unchecked().clone())
+}
 
 // ==============================================================================
 // Script Classification
@@ -38,6 +52,168 @@ pub fn classify_script(script: &Script) -> ScriptType {
     }
 }
 
+/// Render a script as a human-readable ASM string: opcode names for
+/// non-push ops (e.g. `OP_DUP`), hex for pushdata. Malformed data that
+/// can't be decoded into instructions is rendered as `[error]`.
+#[must_use]
+pub fn disassemble_script(script: &Script) -> String {
+    script
+        .instructions()
+        .map(|instruction| match instruction {
+            Ok(Instruction::Op(op)) => op.to_string(),
+            Ok(Instruction::PushBytes(bytes)) => hex_encode(bytes.as_bytes()),
+            Err(_) => "[error]".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Extract the `m`-of-`n` required-signature counts from a bare multisig
+/// scriptPubKey: `OP_m <pubkey> ... <pubkey> OP_n OP_CHECKMULTISIG`.
+///
+/// Returns `None` unless the script is in exactly this shape: the leading
+/// and trailing opcodes must both be small integers (`OP_1..OP_16`), the
+/// trailing count must match the number of pushed keys, and `n` must not
+/// exceed 16.
+#[must_use]
+pub fn multisig_params(script: &Script) -> Option<(u8, u8)> {
+    let instructions: Vec<Instruction<'_>> =
+        script.instructions().collect::<Result<_, _>>().ok()?;
+    let [first, middle @ .., second_last, last] = instructions.as_slice() else {
+        return None;
+    };
+
+    if !matches!(last, Instruction::Op(op) if *op == bitcoin::opcodes::all::OP_CHECKMULTISIG) {
+        return None;
+    }
+
+    let m = small_int_value(first)?;
+    let n = small_int_value(second_last)?;
+
+    if n > 16 || middle.len() != n as usize {
+        return None;
+    }
+    if !middle
+        .iter()
+        .all(|i| matches!(i, Instruction::PushBytes(_)))
+    {
+        return None;
+    }
+
+    Some((m, n))
+}
+
+/// Interpret a small-integer push opcode (`OP_1..OP_16`) as `1..16`.
+fn small_int_value(instruction: &Instruction<'_>) -> Option<u8> {
+    let Instruction::Op(op) = instruction else {
+        return None;
+    };
+    let byte = op.to_u8();
+    // OP_1..OP_16 occupy the contiguous range 0x51..=0x60.
+    if (0x51..=0x60).contains(&byte) {
+        Some(byte - 0x50)
+    } else {
+        None
+    }
+}
+
+// ==============================================================================
+// PSBT Ingestion
+// ==============================================================================
+
+/// Build a [`TxNode`] directly from an unsigned BIP-174 PSBT, without any
+/// RPC access.
+///
+/// Each input's value and script type are resolved from the PSBT's
+/// `witness_utxo`/`non_witness_utxo` fields when present, matching the
+/// coinbase case in [`compute_fee`] by leaving `value: None` when neither
+/// is available. The resulting node is always unconfirmed (`block_hash`,
+/// `block_height`, and `block_time` are all `None`), so it can still be fed
+/// straight into `compute_fee`, `is_rbf_signaling`, and `classify_script`.
+///
+/// `network` is used to derive each input/output's [`TxInput::address`]/
+/// [`TxOutput::address`] from its `scriptPubKey`.
+pub fn tx_node_from_psbt(psbt: &Psbt, network: Network) -> Result<TxNode, CoreError> {
+    let unsigned = &psbt.unsigned_tx;
+    if unsigned.input.len() != psbt.inputs.len() {
+        return Err(CoreError::InvalidTxData(
+            "psbt inputs length does not match unsigned_tx.input length".into(),
+        ));
+    }
+
+    let inputs = unsigned
+        .input
+        .iter()
+        .zip(&psbt.inputs)
+        .map(|(txin, psbt_input)| {
+            let prevout = (!txin.previous_output.is_null()).then_some(txin.previous_output);
+            let (value, script_type, address) = resolve_psbt_input_utxo(
+                psbt_input,
+                txin.previous_output.vout,
+            )
+            .map_or((None, None, None), |utxo| {
+                (
+                    Some(utxo.value),
+                    Some(classify_script(utxo.script_pubkey.as_script())),
+                    address_from_script(&utxo.script_pubkey, network),
+                )
+            });
+
+            TxInput {
+                prevout,
+                sequence: txin.sequence.to_consensus_u32(),
+                value,
+                script_type,
+                address,
+                unresolved_reason: None,
+            }
+        })
+        .collect();
+
+    let outputs = unsigned
+        .output
+        .iter()
+        .map(|txout| TxOutput {
+            value: txout.value,
+            script_pub_key: txout.script_pubkey.clone(),
+            script_type: classify_script(txout.script_pubkey.as_script()),
+            address: address_from_script(&txout.script_pubkey, network),
+        })
+        .collect();
+
+    Ok(TxNode {
+        txid: unsigned.txid(),
+        version: unsigned.version.0,
+        locktime: unsigned.lock_time.to_consensus_u32(),
+        size: unsigned.total_size() as u64,
+        vsize: unsigned.vsize() as u64,
+        weight: unsigned.weight().to_wu(),
+        block_hash: None,
+        block_height: None,
+        block_time: None,
+        inputs,
+        outputs,
+    })
+}
+
+/// Resolve an input's funding [`TxOut`] from whichever of `witness_utxo` or
+/// `non_witness_utxo` the PSBT input carries, preferring the direct
+/// `witness_utxo` when both are present.
+fn resolve_psbt_input_utxo(input: &PsbtInput, vout: u32) -> Option<TxOut> {
+    if let Some(utxo) = &input.witness_utxo {
+        return Some(utxo.clone());
+    }
+    input
+        .non_witness_utxo
+        .as_ref()
+        .and_then(|tx| tx.output.get(vout as usize))
+        .cloned()
+}
+
 // ==============================================================================
 // Fee and Feerate
 // ==============================================================================
@@ -130,6 +306,42 @@ pub fn locktime_info(locktime: u32, has_non_final_sequence: bool) -> LocktimeInf
     }
 }
 
+/// A decoded BIP-68 relative locktime, enforced relative to the height or
+/// time at which the spent output was confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelativeLocktime {
+    /// Minimum number of confirmations the spent output must have.
+    Blocks(u16),
+    /// Minimum elapsed time, in seconds, since the spent output confirmed.
+    Seconds(u32),
+}
+
+/// Decode an input's `nSequence` field under BIP-68.
+///
+/// Returns `None` if bit 31 (`1 << 31`), the disable flag, is set — the
+/// disable flag takes precedence over everything else, including a
+/// nonzero value in the low bits. Otherwise, the low 16 bits are the
+/// value and bit 22 (`1 << 22`) selects the unit: blocks, or 512-second
+/// intervals when the type flag is set.
+#[must_use]
+pub fn relative_locktime(sequence: u32) -> Option<RelativeLocktime> {
+    const DISABLE_FLAG: u32 = 1 << 31;
+    const TYPE_FLAG: u32 = 1 << 22;
+    const VALUE_MASK: u32 = 0xFFFF;
+
+    if sequence & DISABLE_FLAG != 0 {
+        return None;
+    }
+
+    let value = sequence & VALUE_MASK;
+    if sequence & TYPE_FLAG != 0 {
+        Some(RelativeLocktime::Seconds(value * 512))
+    } else {
+        Some(RelativeLocktime::Blocks(value as u16))
+    }
+}
+
 /// Derive a display identifier (address or data) for a script.
 ///
 /// For standard scripts, this returns the Bitcoin address string.
@@ -174,9 +386,91 @@ pub fn derive_display_id(script: &bitcoin::Script, network: bitcoin::Network) ->
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_util::{make_input, make_output, make_tx_node};
+    use crate::test_util::{make_input, make_output, make_tx_node, txid_from_byte};
     use crate::types::TxInput;
 
+    // -- tx_node_from_psbt tests -----------------------------------------------
+
+    fn p2wpkh_script() -> bitcoin::ScriptBuf {
+        bitcoin::ScriptBuf::from_bytes(vec![
+            0x00, 0x14, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
+            0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14,
+        ])
+    }
+
+    fn sample_unsigned_tx() -> bitcoin::Transaction {
+        bitcoin::Transaction {
+            version: bitcoin::transaction::Version(2),
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![bitcoin::TxIn {
+                previous_output: bitcoin::OutPoint::new(txid_from_byte(1), 0),
+                script_sig: bitcoin::ScriptBuf::new(),
+                sequence: bitcoin::Sequence::MAX,
+                witness: bitcoin::Witness::new(),
+            }],
+            output: vec![bitcoin::TxOut {
+                value: Amount::from_sat(3000),
+                script_pubkey: p2wpkh_script(),
+            }],
+        }
+    }
+
+    #[test]
+    fn tx_node_from_psbt_resolves_witness_utxo() {
+        let tx = sample_unsigned_tx();
+        let mut psbt = Psbt::from_unsigned_tx(tx.clone()).expect("valid unsigned tx");
+        psbt.inputs[0].witness_utxo = Some(bitcoin::TxOut {
+            value: Amount::from_sat(5000),
+            script_pubkey: p2wpkh_script(),
+        });
+
+        let node = tx_node_from_psbt(&psbt, Network::Bitcoin).expect("must build node");
+        assert_eq!(node.txid, tx.txid());
+        assert!(node.block_hash.is_none());
+        assert!(node.block_height.is_none());
+        assert_eq!(node.inputs.len(), 1);
+        assert_eq!(node.inputs[0].value, Some(Amount::from_sat(5000)));
+        assert_eq!(node.inputs[0].script_type, Some(ScriptType::P2wpkh));
+        assert_eq!(node.outputs.len(), 1);
+        assert_eq!(node.outputs[0].value, Amount::from_sat(3000));
+
+        let fee = compute_fee(&node).expect("fee must be computable");
+        assert_eq!(fee, Amount::from_sat(2000));
+    }
+
+    #[test]
+    fn tx_node_from_psbt_leaves_value_none_without_utxo_info() {
+        let tx = sample_unsigned_tx();
+        let psbt = Psbt::from_unsigned_tx(tx).expect("valid unsigned tx");
+
+        let node = tx_node_from_psbt(&psbt, Network::Bitcoin).expect("must build node");
+        assert!(node.inputs[0].value.is_none());
+        assert!(node.inputs[0].script_type.is_none());
+        assert!(compute_fee(&node).is_none());
+    }
+
+    #[test]
+    fn tx_node_from_psbt_resolves_non_witness_utxo() {
+        let funding_tx = bitcoin::Transaction {
+            version: bitcoin::transaction::Version(2),
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![bitcoin::TxOut {
+                value: Amount::from_sat(7000),
+                script_pubkey: p2wpkh_script(),
+            }],
+        };
+        let mut tx = sample_unsigned_tx();
+        tx.input[0].previous_output = bitcoin::OutPoint::new(funding_tx.txid(), 0);
+
+        let mut psbt = Psbt::from_unsigned_tx(tx).expect("valid unsigned tx");
+        psbt.inputs[0].non_witness_utxo = Some(funding_tx);
+
+        let node = tx_node_from_psbt(&psbt, Network::Bitcoin).expect("must build node");
+        assert_eq!(node.inputs[0].value, Some(Amount::from_sat(7000)));
+        assert_eq!(node.inputs[0].script_type, Some(ScriptType::P2wpkh));
+    }
+
     // -- compute_fee tests ----------------------------------------------------
 
     #[test]
@@ -225,6 +519,8 @@ mod tests {
                 sequence: 0xFFFFFFFF,
                 value: None,
                 script_type: None,
+                address: None,
+                unresolved_reason: None,
             }],
             vec![make_output(50_0000_0000)],
             140,
@@ -309,6 +605,125 @@ mod tests {
         assert!(!info.active);
     }
 
+    // -- TxNode::is_final / TxNode::locktime_info tests -----------------------
+
+    #[test]
+    fn tx_node_is_final_when_all_sequences_max() {
+        let tx = make_tx_node(
+            vec![
+                make_input(Some(5000), 0xFFFFFFFF),
+                make_input(Some(3000), 0xFFFFFFFF),
+            ],
+            vec![make_output(3000)],
+            140,
+        );
+        assert!(tx.is_final());
+    }
+
+    #[test]
+    fn tx_node_not_final_when_one_sequence_is_not_max() {
+        let tx = make_tx_node(
+            vec![
+                make_input(Some(5000), 0xFFFFFFFF),
+                make_input(Some(3000), 0xFFFFFFFE),
+            ],
+            vec![make_output(3000)],
+            140,
+        );
+        assert!(!tx.is_final());
+    }
+
+    #[test]
+    fn tx_node_locktime_info_inactive_when_final() {
+        let mut tx = make_tx_node(
+            vec![make_input(Some(5000), 0xFFFFFFFF)],
+            vec![make_output(3000)],
+            140,
+        );
+        tx.locktime = 800_000;
+        assert!(!tx.locktime_info().active);
+    }
+
+    #[test]
+    fn tx_node_locktime_info_active_when_not_final() {
+        let mut tx = make_tx_node(
+            vec![make_input(Some(5000), 0xFFFFFFFE)],
+            vec![make_output(3000)],
+            140,
+        );
+        tx.locktime = 800_000;
+        let info = tx.locktime_info();
+        assert_eq!(info.kind, LocktimeKind::BlockHeight);
+        assert!(info.active);
+    }
+
+    // -- relative_locktime tests ------------------------------------------------
+
+    #[test]
+    fn relative_locktime_disable_flag_takes_precedence() {
+        // Disable flag set, plus a nonzero value and the type flag — still None.
+        let sequence = (1 << 31) | (1 << 22) | 5;
+        assert_eq!(relative_locktime(sequence), None);
+    }
+
+    #[test]
+    fn relative_locktime_blocks() {
+        assert_eq!(relative_locktime(10), Some(RelativeLocktime::Blocks(10)));
+    }
+
+    #[test]
+    fn relative_locktime_seconds() {
+        let sequence = (1 << 22) | 5;
+        assert_eq!(
+            relative_locktime(sequence),
+            Some(RelativeLocktime::Seconds(5 * 512))
+        );
+    }
+
+    #[test]
+    fn relative_locktime_masks_high_bits_before_type_check() {
+        // Garbage above the 16-bit value field must not leak into the value,
+        // and must not be mistaken for the disable/type flags.
+        let sequence = 0x00BF_0010; // value = 0x0010, type flag (bit 22) unset
+        assert_eq!(
+            relative_locktime(sequence),
+            Some(RelativeLocktime::Blocks(0x0010))
+        );
+    }
+
+    #[test]
+    fn relative_locktime_max_block_value() {
+        assert_eq!(
+            relative_locktime(0xFFFF),
+            Some(RelativeLocktime::Blocks(0xFFFF))
+        );
+    }
+
+    #[test]
+    fn tx_input_relative_locktime_delegates_to_free_function() {
+        let input = make_input(Some(1000), 10);
+        assert_eq!(
+            input.relative_locktime(),
+            Some(RelativeLocktime::Blocks(10))
+        );
+
+        let final_input = make_input(Some(1000), bitcoin::Sequence::MAX.0);
+        assert_eq!(final_input.relative_locktime(), None);
+    }
+
+    #[test]
+    fn tx_node_reports_relative_locktime_inputs() {
+        let csv_input = make_input(Some(1000), 10);
+        let final_input = make_input(Some(2000), bitcoin::Sequence::MAX.0);
+        let node = make_tx_node(vec![final_input, csv_input], vec![make_output(1000)], 200);
+
+        assert!(node.has_relative_locktime());
+        assert_eq!(
+            node.relative_locktime_inputs(),
+            vec![(1, RelativeLocktime::Blocks(10))]
+        );
+    }
+
     // -- classify_script tests ------------------------------------------------
 
     #[test]
@@ -382,4 +797,71 @@ mod tests {
         let script = bitcoin::ScriptBuf::new();
         assert_eq!(classify_script(script.as_script()), ScriptType::Unknown);
     }
+
+    // -- disassemble_script tests ----------------------------------------------
+
+    #[test]
+    fn disassemble_script_p2pkh() {
+        // OP_DUP OP_HASH160 PUSH20 <hash> OP_EQUALVERIFY OP_CHECKSIG
+        let mut bytes = vec![0x76, 0xa9, 0x14];
+        bytes.extend_from_slice(&[0x01; 20]);
+        bytes.push(0x88);
+        bytes.push(0xac);
+        let script = bitcoin::ScriptBuf::from_bytes(bytes);
+
+        let asm = disassemble_script(script.as_script());
+        assert_eq!(
+            asm,
+            "OP_DUP OP_HASH160 0101010101010101010101010101010101010101 OP_EQUALVERIFY OP_CHECKSIG"
+        );
+    }
+
+    #[test]
+    fn disassemble_script_empty() {
+        let script = bitcoin::ScriptBuf::new();
+        assert_eq!(disassemble_script(script.as_script()), "");
+    }
+
+    // -- multisig_params tests --------------------------------------------------
+
+    fn bare_multisig_script(m: u8, pubkeys: &[[u8; 33]], n: u8) -> bitcoin::ScriptBuf {
+        let mut bytes = vec![0x50 + m];
+        for pubkey in pubkeys {
+            bytes.push(33);
+            bytes.extend_from_slice(pubkey);
+        }
+        bytes.push(0x50 + n);
+        bytes.push(0xae); // OP_CHECKMULTISIG
+        bitcoin::ScriptBuf::from_bytes(bytes)
+    }
+
+    #[test]
+    fn multisig_params_extracts_m_of_n() {
+        let script = bare_multisig_script(2, &[[0x02; 33], [0x03; 33], [0x04; 33]], 3);
+        assert_eq!(multisig_params(script.as_script()), Some((2, 3)));
+    }
+
+    #[test]
+    fn multisig_params_rejects_mismatched_trailing_count() {
+        // Claims n=3 but only pushes 2 keys.
+        let script = bare_multisig_script(1, &[[0x02; 33], [0x03; 33]], 3);
+        assert_eq!(multisig_params(script.as_script()), None);
+    }
+
+    #[test]
+    fn multisig_params_rejects_non_multisig_script() {
+        let script = bitcoin::ScriptBuf::from_bytes(vec![0x6a, 0x04, 0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(multisig_params(script.as_script()), None);
+    }
+
+    #[test]
+    fn multisig_params_rejects_n_above_16() {
+        // Trailing opcode byte 0x61 is outside the OP_1..OP_16 range.
+        let mut bytes = vec![0x51, 33];
+        bytes.extend_from_slice(&[0x02; 33]);
+        bytes.push(0x61);
+        bytes.push(0xae);
+        let script = bitcoin::ScriptBuf::from_bytes(bytes);
+        assert_eq!(multisig_params(script.as_script()), None);
+    }
 }
@@ -6,6 +6,85 @@ use bitcoin::Txid;
 // RPC Errors
 // ==============================================================================
 
+/// Well-known Bitcoin Core JSON-RPC error codes.
+///
+/// Classifying the integer `code` into this enum lets callers branch on
+/// "tx not found" vs. "node still warming up" vs. "auth failed"
+/// deterministically, instead of matching on `message` substrings that
+/// vary across Core versions and locales. `Other` passes through any code
+/// without a dedicated variant so classification is total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitcoinRpcErrorCode {
+    /// RPC_INVALID_ADDRESS_OR_KEY (-5). Also the code `getrawtransaction`
+    /// and `gettxout` return for "transaction not found".
+    InvalidAddressOrKey,
+    /// RPC_INVALID_PARAMETER (-8).
+    InvalidParameter,
+    /// RPC_IN_WARMUP (-28): node is still loading/verifying blocks.
+    InWarmup,
+    /// RPC_METHOD_NOT_FOUND (-32601).
+    MethodNotFound,
+    /// RPC_INVALID_REQUEST (-32600).
+    InvalidRequest,
+    /// Any code without a dedicated variant above.
+    Other(i64),
+}
+
+impl BitcoinRpcErrorCode {
+    /// Classify a raw JSON-RPC error code.
+    pub fn from_code(code: i64) -> Self {
+        match code {
+            -5 => Self::InvalidAddressOrKey,
+            -8 => Self::InvalidParameter,
+            -28 => Self::InWarmup,
+            -32601 => Self::MethodNotFound,
+            -32600 => Self::InvalidRequest,
+            other => Self::Other(other),
+        }
+    }
+
+    /// The raw integer code this variant was classified from.
+    pub fn code(&self) -> i64 {
+        match self {
+            Self::InvalidAddressOrKey => -5,
+            Self::InvalidParameter => -8,
+            Self::InWarmup => -28,
+            Self::MethodNotFound => -32601,
+            Self::InvalidRequest => -32600,
+            Self::Other(code) => *code,
+        }
+    }
+}
+
+impl std::fmt::Display for BitcoinRpcErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// Which phase of an HTTP round-trip a [`RpcError::Timeout`] expired
+/// during, so callers can tell "the node (or a network hop to it) never
+/// answered the connection" apart from "it accepted the connection but
+/// took too long to respond" — the former usually means a wrong address
+/// or a firewall, the latter a slow or overloaded node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutPhase {
+    /// The TCP/TLS handshake itself didn't complete in time.
+    Connect,
+    /// The connection was established but no complete response body
+    /// arrived in time.
+    Response,
+}
+
+impl std::fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connect => write!(f, "connect"),
+            Self::Response => write!(f, "response"),
+        }
+    }
+}
+
 /// Structured errors from the Bitcoin Core JSON-RPC layer.
 ///
 /// Each variant captures a specific failure mode rather than collapsing
@@ -17,13 +96,91 @@ pub enum RpcError {
     Transport(#[source] reqwest::Error),
 
     #[error("JSON-RPC error: code={code}, message={message}")]
-    ServerError { code: i64, message: String },
+    ServerError {
+        code: BitcoinRpcErrorCode,
+        message: String,
+    },
 
     #[error("invalid JSON-RPC response: {0}")]
     InvalidResponse(String),
 
     #[error("batch response missing item id={id}")]
     MissingBatchItem { id: u64 },
+
+    /// Two items in a single batch response carried the same id, which a
+    /// well-behaved server never produces since every request in the
+    /// batch was assigned a distinct id; kept distinct from
+    /// [`Self::MissingBatchItem`] so callers can tell "the server lost an
+    /// item" apart from "the server duplicated one".
+    #[error("batch response had duplicate item id={id}")]
+    DuplicateBatchItem { id: u64 },
+
+    /// A non-2xx, non-auth HTTP response, kept distinct from
+    /// [`Self::ServerError`] (a JSON-RPC application-level error in an
+    /// otherwise-200 response) so callers can tell transport failures apart
+    /// from RPC errors — e.g. to retry 5xx responses.
+    #[error("HTTP error: status={status}")]
+    HttpStatus { status: u16, body: String },
+
+    /// A 401/403 response, kept distinct from [`Self::HttpStatus`] so
+    /// callers can tell "the node rejected our credentials" (worth retrying
+    /// once against freshly re-read auth, since a cookie file rotates on
+    /// every `bitcoind` restart) apart from other HTTP failures.
+    #[error("rpc auth rejected: status={status}")]
+    AuthRejected { status: u16 },
+
+    /// A connect or response timeout elapsed before the request finished.
+    /// Kept distinct from [`Self::Transport`] (which covers every other
+    /// `reqwest::Error`, e.g. DNS failures or connection resets) so
+    /// [`crate::rpc::http_adapter::client::RetryPolicy::is_retryable`] can
+    /// retry timeouts without also retrying unrelated transport failures.
+    #[error("rpc {phase} timed out")]
+    Timeout { phase: TimeoutPhase },
+
+    /// The connected node's `getnetworkinfo` version or feature set didn't
+    /// meet what
+    /// [`crate::rpc::http_adapter::HttpRpcClient::ensure_node_supported`]
+    /// requires, detected by a one-time preflight before the first
+    /// `build_ancestry` call. Kept distinct from [`Self::ServerError`]
+    /// since it's a capability mismatch found locally, not an error the
+    /// node itself reported — the whole point is to fail with a clear
+    /// explanation instead of the confusing "missing transaction" errors
+    /// an incompatible node's RPCs would otherwise eventually produce.
+    #[error("unsupported node: {0}")]
+    UnsupportedNode(String),
+}
+
+// ==============================================================================
+// Amount Parsing Errors
+// ==============================================================================
+
+/// Errors from parsing a denomination-suffixed amount string (e.g.
+/// `"21 bits"`, `"500 mBTC"`) via
+/// [`crate::rpc::http_adapter::parsing::parse_amount_with_denomination`].
+/// Mirrors the shape of rust-bitcoin's `units::ParseDenominationError`/
+/// `ParseAmountError`, kept distinct from [`CoreError::InvalidTxData`] so
+/// callers can tell "bad unit" apart from "bad number" instead of matching
+/// on message text.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg(feature = "alloc")]
+pub enum ParseDenominatedAmountError {
+    /// The trailing unit token wasn't one of the recognized denominations
+    /// (`BTC`, `mBTC`, `uBTC`/`bits`, `sat`/`sats`).
+    #[error("unrecognized amount denomination `{0}`")]
+    UnknownDenomination(String),
+
+    /// The numeric token wasn't a plain, non-negative decimal number.
+    #[error("invalid numeric amount `{0}`")]
+    InvalidNumber(String),
+
+    /// The amount has more fractional digits than its denomination allows,
+    /// i.e. it would require a fractional satoshi — e.g. `"0.001 sat"`.
+    #[error("amount `{0}` is too precise for denomination `{1}`")]
+    TooPrecise(String, String),
+
+    /// The amount is too large to represent as a `u64` satoshi count.
+    #[error("amount `{0}` overflows a satoshi amount")]
+    Overflow(String),
 }
 
 // ==============================================================================
@@ -39,12 +196,85 @@ pub enum CoreError {
     #[error("transaction not found: {0}")]
     TxNotFound(Txid),
 
+    /// `gettxoutproof` refused to build a proof because the node is
+    /// pruned and has discarded the block's data. Kept distinct from
+    /// [`Self::TxNotFound`] so the UI can explain *why* no proof is
+    /// available instead of implying the transaction doesn't exist.
+    #[error("block data unavailable for proof (node is pruned): {0}")]
+    PrunedBlockData(String),
+
     #[error("invalid transaction data: {0}")]
+    #[cfg(feature = "alloc")]
     InvalidTxData(String),
 
+    /// Static-message counterpart to [`Self::InvalidTxData`] for builds
+    /// with the `alloc` feature disabled, where the owned `String` that
+    /// carries per-field detail isn't available. Covers the same failure
+    /// modes, just without the dynamic detail — see
+    /// [`crate::rpc::http_adapter::parsing`] for which parsers keep
+    /// returning errors at all once `alloc` is off; the rest of
+    /// `CoreError` (e.g. [`Self::ObjectStore`], [`Self::LabelParse`]) is
+    /// unaffected by this feature and still requires `alloc` as before.
+    #[error("invalid transaction data: {0}")]
+    #[cfg(not(feature = "alloc"))]
+    InvalidTxDataStatic(&'static str),
+
+    /// `gettxoutproof`'s returned proof doesn't actually cover `txid` —
+    /// the node built a partial merkle tree that matches no transaction
+    /// we asked for. Kept distinct from [`Self::InvalidProof`] so callers
+    /// can tell "wrong proof" apart from "malformed/inconsistent proof".
+    #[error("transaction {0} not included in its merkle proof")]
+    TxNotIncluded(Txid),
+
+    /// A `gettxoutproof` proof's reconstructed merkle root didn't match
+    /// the containing block header's `merkleroot`, fetched independently
+    /// via `getblockheader`. This means the node served a proof and a
+    /// header that disagree with each other, or the proof bytes were
+    /// otherwise unparseable.
+    #[error("invalid merkle proof: {0}")]
+    InvalidProof(String),
+
+    #[error(transparent)]
+    #[cfg(feature = "alloc")]
+    ParseDenominatedAmount(#[from] ParseDenominatedAmountError),
+
     #[error("label parse error at line {line}: {message}")]
     LabelParse { line: usize, message: String },
 
+    #[error("history parse error at line {line}: {message}")]
+    HistoryParse { line: usize, message: String },
+
+    /// A leftover write-ahead journal from a prior crash could not be
+    /// replayed to recover a [`crate::labels::LabelFile`]'s on-disk state.
+    /// Kept distinct from [`Self::Io`] so callers can tell "the disk is
+    /// fine but the journal itself is unreadable" apart from an ordinary
+    /// filesystem error.
+    #[error("journal recovery failed for `{path}`: {message}")]
+    JournalRecovery { path: String, message: String },
+
     #[error(transparent)]
     Io(#[from] std::io::Error),
+
+    /// A remote object-store transport (e.g. [`crate::labels::S3Transport`])
+    /// failed — request signing, connectivity, or a non-2xx response.
+    /// Kept distinct from [`Self::Io`] since it isn't a local filesystem
+    /// error and often carries a status code or service error code worth
+    /// surfacing verbatim.
+    #[error("object store error: {0}")]
+    ObjectStore(String),
+
+    /// A [`crate::jobs`] rescan was stopped via its
+    /// [`crate::jobs::JobCancelToken`] before it finished walking its
+    /// directory. Checkpointed between files, so a cancelled scan never
+    /// leaves a half-read file's records behind.
+    #[error("job cancelled")]
+    Cancelled,
+
+    /// A [`crate::labels::watch::LabelWatcher`] failed to start or
+    /// maintain an OS-level filesystem watch (e.g. the directory vanished,
+    /// or the platform ran out of inotify watches). Kept distinct from
+    /// [`Self::Io`] since it comes from the `notify` crate's own error
+    /// type, not a plain filesystem syscall failure.
+    #[error("label directory watch error: {0}")]
+    Watch(String),
 }
@@ -4,21 +4,47 @@
 //! outpoint to its funding transaction, recursively, producing a DAG
 //! of the spending ancestry bounded by configurable limits.
 
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 
-use bitcoin::Txid;
+use async_trait::async_trait;
+use bitcoin::{Amount, BlockHash, Txid};
 use futures::future::try_join_all;
-use tokio::sync::Semaphore;
+use futures::stream::{self, StreamExt};
+use tokio::sync::{Mutex, Semaphore};
 
 use crate::cache::{Cache, PrevoutInfo};
-use crate::enrich::classify_script;
+use crate::enrich::{classify_script, compute_fee, compute_feerate};
 use crate::error::CoreError;
+use crate::prevout_store::PrevoutStore;
 use crate::rpc::types::{RawInputInfo, RawTxInfo};
-use crate::rpc::BitcoinRpc;
+use crate::rpc::{BitcoinRpc, BlockId};
 use crate::types::{
-    AncestryEdge, AncestryGraph, GraphLimits, GraphStats, ScriptType, TxInput, TxNode, TxOutput,
+    AncestryEdge, AncestryGraph, GraphLimits, GraphStats, GraphStrategy, ScriptType,
+    TruncationReason, TxInput, TxNode, TxOutput,
 };
 
+/// A secondary, read-only transaction source consulted when the primary
+/// `rpc` can't resolve a prevout because its node is pruned and has
+/// discarded the funding block — e.g. a txindex archive node or a block
+/// explorer API. Deliberately narrower than [`BitcoinRpc`]: callers only
+/// ever need the raw transaction a pruned node can no longer serve.
+#[async_trait]
+pub trait TxSource: Send + Sync {
+    /// Fetch a transaction's raw representation by txid.
+    async fn get_raw_tx(&self, txid: &Txid) -> Result<RawTxInfo, CoreError>;
+}
+
+/// Progress callback for a running [`build_ancestry_with_progress`] call,
+/// invoked once per BFS frontier (or, for [`GraphStrategy::ValueWeighted`],
+/// once per expanded candidate) with the running node/edge counts. Lets a
+/// caller watching a long traversal — e.g. the async job queue in `cory`'s
+/// server — report real progress instead of just "still running", without
+/// the builder itself knowing anything about jobs or HTTP.
+pub trait BuildProgress: Send + Sync {
+    fn on_progress(&self, nodes_so_far: usize, edges_so_far: usize);
+}
+
 // ==============================================================================
 // Ancestry Graph Builder
 // ==============================================================================
@@ -34,6 +60,13 @@ use crate::types::{
 /// Frontier nodes at each BFS level are fetched in parallel, bounded by
 /// the `concurrency` semaphore. This dramatically reduces wall-clock time
 /// on wide graphs compared to sequential per-node fetching.
+///
+/// Dispatches on `limits.strategy`: [`GraphStrategy::BreadthFirst`] uses
+/// the level-parallel traversal described above, [`GraphStrategy::ValueWeighted`]
+/// uses [`build_ancestry_value_weighted`] instead.
+///
+/// Thin wrapper over [`build_ancestry_with_fallback`] with no fallback
+/// source configured.
 pub async fn build_ancestry(
     rpc: &dyn BitcoinRpc,
     cache: &Cache,
@@ -41,12 +74,196 @@ pub async fn build_ancestry(
     limits: &GraphLimits,
     concurrency: usize,
 ) -> Result<AncestryGraph, CoreError> {
+    build_ancestry_with_fallback(rpc, cache, root_txid, limits, concurrency, None).await
+}
+
+/// Like [`build_ancestry`], but additionally checks whether `rpc` reports
+/// itself as pruned and, if so, consults `fallback` (a txindex archive node
+/// or block explorer) whenever a prevout can't be resolved from `rpc`
+/// alone. Prevouts that remain unresolved after the fallback is consulted
+/// are marked with [`TruncationReason::Pruned`] on the input, and counted in
+/// [`GraphStats::pruned_unresolved_inputs`].
+pub async fn build_ancestry_with_fallback(
+    rpc: &dyn BitcoinRpc,
+    cache: &Cache,
+    root_txid: Txid,
+    limits: &GraphLimits,
+    concurrency: usize,
+    fallback: Option<&dyn TxSource>,
+) -> Result<AncestryGraph, CoreError> {
+    build_ancestry_inner(
+        rpc,
+        cache,
+        root_txid,
+        limits,
+        concurrency,
+        fallback,
+        None,
+        None,
+        None,
+    )
+    .await
+}
+
+/// Like [`build_ancestry`], but reports running node/edge counts to
+/// `progress` as the traversal proceeds (see [`BuildProgress`]).
+pub async fn build_ancestry_with_progress(
+    rpc: &dyn BitcoinRpc,
+    cache: &Cache,
+    root_txid: Txid,
+    limits: &GraphLimits,
+    concurrency: usize,
+    progress: &dyn BuildProgress,
+) -> Result<AncestryGraph, CoreError> {
+    build_ancestry_inner(
+        rpc,
+        cache,
+        root_txid,
+        limits,
+        concurrency,
+        None,
+        None,
+        Some(progress),
+        None,
+    )
+    .await
+}
+
+/// Like [`build_ancestry`], but consults `store` for already-resolved
+/// prevouts before ever touching `rpc`, and persists every prevout the
+/// traversal resolves back into it — so a later query against an
+/// overlapping history can reuse them across process restarts. See
+/// [`crate::prevout_store::PrevoutStore`].
+pub async fn build_ancestry_with_store(
+    rpc: &dyn BitcoinRpc,
+    cache: &Cache,
+    root_txid: Txid,
+    limits: &GraphLimits,
+    concurrency: usize,
+    store: &dyn PrevoutStore,
+) -> Result<AncestryGraph, CoreError> {
+    build_ancestry_inner(
+        rpc,
+        cache,
+        root_txid,
+        limits,
+        concurrency,
+        None,
+        Some(store),
+        None,
+        None,
+    )
+    .await
+}
+
+/// Like [`build_ancestry`], but reconstructs the graph as it existed at
+/// `at` rather than against current chain state. `at` is resolved once via
+/// [`BitcoinRpc::get_block_header`] into a concrete pinned height; while
+/// walking funding transactions, any node confirmed after that height is
+/// treated as not-yet-existent — excluded from the graph and not followed
+/// any further (its own inputs are never fetched) — and counted in
+/// [`GraphStats::excluded_after_as_of`]. Useful for auditing or reproducing
+/// an analysis against a point-in-time view of the chain.
+///
+/// Excluding a node this way leaves edges pointing at a funding txid with
+/// no corresponding node, the same characteristic an ordinary
+/// [`GraphLimits`] truncation has, so a build that excludes anything also
+/// reports [`AncestryGraph::truncated`] as `true`.
+pub async fn build_ancestry_as_of(
+    rpc: &dyn BitcoinRpc,
+    cache: &Cache,
+    root_txid: Txid,
+    limits: &GraphLimits,
+    concurrency: usize,
+    at: BlockId,
+) -> Result<AncestryGraph, CoreError> {
+    let as_of_height = rpc.get_block_header(at).await?.height;
+    build_ancestry_inner(
+        rpc,
+        cache,
+        root_txid,
+        limits,
+        concurrency,
+        None,
+        None,
+        None,
+        Some(as_of_height),
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn build_ancestry_inner(
+    rpc: &dyn BitcoinRpc,
+    cache: &Cache,
+    root_txid: Txid,
+    limits: &GraphLimits,
+    concurrency: usize,
+    fallback: Option<&dyn TxSource>,
+    persistent_store: Option<&dyn PrevoutStore>,
+    progress: Option<&dyn BuildProgress>,
+    as_of_height: Option<u32>,
+) -> Result<AncestryGraph, CoreError> {
+    let pruned = rpc.get_blockchain_info().await?.pruned;
     let semaphore = Semaphore::new(concurrency);
+    let prefetched_blocks = Mutex::new(HashSet::new());
+    let ctx = FetchContext {
+        rpc,
+        cache,
+        semaphore: &semaphore,
+        pruned,
+        fallback,
+        persistent_store,
+        progress,
+        prefetched_blocks: &prefetched_blocks,
+        as_of_height,
+    };
+
+    match limits.strategy {
+        GraphStrategy::BreadthFirst => build_ancestry_breadth_first(&ctx, root_txid, limits).await,
+        GraphStrategy::ValueWeighted => {
+            build_ancestry_value_weighted(&ctx, root_txid, limits).await
+        }
+    }
+}
+
+/// Shared state threaded through the fetch/convert helpers: the RPC
+/// backend, caches, concurrency limit, and pruning-aware fallback needed to
+/// resolve prevouts even when the primary node has discarded the relevant
+/// block.
+struct FetchContext<'a> {
+    rpc: &'a dyn BitcoinRpc,
+    cache: &'a Cache,
+    semaphore: &'a Semaphore,
+    /// Whether `rpc` reported itself as a pruned node for this build.
+    pruned: bool,
+    fallback: Option<&'a dyn TxSource>,
+    /// Restart-surviving prevout store consulted before RPC; see
+    /// [`crate::prevout_store::PrevoutStore`].
+    persistent_store: Option<&'a dyn PrevoutStore>,
+    progress: Option<&'a dyn BuildProgress>,
+    /// Block hashes already prefetched via [`prefetch_block`] (or found
+    /// unprefetchable), so seeing a second frontier member from the same
+    /// block never issues a second `getblock` call.
+    prefetched_blocks: &'a Mutex<HashSet<BlockHash>>,
+    /// Pinned height from a [`build_ancestry_as_of`] call, resolved once
+    /// before traversal starts. A fetched node with a `block_height` after
+    /// this is excluded rather than followed further; `None` for an
+    /// ordinary build.
+    as_of_height: Option<u32>,
+}
+
+async fn build_ancestry_breadth_first(
+    ctx: &FetchContext<'_>,
+    root_txid: Txid,
+    limits: &GraphLimits,
+) -> Result<AncestryGraph, CoreError> {
     let mut nodes: HashMap<Txid, TxNode> = HashMap::new();
     let mut edges: Vec<AncestryEdge> = Vec::new();
     let mut visited: HashSet<Txid> = HashSet::new();
     let mut truncated = false;
     let mut max_depth_reached: usize = 0;
+    let mut excluded_after_as_of: usize = 0;
 
     // BFS queue: (txid, depth from root).
     let mut queue: VecDeque<(Txid, usize)> = VecDeque::new();
@@ -82,7 +299,7 @@ pub async fn build_ancestry(
         // Fetch all frontier nodes in parallel (semaphore limits concurrency).
         let fetch_futures: Vec<_> = frontier
             .iter()
-            .map(|(txid, _)| fetch_and_convert(rpc, cache, &semaphore, txid))
+            .map(|(txid, _)| fetch_and_convert(ctx, txid))
             .collect();
         let fetched_nodes = try_join_all(fetch_futures).await?;
 
@@ -92,6 +309,16 @@ pub async fn build_ancestry(
                 max_depth_reached = depth;
             }
 
+            // The root is always kept regardless of its own confirmation
+            // height — as-of exclusion only applies to ancestors, since
+            // the whole point of the query is to inspect the root itself
+            // as of a past point in time.
+            if depth != 0 && is_after_as_of(&tx_node, ctx.as_of_height) {
+                excluded_after_as_of += 1;
+                truncated = true;
+                continue;
+            }
+
             if !tx_node.is_coinbase() {
                 let candidate_edge_count = tx_node
                     .inputs
@@ -124,18 +351,333 @@ pub async fn build_ancestry(
 
             nodes.insert(txid, tx_node);
         }
+
+        if let Some(progress) = ctx.progress {
+            progress.on_progress(nodes.len(), edges.len());
+        }
     }
 
     // After BFS is complete, many parent transactions are now present in `nodes`.
     // Backfill any still-unresolved input values from these in-graph parents so
     // fee computation works even when `gettxout` could not resolve spent outputs.
     backfill_inputs_from_graph(&mut nodes);
+    let pruned_unresolved_inputs = count_pruned_unresolved_inputs(&nodes);
+    let (ancestor_package_fee, ancestor_package_vsize, ancestor_package_feerate) =
+        compute_ancestor_package_stats(&nodes);
+
+    Ok(AncestryGraph {
+        stats: GraphStats {
+            node_count: nodes.len(),
+            edge_count: edges.len(),
+            max_depth_reached,
+            pruned_unresolved_inputs,
+            ancestor_package_fee,
+            ancestor_package_vsize,
+            ancestor_package_feerate,
+            excluded_after_as_of,
+        },
+        nodes,
+        edges,
+        root_txid,
+        truncated,
+    })
+}
+
+// ==============================================================================
+// Descendant Graph Builder
+// ==============================================================================
+
+/// Build a transaction spending *descendant* DAG by BFS-expanding outputs
+/// forward: for each output of a transaction, find whoever spent it and
+/// recurse, producing the same [`AncestryGraph`]/[`AncestryEdge`] shape as
+/// [`build_ancestry`] but oriented toward children instead of parents —
+/// "where did these coins go" rather than "where did these coins come
+/// from". Edges keep the same meaning either way (`spending_txid`'s input
+/// spends `funding_txid`'s output); only the traversal direction differs.
+///
+/// Requires `rpc` to answer [`BitcoinRpc::get_spend`] — an
+/// address/outpoint-index backend (e.g. [`crate::rpc::EsploraClient`]), not
+/// a plain `-txindex` node, since Core's RPC has no "who spent this" query.
+///
+/// Always expands level-by-level, mirroring
+/// [`GraphStrategy::BreadthFirst`](crate::types::GraphStrategy::BreadthFirst)
+/// regardless of `limits.strategy` — "highest-value candidate first" has no
+/// obvious analogue when expanding one output into potentially many
+/// spenders. `limits.max_depth`/`max_nodes`/`max_edges` are honored the
+/// same way as [`build_ancestry`], and frontier nodes are fetched in
+/// parallel bounded by the `concurrency` semaphore.
+pub async fn build_descendants(
+    rpc: &dyn BitcoinRpc,
+    cache: &Cache,
+    root_txid: Txid,
+    limits: &GraphLimits,
+    concurrency: usize,
+) -> Result<AncestryGraph, CoreError> {
+    let semaphore = Semaphore::new(concurrency);
+    let prefetched_blocks = Mutex::new(HashSet::new());
+    let ctx = FetchContext {
+        rpc,
+        cache,
+        semaphore: &semaphore,
+        pruned: false,
+        fallback: None,
+        persistent_store: None,
+        progress: None,
+        prefetched_blocks: &prefetched_blocks,
+        as_of_height: None,
+    };
+
+    let mut nodes: HashMap<Txid, TxNode> = HashMap::new();
+    let mut edges: Vec<AncestryEdge> = Vec::new();
+    let mut visited: HashSet<Txid> = HashSet::new();
+    let mut truncated = false;
+    let mut max_depth_reached: usize = 0;
+
+    // BFS queue: (txid, depth from root).
+    let mut queue: VecDeque<(Txid, usize)> = VecDeque::new();
+    queue.push_back((root_txid, 0));
+
+    while !queue.is_empty() {
+        // Drain the current frontier: all txids at this BFS level.
+        let mut frontier: Vec<(Txid, usize)> = Vec::new();
+        while let Some((txid, depth)) = queue.pop_front() {
+            if visited.contains(&txid) {
+                continue;
+            }
+            if nodes.len() + frontier.len() >= limits.max_nodes {
+                truncated = true;
+                break;
+            }
+            if depth > limits.max_depth {
+                truncated = true;
+                continue;
+            }
+            if edges.len() >= limits.max_edges {
+                truncated = true;
+                break;
+            }
+            visited.insert(txid);
+            frontier.push((txid, depth));
+        }
+
+        if frontier.is_empty() {
+            break;
+        }
+
+        // Fetch all frontier nodes in parallel (semaphore limits concurrency).
+        let fetch_futures: Vec<_> = frontier
+            .iter()
+            .map(|(txid, _)| fetch_and_convert(&ctx, txid))
+            .collect();
+        let fetched_nodes = try_join_all(fetch_futures).await?;
+
+        for ((txid, depth), tx_node) in frontier.into_iter().zip(fetched_nodes) {
+            if depth > max_depth_reached {
+                max_depth_reached = depth;
+            }
+
+            let outpoints: Vec<bitcoin::OutPoint> = (0..tx_node.outputs.len())
+                .map(|vout| bitcoin::OutPoint::new(txid, vout as u32))
+                .collect();
+            let spends = rpc.get_spends(&outpoints).await?;
+
+            let candidate_edge_count = spends.iter().filter(|spend| spend.is_some()).count();
+            if edges.len() + candidate_edge_count > limits.max_edges {
+                nodes.insert(txid, tx_node);
+                truncated = true;
+                // Stop processing this frontier — remaining nodes are
+                // already visited so they won't be re-queued.
+                continue;
+            }
+
+            for (vout, spend) in spends.into_iter().enumerate() {
+                if let Some(spend) = spend {
+                    edges.push(AncestryEdge {
+                        spending_txid: spend.spending_txid,
+                        input_index: spend.input_index,
+                        funding_txid: txid,
+                        funding_vout: vout as u32,
+                    });
+
+                    if !visited.contains(&spend.spending_txid) {
+                        queue.push_back((spend.spending_txid, depth + 1));
+                    }
+                }
+            }
+
+            nodes.insert(txid, tx_node);
+        }
+
+        if let Some(progress) = ctx.progress {
+            progress.on_progress(nodes.len(), edges.len());
+        }
+    }
+
+    Ok(AncestryGraph {
+        stats: GraphStats {
+            node_count: nodes.len(),
+            edge_count: edges.len(),
+            max_depth_reached,
+            // Descendant traversal never consults a pruned-node fallback —
+            // `get_spend` either has an index-backed answer or it doesn't.
+            pruned_unresolved_inputs: 0,
+            // Package economics are an ancestor-side concept (CPFP bumps a
+            // low-feerate parent); a descendant graph has no "ancestor set"
+            // to aggregate.
+            ancestor_package_fee: None,
+            ancestor_package_vsize: None,
+            ancestor_package_feerate: None,
+            // As-of pinning is an ancestor-side concept; descendant
+            // traversal never excludes a node on that basis.
+            excluded_after_as_of: 0,
+        },
+        nodes,
+        edges,
+        root_txid,
+        truncated,
+    })
+}
+
+/// A candidate outpoint waiting to be expanded, ordered by the BTC value
+/// that flows into it along the edge that discovered it. `Ord` is
+/// value-first so a max-`BinaryHeap` of these pops the economically
+/// dominant candidate next; `txid` only breaks ties so the order is total.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct ValueCandidate {
+    value: Amount,
+    txid: Txid,
+    depth: usize,
+}
+
+impl Ord for ValueCandidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value
+            .cmp(&other.value)
+            .then_with(|| self.txid.cmp(&other.txid))
+    }
+}
+
+impl PartialOrd for ValueCandidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Build an ancestry DAG by expanding the highest-value unexpanded outpoint
+/// first, so a high-value funding chain survives truncation over dust.
+///
+/// Unlike [`build_ancestry_breadth_first`], candidates are drained one at a
+/// time from a max-heap keyed by the spending input's resolved value rather
+/// than level-by-level: the value of a just-fetched node's inputs decides
+/// where its parents land in the queue relative to everything still
+/// pending, which a parallel frontier fetch can't preserve. The root always
+/// expands first, so it's seeded at `Amount::MAX_MONEY`.
+async fn build_ancestry_value_weighted(
+    ctx: &FetchContext<'_>,
+    root_txid: Txid,
+    limits: &GraphLimits,
+) -> Result<AncestryGraph, CoreError> {
+    let mut nodes: HashMap<Txid, TxNode> = HashMap::new();
+    let mut edges: Vec<AncestryEdge> = Vec::new();
+    let mut visited: HashSet<Txid> = HashSet::new();
+    let mut truncated = false;
+    let mut max_depth_reached: usize = 0;
+    let mut excluded_after_as_of: usize = 0;
+
+    let mut frontier: BinaryHeap<ValueCandidate> = BinaryHeap::new();
+    frontier.push(ValueCandidate {
+        value: Amount::MAX_MONEY,
+        txid: root_txid,
+        depth: 0,
+    });
+
+    while let Some(ValueCandidate { txid, depth, .. }) = frontier.pop() {
+        if visited.contains(&txid) {
+            continue;
+        }
+        if nodes.len() >= limits.max_nodes {
+            truncated = true;
+            break;
+        }
+        if depth > limits.max_depth {
+            truncated = true;
+            continue;
+        }
+        if edges.len() >= limits.max_edges {
+            truncated = true;
+            break;
+        }
+        visited.insert(txid);
+
+        let tx_node = fetch_and_convert(ctx, &txid).await?;
+        if depth > max_depth_reached {
+            max_depth_reached = depth;
+        }
+
+        // The root is always kept regardless of its own confirmation
+        // height — as-of exclusion only applies to ancestors, since the
+        // whole point of the query is to inspect the root itself as of a
+        // past point in time.
+        if depth != 0 && is_after_as_of(&tx_node, ctx.as_of_height) {
+            excluded_after_as_of += 1;
+            truncated = true;
+            continue;
+        }
+
+        if !tx_node.is_coinbase() {
+            let candidate_edge_count = tx_node
+                .inputs
+                .iter()
+                .filter(|input| input.prevout.is_some())
+                .count();
+            if edges.len() + candidate_edge_count > limits.max_edges {
+                nodes.insert(txid, tx_node);
+                truncated = true;
+                continue;
+            }
+
+            for (idx, input) in tx_node.inputs.iter().enumerate() {
+                if let Some(outpoint) = &input.prevout {
+                    edges.push(AncestryEdge {
+                        spending_txid: txid,
+                        input_index: idx as u32,
+                        funding_txid: outpoint.txid,
+                        funding_vout: outpoint.vout,
+                    });
+
+                    if !visited.contains(&outpoint.txid) {
+                        frontier.push(ValueCandidate {
+                            value: input.value.unwrap_or(Amount::ZERO),
+                            txid: outpoint.txid,
+                            depth: depth + 1,
+                        });
+                    }
+                }
+            }
+        }
+
+        nodes.insert(txid, tx_node);
+
+        if let Some(progress) = ctx.progress {
+            progress.on_progress(nodes.len(), edges.len());
+        }
+    }
+
+    backfill_inputs_from_graph(&mut nodes);
+    let pruned_unresolved_inputs = count_pruned_unresolved_inputs(&nodes);
+    let (ancestor_package_fee, ancestor_package_vsize, ancestor_package_feerate) =
+        compute_ancestor_package_stats(&nodes);
 
     Ok(AncestryGraph {
         stats: GraphStats {
             node_count: nodes.len(),
             edge_count: edges.len(),
             max_depth_reached,
+            pruned_unresolved_inputs,
+            ancestor_package_fee,
+            ancestor_package_vsize,
+            ancestor_package_feerate,
+            excluded_after_as_of,
         },
         nodes,
         edges,
@@ -144,66 +686,197 @@ pub async fn build_ancestry(
     })
 }
 
+/// Aggregate ancestor-package economics across every *unconfirmed*
+/// transaction currently in `nodes`, mirroring Bitcoin Core's mempool
+/// ancestor-package accounting: total fee and total vsize across the whole
+/// unconfirmed sub-DAG, not just the root's own (possibly misleadingly low)
+/// feerate, so a parent being bumped by a high-feerate child (CPFP) shows up
+/// directly. Returns `(None, None, None)` when the graph has no unconfirmed
+/// transactions, or when any unconfirmed transaction's fee can't be computed
+/// (an unresolved input) — package accounting only means something if every
+/// member's fee is known.
+fn compute_ancestor_package_stats(
+    nodes: &HashMap<Txid, TxNode>,
+) -> (Option<Amount>, Option<u64>, Option<f64>) {
+    let unconfirmed: Vec<&TxNode> = nodes
+        .values()
+        .filter(|node| node.block_height.is_none())
+        .collect();
+    if unconfirmed.is_empty() {
+        return (None, None, None);
+    }
+
+    let total_vsize: u64 = unconfirmed.iter().map(|node| node.vsize).sum();
+    let total_fee = unconfirmed
+        .iter()
+        .try_fold(Amount::ZERO, |acc, node| acc.checked_add(compute_fee(node)?));
+    let feerate = total_fee.map(|fee| compute_feerate(fee, total_vsize));
+
+    (total_fee, Some(total_vsize), feerate)
+}
+
+/// Count inputs across `nodes` left unresolved specifically because of node
+/// pruning, for [`GraphStats::pruned_unresolved_inputs`].
+fn count_pruned_unresolved_inputs(nodes: &HashMap<Txid, TxNode>) -> usize {
+    nodes
+        .values()
+        .flat_map(|node| &node.inputs)
+        .filter(|input| input.unresolved_reason == Some(TruncationReason::Pruned))
+        .count()
+}
+
+/// Whether `tx_node` was confirmed after `as_of_height` and should be
+/// treated as not-yet-existent at that point in history (see
+/// [`build_ancestry_as_of`]). Unconfirmed transactions (`block_height ==
+/// None`) are never excluded this way — only a concrete, too-late
+/// `block_height` counts.
+fn is_after_as_of(tx_node: &TxNode, as_of_height: Option<u32>) -> bool {
+    match (as_of_height, tx_node.block_height) {
+        (Some(as_of_height), Some(block_height)) => block_height > as_of_height,
+        _ => false,
+    }
+}
+
 // ==============================================================================
 // Transaction Fetching and Conversion
 // ==============================================================================
 
+/// Bound on in-flight cache lookups/RPC fetches while resolving a single
+/// transaction's prevouts. Independent of the per-graph `concurrency`
+/// semaphore (which bounds full-node fetches across the whole BFS/priority
+/// frontier) since these are nested, finer-grained fetches for one node's
+/// inputs and sharing the outer semaphore would risk deadlock at
+/// `concurrency == 1`.
+const PREVOUT_RESOLUTION_CONCURRENCY: usize = 8;
+
 /// Fetch a transaction from the cache or RPC, converting the raw RPC
 /// response into a `TxNode` with enriched inputs (prevout values and
 /// script types).
-async fn fetch_and_convert(
-    rpc: &dyn BitcoinRpc,
-    cache: &Cache,
-    semaphore: &Semaphore,
-    txid: &Txid,
-) -> Result<TxNode, CoreError> {
+async fn fetch_and_convert(ctx: &FetchContext<'_>, txid: &Txid) -> Result<TxNode, CoreError> {
     // Check the transaction cache first.
-    if let Some(cached) = cache.get_tx(txid).await {
+    if let Some(cached) = ctx.cache.get_tx(txid).await {
         return Ok(cached);
     }
 
     // Acquire a semaphore permit to limit concurrent RPC calls.
-    let _permit = semaphore
+    let _permit = ctx
+        .semaphore
         .acquire()
         .await
         .expect("semaphore is never closed");
 
     // Double-check after acquiring the permit (another task may have
     // populated the cache while we were waiting).
-    if let Some(cached) = cache.get_tx(txid).await {
+    if let Some(cached) = ctx.cache.get_tx(txid).await {
         return Ok(cached);
     }
 
-    let raw = rpc.get_transaction(txid).await?;
-    let tx_node = convert_raw_tx(rpc, cache, raw).await?;
+    let raw = ctx.rpc.get_transaction(txid).await?;
+    let tx_node = convert_raw_tx(ctx, raw).await?;
+
+    ctx.cache.insert_tx(*txid, tx_node.clone()).await;
+
+    // This transaction landed in a block; warm the cache with the rest of
+    // that block's transactions (and their outputs, as prevouts) so that
+    // other frontier members funded by or funding the same block are
+    // served from cache instead of each costing their own RPC round trip.
+    if let Some(block_hash) = tx_node.block_hash {
+        prefetch_block(ctx, block_hash).await;
+    }
 
-    cache.insert_tx(*txid, tx_node.clone()).await;
     Ok(tx_node)
 }
 
+/// Fetch `block_hash` in verbose form via [`BitcoinRpc::get_block`] and
+/// populate the transaction and prevout caches with everything it contains,
+/// so that any other frontier member sharing this block is satisfied from
+/// cache rather than issuing its own `get_transaction` call. A no-op (after
+/// the first call) for a given `block_hash` within one graph build, tracked
+/// via `ctx.prefetched_blocks`.
+///
+/// Backends with no cheap way to serve a whole block (e.g. Esplora) return
+/// [`CoreError::InvalidTxData`] from `get_block`; that's treated as
+/// "prefetch unavailable" and logged rather than propagated, since this is
+/// purely an optimization over a per-transaction fetch path that still
+/// works fine on its own.
+async fn prefetch_block(ctx: &FetchContext<'_>, block_hash: BlockHash) {
+    {
+        let mut prefetched = ctx.prefetched_blocks.lock().await;
+        if !prefetched.insert(block_hash) {
+            return;
+        }
+    }
+
+    let block = match ctx.rpc.get_block(BlockId::Hash(block_hash)).await {
+        Ok(block) => block,
+        Err(error) => {
+            tracing::debug!(
+                %block_hash,
+                %error,
+                "block prefetch unavailable, falling back to per-transaction fetches"
+            );
+            return;
+        }
+    };
+    let block_height = block.header.height;
+    let block_time = Some(block.header.header.time as u64);
+
+    for block_tx in block.transactions {
+        for (vout, output) in block_tx.outputs.iter().enumerate() {
+            ctx.cache
+                .insert_prevout(block_tx.txid, vout as u32, output.clone())
+                .await;
+        }
+
+        if ctx.cache.get_tx(&block_tx.txid).await.is_some() {
+            continue;
+        }
+        let vsize = block_tx
+            .weight
+            .map(|weight| weight.div_ceil(4))
+            .unwrap_or_else(|| block_tx.size.unwrap_or(0));
+        let tx_node = TxNode {
+            txid: block_tx.txid,
+            version: block_tx.version,
+            locktime: block_tx.locktime,
+            size: block_tx.size.unwrap_or(0),
+            vsize,
+            weight: block_tx.weight.unwrap_or(0),
+            block_hash: Some(block_hash),
+            block_height,
+            block_time,
+            inputs: block_tx.inputs,
+            outputs: block_tx.outputs,
+        };
+        ctx.cache.insert_tx(block_tx.txid, tx_node).await;
+    }
+}
+
 /// Convert a `RawTxInfo` into a `TxNode`, resolving prevout values and
 /// script types for each input. When the raw response already includes
-/// prevout data (verbosity=2), we use that directly; otherwise we look
-/// up the funding transaction from the cache (which will have been fetched
-/// during earlier BFS levels) or fall back to the prevout cache.
+/// prevout data — Core's `getrawtransaction` verbosity=2, or an
+/// Esplora-style backend's `/tx/:txid`, which inlines it unconditionally —
+/// we use that directly and Phase 2 never runs for that input; otherwise we
+/// look up the funding transaction from the cache (which will have been
+/// fetched during earlier BFS levels), a restart-surviving
+/// [`PrevoutStore`] if one is configured, or fall back to the prevout cache.
 ///
 /// The conversion proceeds in three phases:
-/// 1. Build initial inputs from cache/local data, collecting unresolved
-///    outpoints.
+/// 1. Build initial inputs from cache/local-data/persistent-store,
+///    collecting unresolved outpoints. Lookups fan out concurrently,
+///    bounded by [`PREVOUT_RESOLUTION_CONCURRENCY`].
 /// 2. Resolve unresolved prevouts via batched RPC, then individual
-///    parent-tx fallback.
+///    parent-tx fallback, also bounded and deduplicated by funding txid
+///    so repeated vouts of the same parent only cost one RPC call.
 /// 3. Convert raw outputs to domain `TxOutput` with script classification.
-async fn convert_raw_tx(
-    rpc: &dyn BitcoinRpc,
-    cache: &Cache,
-    raw: RawTxInfo,
-) -> Result<TxNode, CoreError> {
+async fn convert_raw_tx(ctx: &FetchContext<'_>, raw: RawTxInfo) -> Result<TxNode, CoreError> {
     // Phase 1: build inputs from local data, track what still needs RPC.
-    let (mut inputs, unresolved) = build_inputs_initial(cache, &raw.inputs).await;
+    let (mut inputs, unresolved) =
+        build_inputs_initial(ctx.cache, ctx.persistent_store, &raw.inputs).await;
 
     // Phase 2: resolve remaining inputs via batched gettxout + parent tx fallback.
     if !unresolved.is_empty() {
-        resolve_unresolved_prevouts(rpc, cache, &mut inputs, &unresolved, &raw.txid).await;
+        resolve_unresolved_prevouts(ctx, &mut inputs, &unresolved, &raw.txid).await;
     }
 
     // Phase 3: classify output scripts.
@@ -224,45 +897,75 @@ async fn convert_raw_tx(
     })
 }
 
-/// Phase 1: iterate raw inputs, resolve from cache/raw data where possible,
-/// and return the partially-filled inputs along with a list of outpoints
-/// that still need RPC resolution.
+/// Phase 1: resolve every raw input against the cache/local
+/// data/persistent store concurrently (bounded by
+/// [`PREVOUT_RESOLUTION_CONCURRENCY`]), and return the fully-ordered inputs
+/// along with a list of outpoints that still need RPC resolution.
 async fn build_inputs_initial(
     cache: &Cache,
+    persistent_store: Option<&dyn PrevoutStore>,
     raw_inputs: &[RawInputInfo],
 ) -> (Vec<TxInput>, Vec<(usize, bitcoin::OutPoint)>) {
-    let mut inputs = Vec::with_capacity(raw_inputs.len());
+    let resolved: Vec<(
+        usize,
+        Option<bitcoin::OutPoint>,
+        u32,
+        Option<(Amount, ScriptType)>,
+    )> = stream::iter(raw_inputs.iter().enumerate())
+        .map(|(idx, raw_input)| async move {
+            let resolution = match &raw_input.prevout {
+                None => None,
+                Some(outpoint) => {
+                    resolve_prevout_without_rpc(cache, persistent_store, raw_input, outpoint).await
+                }
+            };
+            (idx, raw_input.prevout, raw_input.sequence, resolution)
+        })
+        .buffer_unordered(PREVOUT_RESOLUTION_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut inputs: Vec<Option<TxInput>> = (0..raw_inputs.len()).map(|_| None).collect();
     let mut unresolved = Vec::new();
 
-    for (idx, raw_input) in raw_inputs.iter().enumerate() {
-        let (value, script_type) = match &raw_input.prevout {
-            None => (None, None),
-            Some(outpoint) => match resolve_prevout_without_rpc(cache, raw_input, outpoint).await {
-                Some((value, script_type)) => (Some(value), Some(script_type)),
-                None => {
-                    unresolved.push((idx, *outpoint));
-                    (None, None)
+    for (idx, prevout, sequence, resolution) in resolved {
+        let (value, script_type) = match resolution {
+            Some((value, script_type)) => (Some(value), Some(script_type)),
+            None => {
+                if let Some(outpoint) = prevout {
+                    unresolved.push((idx, outpoint));
                 }
-            },
+                (None, None)
+            }
         };
 
-        inputs.push(TxInput {
-            prevout: raw_input.prevout,
-            sequence: raw_input.sequence,
+        inputs[idx] = Some(TxInput {
+            prevout,
+            sequence,
             value,
             script_type,
+            address: None,
+            unresolved_reason: None,
         });
     }
+    unresolved.sort_by_key(|(idx, _)| *idx);
+
+    let inputs = inputs
+        .into_iter()
+        .map(|input| input.expect("every index populated exactly once above"))
+        .collect();
 
     (inputs, unresolved)
 }
 
 /// Phase 2: for unresolved prevouts, first try a batched `gettxout` call,
 /// then fall back to fetching individual parent transactions for any that
-/// remain unresolved (common for already-spent outputs).
+/// remain unresolved (common for already-spent outputs). If `ctx.rpc`
+/// reports itself as pruned and a parent transaction still can't be found,
+/// consults `ctx.fallback` (if configured) before giving up, and marks
+/// whatever's still unresolved with [`TruncationReason::Pruned`].
 async fn resolve_unresolved_prevouts(
-    rpc: &dyn BitcoinRpc,
-    cache: &Cache,
+    ctx: &FetchContext<'_>,
     inputs: &mut [TxInput],
     unresolved: &[(usize, bitcoin::OutPoint)],
     txid: &Txid,
@@ -270,25 +973,23 @@ async fn resolve_unresolved_prevouts(
     let outpoints: Vec<bitcoin::OutPoint> = unresolved.iter().map(|(_, op)| *op).collect();
 
     // Batch gettxout — resolves unspent outputs in a single RPC call.
-    match rpc.get_tx_outs(&outpoints).await {
+    match ctx.rpc.get_tx_outs(&outpoints, true).await {
         Ok(resolved) => {
             for ((input_idx, outpoint), info_opt) in unresolved.iter().zip(resolved) {
                 if let Some(info) = info_opt {
-                    let script_type = classify_script(info.script_pub_key.as_script());
-                    cache
-                        .insert_prevout(
-                            outpoint.txid,
-                            outpoint.vout,
-                            PrevoutInfo {
-                                value: info.value,
-                                script_pub_key: info.script_pub_key,
-                                script_type,
-                            },
-                        )
+                    let script_type = classify_script(info.output.script_pub_key.as_script());
+                    let prevout_info = PrevoutInfo {
+                        value: info.output.value,
+                        script_pub_key: info.output.script_pub_key,
+                        script_type,
+                    };
+                    ctx.cache
+                        .insert_prevout(outpoint.txid, outpoint.vout, prevout_info.clone())
                         .await;
+                    persist_prevout(ctx.persistent_store, *outpoint, prevout_info);
 
                     if let Some(input) = inputs.get_mut(*input_idx) {
-                        input.value = Some(info.value);
+                        input.value = Some(info.output.value);
                         input.script_type = Some(script_type);
                     }
                 }
@@ -304,40 +1005,97 @@ async fn resolve_unresolved_prevouts(
         }
     }
 
-    // Individual parent-tx fallback for still-unresolved inputs (spent outputs).
-    let still_unresolved: Vec<usize> = inputs
-        .iter()
-        .enumerate()
-        .filter(|(_, inp)| inp.value.is_none() && inp.prevout.is_some())
-        .map(|(idx, _)| idx)
-        .collect();
+    // Individual parent-tx fallback for still-unresolved inputs (spent
+    // outputs). Grouped by funding txid so a tx spending several outputs
+    // of the same still-unresolved parent only fetches that parent once,
+    // and the distinct parents are fetched concurrently (bounded by
+    // `PREVOUT_RESOLUTION_CONCURRENCY`) rather than one RPC round-trip at
+    // a time.
+    let mut by_funding_txid: HashMap<Txid, Vec<usize>> = HashMap::new();
+    for (idx, input) in inputs.iter().enumerate() {
+        if input.value.is_some() {
+            continue;
+        }
+        let Some(outpoint) = input.prevout else {
+            continue;
+        };
+        by_funding_txid.entry(outpoint.txid).or_default().push(idx);
+    }
 
-    for idx in still_unresolved {
-        let outpoint = inputs[idx]
-            .prevout
-            .expect("filtered for Some prevout above");
+    let mut fetched_parents: HashMap<Txid, RawTxInfo> =
+        stream::iter(by_funding_txid.keys().copied())
+            .map(|funding_txid| async move {
+                let parent_tx = ctx.rpc.get_transaction(&funding_txid).await.ok();
+                (funding_txid, parent_tx)
+            })
+            .buffer_unordered(PREVOUT_RESOLUTION_CONCURRENCY)
+            .filter_map(|(funding_txid, parent_tx)| async move {
+                parent_tx.map(|tx| (funding_txid, tx))
+            })
+            .collect()
+            .await;
 
-        if cache
-            .get_prevout(&outpoint.txid, outpoint.vout)
-            .await
-            .is_some()
-        {
-            continue;
+    // Pruned-node fallback: any funding txid the primary rpc still couldn't
+    // produce gets one more try against the secondary source, if configured.
+    if ctx.pruned {
+        if let Some(fallback) = ctx.fallback {
+            let still_missing: Vec<Txid> = by_funding_txid
+                .keys()
+                .filter(|funding_txid| !fetched_parents.contains_key(*funding_txid))
+                .copied()
+                .collect();
+
+            let recovered: Vec<(Txid, RawTxInfo)> = stream::iter(still_missing)
+                .map(|funding_txid| async move {
+                    let parent_tx = fallback.get_raw_tx(&funding_txid).await.ok();
+                    (funding_txid, parent_tx)
+                })
+                .buffer_unordered(PREVOUT_RESOLUTION_CONCURRENCY)
+                .filter_map(|(funding_txid, parent_tx)| async move {
+                    parent_tx.map(|tx| (funding_txid, tx))
+                })
+                .collect()
+                .await;
+            fetched_parents.extend(recovered);
         }
-        if let Ok(parent_tx) = rpc.get_transaction(&outpoint.txid).await {
+    }
+
+    for (funding_txid, indices) in by_funding_txid {
+        let Some(parent_tx) = fetched_parents.get(&funding_txid) else {
+            // Still unresolved after the batched gettxout, per-parent
+            // fetch, and (if configured) fallback source — if the node is
+            // pruned, that's almost certainly why.
+            if ctx.pruned {
+                for idx in indices {
+                    inputs[idx].unresolved_reason = Some(TruncationReason::Pruned);
+                }
+            }
+            continue;
+        };
+        for idx in indices {
+            let outpoint = inputs[idx]
+                .prevout
+                .expect("filtered for Some prevout above");
+
+            if ctx
+                .cache
+                .get_prevout(&outpoint.txid, outpoint.vout)
+                .await
+                .is_some()
+            {
+                continue;
+            }
             if let Some(output) = parent_tx.outputs.get(outpoint.vout as usize) {
                 let st = classify_script(output.script_pub_key.as_script());
-                cache
-                    .insert_prevout(
-                        outpoint.txid,
-                        outpoint.vout,
-                        PrevoutInfo {
-                            value: output.value,
-                            script_pub_key: output.script_pub_key.clone(),
-                            script_type: st,
-                        },
-                    )
+                let prevout_info = PrevoutInfo {
+                    value: output.value,
+                    script_pub_key: output.script_pub_key.clone(),
+                    script_type: st,
+                };
+                ctx.cache
+                    .insert_prevout(outpoint.txid, outpoint.vout, prevout_info.clone())
                     .await;
+                persist_prevout(ctx.persistent_store, outpoint, prevout_info);
                 inputs[idx].value = Some(output.value);
                 inputs[idx].script_type = Some(st);
             }
@@ -353,6 +1111,7 @@ fn convert_outputs(raw_outputs: &[crate::rpc::types::RawOutputInfo]) -> Vec<TxOu
             value: o.value,
             script_pub_key: o.script_pub_key.clone(),
             script_type: classify_script(o.script_pub_key.as_script()),
+            address: None,
         })
         .collect()
 }
@@ -360,28 +1119,30 @@ fn convert_outputs(raw_outputs: &[crate::rpc::types::RawOutputInfo]) -> Vec<TxOu
 /// Try to resolve the value and script type for a prevout using (in order):
 /// 1. Data already present in the raw RPC response (verbosity=2)
 /// 2. The prevout cache
-/// 3. The transaction cache (the funding tx may already be fetched)
-/// 4. The gettxout RPC call (last resort, only works for unspent outputs)
+/// 3. The persistent prevout store, if one is configured
+/// 4. The transaction cache (the funding tx may already be fetched)
+///
+/// Makes no RPC call: returns `None` when none of these resolve it, and the
+/// caller falls back to batched `gettxout` in `resolve_unresolved_prevouts`.
 async fn resolve_prevout_without_rpc(
     cache: &Cache,
+    persistent_store: Option<&dyn PrevoutStore>,
     raw_input: &RawInputInfo,
     outpoint: &bitcoin::OutPoint,
 ) -> Option<(bitcoin::Amount, ScriptType)> {
     // 1. Check if the raw response already has prevout info.
     if let (Some(value), Some(script)) = (&raw_input.prevout_value, &raw_input.prevout_script) {
         let st = classify_script(script.as_script());
+        let info = PrevoutInfo {
+            value: *value,
+            script_pub_key: script.clone(),
+            script_type: st,
+        };
         // Cache for future lookups.
         cache
-            .insert_prevout(
-                outpoint.txid,
-                outpoint.vout,
-                PrevoutInfo {
-                    value: *value,
-                    script_pub_key: script.clone(),
-                    script_type: st,
-                },
-            )
+            .insert_prevout(outpoint.txid, outpoint.vout, info.clone())
             .await;
+        persist_prevout(persistent_store, *outpoint, info);
         return Some((*value, st));
     }
 
@@ -390,20 +1151,30 @@ async fn resolve_prevout_without_rpc(
         return Some((info.value, info.script_type));
     }
 
-    // 3. Check if the funding transaction is already in the tx cache.
+    // 3. Check the persistent prevout store, if configured. A hit is also
+    // copied into the in-memory cache so the next lookup for this outpoint
+    // in this process doesn't touch disk again.
+    if let Some(store) = persistent_store {
+        if let Some(info) = store.get(*outpoint) {
+            cache
+                .insert_prevout(outpoint.txid, outpoint.vout, info.clone())
+                .await;
+            return Some((info.value, info.script_type));
+        }
+    }
+
+    // 4. Check if the funding transaction is already in the tx cache.
     if let Some(funding_tx) = cache.get_tx(&outpoint.txid).await {
         if let Some(output) = funding_tx.outputs.get(outpoint.vout as usize) {
+            let info = PrevoutInfo {
+                value: output.value,
+                script_pub_key: output.script_pub_key.clone(),
+                script_type: output.script_type,
+            };
             cache
-                .insert_prevout(
-                    outpoint.txid,
-                    outpoint.vout,
-                    PrevoutInfo {
-                        value: output.value,
-                        script_pub_key: output.script_pub_key.clone(),
-                        script_type: output.script_type,
-                    },
-                )
+                .insert_prevout(outpoint.txid, outpoint.vout, info.clone())
                 .await;
+            persist_prevout(persistent_store, *outpoint, info);
             return Some((output.value, output.script_type));
         }
     }
@@ -412,6 +1183,23 @@ async fn resolve_prevout_without_rpc(
     None
 }
 
+/// Persist a resolved prevout to `store`, if configured. Failures are
+/// logged rather than propagated: the in-memory cache already has the
+/// value, so a store write failure (e.g. a full disk) shouldn't fail the
+/// whole ancestry query.
+fn persist_prevout(
+    persistent_store: Option<&dyn PrevoutStore>,
+    outpoint: bitcoin::OutPoint,
+    info: PrevoutInfo,
+) {
+    let Some(store) = persistent_store else {
+        return;
+    };
+    if let Err(e) = store.put(outpoint, info) {
+        tracing::warn!(%outpoint, error = %e, "failed to persist resolved prevout");
+    }
+}
+
 /// Fill unresolved input value/script metadata using parent transactions that are
 /// already part of the built graph. This is intentionally post-build so children
 /// fetched before their parents can still be enriched once the full BFS pass ends.
@@ -479,8 +1267,149 @@ mod tests {
         let tx_b = make_raw_tx(
             tx_b_txid,
             vec![spending_input(tx_a_txid, 0)],
-            vec![simple_output(3000)],
+            vec![simple_output(3000)],
+        );
+
+        let rpc = MockRpc::builder()
+            .with_tx(coinbase)
+            .with_tx(tx_a)
+            .with_tx(tx_b)
+            .build();
+        let cache = Cache::new();
+        let limits = GraphLimits::default();
+
+        let graph = build_ancestry(&rpc, &cache, tx_b_txid, &limits, 4)
+            .await
+            .expect("build ancestry");
+
+        assert_eq!(graph.nodes.len(), 3, "should have 3 nodes");
+        assert_eq!(graph.edges.len(), 2, "should have 2 edges");
+        assert!(!graph.truncated, "should not be truncated");
+        assert_eq!(graph.root_txid, tx_b_txid);
+        assert_eq!(graph.stats.max_depth_reached, 2);
+    }
+
+    #[tokio::test]
+    async fn package_stats_aggregate_unconfirmed_ancestors() {
+        // coinbase (confirmed) -> tx_a (unconfirmed, low feerate parent)
+        //                      -> tx_b / root (unconfirmed, bumps tx_a via CPFP)
+        let coinbase_txid = txid_from_byte(1);
+        let tx_a_txid = txid_from_byte(2);
+        let tx_b_txid = txid_from_byte(3);
+
+        let coinbase = make_raw_tx(
+            coinbase_txid,
+            vec![coinbase_input()],
+            vec![simple_output(10_000)],
+        );
+
+        // tx_a: 10_000 in, 9_950 out -> fee 50, vsize 140 -> ~0.36 sat/vB.
+        let mut tx_a = make_raw_tx(
+            tx_a_txid,
+            vec![spending_input(coinbase_txid, 0)],
+            vec![simple_output(9_950)],
+        );
+        tx_a.block_hash = None;
+        tx_a.block_height = None;
+        tx_a.block_time = None;
+
+        // tx_b (root): 9_950 in, 9_000 out -> fee 950, vsize 140.
+        let mut tx_b = make_raw_tx(
+            tx_b_txid,
+            vec![spending_input(tx_a_txid, 0)],
+            vec![simple_output(9_000)],
+        );
+        tx_b.block_hash = None;
+        tx_b.block_height = None;
+        tx_b.block_time = None;
+
+        let rpc = MockRpc::builder()
+            .with_tx(coinbase)
+            .with_tx(tx_a)
+            .with_tx(tx_b)
+            .build();
+        let cache = Cache::new();
+        let limits = GraphLimits::default();
+
+        let graph = build_ancestry(&rpc, &cache, tx_b_txid, &limits, 4)
+            .await
+            .expect("build ancestry");
+
+        // Package fee/vsize cover only the unconfirmed sub-DAG (tx_a, tx_b),
+        // not the confirmed coinbase.
+        assert_eq!(
+            graph.stats.ancestor_package_fee,
+            Some(Amount::from_sat(1_000))
+        );
+        assert_eq!(graph.stats.ancestor_package_vsize, Some(280));
+        let feerate = graph
+            .stats
+            .ancestor_package_feerate
+            .expect("feerate computed");
+        assert!((feerate - 1_000.0 / 280.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn package_stats_none_when_fully_confirmed() {
+        let coinbase_txid = txid_from_byte(1);
+        let tx_a_txid = txid_from_byte(2);
+
+        let coinbase = make_raw_tx(
+            coinbase_txid,
+            vec![coinbase_input()],
+            vec![simple_output(5_000)],
+        );
+        let tx_a = make_raw_tx(
+            tx_a_txid,
+            vec![spending_input(coinbase_txid, 0)],
+            vec![simple_output(4_000)],
+        );
+
+        let rpc = MockRpc::builder()
+            .with_tx(coinbase)
+            .with_tx(tx_a)
+            .build();
+        let cache = Cache::new();
+        let limits = GraphLimits::default();
+
+        let graph = build_ancestry(&rpc, &cache, tx_a_txid, &limits, 4)
+            .await
+            .expect("build ancestry");
+
+        assert_eq!(graph.stats.ancestor_package_fee, None);
+        assert_eq!(graph.stats.ancestor_package_vsize, None);
+        assert_eq!(graph.stats.ancestor_package_feerate, None);
+    }
+
+    #[tokio::test]
+    async fn as_of_excludes_funding_tx_confirmed_after_pinned_height() {
+        // coinbase (height 50) -> tx_a (height 150) -> tx_b / root (height 200).
+        // Pinning at height 100 must exclude both tx_a and the coinbase,
+        // since tx_a (tx_b's only funding transaction) confirmed after it.
+        let coinbase_txid = txid_from_byte(1);
+        let tx_a_txid = txid_from_byte(2);
+        let tx_b_txid = txid_from_byte(3);
+
+        let mut coinbase = make_raw_tx(
+            coinbase_txid,
+            vec![coinbase_input()],
+            vec![simple_output(5_000)],
+        );
+        coinbase.block_height = Some(50);
+
+        let mut tx_a = make_raw_tx(
+            tx_a_txid,
+            vec![spending_input(coinbase_txid, 0)],
+            vec![simple_output(4_000)],
+        );
+        tx_a.block_height = Some(150);
+
+        let mut tx_b = make_raw_tx(
+            tx_b_txid,
+            vec![spending_input(tx_a_txid, 0)],
+            vec![simple_output(3_000)],
         );
+        tx_b.block_height = Some(200);
 
         let rpc = MockRpc::builder()
             .with_tx(coinbase)
@@ -490,15 +1419,52 @@ mod tests {
         let cache = Cache::new();
         let limits = GraphLimits::default();
 
-        let graph = build_ancestry(&rpc, &cache, tx_b_txid, &limits, 4)
+        let graph = build_ancestry_as_of(
+            &rpc,
+            &cache,
+            tx_b_txid,
+            &limits,
+            4,
+            BlockId::Height(100),
+        )
+        .await
+        .expect("build ancestry as of height 100");
+
+        assert!(graph.nodes.contains_key(&tx_b_txid), "root is always kept");
+        assert!(
+            !graph.nodes.contains_key(&tx_a_txid),
+            "tx_a confirmed after the pinned height must be excluded"
+        );
+        assert!(
+            !graph.nodes.contains_key(&coinbase_txid),
+            "coinbase is never reached once tx_a is excluded"
+        );
+        assert_eq!(graph.stats.excluded_after_as_of, 1);
+        assert!(graph.truncated, "excluding a node marks the graph truncated");
+    }
+
+    #[tokio::test]
+    async fn as_of_keeps_unconfirmed_root_even_when_pinned_to_genesis() {
+        // An unconfirmed root (no block_height) must never be excluded by
+        // an as-of pin, even at height 0 — only a concrete, too-late
+        // block_height counts.
+        let tx_a_txid = txid_from_byte(1);
+        let mut tx_a = make_raw_tx(tx_a_txid, vec![coinbase_input()], vec![simple_output(1_000)]);
+        tx_a.block_height = None;
+        tx_a.block_hash = None;
+        tx_a.confirmations = Some(0);
+
+        let rpc = MockRpc::builder().with_tx(tx_a).build();
+        let cache = Cache::new();
+        let limits = GraphLimits::default();
+
+        let graph = build_ancestry_as_of(&rpc, &cache, tx_a_txid, &limits, 4, BlockId::Height(0))
             .await
-            .expect("build ancestry");
+            .expect("build ancestry as of genesis");
 
-        assert_eq!(graph.nodes.len(), 3, "should have 3 nodes");
-        assert_eq!(graph.edges.len(), 2, "should have 2 edges");
-        assert!(!graph.truncated, "should not be truncated");
-        assert_eq!(graph.root_txid, tx_b_txid);
-        assert_eq!(graph.stats.max_depth_reached, 2);
+        assert!(graph.nodes.contains_key(&tx_a_txid));
+        assert_eq!(graph.stats.excluded_after_as_of, 0);
+        assert!(!graph.truncated);
     }
 
     #[tokio::test]
@@ -638,6 +1604,73 @@ mod tests {
         assert!(!graph.truncated);
     }
 
+    /// Records every `(nodes_so_far, edges_so_far)` report it receives, so
+    /// tests can assert progress was observed without caring about timing.
+    struct RecordingProgress {
+        reports: std::sync::Mutex<Vec<(usize, usize)>>,
+    }
+
+    impl RecordingProgress {
+        fn new() -> Self {
+            Self {
+                reports: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl BuildProgress for RecordingProgress {
+        fn on_progress(&self, nodes_so_far: usize, edges_so_far: usize) {
+            self.reports
+                .lock()
+                .expect("progress lock poisoned")
+                .push((nodes_so_far, edges_so_far));
+        }
+    }
+
+    #[tokio::test]
+    async fn progress_reports_final_counts() {
+        let coinbase_txid = txid_from_byte(1);
+        let parent_txid = txid_from_byte(2);
+        let root_txid = txid_from_byte(3);
+
+        let coinbase = make_raw_tx(
+            coinbase_txid,
+            vec![coinbase_input()],
+            vec![simple_output(10_000)],
+        );
+        let parent = make_raw_tx(
+            parent_txid,
+            vec![spending_input(coinbase_txid, 0)],
+            vec![simple_output(9_000)],
+        );
+        let root = make_raw_tx(
+            root_txid,
+            vec![spending_input(parent_txid, 0)],
+            vec![simple_output(8_000)],
+        );
+
+        let rpc = MockRpc::builder()
+            .with_tx(coinbase)
+            .with_tx(parent)
+            .with_tx(root)
+            .build();
+        let cache = Cache::new();
+        let limits = GraphLimits::default();
+        let progress = RecordingProgress::new();
+
+        let graph = build_ancestry_with_progress(&rpc, &cache, root_txid, &limits, 4, &progress)
+            .await
+            .expect("build ancestry");
+
+        let reports = progress.reports.lock().expect("progress lock poisoned");
+        assert!(!reports.is_empty(), "should have reported progress");
+        assert_eq!(
+            *reports.last().expect("at least one report"),
+            (graph.nodes.len(), graph.edges.len()),
+            "final report should match the finished graph's counts"
+        );
+    }
+
     #[tokio::test]
     async fn edge_limit_truncates_without_partial_node_edges() {
         // root has 2 parent edges; max_edges=1 means we should truncate before
@@ -693,4 +1726,484 @@ mod tests {
             "no partial edges from truncated expansion should be emitted"
         );
     }
+
+    #[tokio::test]
+    async fn value_weighted_strategy_keeps_the_high_value_chain_over_dust() {
+        // root spends from two independent chains: a high-value one
+        // (coinbase_rich -> parent_rich) and a low-value one
+        // (coinbase_dust -> parent_dust). A node budget that can only fit
+        // one of the two non-root chains should keep the high-value one.
+        let coinbase_rich_txid = txid_from_byte(1);
+        let coinbase_dust_txid = txid_from_byte(2);
+        let parent_rich_txid = txid_from_byte(3);
+        let parent_dust_txid = txid_from_byte(4);
+        let root_txid = txid_from_byte(5);
+
+        let coinbase_rich = make_raw_tx(
+            coinbase_rich_txid,
+            vec![coinbase_input()],
+            vec![simple_output(1_000_000)],
+        );
+        let coinbase_dust = make_raw_tx(
+            coinbase_dust_txid,
+            vec![coinbase_input()],
+            vec![simple_output(100)],
+        );
+        let parent_rich = make_raw_tx(
+            parent_rich_txid,
+            vec![spending_input(coinbase_rich_txid, 0)],
+            vec![simple_output(900_000)],
+        );
+        let parent_dust = make_raw_tx(
+            parent_dust_txid,
+            vec![spending_input(coinbase_dust_txid, 0)],
+            vec![simple_output(90)],
+        );
+        let root = make_raw_tx(
+            root_txid,
+            vec![
+                spending_input(parent_dust_txid, 0),
+                spending_input(parent_rich_txid, 0),
+            ],
+            vec![simple_output(800_000)],
+        );
+
+        let rpc = MockRpc::builder()
+            .with_tx(coinbase_rich)
+            .with_tx(coinbase_dust)
+            .with_tx(parent_rich)
+            .with_tx(parent_dust)
+            .with_tx(root)
+            .build();
+        let cache = Cache::new();
+        let limits = GraphLimits {
+            // Root plus budget for exactly one of the two non-root chains.
+            max_nodes: 3,
+            strategy: GraphStrategy::ValueWeighted,
+            ..Default::default()
+        };
+
+        let graph = build_ancestry(&rpc, &cache, root_txid, &limits, 4)
+            .await
+            .expect("build ancestry");
+
+        assert!(graph.truncated, "should be truncated");
+        assert_eq!(graph.nodes.len(), 3, "root plus one expanded chain");
+        assert!(
+            graph.nodes.contains_key(&parent_rich_txid),
+            "high-value parent should be kept"
+        );
+        assert!(
+            graph.nodes.contains_key(&coinbase_rich_txid),
+            "high-value grandparent should be kept over the dust chain"
+        );
+        assert!(
+            !graph.nodes.contains_key(&parent_dust_txid),
+            "dust chain should be dropped in favor of the high-value chain"
+        );
+    }
+
+    #[tokio::test]
+    async fn fallback_resolution_dedupes_shared_parent_fetch() {
+        // root spends two outputs of the same parent. Force the batched
+        // gettxout call to fail so both inputs fall back to the
+        // individual parent-tx path, and assert that path only fetches
+        // the shared parent once.
+        let parent_txid = txid_from_byte(1);
+        let root_txid = txid_from_byte(2);
+
+        let mut parent_out_0 = simple_output(1_000);
+        parent_out_0.n = 0;
+        let mut parent_out_1 = simple_output(2_000);
+        parent_out_1.n = 1;
+        let parent = make_raw_tx(
+            parent_txid,
+            vec![coinbase_input()],
+            vec![parent_out_0, parent_out_1],
+        );
+
+        let raw_root = make_raw_tx(
+            root_txid,
+            vec![
+                spending_input(parent_txid, 0),
+                spending_input(parent_txid, 1),
+            ],
+            vec![simple_output(2_500)],
+        );
+
+        let rpc = MockRpc::builder()
+            .with_tx(parent)
+            .with_queued_error(crate::error::CoreError::InvalidTxData(
+                "simulated gettxout failure".into(),
+            ))
+            .build();
+        let cache = Cache::new();
+        let semaphore = Semaphore::new(4);
+        let prefetched_blocks = Mutex::new(HashSet::new());
+        let ctx = FetchContext {
+            rpc: &rpc,
+            cache: &cache,
+            semaphore: &semaphore,
+            pruned: false,
+            fallback: None,
+            persistent_store: None,
+            progress: None,
+            prefetched_blocks: &prefetched_blocks,
+            as_of_height: None,
+        };
+
+        let tx_node = convert_raw_tx(&ctx, raw_root).await.expect("convert");
+
+        assert_eq!(
+            tx_node.inputs[0].value,
+            Some(bitcoin::Amount::from_sat(1_000))
+        );
+        assert_eq!(
+            tx_node.inputs[1].value,
+            Some(bitcoin::Amount::from_sat(2_000))
+        );
+        assert_eq!(
+            rpc.get_transaction_call_count(&parent_txid),
+            1,
+            "shared parent should only be fetched once across both unresolved inputs"
+        );
+    }
+
+    #[tokio::test]
+    async fn block_prefetch_serves_sibling_transaction_from_cache() {
+        // `root` is confirmed in `block_hash`, which also contains
+        // `sibling` — a transaction the mock RPC has no standalone
+        // `get_transaction` answer for at all. Fetching `root` should
+        // prefetch the whole block and warm the cache for `sibling` too.
+        let root_txid = txid_from_byte(1);
+        let sibling_txid = txid_from_byte(2);
+        let block_hash = bitcoin::BlockHash::all_zeros();
+
+        let mut root = make_raw_tx(root_txid, vec![coinbase_input()], vec![simple_output(1_000)]);
+        root.block_hash = Some(block_hash);
+
+        let header = bitcoin::block::Header {
+            version: bitcoin::block::Version::from_consensus(1),
+            prev_blockhash: bitcoin::BlockHash::all_zeros(),
+            merkle_root: bitcoin::TxMerkleNode::all_zeros(),
+            time: 1_700_000_000,
+            bits: bitcoin::pow::CompactTarget::from_consensus(0x1d00ffff),
+            nonce: 0,
+        };
+        let block = crate::rpc::Block {
+            header: crate::rpc::BlockHeaderData {
+                header,
+                chainwork: bitcoin::pow::Work::from_be_bytes([0u8; 32]),
+                height: Some(100),
+                confirmations: Some(10),
+            },
+            transactions: vec![crate::rpc::BlockTx {
+                txid: sibling_txid,
+                version: 2,
+                locktime: 0,
+                size: Some(200),
+                weight: Some(800),
+                inputs: vec![],
+                outputs: vec![make_output(2_000)],
+            }],
+        };
+
+        let rpc = MockRpc::builder()
+            .with_tx(root)
+            .with_block(block_hash, block)
+            .build();
+        let cache = Cache::new();
+        let semaphore = Semaphore::new(4);
+        let prefetched_blocks = Mutex::new(HashSet::new());
+        let ctx = FetchContext {
+            rpc: &rpc,
+            cache: &cache,
+            semaphore: &semaphore,
+            pruned: false,
+            fallback: None,
+            persistent_store: None,
+            progress: None,
+            prefetched_blocks: &prefetched_blocks,
+            as_of_height: None,
+        };
+
+        fetch_and_convert(&ctx, &root_txid)
+            .await
+            .expect("fetch root");
+        let sibling = fetch_and_convert(&ctx, &sibling_txid)
+            .await
+            .expect("sibling should be served from the block prefetch");
+
+        assert_eq!(sibling.txid, sibling_txid);
+        assert_eq!(
+            rpc.get_transaction_call_count(&sibling_txid),
+            0,
+            "sibling has no get_transaction answer at all, so it must come from the block prefetch"
+        );
+        assert_eq!(rpc.get_block_call_count(), 1);
+
+        // Re-fetching root (cached) or sibling (cached) triggers no further
+        // getblock calls.
+        fetch_and_convert(&ctx, &root_txid).await.expect("root");
+        fetch_and_convert(&ctx, &sibling_txid).await.expect("sibling");
+        assert_eq!(rpc.get_block_call_count(), 1);
+    }
+
+    /// A [`TxSource`] backed by a fixed set of transactions, standing in for
+    /// a txindex archive node or block explorer in tests.
+    struct StaticTxSource(HashMap<Txid, RawTxInfo>);
+
+    #[async_trait]
+    impl TxSource for StaticTxSource {
+        async fn get_raw_tx(&self, txid: &Txid) -> Result<RawTxInfo, CoreError> {
+            self.0
+                .get(txid)
+                .cloned()
+                .ok_or(CoreError::TxNotFound(*txid))
+        }
+    }
+
+    #[tokio::test]
+    async fn pruned_node_marks_unresolved_inputs_with_pruned_reason() {
+        // root spends a parent the (pruned) rpc has no record of, and no
+        // fallback is configured, so resolution must fail and be attributed
+        // to pruning rather than left as an unexplained gap.
+        let parent_txid = txid_from_byte(1);
+        let root_txid = txid_from_byte(2);
+
+        let raw_root = make_raw_tx(
+            root_txid,
+            vec![spending_input(parent_txid, 0)],
+            vec![simple_output(1_000)],
+        );
+
+        let rpc = MockRpc::builder()
+            .with_tx(raw_root)
+            .with_chain_info(crate::types::ChainInfo {
+                chain: "main".into(),
+                blocks: 800_000,
+                best_block_hash: bitcoin::BlockHash::all_zeros(),
+                pruned: true,
+            })
+            .build();
+        let cache = Cache::new();
+        let limits = GraphLimits {
+            max_depth: 10,
+            max_nodes: 10,
+            max_edges: 10,
+            strategy: GraphStrategy::BreadthFirst,
+        };
+
+        let graph = build_ancestry(&rpc, &cache, root_txid, &limits, 4)
+            .await
+            .expect("graph build must succeed even with an unresolved prevout");
+
+        let root = &graph.nodes[&root_txid];
+        assert_eq!(
+            root.inputs[0].unresolved_reason,
+            Some(TruncationReason::Pruned)
+        );
+        assert_eq!(graph.stats.pruned_unresolved_inputs, 1);
+    }
+
+    #[tokio::test]
+    async fn fallback_source_resolves_prevout_when_primary_is_pruned() {
+        // Same setup as above, but a fallback TxSource has the parent the
+        // pruned primary node lacks, so the prevout resolves cleanly.
+        let parent_txid = txid_from_byte(1);
+        let root_txid = txid_from_byte(2);
+
+        let parent = make_raw_tx(
+            parent_txid,
+            vec![coinbase_input()],
+            vec![simple_output(1_000)],
+        );
+        let raw_root = make_raw_tx(
+            root_txid,
+            vec![spending_input(parent_txid, 0)],
+            vec![simple_output(1_000)],
+        );
+
+        let rpc = MockRpc::builder()
+            .with_tx(raw_root)
+            .with_chain_info(crate::types::ChainInfo {
+                chain: "main".into(),
+                blocks: 800_000,
+                best_block_hash: bitcoin::BlockHash::all_zeros(),
+                pruned: true,
+            })
+            .build();
+        let cache = Cache::new();
+        let limits = GraphLimits {
+            max_depth: 10,
+            max_nodes: 10,
+            max_edges: 10,
+            strategy: GraphStrategy::BreadthFirst,
+        };
+        let fallback = StaticTxSource(HashMap::from([(parent_txid, parent)]));
+
+        let graph =
+            build_ancestry_with_fallback(&rpc, &cache, root_txid, &limits, 4, Some(&fallback))
+                .await
+                .expect("graph build must succeed");
+
+        let root = &graph.nodes[&root_txid];
+        assert_eq!(root.inputs[0].value, Some(bitcoin::Amount::from_sat(1_000)));
+        assert_eq!(root.inputs[0].unresolved_reason, None);
+        assert_eq!(graph.stats.pruned_unresolved_inputs, 0);
+    }
+
+    #[tokio::test]
+    async fn inline_prevout_data_skips_phase_two_entirely() {
+        // root's only input already carries its prevout value/script, as an
+        // Esplora-style `/tx/:txid` response (or Core's getrawtransaction
+        // verbosity=2) would supply it. max_depth=0 keeps the BFS from
+        // visiting the parent on its own, so the only way `parent_txid`
+        // would ever be fetched is via Phase 2's gettxout/parent-tx
+        // fallback — which should never fire here.
+        let parent_txid = txid_from_byte(1);
+        let root_txid = txid_from_byte(2);
+
+        let prevout_value = bitcoin::Amount::from_sat(4_000);
+        let prevout_script = simple_output(4_000).script_pub_key;
+
+        let root = make_raw_tx(
+            root_txid,
+            vec![spending_input_with_prevout(
+                parent_txid,
+                0,
+                prevout_value,
+                prevout_script,
+            )],
+            vec![simple_output(3_000)],
+        );
+
+        let rpc = MockRpc::builder()
+            .with_tx(root)
+            // No transaction is registered for `parent_txid`, and this error
+            // would replace whatever Phase 2 would otherwise see when
+            // fetching it — so if the test passes, Phase 2 never asked.
+            .with_error_for(
+                parent_txid,
+                CoreError::InvalidTxData("phase 2 should not have run".into()),
+            )
+            .build();
+        let cache = Cache::new();
+        let limits = GraphLimits {
+            max_depth: 0,
+            ..Default::default()
+        };
+
+        let graph = build_ancestry(&rpc, &cache, root_txid, &limits, 4)
+            .await
+            .expect("build ancestry");
+
+        let root = &graph.nodes[&root_txid];
+        assert_eq!(root.inputs[0].value, Some(prevout_value));
+        assert_eq!(root.inputs[0].unresolved_reason, None);
+        assert_eq!(
+            rpc.get_transaction_call_count(&parent_txid),
+            0,
+            "parent should never be fetched when the root's prevout is already inline"
+        );
+    }
+
+    #[tokio::test]
+    async fn build_descendants_follows_spends_forward() {
+        // root -> child (spends root:0) -> grandchild (spends child:0)
+        let root_txid = txid_from_byte(1);
+        let child_txid = txid_from_byte(2);
+        let grandchild_txid = txid_from_byte(3);
+
+        let root = make_raw_tx(
+            root_txid,
+            vec![coinbase_input()],
+            vec![simple_output(5_000)],
+        );
+        let child = make_raw_tx(
+            child_txid,
+            vec![spending_input(root_txid, 0)],
+            vec![simple_output(4_000)],
+        );
+        let grandchild = make_raw_tx(
+            grandchild_txid,
+            vec![spending_input(child_txid, 0)],
+            vec![simple_output(3_000)],
+        );
+
+        let rpc = MockRpc::builder()
+            .with_tx(root)
+            .with_tx(child)
+            .with_tx(grandchild)
+            .with_spend(bitcoin::OutPoint::new(root_txid, 0), child_txid, 0)
+            .with_spend(bitcoin::OutPoint::new(child_txid, 0), grandchild_txid, 0)
+            .build();
+        let cache = Cache::new();
+        let limits = GraphLimits::default();
+
+        let graph = build_descendants(&rpc, &cache, root_txid, &limits, 4)
+            .await
+            .expect("build descendants");
+
+        assert_eq!(graph.nodes.len(), 3, "should have 3 nodes");
+        assert_eq!(graph.edges.len(), 2, "should have 2 edges");
+        assert!(!graph.truncated, "should not be truncated");
+        assert_eq!(graph.root_txid, root_txid);
+        assert_eq!(graph.stats.max_depth_reached, 2);
+
+        assert!(graph.edges.iter().any(|e| e.spending_txid == child_txid
+            && e.funding_txid == root_txid
+            && e.funding_vout == 0));
+        assert!(graph
+            .edges
+            .iter()
+            .any(|e| e.spending_txid == grandchild_txid
+                && e.funding_txid == child_txid
+                && e.funding_vout == 0));
+    }
+
+    #[tokio::test]
+    async fn build_descendants_depth_limit_truncates() {
+        let root_txid = txid_from_byte(1);
+        let child_txid = txid_from_byte(2);
+        let grandchild_txid = txid_from_byte(3);
+
+        let root = make_raw_tx(
+            root_txid,
+            vec![coinbase_input()],
+            vec![simple_output(5_000)],
+        );
+        let child = make_raw_tx(
+            child_txid,
+            vec![spending_input(root_txid, 0)],
+            vec![simple_output(4_000)],
+        );
+        let grandchild = make_raw_tx(
+            grandchild_txid,
+            vec![spending_input(child_txid, 0)],
+            vec![simple_output(3_000)],
+        );
+
+        let rpc = MockRpc::builder()
+            .with_tx(root)
+            .with_tx(child)
+            .with_tx(grandchild)
+            .with_spend(bitcoin::OutPoint::new(root_txid, 0), child_txid, 0)
+            .with_spend(bitcoin::OutPoint::new(child_txid, 0), grandchild_txid, 0)
+            .build();
+        let cache = Cache::new();
+        let limits = GraphLimits {
+            max_depth: 1,
+            ..Default::default()
+        };
+
+        let graph = build_descendants(&rpc, &cache, root_txid, &limits, 4)
+            .await
+            .expect("build descendants");
+
+        // root (depth 0) and child (depth 1) are fetched, but grandchild
+        // (depth 2) is not.
+        assert_eq!(graph.nodes.len(), 2, "should have 2 nodes (depth limited)");
+        assert!(graph.truncated, "should be truncated");
+    }
 }
@@ -0,0 +1,245 @@
+//! Durable search-history store.
+//!
+//! Records how a prior ancestry query was parameterized (network, the
+//! `GraphLimits` in effect, and the resulting node/edge counts), not just
+//! the txid and a timestamp, so a user can understand — or reproduce — a
+//! past search after the server has restarted.
+//!
+//! Entries are capped at `max_entries`, evicted oldest-first like the
+//! in-memory history this replaces. When backed by a file, the full
+//! capped entry set is rewritten on every `record` call via [`Transport`],
+//! the same write-then-rename strategy `LabelStore` uses for its files —
+//! the entry count is small and bounded, so a full rewrite is cheap.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CoreError;
+use crate::labels::{LocalTransport, Transport};
+use crate::types::{GraphLimits, GraphStrategy};
+
+/// One recorded ancestry search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub txid: String,
+    pub searched_at: String,
+    pub network: String,
+    pub limits: GraphLimits,
+    pub node_count: usize,
+    pub edge_count: usize,
+}
+
+pub struct HistoryStore {
+    entries: HashMap<String, HistoryEntry>,
+    max_entries: usize,
+    source_path: Option<PathBuf>,
+    transport: Arc<dyn Transport>,
+}
+
+impl HistoryStore {
+    /// Creates an in-memory-only store; entries are lost on restart.
+    pub fn new(max_entries: usize) -> Self {
+        Self::with_transport(max_entries, Arc::new(LocalTransport::new()))
+    }
+
+    /// Create a store backed by a custom [`Transport`] instead of the local
+    /// filesystem, e.g. an in-memory transport for tests.
+    pub fn with_transport(max_entries: usize, transport: Arc<dyn Transport>) -> Self {
+        Self {
+            entries: HashMap::new(),
+            max_entries,
+            source_path: None,
+            transport,
+        }
+    }
+
+    /// Loads any existing entries from `path` (a JSONL file, one entry per
+    /// line; missing file is not an error) and records it as the rewrite
+    /// target for future [`Self::record`] calls.
+    pub fn load_file(&mut self, path: &Path) -> Result<(), CoreError> {
+        self.source_path = Some(path.to_path_buf());
+
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let content = self.transport.read_file(path)?;
+        let content = String::from_utf8_lossy(&content);
+        for (line_num, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let entry: HistoryEntry =
+                serde_json::from_str(line).map_err(|e| CoreError::HistoryParse {
+                    line: line_num + 1,
+                    message: e.to_string(),
+                })?;
+            self.entries.insert(entry.txid.clone(), entry);
+        }
+        self.evict_to_capacity();
+        Ok(())
+    }
+
+    /// Records a search, overwriting any prior entry for the same txid
+    /// (so repeated searches update the timestamp/limits instead of
+    /// piling up duplicates), then evicts the oldest entry if over
+    /// capacity and flushes to disk if persistent.
+    pub fn record(&mut self, entry: HistoryEntry) -> Result<(), CoreError> {
+        self.entries.insert(entry.txid.clone(), entry);
+        self.evict_to_capacity();
+        self.flush()
+    }
+
+    /// Returns every entry, sorted newest-first.
+    pub fn list(&self) -> Vec<HistoryEntry> {
+        let mut entries: Vec<HistoryEntry> = self.entries.values().cloned().collect();
+        // RFC3339 UTC strings are lexicographically sortable by recency.
+        entries.sort_by(|a, b| b.searched_at.cmp(&a.searched_at));
+        entries
+    }
+
+    /// Returns a page of entries (newest-first), `offset` entries in.
+    pub fn list_page(&self, offset: usize, limit: usize) -> Vec<HistoryEntry> {
+        self.list().into_iter().skip(offset).take(limit).collect()
+    }
+
+    fn evict_to_capacity(&mut self) {
+        while self.entries.len() > self.max_entries {
+            let Some(oldest_txid) = self
+                .entries
+                .iter()
+                .min_by(|a, b| a.1.searched_at.cmp(&b.1.searched_at))
+                .map(|(txid, _)| txid.clone())
+            else {
+                break;
+            };
+            self.entries.remove(&oldest_txid);
+        }
+    }
+
+    fn flush(&self) -> Result<(), CoreError> {
+        let Some(path) = &self.source_path else {
+            return Ok(());
+        };
+
+        let content: String = self
+            .list()
+            .into_iter()
+            .map(|entry| format!("{}\n", serde_json::to_string(&entry).expect("valid JSON")))
+            .collect();
+        self.transport.write_file(path, content.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_entry(txid: &str, searched_at: &str) -> HistoryEntry {
+        HistoryEntry {
+            txid: txid.to_string(),
+            searched_at: searched_at.to_string(),
+            network: "regtest".to_string(),
+            limits: GraphLimits {
+                max_depth: 10,
+                max_nodes: 100,
+                max_edges: 200,
+                strategy: GraphStrategy::BreadthFirst,
+            },
+            node_count: 3,
+            edge_count: 2,
+        }
+    }
+
+    #[test]
+    fn record_overwrites_existing_txid_without_growth() {
+        let mut store = HistoryStore::new(10);
+        store
+            .record(sample_entry("abc", "2024-01-01T00:00:00Z"))
+            .expect("record must succeed");
+        store
+            .record(sample_entry("abc", "2024-01-02T00:00:00Z"))
+            .expect("record must succeed");
+
+        let entries = store.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].searched_at, "2024-01-02T00:00:00Z");
+    }
+
+    #[test]
+    fn record_evicts_oldest_when_over_capacity() {
+        let mut store = HistoryStore::new(2);
+        store
+            .record(sample_entry("old", "2024-01-01T00:00:00Z"))
+            .expect("record must succeed");
+        store
+            .record(sample_entry("newer", "2024-01-02T00:00:00Z"))
+            .expect("record must succeed");
+        store
+            .record(sample_entry("latest", "2024-01-03T00:00:00Z"))
+            .expect("record must succeed");
+
+        let entries = store.list();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.txid != "old"));
+    }
+
+    #[test]
+    fn list_is_sorted_newest_first() {
+        let mut store = HistoryStore::new(10);
+        store
+            .record(sample_entry("first", "2024-01-01T00:00:00Z"))
+            .expect("record must succeed");
+        store
+            .record(sample_entry("second", "2024-01-02T00:00:00Z"))
+            .expect("record must succeed");
+
+        let entries = store.list();
+        assert_eq!(entries[0].txid, "second");
+        assert_eq!(entries[1].txid, "first");
+    }
+
+    #[test]
+    fn list_page_paginates_the_sorted_list() {
+        let mut store = HistoryStore::new(10);
+        for (txid, searched_at) in [
+            ("a", "2024-01-01T00:00:00Z"),
+            ("b", "2024-01-02T00:00:00Z"),
+            ("c", "2024-01-03T00:00:00Z"),
+        ] {
+            store
+                .record(sample_entry(txid, searched_at))
+                .expect("record must succeed");
+        }
+
+        let page = store.list_page(1, 1);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].txid, "b");
+    }
+
+    #[test]
+    fn load_file_round_trips_through_a_real_file() {
+        let dir = std::env::temp_dir().join(format!("cory-history-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let path = dir.join("history.jsonl");
+
+        let mut store = HistoryStore::new(10);
+        store
+            .load_file(&path)
+            .expect("load missing file is not an error");
+        store
+            .record(sample_entry("abc", "2024-01-01T00:00:00Z"))
+            .expect("record must succeed");
+
+        let mut reloaded = HistoryStore::new(10);
+        reloaded.load_file(&path).expect("load must succeed");
+        assert_eq!(reloaded.list().len(), 1);
+        assert_eq!(reloaded.list()[0].txid, "abc");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,170 @@
+//! Cancellable, progress-reporting background jobs — currently just
+//! rescans of a `--labels-rw`/`--labels-ro` directory tree.
+//!
+//! [`LabelStore::load_rw_dir`]/[`LabelStore::load_ro_dir`] only ever run
+//! once at startup; there's no way to pick up files added to a live
+//! directory without restarting the process. [`rescan_dir`] re-walks one
+//! of those directories on demand, reporting [`JobProgress`] snapshots
+//! over a `tokio::sync::watch` channel as it goes and checking a
+//! [`JobCancelToken`] between files so a caller (see `cory`'s
+//! `server::rescan` module) can stop it without leaving the store
+//! half-populated — the scratch-buffer-and-swap behind that guarantee
+//! lives in [`LabelStore::rescan_rw_dir`]/[`LabelStore::rescan_ro_dir`].
+//!
+//! The walk itself is synchronous filesystem I/O (see
+//! [`crate::labels`]), so `rescan_dir` is a plain blocking function
+//! rather than an `async fn`; callers run it on a blocking thread (e.g.
+//! via `tokio::task::spawn_blocking`).
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+use crate::error::CoreError;
+use crate::labels::{LabelFileKind, LabelStore};
+
+/// A snapshot of a running rescan, broadcast over a `tokio::sync::watch`
+/// channel so any number of pollers can observe the latest state without
+/// consuming it (unlike an `mpsc::Receiver`, which only one side can
+/// drain).
+#[derive(Debug, Clone, Default)]
+pub struct JobProgress {
+    pub files_scanned: usize,
+    pub records_parsed: usize,
+    /// The file the walk is about to read. `None` before the first file;
+    /// once the scan finishes it still holds the last file's path.
+    pub current_path: Option<PathBuf>,
+}
+
+/// Shared flag a caller can set to stop an in-flight [`rescan_dir`] call.
+/// Checked once per file, between reads, so a cancelled scan can never
+/// leave a half-read file's records in the scratch buffer that
+/// [`LabelStore::rescan_rw_dir`]/[`LabelStore::rescan_ro_dir`] builds into.
+#[derive(Clone, Default)]
+pub struct JobCancelToken(Arc<AtomicBool>);
+
+impl JobCancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request that the next file-boundary checkpoint abort the walk.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Re-walk `dir` and load it into `store` as label files of `kind`,
+/// publishing a [`JobProgress`] snapshot to `progress` before each file
+/// and aborting with [`CoreError::Cancelled`] if `cancel` is set by then.
+/// `kind` must be [`LabelFileKind::PersistentRw`] or
+/// [`LabelFileKind::PersistentRo`] — any other kind isn't backed by a
+/// directory walk and is rejected outright.
+pub fn rescan_dir(
+    store: &mut LabelStore,
+    dir: &Path,
+    kind: LabelFileKind,
+    progress: &watch::Sender<JobProgress>,
+    cancel: &JobCancelToken,
+) -> Result<(), CoreError> {
+    let checkpoint = |files_scanned: usize, records_parsed: usize, current_path: &Path| {
+        if cancel.is_cancelled() {
+            return Err(CoreError::Cancelled);
+        }
+        // A dropped receiver just means nobody's polling progress anymore;
+        // the scan itself still runs to completion.
+        let _ = progress.send(JobProgress {
+            files_scanned,
+            records_parsed,
+            current_path: Some(current_path.to_path_buf()),
+        });
+        Ok(())
+    };
+
+    match kind {
+        LabelFileKind::PersistentRw => store.rescan_rw_dir(dir, Some(&checkpoint)),
+        LabelFileKind::PersistentRo => store.rescan_ro_dir(dir, Some(&checkpoint)),
+        other => Err(CoreError::LabelParse {
+            line: 0,
+            message: format!("cannot rescan a `{other:?}` directory"),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unique_test_dir(label: &str) -> PathBuf {
+        std::path::Path::new("tmp").join(format!(
+            "{label}-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("system time before unix epoch")
+                .as_nanos()
+        ))
+    }
+
+    #[test]
+    fn rescan_reports_progress_and_loads_files() {
+        let dir = unique_test_dir("rescan-progress");
+        std::fs::create_dir_all(&dir).expect("create test dir");
+        std::fs::write(
+            dir.join("wallet.jsonl"),
+            r#"{"type":"tx","ref":"abc","label":"Test"}"#,
+        )
+        .expect("write label file");
+
+        let mut store = LabelStore::new();
+        let (sender, receiver) = watch::channel(JobProgress::default());
+        let cancel = JobCancelToken::new();
+
+        rescan_dir(&mut store, &dir, LabelFileKind::PersistentRw, &sender, &cancel)
+            .expect("rescan must succeed");
+
+        assert_eq!(store.list_files().len(), 1);
+        let last = receiver.borrow().clone();
+        assert_eq!(last.files_scanned, 0);
+        assert_eq!(
+            last.current_path,
+            Some(dir.join("wallet.jsonl"))
+        );
+
+        std::fs::remove_dir_all(&dir).expect("cleanup test dir");
+    }
+
+    #[test]
+    fn rescan_stops_between_files_once_cancelled() {
+        let dir = unique_test_dir("rescan-cancel");
+        std::fs::create_dir_all(&dir).expect("create test dir");
+        std::fs::write(
+            dir.join("a.jsonl"),
+            r#"{"type":"tx","ref":"abc","label":"A"}"#,
+        )
+        .expect("write label file");
+        std::fs::write(
+            dir.join("b.jsonl"),
+            r#"{"type":"tx","ref":"def","label":"B"}"#,
+        )
+        .expect("write label file");
+
+        let mut store = LabelStore::new();
+        let (sender, _receiver) = watch::channel(JobProgress::default());
+        let cancel = JobCancelToken::new();
+        cancel.cancel();
+
+        let result = rescan_dir(&mut store, &dir, LabelFileKind::PersistentRw, &sender, &cancel);
+        assert!(matches!(result, Err(CoreError::Cancelled)));
+        // A cancelled scan must leave the store untouched, not half-loaded.
+        assert_eq!(store.list_files().len(), 0);
+
+        std::fs::remove_dir_all(&dir).expect("cleanup test dir");
+    }
+}
@@ -0,0 +1,303 @@
+//! CSV serialization and deserialization for BIP-329 label records.
+//!
+//! Alongside JSONL, some label-sharing workflows prefer a spreadsheet-
+//! friendly format. Uses a `type,ref,label,origin,spendable` header row
+//! mapping onto [`Bip329Record`]'s fields, and reports malformed rows
+//! through the same [`CoreError::LabelParse`] used by the JSONL path.
+
+use std::collections::HashMap;
+
+use crate::error::CoreError;
+
+use super::types::{Bip329Record, Bip329Type, LabelKey};
+
+const HEADER: &str = "type,ref,label,origin,spendable";
+
+/// Parse CSV content into a label map, skipping blank lines.
+/// Duplicate entries (same type+ref) are accepted but logged as warnings,
+/// matching the JSONL parser's behavior.
+pub fn parse_csv_records(content: &str) -> Result<HashMap<LabelKey, Bip329Record>, CoreError> {
+    let mut rows = split_csv_rows(content)?.into_iter();
+
+    let Some((header_line, header)) = rows.next() else {
+        return Ok(HashMap::new());
+    };
+    if header.len() != 1 || !header[0].trim().is_empty() {
+        let joined = header.join(",");
+        if joined.trim() != HEADER {
+            return Err(CoreError::LabelParse {
+                line: header_line,
+                message: format!("expected header `{HEADER}`, found `{joined}`"),
+            });
+        }
+    }
+
+    rows.try_fold(HashMap::new(), |mut map, (line_num, fields)| {
+        if fields.len() == 1 && fields[0].trim().is_empty() {
+            return Ok(map);
+        }
+        if fields.len() != 5 {
+            return Err(CoreError::LabelParse {
+                line: line_num,
+                message: format!("expected 5 columns, found {}", fields.len()),
+            });
+        }
+
+        let label_type: Bip329Type =
+            fields[0]
+                .trim()
+                .parse()
+                .map_err(|()| CoreError::LabelParse {
+                    line: line_num,
+                    message: format!("invalid type: {}", fields[0]),
+                })?;
+        let ref_id = fields[1].clone();
+        let label = fields[2].clone();
+        let origin = (!fields[3].is_empty()).then(|| fields[3].clone());
+        let spendable = (!fields[4].trim().is_empty())
+            .then(|| fields[4].trim().parse::<bool>())
+            .transpose()
+            .map_err(|e| CoreError::LabelParse {
+                line: line_num,
+                message: format!("invalid spendable: {e}"),
+            })?;
+
+        let record = Bip329Record {
+            label_type,
+            ref_id,
+            label,
+            origin,
+            spendable,
+        };
+        let key = (record.label_type, record.ref_id.clone());
+        if map.contains_key(&key) {
+            tracing::warn!(
+                line = line_num,
+                label_type = %record.label_type,
+                ref_id = %record.ref_id,
+                "duplicate CSV entry overwrites previous value"
+            );
+        }
+        map.insert(key, record);
+        Ok(map)
+    })
+}
+
+/// Export a label map to sorted CSV. Records are ordered by (type, ref)
+/// for deterministic output, matching the JSONL exporter.
+pub fn export_map_to_csv(map: &HashMap<LabelKey, Bip329Record>) -> String {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|(k1, _), (k2, _)| k1.0.cmp(&k2.0).then_with(|| k1.1.cmp(&k2.1)));
+
+    let mut out = String::from(HEADER);
+    out.push('\n');
+    for (_, record) in entries {
+        let origin = record.origin.as_deref().unwrap_or("");
+        let spendable = record
+            .spendable
+            .map(|b| if b { "true" } else { "false" })
+            .unwrap_or("");
+        out.push_str(&quote_field(&record.label_type.to_string()));
+        out.push(',');
+        out.push_str(&quote_field(&record.ref_id));
+        out.push(',');
+        out.push_str(&quote_field(&record.label));
+        out.push(',');
+        out.push_str(&quote_field(origin));
+        out.push(',');
+        out.push_str(&quote_field(spendable));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quote a field if it contains a comma, quote, or newline, doubling any
+/// embedded quotes per standard CSV escaping.
+fn quote_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Split CSV content into rows of fields, honoring quoted fields that may
+/// contain embedded commas or newlines. Returns each row's 1-based starting
+/// line number alongside its fields, so callers can report errors the way
+/// [`CoreError::LabelParse`] expects.
+fn split_csv_rows(content: &str) -> Result<Vec<(usize, Vec<String>)>, CoreError> {
+    let mut rows = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1usize;
+    let mut row_start_line = 1usize;
+    let mut row_has_content = false;
+
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            row_has_content = true;
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_quotes = true;
+                row_has_content = true;
+            }
+            ',' => {
+                fields.push(std::mem::take(&mut field));
+                row_has_content = true;
+            }
+            '\r' => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                rows.push((row_start_line, std::mem::take(&mut fields)));
+                line += 1;
+                row_start_line = line;
+                row_has_content = false;
+            }
+            _ => {
+                field.push(c);
+                row_has_content = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err(CoreError::LabelParse {
+            line: row_start_line,
+            message: "unterminated quoted field".to_string(),
+        });
+    }
+    if row_has_content || !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        rows.push((row_start_line, fields));
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::labels::types::Bip329Type;
+
+    #[test]
+    fn parse_csv_records_basic() {
+        let csv = "type,ref,label,origin,spendable\ntx,txid1,My label,wallet1,true\n";
+        let map = parse_csv_records(csv).expect("must parse");
+        let record = map
+            .get(&(Bip329Type::Tx, "txid1".to_string()))
+            .expect("record must be present");
+        assert_eq!(record.label, "My label");
+        assert_eq!(record.origin.as_deref(), Some("wallet1"));
+        assert_eq!(record.spendable, Some(true));
+    }
+
+    #[test]
+    fn parse_csv_records_empty_origin_and_spendable() {
+        let csv = "type,ref,label,origin,spendable\naddr,bc1q...,note,,\n";
+        let map = parse_csv_records(csv).expect("must parse");
+        let record = map
+            .get(&(Bip329Type::Addr, "bc1q...".to_string()))
+            .expect("record must be present");
+        assert!(record.origin.is_none());
+        assert!(record.spendable.is_none());
+    }
+
+    #[test]
+    fn parse_csv_records_handles_quoted_commas_and_newlines() {
+        let csv = "type,ref,label,origin,spendable\ntx,txid1,\"has, a comma\nand a newline\",,\n";
+        let map = parse_csv_records(csv).expect("must parse");
+        let record = map
+            .get(&(Bip329Type::Tx, "txid1".to_string()))
+            .expect("record must be present");
+        assert_eq!(record.label, "has, a comma\nand a newline");
+    }
+
+    #[test]
+    fn parse_csv_records_rejects_wrong_column_count() {
+        let csv = "type,ref,label,origin,spendable\ntx,txid1,only three\n";
+        let err = parse_csv_records(csv).expect_err("must reject short row");
+        assert!(matches!(err, CoreError::LabelParse { line: 2, .. }));
+    }
+
+    #[test]
+    fn parse_csv_records_rejects_invalid_type() {
+        let csv = "type,ref,label,origin,spendable\nbogus,txid1,label,,\n";
+        let err = parse_csv_records(csv).expect_err("must reject invalid type");
+        assert!(matches!(err, CoreError::LabelParse { line: 2, .. }));
+    }
+
+    #[test]
+    fn parse_csv_records_rejects_unterminated_quote() {
+        let csv = "type,ref,label,origin,spendable\ntx,txid1,\"unterminated,,\n";
+        let err = parse_csv_records(csv).expect_err("must reject unterminated quote");
+        assert!(matches!(err, CoreError::LabelParse { .. }));
+    }
+
+    #[test]
+    fn export_map_to_csv_sorts_and_quotes() {
+        let mut map = HashMap::new();
+        map.insert(
+            (Bip329Type::Tx, "b".to_string()),
+            Bip329Record {
+                label_type: Bip329Type::Tx,
+                ref_id: "b".to_string(),
+                label: "has, a comma".to_string(),
+                origin: None,
+                spendable: Some(false),
+            },
+        );
+        map.insert(
+            (Bip329Type::Tx, "a".to_string()),
+            Bip329Record {
+                label_type: Bip329Type::Tx,
+                ref_id: "a".to_string(),
+                label: "plain".to_string(),
+                origin: Some("wallet".to_string()),
+                spendable: None,
+            },
+        );
+
+        let csv = export_map_to_csv(&map);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(HEADER));
+        assert_eq!(lines.next(), Some("tx,a,plain,wallet,"));
+        assert_eq!(lines.next(), Some("tx,b,\"has, a comma\",,false"));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn export_then_parse_round_trips() {
+        let mut map = HashMap::new();
+        map.insert(
+            (Bip329Type::Output, "txid:0".to_string()),
+            Bip329Record {
+                label_type: Bip329Type::Output,
+                ref_id: "txid:0".to_string(),
+                label: "change".to_string(),
+                origin: Some("m/84'/0'/0'".to_string()),
+                spendable: Some(true),
+            },
+        );
+
+        let csv = export_map_to_csv(&map);
+        let parsed = parse_csv_records(&csv).expect("must parse exported csv");
+        assert_eq!(parsed, map);
+    }
+}
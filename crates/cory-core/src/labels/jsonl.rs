@@ -55,26 +55,82 @@ pub(super) fn export_map_to_jsonl(map: &HashMap<LabelKey, Bip329Record>) -> Stri
         .collect()
 }
 
-/// Normalize a human-readable file name into a stable, lowercase,
-/// hyphen-separated identifier suitable for use as a file ID.
+/// Normalize a human-readable file name into a stable identifier: strip a
+/// trailing `.jsonl` extension and normalize `\` path separators to `/`.
+/// Folder segments and casing are otherwise preserved so IDs stay readable
+/// and round-trip through `export_file`/display unchanged.
 pub fn normalize_label_file_id(name: &str) -> String {
-    // Preserve folder structure in IDs while still normalizing each segment.
-    // This lets names like `exchanges/binance` round-trip as subfolders.
-    name.split(['/', '\\'])
-        .map(|segment| {
-            segment
-                .chars()
-                .flat_map(|c| c.to_lowercase())
-                .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
-                .collect::<String>()
-                .split('-')
-                .filter(|s| !s.is_empty())
-                .collect::<Vec<_>>()
-                .join("-")
-        })
-        .filter(|segment| !segment.is_empty())
-        .collect::<Vec<_>>()
-        .join("/")
+    name.strip_suffix(".jsonl")
+        .unwrap_or(name)
+        .replace('\\', "/")
+}
+
+// ==============================================================================
+// Structured file IDs
+// ==============================================================================
+
+/// A parsed label-file identifier, splitting a `/`-separated id into an
+/// optional namespace `scope` and a leaf name — `Exchanges/Binance Hot`
+/// parses to scope `Some("Exchanges")`, leaf `"Binance Hot"`; `Binance Hot`
+/// alone parses to scope `None`. Mirrors how structured repository/package
+/// forms split a leading namespace off a leaf component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileId {
+    pub scope: Option<String>,
+    pub leaf: String,
+}
+
+impl FileId {
+    /// The full normalized id, round-tripping back through [`FromStr`](std::str::FromStr).
+    pub fn full(&self) -> String {
+        match &self.scope {
+            Some(scope) => format!("{scope}/{}", self.leaf),
+            None => self.leaf.clone(),
+        }
+    }
+
+    /// `true` if this id's scope is `scope_prefix` itself, or nested under it
+    /// (e.g. `"Exchanges"` matches both `Exchanges/Binance Hot` and
+    /// `Exchanges/Sub/Leaf`, but not a scopeless `Binance Hot`).
+    pub fn is_in_scope(&self, scope_prefix: &str) -> bool {
+        let scope_prefix = scope_prefix.trim_end_matches('/');
+        match &self.scope {
+            Some(scope) => {
+                scope == scope_prefix || scope.starts_with(&format!("{scope_prefix}/"))
+            }
+            None => false,
+        }
+    }
+}
+
+impl std::fmt::Display for FileId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.full())
+    }
+}
+
+impl std::str::FromStr for FileId {
+    type Err = LabelStoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = normalize_label_file_id(s.trim());
+        if normalized.is_empty() {
+            return Err(LabelStoreError::EmptyFileName);
+        }
+
+        let mut segments: Vec<&str> = normalized.split('/').collect();
+        if segments.iter().any(|seg| seg.trim().is_empty()) {
+            return Err(LabelStoreError::InvalidFileId(normalized));
+        }
+
+        let leaf = segments
+            .pop()
+            .expect("split always yields at least one segment")
+            .to_string();
+        let scope = (!segments.is_empty()).then(|| segments.join("/"));
+
+        Ok(FileId { scope, leaf })
+    }
 }
 
 pub(super) struct ParsedLocalFileName {
@@ -88,19 +144,13 @@ pub(super) fn parse_local_file_name(raw: &str) -> Result<ParsedLocalFileName, La
         return Err(LabelStoreError::EmptyFileName);
     }
 
-    let name = trimmed
-        .strip_suffix(".jsonl")
-        .unwrap_or(trimmed)
-        .trim()
-        .to_string();
-    if name.is_empty() {
-        return Err(LabelStoreError::EmptyFileName);
-    }
-
-    let id = normalize_label_file_id(&name);
+    let id = normalize_label_file_id(trimmed);
     if id.is_empty() {
         return Err(LabelStoreError::EmptyFileName);
     }
 
-    Ok(ParsedLocalFileName { id, name })
+    Ok(ParsedLocalFileName {
+        id: id.clone(),
+        name: id,
+    })
 }
@@ -3,11 +3,26 @@
 //! Provides in-memory label storage with optional disk persistence,
 //! JSONL serialisation, and read-only pack file loading.
 
+mod csv;
 mod jsonl;
+mod oplog;
 mod pack;
+mod s3;
 mod store;
+mod sync;
+mod transport;
 mod types;
+pub mod watch;
 
-pub use jsonl::normalize_label_file_id;
-pub use store::LabelStore;
-pub use types::{Bip329Record, Bip329Type, LabelFile, LabelFileKind, LabelStoreError};
+pub use csv::{export_map_to_csv, parse_csv_records};
+pub use jsonl::{normalize_label_file_id, FileId};
+pub use oplog::{HybridClock, LabelOp, OpKind};
+pub use s3::{S3Config, S3Transport, S3UrlStyle};
+pub use store::{LabelStore, ResolvedLabel};
+pub use sync::{ManifestEntry, SyncConflict, SyncOutcome};
+pub use transport::{DirEntry, LocalTransport, ReadOnlyTransport, Transport};
+pub use types::{
+    Bip329Record, Bip329Type, LabelDiff, LabelFile, LabelFileKind, LabelStoreError,
+    ValidateProblem, ValidateStats,
+};
+pub use watch::{LabelChangeEvent, LabelWatcher};
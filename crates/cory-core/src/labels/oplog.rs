@@ -0,0 +1,390 @@
+//! Append-only operation log backing [`super::types::LabelFile`].
+//!
+//! Each `set`/`delete` is recorded as a [`LabelOp`] rather than applied
+//! directly to a map, so two independently edited copies of the same file
+//! (e.g. edited on two machines) can be reconciled deterministically via
+//! [`merge`], and the history of a single `(type, ref)` key can be recovered
+//! via [`LabelOp::key`] instead of only ever seeing the current winner.
+//!
+//! Ordering uses a [`HybridClock`]: wall-clock millis give ops a roughly
+//! chronological order across stores, and the per-store counter breaks ties
+//! (and keeps ordering monotonic even if the wall clock itself steps
+//! backward, e.g. after an NTP correction). The materialized value for a
+//! key is always the op with the highest `(clock, op_id)`, so replay order
+//! doesn't matter — [`materialize`] produces the same map no matter what
+//! order the log is iterated in.
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bitcoin::hashes::{sha256, Hash};
+use serde::{Deserialize, Serialize};
+
+use crate::error::CoreError;
+
+use super::types::{Bip329Record, Bip329Type, LabelKey};
+
+// ==============================================================================
+// Hybrid logical clock
+// ==============================================================================
+
+/// Wall-clock millis paired with a per-store monotonic counter, so two ops
+/// stamped in the same millisecond (or across a backward clock step) still
+/// compare unambiguously. Compares lexicographically: `millis` first, then
+/// `counter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct HybridClock {
+    pub millis: u64,
+    pub counter: u64,
+}
+
+/// Stamps monotonically increasing [`HybridClock`] values for one
+/// [`super::LabelStore`]. The counter only resets when the wall clock
+/// advances past the last-seen value, so a clock that jumps backward (or
+/// stays flat, e.g. two ops in the same millisecond) still produces
+/// strictly increasing clocks.
+#[derive(Debug, Default)]
+pub(super) struct ClockSource {
+    last_millis: u64,
+    last_counter: u64,
+}
+
+impl ClockSource {
+    pub(super) fn tick(&mut self) -> HybridClock {
+        let millis = now_millis();
+        if millis > self.last_millis {
+            self.last_millis = millis;
+            self.last_counter = 0;
+        } else {
+            self.last_counter += 1;
+        }
+        HybridClock {
+            millis: self.last_millis,
+            counter: self.last_counter,
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// ==============================================================================
+// Label operations
+// ==============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpKind {
+    Set,
+    Delete,
+}
+
+/// A single recorded mutation to a label file. The authoritative state of a
+/// [`super::types::LabelFile`] is the log of these, not a map — see
+/// [`materialize`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelOp {
+    pub op: OpKind,
+    #[serde(rename = "type")]
+    pub label_type: Bip329Type,
+    #[serde(rename = "ref")]
+    pub ref_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub clock: HybridClock,
+    /// Content hash of every other field, used to dedup identical ops
+    /// across logs in [`merge`] and to break ties between ops stamped with
+    /// the same `clock`.
+    pub op_id: String,
+}
+
+impl LabelOp {
+    pub fn new_set(
+        label_type: Bip329Type,
+        ref_id: String,
+        label: String,
+        clock: HybridClock,
+    ) -> Self {
+        Self::new(OpKind::Set, label_type, ref_id, Some(label), clock)
+    }
+
+    pub fn new_delete(label_type: Bip329Type, ref_id: String, clock: HybridClock) -> Self {
+        Self::new(OpKind::Delete, label_type, ref_id, None, clock)
+    }
+
+    fn new(
+        op: OpKind,
+        label_type: Bip329Type,
+        ref_id: String,
+        label: Option<String>,
+        clock: HybridClock,
+    ) -> Self {
+        let op_id = content_hash(op, label_type, &ref_id, label.as_deref(), clock);
+        Self {
+            op,
+            label_type,
+            ref_id,
+            label,
+            clock,
+            op_id,
+        }
+    }
+
+    /// The `(type, ref)` this op applies to.
+    pub fn key(&self) -> LabelKey {
+        (self.label_type, self.ref_id.clone())
+    }
+}
+
+fn content_hash(
+    op: OpKind,
+    label_type: Bip329Type,
+    ref_id: &str,
+    label: Option<&str>,
+    clock: HybridClock,
+) -> String {
+    let op_tag = match op {
+        OpKind::Set => "set",
+        OpKind::Delete => "delete",
+    };
+    let preimage = format!(
+        "{op_tag}\0{label_type}\0{ref_id}\0{}\0{}\0{}",
+        label.unwrap_or(""),
+        clock.millis,
+        clock.counter
+    );
+    sha256::Hash::hash(preimage.as_bytes()).to_string()
+}
+
+// ==============================================================================
+// Replay and merge
+// ==============================================================================
+
+/// Replay a log into the map of currently effective records. For each key,
+/// the winner is the op with the highest `(clock, op_id)` (ties broken by
+/// the lexicographically larger `op_id`, since `op_id` is a content hash
+/// with no other ordering meaning); a `Delete`-kind winner leaves the key
+/// absent from the result (a tombstone), even if an earlier `Set` for the
+/// same key exists elsewhere in the log.
+pub fn materialize(log: &[LabelOp]) -> HashMap<LabelKey, Bip329Record> {
+    let mut winners: HashMap<LabelKey, &LabelOp> = HashMap::new();
+
+    for op in log {
+        match winners.get(&op.key()) {
+            Some(current) if (current.clock, &current.op_id) >= (op.clock, &op.op_id) => {}
+            _ => {
+                winners.insert(op.key(), op);
+            }
+        }
+    }
+
+    winners
+        .into_iter()
+        .filter_map(|(key, op)| match op.op {
+            OpKind::Delete => None,
+            OpKind::Set => Some((
+                key,
+                Bip329Record {
+                    label_type: op.label_type,
+                    ref_id: op.ref_id.clone(),
+                    label: op.label.clone().unwrap_or_default(),
+                    origin: None,
+                    spendable: None,
+                },
+            )),
+        })
+        .collect()
+}
+
+/// Union two logs by `op_id` (deduplicating ops both sides already share)
+/// and return them sorted by `(clock, op_id)`, so two stores merging the
+/// same pair of logs in either order end up with byte-identical results
+/// regardless of each log's original insertion order.
+pub fn merge(a: &[LabelOp], b: &[LabelOp]) -> Vec<LabelOp> {
+    let mut by_id: HashMap<&str, &LabelOp> = HashMap::new();
+    for op in a.iter().chain(b.iter()) {
+        by_id.entry(op.op_id.as_str()).or_insert(op);
+    }
+
+    let mut merged: Vec<LabelOp> = by_id.into_values().cloned().collect();
+    merged.sort_by(|x, y| x.clock.cmp(&y.clock).then_with(|| x.op_id.cmp(&y.op_id)));
+    merged
+}
+
+/// Synthesize a baseline log for a label map that was loaded from a plain
+/// flat JSONL file and so has no recorded history of its own. Each record
+/// becomes a single `Set` stamped at clock zero, in sorted key order, so
+/// repeated seeding of the same map is deterministic and stays out of the
+/// way of any real op stamped afterward (which always sorts later).
+pub(super) fn seed_ops(labels: &HashMap<LabelKey, Bip329Record>) -> Vec<LabelOp> {
+    let mut entries: Vec<_> = labels.values().collect();
+    entries.sort_by(|a, b| {
+        a.label_type
+            .cmp(&b.label_type)
+            .then_with(|| a.ref_id.cmp(&b.ref_id))
+    });
+
+    let zero = HybridClock {
+        millis: 0,
+        counter: 0,
+    };
+    entries
+        .into_iter()
+        .map(|record| {
+            LabelOp::new_set(
+                record.label_type,
+                record.ref_id.clone(),
+                record.label.clone(),
+                zero,
+            )
+        })
+        .collect()
+}
+
+/// The effective log for a file: its own recorded log if non-empty,
+/// otherwise a baseline synthesized from its materialized labels (e.g. a
+/// `PersistentRw`/`PersistentRo` file loaded from a flat JSONL file with no
+/// `.ops.jsonl` sidecar next to it). Used for read paths
+/// ([`super::LabelStore::history_for`], sidecar export) that shouldn't
+/// require a prior mutation to have seeded `LabelFile::log` first.
+pub(super) fn effective_ops(
+    log: &[LabelOp],
+    labels: &HashMap<LabelKey, Bip329Record>,
+) -> Vec<LabelOp> {
+    if log.is_empty() {
+        seed_ops(labels)
+    } else {
+        log.to_vec()
+    }
+}
+
+// ==============================================================================
+// JSONL sidecar persistence
+// ==============================================================================
+
+/// Serialize a log to JSONL, one op per line, for the `.ops.jsonl` sidecar
+/// written alongside a `PersistentRw` file's flat export.
+pub(super) fn export_log_to_jsonl(log: &[LabelOp]) -> String {
+    log.iter()
+        .map(|op| serde_json::to_string(op).expect("valid JSON"))
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+/// Path of the write-ahead journal for a `PersistentRw` file's flush: the
+/// log about to be written is recorded here first, so a crash between
+/// writing the flat content file and its `.ops.jsonl` sidecar leaves
+/// evidence that [`super::pack::recover_journal`] can replay on the next
+/// load instead of silently leaving the pair inconsistent.
+pub(super) fn journal_path(path: &std::path::Path) -> std::path::PathBuf {
+    path.with_extension("journal")
+}
+
+/// Parse a `.ops.jsonl` sidecar back into a log, skipping empty lines.
+pub(super) fn parse_log_jsonl(content: &str) -> Result<Vec<LabelOp>, CoreError> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_num, line)| {
+            serde_json::from_str(line.trim()).map_err(|e| CoreError::LabelParse {
+                line: line_num + 1,
+                message: e.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(millis: u64, counter: u64) -> HybridClock {
+        HybridClock { millis, counter }
+    }
+
+    #[test]
+    fn materialize_prefers_higher_clock() {
+        let older = LabelOp::new_set(Bip329Type::Tx, "abc".into(), "old".into(), clock(1, 0));
+        let newer = LabelOp::new_set(Bip329Type::Tx, "abc".into(), "new".into(), clock(2, 0));
+
+        let map = materialize(&[older, newer]);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map[&(Bip329Type::Tx, "abc".into())].label, "new");
+    }
+
+    #[test]
+    fn materialize_is_insertion_order_independent() {
+        let older = LabelOp::new_set(Bip329Type::Tx, "abc".into(), "old".into(), clock(1, 0));
+        let newer = LabelOp::new_set(Bip329Type::Tx, "abc".into(), "new".into(), clock(2, 0));
+
+        let forward = materialize(&[older.clone(), newer.clone()]);
+        let backward = materialize(&[newer, older]);
+        assert_eq!(
+            forward[&(Bip329Type::Tx, "abc".into())].label,
+            backward[&(Bip329Type::Tx, "abc".into())].label
+        );
+    }
+
+    #[test]
+    fn delete_tombstones_an_earlier_set() {
+        let set = LabelOp::new_set(Bip329Type::Tx, "abc".into(), "label".into(), clock(1, 0));
+        let delete = LabelOp::new_delete(Bip329Type::Tx, "abc".into(), clock(2, 0));
+
+        let map = materialize(&[set, delete]);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn a_set_after_a_delete_resurrects_the_key() {
+        let delete = LabelOp::new_delete(Bip329Type::Tx, "abc".into(), clock(1, 0));
+        let set = LabelOp::new_set(Bip329Type::Tx, "abc".into(), "back".into(), clock(2, 0));
+
+        let map = materialize(&[delete, set]);
+        assert_eq!(map[&(Bip329Type::Tx, "abc".into())].label, "back");
+    }
+
+    #[test]
+    fn tie_broken_by_larger_op_id() {
+        let a = LabelOp::new_set(Bip329Type::Tx, "abc".into(), "a".into(), clock(1, 0));
+        let b = LabelOp::new_set(Bip329Type::Tx, "abc".into(), "b".into(), clock(1, 0));
+
+        let expected_winner = if a.op_id > b.op_id { &a } else { &b };
+        let map = materialize(&[a.clone(), b.clone()]);
+        assert_eq!(
+            map[&(Bip329Type::Tx, "abc".into())].label,
+            expected_winner.label.clone().unwrap()
+        );
+    }
+
+    #[test]
+    fn merge_dedups_by_op_id_and_sorts_deterministically() {
+        let shared = LabelOp::new_set(Bip329Type::Tx, "abc".into(), "shared".into(), clock(1, 0));
+        let only_a = LabelOp::new_set(Bip329Type::Addr, "x".into(), "a-only".into(), clock(2, 0));
+        let only_b = LabelOp::new_set(Bip329Type::Addr, "y".into(), "b-only".into(), clock(0, 5));
+
+        let log_a = vec![shared.clone(), only_a.clone()];
+        let log_b = vec![only_b.clone(), shared.clone()];
+
+        let merged_ab = merge(&log_a, &log_b);
+        let merged_ba = merge(&log_b, &log_a);
+        assert_eq!(merged_ab, merged_ba);
+        assert_eq!(merged_ab.len(), 3);
+    }
+
+    #[test]
+    fn jsonl_round_trips() {
+        let log = vec![
+            LabelOp::new_set(Bip329Type::Tx, "abc".into(), "label".into(), clock(1, 0)),
+            LabelOp::new_delete(Bip329Type::Addr, "xyz".into(), clock(2, 3)),
+        ];
+
+        let text = export_log_to_jsonl(&log);
+        let parsed = parse_log_jsonl(&text).expect("valid sidecar");
+        assert_eq!(parsed, log);
+    }
+}
@@ -2,59 +2,132 @@
 //! files from a directory tree, parameterized by label file kind.
 
 use std::collections::HashSet;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::error::CoreError;
 
-use super::jsonl::parse_jsonl_records;
-use super::types::{LabelFile, LabelFileKind};
+use super::jsonl::{export_map_to_jsonl, parse_jsonl_records};
+use super::oplog::{export_log_to_jsonl, journal_path, materialize, parse_log_jsonl};
+use super::transport::Transport;
+use super::types::{Bip329Type, LabelFile, LabelFileKind};
+
+/// Name of the optional manifest file composing a pack directory.
+const MANIFEST_FILE_NAME: &str = "pack.manifest";
+
+/// Called just before each candidate `.jsonl` file is read, with the
+/// running file/record counts and the path about to be loaded. Returning
+/// `Err` (e.g. [`CoreError::Cancelled`]) aborts the walk before that file
+/// is touched, so a cancellation always lands between files rather than
+/// mid-file. Used by [`crate::jobs::rescan_dir`] to report live progress
+/// and support cooperative cancellation on an otherwise-synchronous walk;
+/// `None` for the ordinary startup load path.
+pub(super) type WalkCheckpoint<'a> = &'a dyn Fn(usize, usize, &Path) -> Result<(), CoreError>;
 
 /// Recursively walk a directory, loading `.jsonl` files as label files
 /// of the given `kind`. The caller provides a `base` path (the CLI arg
 /// directory) and the set of IDs already seen across all three kinds so
-/// cross-kind collisions are detected.
+/// cross-kind collisions are detected. All I/O goes through `transport`,
+/// so this is testable without a real filesystem.
 pub(super) fn walk_label_dir(
+    transport: &dyn Transport,
     base: &Path,
     current: &Path,
     kind: LabelFileKind,
     files: &mut Vec<LabelFile>,
     seen_ids: &mut HashSet<String>,
+    checkpoint: Option<WalkCheckpoint>,
 ) -> Result<(), CoreError> {
-    // Sort directory entries by path for deterministic load order across
-    // platforms and filesystems.
-    let mut entries: Vec<_> = std::fs::read_dir(current)?.collect::<Result<Vec<_>, _>>()?;
-    entries.sort_by_key(|e| e.path());
+    // `list_dir` already returns entries sorted by path for deterministic
+    // load order across platforms and transports.
+    let entries = transport.list_dir(current)?;
 
     entries.into_iter().try_for_each(|entry| {
-        let path = entry.path();
-        if path.is_dir() {
-            walk_label_dir(base, &path, kind, files, seen_ids)?;
+        if entry.is_dir {
+            walk_label_dir(transport, base, &entry.path, kind, files, seen_ids, checkpoint)?;
             return Ok(());
         }
 
-        if path.extension().is_none_or(|ext| ext != "jsonl") {
+        if entry.path.extension().is_none_or(|ext| ext != "jsonl") {
+            return Ok(());
+        }
+        // The op-log sidecar written by `LabelStore::flush_file` sits next
+        // to its flat file as `<id>.ops.jsonl`; it's read by
+        // `load_single_label_file` below, not walked as a label file of
+        // its own.
+        if entry
+            .path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().ends_with(".ops.jsonl"))
+        {
             return Ok(());
         }
 
-        load_single_label_file(base, &path, kind, files, seen_ids)
+        if let Some(checkpoint) = checkpoint {
+            let records_so_far = files.iter().map(LabelFile::record_count).sum();
+            checkpoint(files.len(), records_so_far, &entry.path)?;
+        }
+
+        load_single_label_file(transport, base, &entry.path, kind, files, seen_ids)
     })
 }
 
+/// Detect and repair a leftover write-ahead journal next to `path` from a
+/// crash mid-`flush_file`. The journal, if present, always carries the full
+/// log that was about to be written — so recovery is just re-running the
+/// flush's writes from it (idempotent whether the original crash happened
+/// before or after the content file landed) and then deleting the journal.
+/// A missing journal is the common case and isn't an error.
+fn recover_journal(transport: &dyn Transport, path: &Path) -> Result<(), CoreError> {
+    let journal = journal_path(path);
+    let bytes = match transport.read_file(&journal) {
+        Ok(bytes) => bytes,
+        Err(_) => return Ok(()),
+    };
+
+    let content = String::from_utf8(bytes).map_err(|e| CoreError::JournalRecovery {
+        path: journal.display().to_string(),
+        message: format!("journal is not valid UTF-8: {e}"),
+    })?;
+    let ops = parse_log_jsonl(&content).map_err(|e| CoreError::JournalRecovery {
+        path: journal.display().to_string(),
+        message: e.to_string(),
+    })?;
+
+    let labels = materialize(&ops);
+    transport.write_file(path, export_map_to_jsonl(&labels).as_bytes())?;
+    transport.write_file(
+        &path.with_extension("ops.jsonl"),
+        export_log_to_jsonl(&ops).as_bytes(),
+    )?;
+    transport.remove_file(&journal)?;
+    Ok(())
+}
+
+/// Derive a file ID from `path` relative to `base`: strip the `.jsonl`
+/// extension and normalize `\` to `/`. Shared by the directory walk and by
+/// [`super::store::LabelStore`]'s incremental watch-event handling, so a
+/// file picked up by a live watcher gets exactly the same ID it would
+/// have gotten from a full rescan.
+pub(super) fn relative_label_file_id(base: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(base).unwrap_or(path);
+    relative
+        .with_extension("")
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
 /// Load a single `.jsonl` file. The file ID is derived from the path
 /// relative to `base`, with the `.jsonl` extension stripped and `\`
 /// normalized to `/`. Duplicate IDs (across any kind) are rejected.
-fn load_single_label_file(
+pub(super) fn load_single_label_file(
+    transport: &dyn Transport,
     base: &Path,
     path: &Path,
     kind: LabelFileKind,
     files: &mut Vec<LabelFile>,
     seen_ids: &mut HashSet<String>,
 ) -> Result<(), CoreError> {
-    let relative = path.strip_prefix(base).unwrap_or(path);
-    let id = relative
-        .with_extension("")
-        .to_string_lossy()
-        .replace('\\', "/");
+    let id = relative_label_file_id(base, path);
 
     if id.is_empty() {
         return Ok(());
@@ -67,16 +140,42 @@ fn load_single_label_file(
         });
     }
 
-    let content = std::fs::read_to_string(path)?;
+    if kind == LabelFileKind::PersistentRw {
+        recover_journal(transport, path)?;
+    }
+
+    let content = transport.read_file(path)?;
+    let content = String::from_utf8(content).map_err(|e| CoreError::LabelParse {
+        line: 0,
+        message: format!("label file `{id}` is not valid UTF-8: {e}"),
+    })?;
     let labels = parse_jsonl_records(&content)?;
 
     let (editable, source_path) = match kind {
-        LabelFileKind::PersistentRw => (true, Some(path.to_path_buf())),
+        // A PersistentRw directory is normally editable, but defers to the
+        // transport if it can't actually honor writes (e.g. a remote
+        // backend reached with read-only credentials), so the file comes
+        // back correctly marked instead of failing on first save.
+        LabelFileKind::PersistentRw => (transport.supports_write(), Some(path.to_path_buf())),
         // PersistentRo don't have a source path, so that there's no way we can write to them
         LabelFileKind::PersistentRo => (false, None),
         // BrowserRw files are never loaded from disk via walk_label_dir,
         // but handle the variant for completeness.
         LabelFileKind::BrowserRw => (true, None),
+        // WalletDerived entries are synthesized at graph-scan time, never
+        // loaded from a pack directory, but handled for completeness.
+        LabelFileKind::WalletDerived => (false, None),
+    };
+
+    // Restore the op-log sidecar if a previous flush left one next to this
+    // file, so history/merge continuity survives a process restart. Its
+    // absence (a fresh flat file, or one never edited through the log)
+    // just leaves `log` empty — reads fall back to a synthesized baseline,
+    // see `oplog::effective_ops`.
+    let ops_path = path.with_extension("ops.jsonl");
+    let log = match transport.read_file(&ops_path) {
+        Ok(bytes) => parse_log_jsonl(&String::from_utf8_lossy(&bytes))?,
+        Err(_) => Vec::new(),
     };
 
     files.push(LabelFile {
@@ -86,12 +185,192 @@ fn load_single_label_file(
         editable,
         source_path,
         labels,
+        log,
+        undone: Vec::new(),
+        session_op_count: 0,
     });
     Ok(())
 }
 
+// ==============================================================================
+// Pack manifests (%include / %unset composition)
+// ==============================================================================
+
+/// Parsed directives from a `pack.manifest` file.
+struct Manifest {
+    /// `%include <relative-path-or-dir>` lines, in file order.
+    includes: Vec<PathBuf>,
+    /// `%unset <type>:<ref>` lines, applied after includes are loaded.
+    unsets: Vec<(Bip329Type, String)>,
+}
+
+fn parse_manifest(content: &str) -> Result<Manifest, CoreError> {
+    let mut includes = Vec::new();
+    let mut unsets = Vec::new();
+
+    for (line_num, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("%include ") {
+            includes.push(PathBuf::from(rest.trim()));
+        } else if let Some(rest) = line.strip_prefix("%unset ") {
+            let rest = rest.trim();
+            let (type_part, ref_part) =
+                rest.split_once(':').ok_or_else(|| CoreError::LabelParse {
+                    line: line_num + 1,
+                    message: format!("malformed %unset directive: `{rest}` (expected `type:ref`)"),
+                })?;
+            let label_type: Bip329Type = type_part.parse().map_err(|_| CoreError::LabelParse {
+                line: line_num + 1,
+                message: format!("unknown label type `{type_part}` in %unset directive"),
+            })?;
+            unsets.push((label_type, ref_part.to_string()));
+        } else {
+            return Err(CoreError::LabelParse {
+                line: line_num + 1,
+                message: format!("unknown pack.manifest directive: `{line}`"),
+            });
+        }
+    }
+
+    Ok(Manifest { includes, unsets })
+}
+
+/// Load the `.jsonl` files directly inside `dir` (not its subdirectories).
+/// Used for manifest-composed packs, where subtrees are pulled in
+/// explicitly via `%include` instead of an implicit recursive walk.
+fn load_flat_jsonl_files(
+    transport: &dyn Transport,
+    base: &Path,
+    dir: &Path,
+    kind: LabelFileKind,
+    files: &mut Vec<LabelFile>,
+    seen_ids: &mut HashSet<String>,
+    checkpoint: Option<WalkCheckpoint>,
+) -> Result<(), CoreError> {
+    transport.list_dir(dir)?.into_iter().try_for_each(|entry| {
+        if entry.is_dir || entry.path.extension().is_none_or(|ext| ext != "jsonl") {
+            return Ok(());
+        }
+        if entry
+            .path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().ends_with(".ops.jsonl"))
+        {
+            return Ok(());
+        }
+        if let Some(checkpoint) = checkpoint {
+            let records_so_far = files.iter().map(LabelFile::record_count).sum();
+            checkpoint(files.len(), records_so_far, &entry.path)?;
+        }
+        load_single_label_file(transport, base, &entry.path, kind, files, seen_ids)
+    })
+}
+
+/// Load a pack directory, composing it via its optional `pack.manifest`.
+///
+/// If `dir` has no `pack.manifest`, this behaves exactly like
+/// [`walk_label_dir`]: every `.jsonl` file in the tree is unioned in.
+/// If a manifest is present, `%include` lines pull in other pack trees
+/// (recursively, with cycle detection) in the order they appear, this
+/// directory's own `.jsonl` files are loaded, and finally `%unset type:ref`
+/// lines remove matching `(Bip329Type, ref_id)` entries that this pack
+/// (including everything it pulled in) contributed — letting a downstream
+/// manifest override a shared community pack without editing it.
+pub(super) fn load_pack_dir(
+    transport: &dyn Transport,
+    dir: &Path,
+    kind: LabelFileKind,
+    files: &mut Vec<LabelFile>,
+    seen_ids: &mut HashSet<String>,
+    checkpoint: Option<WalkCheckpoint>,
+) -> Result<(), CoreError> {
+    let mut visiting = HashSet::new();
+    load_pack_dir_inner(transport, dir, kind, files, seen_ids, &mut visiting, checkpoint)
+}
+
+/// Lexically collapse `.`/`..` components without touching the
+/// filesystem, so two paths to the same directory (e.g. `vendor` and
+/// `vendor/.`) are recognized as identical for cycle detection.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                result.pop();
+            }
+            other => result.push(other.as_os_str()),
+        }
+    }
+    result
+}
+
+fn load_pack_dir_inner(
+    transport: &dyn Transport,
+    dir: &Path,
+    kind: LabelFileKind,
+    files: &mut Vec<LabelFile>,
+    seen_ids: &mut HashSet<String>,
+    visiting: &mut HashSet<PathBuf>,
+    checkpoint: Option<WalkCheckpoint>,
+) -> Result<(), CoreError> {
+    let normalized = normalize_path(dir);
+    if !visiting.insert(normalized.clone()) {
+        return Err(CoreError::LabelParse {
+            line: 0,
+            message: format!("cycle detected including pack dir `{}`", dir.display()),
+        });
+    }
+
+    let manifest = match transport.read_file(&dir.join(MANIFEST_FILE_NAME)) {
+        Ok(bytes) => {
+            let content = String::from_utf8(bytes).map_err(|e| CoreError::LabelParse {
+                line: 0,
+                message: format!("pack.manifest at {} is not valid UTF-8: {e}", dir.display()),
+            })?;
+            Some(parse_manifest(&content)?)
+        }
+        Err(_) => None,
+    };
+
+    let Some(manifest) = manifest else {
+        visiting.remove(&normalized);
+        return walk_label_dir(transport, dir, dir, kind, files, seen_ids, checkpoint);
+    };
+
+    let range_start = files.len();
+
+    for include in &manifest.includes {
+        load_pack_dir_inner(
+            transport,
+            &dir.join(include),
+            kind,
+            files,
+            seen_ids,
+            visiting,
+            checkpoint,
+        )?;
+    }
+    load_flat_jsonl_files(transport, dir, dir, kind, files, seen_ids, checkpoint)?;
+
+    for (label_type, ref_id) in &manifest.unsets {
+        let key = (*label_type, ref_id.clone());
+        for file in files[range_start..].iter_mut() {
+            file.labels.remove(&key);
+        }
+    }
+
+    visiting.remove(&normalized);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
+    use super::super::transport::LocalTransport;
     use super::*;
 
     #[test]
@@ -114,11 +393,13 @@ mod tests {
         let mut files = Vec::new();
         let mut seen_ids = HashSet::new();
         walk_label_dir(
+            &LocalTransport::new(),
             &base,
             &base,
             LabelFileKind::PersistentRo,
             &mut files,
             &mut seen_ids,
+            None,
         )
         .expect("load label dir");
 
@@ -150,11 +431,13 @@ mod tests {
         let mut files = Vec::new();
         let mut seen_ids = HashSet::new();
         walk_label_dir(
+            &LocalTransport::new(),
             &base,
             &base,
             LabelFileKind::PersistentRw,
             &mut files,
             &mut seen_ids,
+            None,
         )
         .expect("load label dir");
 
@@ -165,4 +448,180 @@ mod tests {
 
         std::fs::remove_dir_all(&base).expect("cleanup test dir");
     }
+
+    fn unique_test_dir(label: &str) -> std::path::PathBuf {
+        std::path::Path::new("tmp").join(format!(
+            "{label}-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("system time before unix epoch")
+                .as_nanos()
+        ))
+    }
+
+    #[test]
+    fn manifest_include_composes_another_pack_tree() {
+        let base = unique_test_dir("manifest-include");
+        let vendor = base.join("vendor");
+        std::fs::create_dir_all(&vendor).expect("create vendor dir");
+        std::fs::write(
+            vendor.join("shared.jsonl"),
+            r#"{"type":"tx","ref":"abc","label":"Shared"}"#,
+        )
+        .expect("write vendor file");
+        std::fs::write(base.join("pack.manifest"), "%include vendor\n").expect("write manifest");
+
+        let mut files = Vec::new();
+        let mut seen_ids = HashSet::new();
+        load_pack_dir(
+            &LocalTransport::new(),
+            &base,
+            LabelFileKind::PersistentRo,
+            &mut files,
+            &mut seen_ids,
+            None,
+        )
+        .expect("load pack dir");
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].id, "shared");
+
+        std::fs::remove_dir_all(&base).expect("cleanup test dir");
+    }
+
+    #[test]
+    fn manifest_unset_removes_included_entry() {
+        let base = unique_test_dir("manifest-unset");
+        let vendor = base.join("vendor");
+        std::fs::create_dir_all(&vendor).expect("create vendor dir");
+        std::fs::write(
+            vendor.join("shared.jsonl"),
+            "{\"type\":\"tx\",\"ref\":\"abc\",\"label\":\"Shared\"}\n{\"type\":\"tx\",\"ref\":\"def\",\"label\":\"Keep\"}",
+        )
+        .expect("write vendor file");
+        std::fs::write(
+            base.join("pack.manifest"),
+            "%include vendor\n%unset tx:abc\n",
+        )
+        .expect("write manifest");
+
+        let mut files = Vec::new();
+        let mut seen_ids = HashSet::new();
+        load_pack_dir(
+            &LocalTransport::new(),
+            &base,
+            LabelFileKind::PersistentRo,
+            &mut files,
+            &mut seen_ids,
+            None,
+        )
+        .expect("load pack dir");
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].record_count(), 1);
+
+        std::fs::remove_dir_all(&base).expect("cleanup test dir");
+    }
+
+    #[test]
+    fn manifest_cycle_is_rejected() {
+        let base = unique_test_dir("manifest-cycle");
+        std::fs::create_dir_all(&base).expect("create test dir");
+        std::fs::write(base.join("pack.manifest"), "%include .\n").expect("write manifest");
+
+        let mut files = Vec::new();
+        let mut seen_ids = HashSet::new();
+        let result = load_pack_dir(
+            &LocalTransport::new(),
+            &base,
+            LabelFileKind::PersistentRo,
+            &mut files,
+            &mut seen_ids,
+            None,
+        );
+        assert!(result.is_err());
+
+        std::fs::remove_dir_all(&base).expect("cleanup test dir");
+    }
+
+    #[test]
+    fn dir_without_manifest_behaves_like_plain_walk() {
+        let base = unique_test_dir("no-manifest");
+        std::fs::create_dir_all(&base).expect("create test dir");
+        std::fs::write(
+            base.join("wallet.jsonl"),
+            r#"{"type":"tx","ref":"abc","label":"Plain"}"#,
+        )
+        .expect("write label file");
+
+        let mut files = Vec::new();
+        let mut seen_ids = HashSet::new();
+        load_pack_dir(
+            &LocalTransport::new(),
+            &base,
+            LabelFileKind::PersistentRo,
+            &mut files,
+            &mut seen_ids,
+            None,
+        )
+        .expect("load pack dir");
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].id, "wallet");
+
+        std::fs::remove_dir_all(&base).expect("cleanup test dir");
+    }
+
+    #[test]
+    fn leftover_journal_is_replayed_on_load() {
+        use super::super::oplog::{HybridClock, LabelOp};
+
+        let base = unique_test_dir("journal-recovery");
+        std::fs::create_dir_all(&base).expect("create test dir");
+        let file = base.join("wallet.jsonl");
+        // Simulate a crash right after the journal was written but before
+        // either the content file or its `.ops.jsonl` sidecar landed: the
+        // flat file on disk is stale, and the sidecar doesn't exist yet.
+        std::fs::write(&file, r#"{"type":"tx","ref":"abc","label":"Stale"}"#)
+            .expect("write stale label file");
+        let journal_op = LabelOp::new_set(
+            Bip329Type::Tx,
+            "abc".to_string(),
+            "Recovered".to_string(),
+            HybridClock {
+                millis: 1,
+                counter: 0,
+            },
+        );
+        std::fs::write(
+            journal_path(&file),
+            export_log_to_jsonl(std::slice::from_ref(&journal_op)),
+        )
+        .expect("write journal");
+
+        let mut files = Vec::new();
+        let mut seen_ids = HashSet::new();
+        walk_label_dir(
+            &LocalTransport::new(),
+            &base,
+            &base,
+            LabelFileKind::PersistentRw,
+            &mut files,
+            &mut seen_ids,
+            None,
+        )
+        .expect("load label dir");
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].record_count(), 1);
+        let record = files[0]
+            .labels
+            .get(&(Bip329Type::Tx, "abc".to_string()))
+            .expect("recovered record");
+        assert_eq!(record.label, "Recovered");
+        assert!(!journal_path(&file).exists());
+
+        std::fs::remove_dir_all(&base).expect("cleanup test dir");
+    }
 }
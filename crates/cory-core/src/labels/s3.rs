@@ -0,0 +1,496 @@
+//! S3-compatible object-store [`Transport`] for label persistence.
+//!
+//! Signs every request with AWS Signature Version 4 and issues it over
+//! blocking HTTP, matching [`super::transport::LocalTransport`]'s own use
+//! of blocking `std::fs` calls to satisfy the synchronous [`Transport`]
+//! contract — callers already accept that trade-off for local disk I/O, and
+//! a remote object store is no different from `LabelStore`'s point of view.
+//!
+//! SigV4 is implemented by hand (HMAC-SHA256 built from
+//! [`bitcoin::hashes::sha256`], already a dependency via the rest of the
+//! crate) rather than pulling in a dedicated AWS SDK or `hmac`/`sha2` crate.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bitcoin::hashes::{sha256, Hash};
+
+use crate::error::CoreError;
+
+use super::transport::{DirEntry, Transport};
+
+const SHA256_BLOCK_SIZE: usize = 64;
+
+/// How bucket-relative object keys are turned into request URLs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum S3UrlStyle {
+    /// `https://{bucket}.{host}/{key}` — the default for AWS S3 itself.
+    VirtualHost,
+    /// `https://{host}/{bucket}/{key}` — required by most self-hosted
+    /// S3-compatible stores (MinIO, Ceph RGW) that don't do bucket-based
+    /// virtual-host routing.
+    Path,
+}
+
+/// Configuration for an [`S3Transport`].
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    /// `scheme://host[:port]` of the S3-compatible endpoint, with no
+    /// trailing slash, e.g. `https://s3.us-east-1.amazonaws.com` or
+    /// `https://minio.example.com:9000`.
+    pub endpoint: String,
+    pub bucket: String,
+    /// Key prefix prepended to every path this transport is asked to read
+    /// or write, so a single bucket can host more than one Cory instance's
+    /// label files without collisions.
+    pub prefix: String,
+    /// SigV4 region, e.g. `us-east-1`. Self-hosted stores that don't check
+    /// the region still require *some* value to sign with.
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub url_style: S3UrlStyle,
+}
+
+/// [`Transport`] backed by an S3-compatible object store.
+#[derive(Debug, Clone)]
+pub struct S3Transport {
+    config: S3Config,
+    client: reqwest::blocking::Client,
+}
+
+impl S3Transport {
+    pub fn new(config: S3Config) -> Self {
+        Self {
+            config,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    fn object_key(&self, path: &Path) -> String {
+        let suffix = path.to_string_lossy().replace('\\', "/");
+        let prefix = self.config.prefix.trim_matches('/');
+        if prefix.is_empty() {
+            suffix.trim_start_matches('/').to_string()
+        } else {
+            format!("{prefix}/{}", suffix.trim_start_matches('/'))
+        }
+    }
+
+    fn host(&self) -> Result<String, CoreError> {
+        self.config
+            .endpoint
+            .split_once("://")
+            .map(|(_, rest)| rest.to_string())
+            .ok_or_else(|| {
+                CoreError::ObjectStore(format!(
+                    "endpoint `{}` is missing a scheme",
+                    self.config.endpoint
+                ))
+            })
+    }
+
+    /// Full request URL and the `Host` header value to sign against, for a
+    /// given object key (empty for a bucket-level `ListObjectsV2` call).
+    fn request_url(&self, key: &str) -> Result<(String, String), CoreError> {
+        let host = self.host()?;
+        match self.config.url_style {
+            S3UrlStyle::VirtualHost => {
+                let scheme = self.config.endpoint.split_once("://").map(|(s, _)| s).unwrap_or("https");
+                let virtual_host = format!("{}.{host}", self.config.bucket);
+                let url = format!("{scheme}://{virtual_host}/{key}");
+                Ok((url, virtual_host))
+            }
+            S3UrlStyle::Path => {
+                let scheme = self.config.endpoint.split_once("://").map(|(s, _)| s).unwrap_or("https");
+                let url = format!("{scheme}://{host}/{}/{key}", self.config.bucket);
+                Ok((url, host))
+            }
+        }
+    }
+
+    fn signed_request(
+        &self,
+        method: &str,
+        key: &str,
+        query: &str,
+        body: &[u8],
+    ) -> Result<reqwest::blocking::RequestBuilder, CoreError> {
+        let (url, host) = self.request_url(key)?;
+        let url = if query.is_empty() {
+            url
+        } else {
+            format!("{url}?{query}")
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| CoreError::ObjectStore(format!("system clock before unix epoch: {e}")))?;
+        let amz_date = format_amz_date(now.as_secs());
+        let date_stamp = &amz_date[..8];
+        let payload_hash = hex_sha256(body);
+
+        let canonical_uri = format!("/{key}");
+        let canonical_headers =
+            format!("host:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n");
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+
+        let canonical_request = format!(
+            "{method}\n{canonical_uri}\n{query}\n{canonical_headers}\n{signed_headers}\n{payload_hash}"
+        );
+
+        let credential_scope = format!("{date_stamp}/{}/s3/aws4_request", self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{credential_scope}\n{}",
+            hex_sha256(canonical_request.as_bytes())
+        );
+
+        let signing_key = derive_signing_key(
+            &self.config.secret_access_key,
+            date_stamp,
+            &self.config.region,
+            "s3",
+        );
+        let signature = hex_encode(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{credential_scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.config.access_key_id
+        );
+
+        let mut builder = self
+            .client
+            .request(
+                method.parse().expect("method is one of our own fixed strings"),
+                &url,
+            )
+            .header("host", host)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header("authorization", authorization);
+
+        if !body.is_empty() {
+            builder = builder.body(body.to_vec());
+        }
+
+        Ok(builder)
+    }
+}
+
+impl Transport for S3Transport {
+    fn read_file(&self, path: &Path) -> Result<Vec<u8>, CoreError> {
+        let key = self.object_key(path);
+        let response = self
+            .signed_request("GET", &key, "", &[])?
+            .send()
+            .map_err(|e| CoreError::ObjectStore(format!("GET {key} failed: {e}")))?;
+
+        if !response.status().is_success() {
+            return Err(CoreError::ObjectStore(format!(
+                "GET {key} returned status {}",
+                response.status()
+            )));
+        }
+
+        response
+            .bytes()
+            .map(|b| b.to_vec())
+            .map_err(|e| CoreError::ObjectStore(format!("reading body for {key} failed: {e}")))
+    }
+
+    fn write_file(&self, path: &Path, content: &[u8]) -> Result<(), CoreError> {
+        let key = self.object_key(path);
+        let response = self
+            .signed_request("PUT", &key, "", content)?
+            .send()
+            .map_err(|e| CoreError::ObjectStore(format!("PUT {key} failed: {e}")))?;
+
+        if !response.status().is_success() {
+            return Err(CoreError::ObjectStore(format!(
+                "PUT {key} returned status {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    fn list_dir(&self, path: &Path) -> Result<Vec<DirEntry>, CoreError> {
+        let mut prefix = self.object_key(path);
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+        let query = canonical_query_string(&[("list-type", "2"), ("delimiter", "/"), ("prefix", &prefix)]);
+
+        let response = self
+            .signed_request("GET", "", &query, &[])?
+            .send()
+            .map_err(|e| CoreError::ObjectStore(format!("ListObjectsV2 failed: {e}")))?;
+
+        if !response.status().is_success() {
+            return Err(CoreError::ObjectStore(format!(
+                "ListObjectsV2 returned status {}",
+                response.status()
+            )));
+        }
+
+        let body = response
+            .text()
+            .map_err(|e| CoreError::ObjectStore(format!("reading ListObjectsV2 body: {e}")))?;
+
+        Ok(parse_list_objects_response(&body, &prefix))
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), CoreError> {
+        let key = self.object_key(path);
+        let response = self
+            .signed_request("DELETE", &key, "", &[])?
+            .send()
+            .map_err(|e| CoreError::ObjectStore(format!("DELETE {key} failed: {e}")))?;
+
+        // S3 returns 204 both when a key existed and was removed and when
+        // it never existed, matching Transport::remove_file's "missing is
+        // not an error" contract for free.
+        if !response.status().is_success() && response.status().as_u16() != 404 {
+            return Err(CoreError::ObjectStore(format!(
+                "DELETE {key} returned status {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Very small, dependency-free extraction of `<Key>`/`<Prefix>` (common
+/// prefix, i.e. subdirectory) entries from a `ListObjectsV2` XML response —
+/// just enough structure for [`S3Transport::list_dir`], not a general XML
+/// parser.
+fn parse_list_objects_response(body: &str, prefix: &str) -> Vec<DirEntry> {
+    let mut entries = Vec::new();
+
+    for key in extract_tag_values(body, "Key") {
+        if key == prefix {
+            continue;
+        }
+        entries.push(DirEntry {
+            path: PathBuf::from(key),
+            is_dir: false,
+        });
+    }
+    for common_prefix in extract_tag_values(body, "Prefix") {
+        if common_prefix == prefix {
+            continue;
+        }
+        entries.push(DirEntry {
+            path: PathBuf::from(common_prefix.trim_end_matches('/')),
+            is_dir: true,
+        });
+    }
+
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries
+}
+
+fn extract_tag_values(xml: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after_open = &rest[start + open.len()..];
+        let Some(end) = after_open.find(&close) else {
+            break;
+        };
+        values.push(after_open[..end].to_string());
+        rest = &after_open[end + close.len()..];
+    }
+    values
+}
+
+fn urlencode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                out.push(byte as char);
+            }
+            other => out.push_str(&format!("%{other:02X}")),
+        }
+    }
+    out
+}
+
+/// Percent-encodes `s` per SigV4's `UriEncode` rule for canonical query
+/// values: every byte outside `A-Za-z0-9-_.~` is escaped, including `/` —
+/// unlike [`urlencode`], which leaves `/` unescaped because it only ever
+/// encodes path segments of an object key, not a query value.
+fn uri_encode_query_value(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char);
+            }
+            other => out.push_str(&format!("%{other:02X}")),
+        }
+    }
+    out
+}
+
+/// Builds a SigV4 canonical query string from `params`: sorts pairs by key
+/// and percent-encodes every value with [`uri_encode_query_value`]. AWS
+/// recomputes the canonical request by re-sorting and re-encoding whatever
+/// query it actually received, so the string built here must both be
+/// correctly canonical *and* be the literal query string sent on the wire —
+/// [`S3Transport::signed_request`] signs and sends the same value.
+fn canonical_query_string(params: &[(&str, &str)]) -> String {
+    let mut sorted: Vec<(&str, &str)> = params.to_vec();
+    sorted.sort_by_key(|(key, _)| *key);
+    sorted
+        .into_iter()
+        .map(|(key, value)| format!("{key}={}", uri_encode_query_value(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn format_amz_date(unix_secs: u64) -> String {
+    let days_since_epoch = unix_secs / 86_400;
+    let secs_of_day = unix_secs % 86_400;
+    let (year, month, day) = civil_from_days(days_since_epoch as i64);
+    let (h, m, s) = (secs_of_day / 3600, (secs_of_day % 3600) / 60, secs_of_day % 60);
+    format!("{year:04}{month:02}{day:02}T{h:02}{m:02}{s:02}Z")
+}
+
+/// Howard Hinnant's `civil_from_days` algorithm, turning a day count since
+/// the Unix epoch into a (year, month, day) civil calendar date without
+/// pulling in a date/time crate.
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = (z - era * 146_097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = if m <= 2 { y + 1 } else { y };
+    (y, m, d)
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    hex_encode(sha256::Hash::hash(data).as_byte_array())
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// HMAC-SHA256, built from [`sha256::Hash`] per RFC 2104 since this crate
+/// has no `hmac` dependency to reach for.
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block_key = [0u8; SHA256_BLOCK_SIZE];
+    if key.len() > SHA256_BLOCK_SIZE {
+        let hashed = sha256::Hash::hash(key);
+        block_key[..32].copy_from_slice(hashed.as_byte_array());
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut ipad = [0x36u8; SHA256_BLOCK_SIZE];
+    let mut opad = [0x5cu8; SHA256_BLOCK_SIZE];
+    for i in 0..SHA256_BLOCK_SIZE {
+        ipad[i] ^= block_key[i];
+        opad[i] ^= block_key[i];
+    }
+
+    let mut inner = ipad.to_vec();
+    inner.extend_from_slice(message);
+    let inner_hash = sha256::Hash::hash(&inner);
+
+    let mut outer = opad.to_vec();
+    outer.extend_from_slice(inner_hash.as_byte_array());
+    *sha256::Hash::hash(&outer).as_byte_array()
+}
+
+/// Derives the SigV4 chained signing key: `kSecret` -> `kDate` -> `kRegion`
+/// -> `kService` -> `kSigning`.
+fn derive_signing_key(secret: &str, date_stamp: &str, region: &str, service: &str) -> [u8; 32] {
+    let k_date = hmac_sha256(format!("AWS4{secret}").as_bytes(), date_stamp.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_from_days_matches_known_date() {
+        // 2023-06-15 is 19523 days after the Unix epoch.
+        assert_eq!(civil_from_days(19_523), (2023, 6, 15));
+    }
+
+    #[test]
+    fn format_amz_date_matches_expected_shape() {
+        // 2023-06-15T00:00:00Z, i.e. 19523 * 86400 seconds since epoch.
+        let date = format_amz_date(19_523 * 86_400);
+        assert_eq!(date, "20230615T000000Z");
+    }
+
+    #[test]
+    fn hmac_sha256_matches_rfc4231_test_case_1() {
+        let key = [0x0bu8; 20];
+        let data = b"Hi There";
+        let expected = "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff";
+        assert_eq!(hex_encode(&hmac_sha256(&key, data)), expected);
+    }
+
+    #[test]
+    fn object_key_joins_prefix_and_relative_path() {
+        let transport = S3Transport::new(S3Config {
+            endpoint: "https://s3.example.com".to_string(),
+            bucket: "cory-labels".to_string(),
+            prefix: "instance-a".to_string(),
+            region: "us-east-1".to_string(),
+            access_key_id: "key".to_string(),
+            secret_access_key: "secret".to_string(),
+            url_style: S3UrlStyle::Path,
+        });
+        assert_eq!(
+            transport.object_key(Path::new("browser/abc.jsonl")),
+            "instance-a/browser/abc.jsonl"
+        );
+    }
+
+    #[test]
+    fn canonical_query_string_sorts_keys_and_encodes_slash_in_values() {
+        let query = canonical_query_string(&[
+            ("list-type", "2"),
+            ("delimiter", "/"),
+            ("prefix", "browser/abc def"),
+        ]);
+        assert_eq!(
+            query,
+            "delimiter=%2F&list-type=2&prefix=browser%2Fabc%20def"
+        );
+    }
+
+    #[test]
+    fn parse_list_objects_response_extracts_keys_and_common_prefixes() {
+        let body = r#"<ListBucketResult>
+            <Prefix>browser/</Prefix>
+            <Contents><Key>browser/abc.jsonl</Key></Contents>
+            <Contents><Key>browser/def.jsonl</Key></Contents>
+            <CommonPrefixes><Prefix>browser/nested/</Prefix></CommonPrefixes>
+        </ListBucketResult>"#;
+        let entries = parse_list_objects_response(body, "browser/");
+        assert_eq!(entries.len(), 3);
+        assert!(entries
+            .iter()
+            .any(|e| e.path == Path::new("browser/abc.jsonl") && !e.is_dir));
+        assert!(entries
+            .iter()
+            .any(|e| e.path == Path::new("browser/nested") && e.is_dir));
+    }
+}
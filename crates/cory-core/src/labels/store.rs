@@ -12,18 +12,48 @@
 //! precedence: PersistentRw → BrowserRw → PersistentRo.
 
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::error::CoreError;
 
-use super::jsonl::{export_map_to_jsonl, parse_jsonl_records, parse_local_file_name};
-use super::pack::walk_label_dir;
-use super::types::{Bip329Record, Bip329Type, LabelFile, LabelFileKind, LabelStoreError};
+use super::jsonl::{export_map_to_jsonl, parse_jsonl_records, parse_local_file_name, FileId};
+use super::oplog::{
+    effective_ops, export_log_to_jsonl, materialize, ClockSource, HybridClock, LabelOp,
+};
+use super::pack::{
+    load_pack_dir, load_single_label_file, relative_label_file_id, walk_label_dir, WalkCheckpoint,
+};
+use super::sync::{canonical_record_hash, ManifestEntry, SyncConflict, SyncOutcome};
+use super::transport::{LocalTransport, Transport};
+use super::types::{
+    Bip329Record, Bip329Type, LabelDiff, LabelFile, LabelFileKind, LabelKey, LabelStoreError,
+    ValidateProblem, ValidateStats,
+};
+
+/// The winning record for a `(type, ref)` lookup, plus the lower-precedence
+/// records it shadows, for provenance. The precedence order matches
+/// [`LabelStore::get_all_labels_for`]: PersistentRw → BrowserRw → PersistentRo.
+pub struct ResolvedLabel<'a> {
+    pub file: &'a LabelFile,
+    pub record: &'a Bip329Record,
+    pub shadowed: Vec<(&'a LabelFile, &'a Bip329Record)>,
+}
 
 pub struct LabelStore {
     persistent_rw_files: Vec<LabelFile>,
     browser_rw_files: Vec<LabelFile>,
     persistent_ro_files: Vec<LabelFile>,
+    transport: Arc<dyn Transport>,
+    /// Stamps every [`LabelOp`] this store appends, so ops from the same
+    /// store always compare unambiguously even within the same
+    /// millisecond.
+    clock: ClockSource,
+    /// Bumped by every mutating method (see [`Self::bump_revision`]).
+    /// Exposed via [`Self::revision`] so callers that cache derived data
+    /// (e.g. `crates/cory/src/server/preview.rs`'s rendered-graph cache)
+    /// can key on it instead of re-deriving from scratch on every request.
+    revision: u64,
 }
 
 impl Default for LabelStore {
@@ -34,13 +64,35 @@ impl Default for LabelStore {
 
 impl LabelStore {
     pub fn new() -> Self {
+        Self::with_transport(Arc::new(LocalTransport::new()))
+    }
+
+    /// Create a store backed by a custom [`Transport`] instead of the
+    /// local filesystem, e.g. an in-memory transport for tests or a
+    /// remote object-store-backed one.
+    pub fn with_transport(transport: Arc<dyn Transport>) -> Self {
         Self {
             persistent_rw_files: Vec::new(),
             browser_rw_files: Vec::new(),
             persistent_ro_files: Vec::new(),
+            transport,
+            clock: ClockSource::default(),
+            revision: 0,
         }
     }
 
+    /// Monotonically increasing counter, bumped once per mutating call
+    /// (label set/delete, file create/import/replace/remove, undo/redo,
+    /// squash, merge). Two reads returning the same value guarantee no
+    /// label data changed in between.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn bump_revision(&mut self) {
+        self.revision += 1;
+    }
+
     // ========================================================================
     // Directory loading
     // ========================================================================
@@ -57,15 +109,21 @@ impl LabelStore {
 
         let mut seen_ids = self.all_ids();
         walk_label_dir(
+            self.transport.as_ref(),
             dir,
             dir,
             LabelFileKind::PersistentRw,
             &mut self.persistent_rw_files,
             &mut seen_ids,
+            None,
         )
     }
 
     /// Load a `--labels-ro` directory. Files are read-only in the UI.
+    ///
+    /// If `dir` contains a `pack.manifest`, it is treated as a composed
+    /// pack: `%include` pulls in other pack trees in order and `%unset`
+    /// removes specific entries afterward. See [`super::pack::load_pack_dir`].
     pub fn load_ro_dir(&mut self, dir: &Path) -> Result<(), CoreError> {
         if !dir.is_dir() {
             return Err(CoreError::Io(std::io::Error::new(
@@ -75,15 +133,149 @@ impl LabelStore {
         }
 
         let mut seen_ids = self.all_ids();
-        walk_label_dir(
-            dir,
+        load_pack_dir(
+            self.transport.as_ref(),
             dir,
             LabelFileKind::PersistentRo,
             &mut self.persistent_ro_files,
             &mut seen_ids,
+            None,
         )
     }
 
+    /// Re-walk a `--labels-rw` directory at runtime, replacing the current
+    /// `PersistentRw` files wholesale. Unlike [`Self::load_rw_dir`] (which
+    /// only ever runs once at startup, before any reader exists), this can
+    /// race a concurrent query — so the walk is built into a scratch
+    /// `Vec`/`HashSet` and only swapped into `self.persistent_rw_files` on
+    /// success, meaning a cancelled or failed rescan (see
+    /// [`crate::jobs::rescan_dir`]) never leaves the store half-populated.
+    /// `checkpoint`, if given, is invoked once per file so a caller can
+    /// report progress and cooperatively cancel between files.
+    pub fn rescan_rw_dir(
+        &mut self,
+        dir: &Path,
+        checkpoint: Option<WalkCheckpoint>,
+    ) -> Result<(), CoreError> {
+        if !dir.is_dir() {
+            return Err(CoreError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("labels-rw directory not found: {}", dir.display()),
+            )));
+        }
+
+        let mut seen_ids = self.ids_excluding(LabelFileKind::PersistentRw);
+        let mut scratch = Vec::new();
+        walk_label_dir(
+            self.transport.as_ref(),
+            dir,
+            dir,
+            LabelFileKind::PersistentRw,
+            &mut scratch,
+            &mut seen_ids,
+            checkpoint,
+        )?;
+        self.persistent_rw_files = scratch;
+        self.bump_revision();
+        Ok(())
+    }
+
+    /// Re-walk a `--labels-ro` directory at runtime; see
+    /// [`Self::rescan_rw_dir`] for the scratch-buffer-and-swap rationale
+    /// and `checkpoint` semantics. Honors `pack.manifest` composition the
+    /// same way [`Self::load_ro_dir`] does.
+    pub fn rescan_ro_dir(
+        &mut self,
+        dir: &Path,
+        checkpoint: Option<WalkCheckpoint>,
+    ) -> Result<(), CoreError> {
+        if !dir.is_dir() {
+            return Err(CoreError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("labels-ro directory not found: {}", dir.display()),
+            )));
+        }
+
+        let mut seen_ids = self.ids_excluding(LabelFileKind::PersistentRo);
+        let mut scratch = Vec::new();
+        load_pack_dir(
+            self.transport.as_ref(),
+            dir,
+            LabelFileKind::PersistentRo,
+            &mut scratch,
+            &mut seen_ids,
+            checkpoint,
+        )?;
+        self.persistent_ro_files = scratch;
+        self.bump_revision();
+        Ok(())
+    }
+
+    /// Apply a filesystem watcher's create/modify event for `path` (a file
+    /// under `base`, a configured `--labels-rw`/`--labels-ro` directory) of
+    /// the given `kind`: re-parses just that file and replaces its entry
+    /// in-place, leaving every other loaded file untouched. The ID is
+    /// derived the same way a full walk would derive it, so a watched
+    /// create/modify always lands on the same file a rescan would produce.
+    /// Rejects a cross-kind ID collision exactly like a full walk would
+    /// (a pre-existing same-kind entry for this path is simply replaced,
+    /// not treated as a collision).
+    pub fn apply_watched_file(
+        &mut self,
+        base: &Path,
+        path: &Path,
+        kind: LabelFileKind,
+    ) -> Result<(), CoreError> {
+        let mut seen_ids = self.ids_excluding(kind);
+        let mut scratch = Vec::new();
+        load_single_label_file(
+            self.transport.as_ref(),
+            base,
+            path,
+            kind,
+            &mut scratch,
+            &mut seen_ids,
+        )?;
+        let Some(file) = scratch.into_iter().next() else {
+            // An empty relative path normalizes to an empty ID and is
+            // silently skipped by `load_single_label_file`; nothing to do.
+            return Ok(());
+        };
+        let files = self.files_of_kind_mut(kind);
+        files.retain(|f| f.id != file.id);
+        files.push(file);
+        self.bump_revision();
+        Ok(())
+    }
+
+    /// Apply a filesystem watcher's delete/rename-away event for `path`
+    /// under `base`: drops the matching `kind` file, if still loaded. A
+    /// no-op if the ID is already gone (e.g. duplicate delete events from
+    /// a debounced burst).
+    pub fn remove_watched_file(&mut self, base: &Path, path: &Path, kind: LabelFileKind) {
+        let id = relative_label_file_id(base, path);
+        let files = self.files_of_kind_mut(kind);
+        let before = files.len();
+        files.retain(|f| f.id != id);
+        if files.len() != before {
+            self.bump_revision();
+        }
+    }
+
+    /// The backing `Vec` for `kind`'s files. Only `PersistentRw` and
+    /// `PersistentRo` are meaningful directory-backed kinds here — watchers
+    /// only ever watch those two.
+    fn files_of_kind_mut(&mut self, kind: LabelFileKind) -> &mut Vec<LabelFile> {
+        match kind {
+            LabelFileKind::PersistentRw => &mut self.persistent_rw_files,
+            LabelFileKind::PersistentRo => &mut self.persistent_ro_files,
+            LabelFileKind::BrowserRw => &mut self.browser_rw_files,
+            LabelFileKind::WalletDerived => {
+                unreachable!("WalletDerived files are synthesized, never directory-watched")
+            }
+        }
+    }
+
     // ========================================================================
     // Browser file lifecycle (create, import, remove, replace)
     // ========================================================================
@@ -99,10 +291,15 @@ impl LabelStore {
             name: parsed.name,
             kind: LabelFileKind::BrowserRw,
             editable: true,
-            source_path: None,
+            source_path: Some(browser_file_path(&parsed.id)),
             labels: HashMap::new(),
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
         };
         self.browser_rw_files.push(file);
+        self.flush_file(&parsed.id)?;
+        self.bump_revision();
         Ok(parsed.id)
     }
 
@@ -123,10 +320,15 @@ impl LabelStore {
             name: parsed.name,
             kind: LabelFileKind::BrowserRw,
             editable: true,
-            source_path: None,
+            source_path: Some(browser_file_path(&parsed.id)),
             labels,
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
         };
         self.browser_rw_files.push(file);
+        self.flush_file(&parsed.id)?;
+        self.bump_revision();
 
         Ok(parsed.id)
     }
@@ -146,6 +348,14 @@ impl LabelStore {
 
         let labels = parse_jsonl_records(content)?;
         file.labels = labels;
+        // A wholesale content replace isn't expressible as a handful of
+        // ops, so it starts the log over; the next `set_label`/
+        // `delete_label` re-seeds a baseline from the new `labels` first.
+        file.log.clear();
+        file.undone.clear();
+        file.session_op_count = 0;
+        self.flush_file(file_id)?;
+        self.bump_revision();
         Ok(())
     }
 
@@ -159,12 +369,25 @@ impl LabelStore {
             return Err(LabelStoreError::NotBrowserFile(file_id.to_string()));
         }
 
+        let path = file
+            .source_path
+            .clone()
+            .expect("browser files always have a source_path");
+
         let idx = self
             .browser_rw_files
             .iter()
             .position(|f| f.id == file_id)
             .expect("file verified to exist above");
         self.browser_rw_files.remove(idx);
+
+        self.transport
+            .remove_file(&path)
+            .map_err(LabelStoreError::from)?;
+        self.transport
+            .remove_file(&path.with_extension("ops.jsonl"))
+            .map_err(LabelStoreError::from)?;
+        self.bump_revision();
         Ok(())
     }
 
@@ -197,6 +420,7 @@ impl LabelStore {
             return Err(LabelStoreError::EmptyLabel);
         }
 
+        let clock = self.clock.tick();
         let file = self
             .find_file_mut(file_id)
             .ok_or_else(|| LabelStoreError::FileNotFound(file_id.to_string()))?;
@@ -205,20 +429,16 @@ impl LabelStore {
             return Err(LabelStoreError::ReadOnlyFile(file_id.to_string()));
         }
 
-        let key = (label_type, ref_id.clone());
-        file.labels.insert(
-            key,
-            Bip329Record {
-                label_type,
-                ref_id,
-                label,
-                origin: None,
-                spendable: None,
-            },
-        );
+        ensure_log_seeded(file);
+        file.log
+            .push(LabelOp::new_set(label_type, ref_id, label, clock));
+        file.undone.clear();
+        file.session_op_count += 1;
+        file.labels = materialize(&file.log);
 
         // Auto-flush PersistentRw files to disk.
         self.flush_file(file_id)?;
+        self.bump_revision();
         Ok(())
     }
 
@@ -228,6 +448,7 @@ impl LabelStore {
         label_type: Bip329Type,
         ref_id: &str,
     ) -> Result<(), LabelStoreError> {
+        let clock = self.clock.tick();
         let file = self
             .find_file_mut(file_id)
             .ok_or_else(|| LabelStoreError::FileNotFound(file_id.to_string()))?;
@@ -236,13 +457,277 @@ impl LabelStore {
             return Err(LabelStoreError::ReadOnlyFile(file_id.to_string()));
         }
 
-        let key = (label_type, ref_id.to_string());
-        file.labels.remove(&key);
+        ensure_log_seeded(file);
+        file.log
+            .push(LabelOp::new_delete(label_type, ref_id.to_string(), clock));
+        file.undone.clear();
+        file.session_op_count += 1;
+        file.labels = materialize(&file.log);
+
+        self.flush_file(file_id)?;
+        self.bump_revision();
+        Ok(())
+    }
+
+    /// Undo the most recent layer (the last-pushed `set`/`delete`) for a
+    /// file, pushing it onto a redo stack and re-materializing from what
+    /// remains. Non-destructive: the op is kept, not discarded, so
+    /// [`Self::redo`] can restore it.
+    ///
+    /// Only undoes ops recorded by this `LabelStore` instance: a file just
+    /// loaded from disk (or seeded from a pre-existing `labels` map by
+    /// [`ensure_log_seeded`]) with no `set_label`/`delete_label` calls of its
+    /// own has nothing to undo, even though `log` itself is non-empty. This
+    /// keeps a stray `undo` from popping a real, already-on-disk label out
+    /// of the seeded baseline.
+    pub fn undo(&mut self, file_id: &str) -> Result<(), LabelStoreError> {
+        let file = self
+            .find_file_mut(file_id)
+            .ok_or_else(|| LabelStoreError::FileNotFound(file_id.to_string()))?;
+
+        if !file.editable {
+            return Err(LabelStoreError::ReadOnlyFile(file_id.to_string()));
+        }
+
+        if file.session_op_count == 0 {
+            return Err(LabelStoreError::NothingToUndo(file_id.to_string()));
+        }
+
+        ensure_log_seeded(file);
+        let op = file
+            .log
+            .pop()
+            .ok_or_else(|| LabelStoreError::NothingToUndo(file_id.to_string()))?;
+        file.session_op_count -= 1;
+        file.undone.push(op);
+        file.labels = materialize(&file.log);
+
+        self.flush_file(file_id)?;
+        self.bump_revision();
+        Ok(())
+    }
+
+    /// Re-apply the most recently undone layer for a file. Cleared whenever
+    /// a new `set_label`/`delete_label`/`merge_file` call records a fresh
+    /// layer, since redoing past a new edit would silently discard it.
+    pub fn redo(&mut self, file_id: &str) -> Result<(), LabelStoreError> {
+        let file = self
+            .find_file_mut(file_id)
+            .ok_or_else(|| LabelStoreError::FileNotFound(file_id.to_string()))?;
+
+        if !file.editable {
+            return Err(LabelStoreError::ReadOnlyFile(file_id.to_string()));
+        }
+
+        let op = file
+            .undone
+            .pop()
+            .ok_or_else(|| LabelStoreError::NothingToRedo(file_id.to_string()))?;
+        file.log.push(op);
+        file.session_op_count += 1;
+        file.labels = materialize(&file.log);
+
+        self.flush_file(file_id)?;
+        self.bump_revision();
+        Ok(())
+    }
+
+    /// Net added/removed/changed records between two versions of a file,
+    /// where version `n` means "the first `n` recorded layers applied" (`0`
+    /// is the empty file, [`LabelFile::version_count`] is the current
+    /// state). Lets the UI show a review-before-flush diff without
+    /// duplicating whole files in memory.
+    pub fn diff(
+        &self,
+        file_id: &str,
+        from_version: usize,
+        to_version: usize,
+    ) -> Result<LabelDiff, LabelStoreError> {
+        let file = self
+            .find_file_by_id(file_id)
+            .ok_or_else(|| LabelStoreError::FileNotFound(file_id.to_string()))?;
+
+        let log = effective_ops(&file.log, &file.labels);
+        if from_version > log.len() {
+            return Err(LabelStoreError::InvalidVersion(
+                from_version,
+                file_id.to_string(),
+                log.len(),
+            ));
+        }
+        if to_version > log.len() {
+            return Err(LabelStoreError::InvalidVersion(
+                to_version,
+                file_id.to_string(),
+                log.len(),
+            ));
+        }
+
+        let from_map = materialize(&log[..from_version]);
+        let to_map = materialize(&log[..to_version]);
+        Ok(diff_maps(&from_map, &to_map))
+    }
+
+    /// Collapse every recorded layer for a file into a single baseline
+    /// reflecting its current materialized state, discarding undo/redo
+    /// history. Keeps the `.ops.jsonl` sidecar from growing unboundedly
+    /// once the per-edit history is no longer needed.
+    pub fn squash(&mut self, file_id: &str) -> Result<(), LabelStoreError> {
+        let file = self
+            .find_file_mut(file_id)
+            .ok_or_else(|| LabelStoreError::FileNotFound(file_id.to_string()))?;
+
+        if !file.editable {
+            return Err(LabelStoreError::ReadOnlyFile(file_id.to_string()));
+        }
+
+        file.log = super::oplog::seed_ops(&file.labels);
+        file.undone.clear();
+        file.session_op_count = 0;
+
+        self.flush_file(file_id)?;
+        self.bump_revision();
+        Ok(())
+    }
+
+    /// Merge another copy of this file's op log into it (e.g. the same
+    /// BIP-329 file edited independently on another machine): the union of
+    /// both logs, deduped by `op_id`, is replayed to produce a new
+    /// materialized state. The result doesn't depend on which side calls
+    /// `merge_file` on which — merging `a` into `b` and `b` into `a`
+    /// converge to the same log.
+    pub fn merge_file(
+        &mut self,
+        file_id: &str,
+        other_log: &[LabelOp],
+    ) -> Result<(), LabelStoreError> {
+        let file = self
+            .find_file_mut(file_id)
+            .ok_or_else(|| LabelStoreError::FileNotFound(file_id.to_string()))?;
+
+        if !file.editable {
+            return Err(LabelStoreError::ReadOnlyFile(file_id.to_string()));
+        }
+
+        ensure_log_seeded(file);
+        file.log = super::oplog::merge(&file.log, other_log);
+        file.undone.clear();
+        file.session_op_count = 0;
+        file.labels = materialize(&file.log);
 
         self.flush_file(file_id)?;
+        self.bump_revision();
         Ok(())
     }
 
+    /// Per-record content hashes for a file, for a remote peer to diff
+    /// against its own records and determine what it's missing before
+    /// calling [`Self::sync_records`] — see [`super::sync`].
+    pub fn manifest(&self, file_id: &str) -> Result<Vec<ManifestEntry>, LabelStoreError> {
+        let file = self
+            .find_file_by_id(file_id)
+            .ok_or_else(|| LabelStoreError::FileNotFound(file_id.to_string()))?;
+
+        let mut entries: Vec<ManifestEntry> = file
+            .labels
+            .values()
+            .map(|record| ManifestEntry {
+                label_type: record.label_type,
+                ref_id: record.ref_id.clone(),
+                hash: canonical_record_hash(record),
+            })
+            .collect();
+        entries.sort_by(|a, b| (a.label_type, &a.ref_id).cmp(&(b.label_type, &b.ref_id)));
+        Ok(entries)
+    }
+
+    /// Additively merge `incoming` records (ones a remote peer determined,
+    /// via [`Self::manifest`], that this store was missing) into a file.
+    /// Built against a scratch copy of the op log and only swapped in (then
+    /// flushed) once every record has been classified, so a sync that's
+    /// interrupted partway through never leaves the file half-updated.
+    ///
+    /// A record whose `(type, ref)` already exists locally with a
+    /// different `label` is a conflict: it's left out of the scratch log
+    /// and reported in [`SyncOutcome::conflicts`] instead of being applied,
+    /// since picking a winner is the caller's call (keep-local /
+    /// take-remote / keep-both-renamed), not this store's.
+    pub fn sync_records(
+        &mut self,
+        file_id: &str,
+        incoming: Vec<Bip329Record>,
+    ) -> Result<SyncOutcome, LabelStoreError> {
+        let clocks: Vec<HybridClock> = incoming.iter().map(|_| self.clock.tick()).collect();
+
+        let file = self
+            .find_file_mut(file_id)
+            .ok_or_else(|| LabelStoreError::FileNotFound(file_id.to_string()))?;
+        if !file.editable {
+            return Err(LabelStoreError::ReadOnlyFile(file_id.to_string()));
+        }
+
+        ensure_log_seeded(file);
+        let mut scratch_log = file.log.clone();
+        let mut outcome = SyncOutcome::default();
+
+        for (record, clock) in incoming.into_iter().zip(clocks) {
+            match file.labels.get(&(record.label_type, record.ref_id.clone())) {
+                Some(existing) if existing.label != record.label => {
+                    outcome.conflicts.push(SyncConflict {
+                        label_type: record.label_type,
+                        ref_id: record.ref_id,
+                        local_label: existing.label.clone(),
+                        incoming_label: record.label,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    outcome.applied.push((record.label_type, record.ref_id.clone()));
+                    scratch_log.push(LabelOp::new_set(
+                        record.label_type,
+                        record.ref_id,
+                        record.label,
+                        clock,
+                    ));
+                }
+            }
+        }
+
+        if !outcome.applied.is_empty() {
+            file.log = scratch_log;
+            file.undone.clear();
+            file.session_op_count = 0;
+            file.labels = materialize(&file.log);
+            self.flush_file(file_id)?;
+            self.bump_revision();
+        }
+
+        Ok(outcome)
+    }
+
+    /// The ordered history of ops recorded against a single `(type, ref)`
+    /// key in one file, oldest first. For a file that has never been
+    /// mutated through the log and has no `.ops.jsonl` sidecar of its own,
+    /// this is the synthesized baseline (see `oplog::effective_ops`): one
+    /// zero-clock `set` op if the key has a label, otherwise empty.
+    pub fn history_for(
+        &self,
+        file_id: &str,
+        label_type: Bip329Type,
+        ref_id: &str,
+    ) -> Result<Vec<LabelOp>, LabelStoreError> {
+        let file = self
+            .find_file_by_id(file_id)
+            .ok_or_else(|| LabelStoreError::FileNotFound(file_id.to_string()))?;
+
+        let mut ops: Vec<LabelOp> = effective_ops(&file.log, &file.labels)
+            .into_iter()
+            .filter(|op| op.label_type == label_type && op.ref_id == ref_id)
+            .collect();
+        ops.sort_by(|a, b| a.clock.cmp(&b.clock).then_with(|| a.op_id.cmp(&b.op_id)));
+        Ok(ops)
+    }
+
     // ========================================================================
     // Query
     // ========================================================================
@@ -259,6 +744,34 @@ impl LabelStore {
         self.find_file_by_id(file_id)
     }
 
+    /// Like [`Self::list_files`] but restricted to files whose id's scope is
+    /// `scope_prefix` or nested under it (see [`FileId::is_in_scope`]). Files
+    /// whose id has no scope component never match.
+    pub fn list_files_in(&self, scope_prefix: &str) -> Vec<&LabelFile> {
+        self.list_files()
+            .into_iter()
+            .filter(|f| id_in_scope(&f.id, scope_prefix))
+            .collect()
+    }
+
+    /// Like [`Self::get_all_labels_for`] but restricted to files within
+    /// `scope_prefix`, keeping the same PersistentRw → BrowserRw →
+    /// PersistentRo precedence within that scope. Makes multi-wallet setups
+    /// with dozens of loaded files tractable by resolving only within a
+    /// single namespace (e.g. `Exchanges/*`) instead of merging across
+    /// everything loaded.
+    pub fn get_all_labels_for_in(
+        &self,
+        scope_prefix: &str,
+        label_type: Bip329Type,
+        ref_id: &str,
+    ) -> Vec<(&LabelFile, &Bip329Record)> {
+        self.get_all_labels_for(label_type, ref_id)
+            .into_iter()
+            .filter(|(file, _)| id_in_scope(&file.id, scope_prefix))
+            .collect()
+    }
+
     /// Returns labels for a specific `(type, ref)` in deterministic
     /// precedence order: PersistentRw → BrowserRw → PersistentRo.
     pub fn get_all_labels_for(
@@ -283,6 +796,128 @@ impl LabelStore {
         results
     }
 
+    /// Resolve the single winning record for a `(type, ref)` lookup,
+    /// treating loaded files as a precedence-ordered layer stack. The
+    /// lower-precedence records it shadows are attached for provenance,
+    /// so a caller no longer has to re-derive winner-takes-all semantics
+    /// from [`Self::get_all_labels_for`] itself.
+    pub fn get_effective_label(
+        &self,
+        label_type: Bip329Type,
+        ref_id: &str,
+    ) -> Option<ResolvedLabel<'_>> {
+        let mut results = self.get_all_labels_for(label_type, ref_id);
+        if results.is_empty() {
+            return None;
+        }
+        let (file, record) = results.remove(0);
+        Some(ResolvedLabel {
+            file,
+            record,
+            shadowed: results,
+        })
+    }
+
+    /// Bulk variant of [`Self::get_effective_label`]: resolves every key in
+    /// `queries` in a single pass over the layer stack, rather than one
+    /// independent lookup per key. Keys with no match are absent from the
+    /// result. Useful for rendering a transaction view with dozens of
+    /// inputs/outputs at consistent cost.
+    pub fn resolve_all(
+        &self,
+        queries: &[(Bip329Type, &str)],
+    ) -> HashMap<LabelKey, ResolvedLabel<'_>> {
+        let wanted: HashSet<LabelKey> = queries
+            .iter()
+            .map(|(label_type, ref_id)| (*label_type, ref_id.to_string()))
+            .collect();
+        let mut resolved: HashMap<LabelKey, ResolvedLabel<'_>> = HashMap::new();
+
+        for file in self
+            .persistent_rw_files
+            .iter()
+            .chain(self.browser_rw_files.iter())
+            .chain(self.persistent_ro_files.iter())
+        {
+            for (key, record) in &file.labels {
+                if !wanted.contains(key) {
+                    continue;
+                }
+                match resolved.get_mut(key) {
+                    None => {
+                        resolved.insert(
+                            key.clone(),
+                            ResolvedLabel {
+                                file,
+                                record,
+                                shadowed: Vec::new(),
+                            },
+                        );
+                    }
+                    Some(existing) => existing.shadowed.push((file, record)),
+                }
+            }
+        }
+
+        resolved
+    }
+
+    /// Walk every loaded file and collect a structured integrity report:
+    /// empty refs/labels, blank `origin` strings, cross-file `(type, ref)`
+    /// duplicates, and file-ID collisions. A one-call health check before
+    /// import/export, instead of silently overwriting or dropping data.
+    pub fn validate(&self) -> ValidateStats {
+        let mut stats = ValidateStats::default();
+        let mut seen_keys: HashMap<(Bip329Type, String), String> = HashMap::new();
+        let mut seen_file_ids: HashSet<&str> = HashSet::new();
+
+        for file in self.list_files() {
+            stats.files_scanned += 1;
+
+            if !seen_file_ids.insert(file.id.as_str()) {
+                stats.id_collisions.push(ValidateProblem {
+                    file_id: file.id.clone(),
+                    record: None,
+                    message: format!("duplicate file id `{}`", file.id),
+                });
+            }
+
+            for ((label_type, ref_id), record) in &file.labels {
+                stats.records_scanned += 1;
+                let record_desc = format!("{label_type}:{ref_id}");
+
+                if ref_id.trim().is_empty() || record.label.trim().is_empty() {
+                    stats.empty_labels.push(ValidateProblem {
+                        file_id: file.id.clone(),
+                        record: Some(record_desc.clone()),
+                        message: "ref or label is empty or all whitespace".to_string(),
+                    });
+                }
+
+                if record.origin.as_ref().is_some_and(|o| o.trim().is_empty()) {
+                    stats.malformed_origins.push(ValidateProblem {
+                        file_id: file.id.clone(),
+                        record: Some(record_desc.clone()),
+                        message: "origin is present but blank".to_string(),
+                    });
+                }
+
+                let key = (*label_type, ref_id.clone());
+                if let Some(first_file_id) = seen_keys.get(&key) {
+                    stats.duplicate_refs.push(ValidateProblem {
+                        file_id: file.id.clone(),
+                        record: Some(record_desc),
+                        message: format!("also present in file `{first_file_id}`"),
+                    });
+                } else {
+                    seen_keys.insert(key, file.id.clone());
+                }
+            }
+        }
+
+        stats
+    }
+
     // ========================================================================
     // Internal helpers
     // ========================================================================
@@ -297,6 +932,20 @@ impl LabelStore {
             .collect()
     }
 
+    /// Like [`Self::all_ids`], but omitting `kind`'s own files — used by
+    /// [`Self::rescan_rw_dir`]/[`Self::rescan_ro_dir`] to seed cross-kind
+    /// duplicate detection for a rescan without the files it's about to
+    /// replace colliding with themselves.
+    fn ids_excluding(&self, kind: LabelFileKind) -> HashSet<String> {
+        self.persistent_rw_files
+            .iter()
+            .chain(self.browser_rw_files.iter())
+            .chain(self.persistent_ro_files.iter())
+            .filter(|f| f.kind != kind)
+            .map(|f| f.id.clone())
+            .collect()
+    }
+
     fn find_file_by_id(&self, file_id: &str) -> Option<&LabelFile> {
         self.persistent_rw_files
             .iter()
@@ -313,8 +962,19 @@ impl LabelStore {
             .find(|f| f.id == file_id)
     }
 
-    /// Flush a file to disk if it has a `source_path` (PersistentRw).
-    /// BrowserRw and PersistentRo files are no-ops as they have source_path None
+    /// Flush a file through the transport if it has a `source_path`
+    /// (PersistentRw, and BrowserRw under `browser/`). PersistentRo files
+    /// are no-ops as they have source_path None.
+    ///
+    /// Writes the flat BIP-329 JSONL export and its `.ops.jsonl` sidecar as
+    /// two separate [`Transport::write_file`] calls, each individually
+    /// atomic via write-then-rename — but a crash between the two would
+    /// otherwise leave the pair inconsistent (e.g. content updated, sidecar
+    /// stale). To guard against that, the full log about to be written is
+    /// journaled first; the journal is only removed once both files have
+    /// landed, so a leftover journal on the next [`Self::load_rw_dir`]
+    /// unambiguously marks an interrupted flush — see `pack::recover_journal`,
+    /// which replays it on load.
     fn flush_file(&self, file_id: &str) -> Result<(), LabelStoreError> {
         let file = self
             .find_file_by_id(file_id)
@@ -324,19 +984,133 @@ impl LabelStore {
             return Ok(());
         };
 
+        let ops = effective_ops(&file.log, &file.labels);
+        let ops_content = export_log_to_jsonl(&ops);
+        let journal_path = super::oplog::journal_path(path);
+        self.transport
+            .write_file(&journal_path, ops_content.as_bytes())
+            .map_err(LabelStoreError::from)?;
+
         let content = export_map_to_jsonl(&file.labels);
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).map_err(CoreError::Io)?;
-        }
-        std::fs::write(path, content).map_err(CoreError::Io)?;
+        self.transport
+            .write_file(path, content.as_bytes())
+            .map_err(LabelStoreError::from)?;
+        self.transport
+            .write_file(&path.with_extension("ops.jsonl"), ops_content.as_bytes())
+            .map_err(LabelStoreError::from)?;
+
+        self.transport
+            .remove_file(&journal_path)
+            .map_err(LabelStoreError::from)?;
         Ok(())
     }
 }
 
+/// Source path a `BrowserRw` file is flushed to, namespaced under a
+/// `browser/` prefix so it can't collide with a `PersistentRw` file loaded
+/// from a directory root. Giving `BrowserRw` files a real `source_path`
+/// (rather than `None`) is what makes [`LabelStore::flush_file`] persist
+/// them through `self.transport` on every mutation, the same as any other
+/// file — no separate write path is needed.
+fn browser_file_path(file_id: &str) -> PathBuf {
+    PathBuf::from("browser").join(format!("{file_id}.jsonl"))
+}
+
+/// Seed `file.log` from its currently materialized `labels` if it has no
+/// recorded ops yet (e.g. a `PersistentRw` file loaded from a flat JSONL
+/// file with no `.ops.jsonl` sidecar next to it), so the first real
+/// mutation appends onto a log that already reflects everything already on
+/// disk instead of replacing it.
+fn ensure_log_seeded(file: &mut LabelFile) {
+    if file.log.is_empty() && !file.labels.is_empty() {
+        file.log = super::oplog::seed_ops(&file.labels);
+    }
+}
+
+/// `true` if `file_id` parses as a [`FileId`] whose scope is `scope_prefix`
+/// or nested under it. A malformed id (which shouldn't occur for anything
+/// that made it through [`parse_local_file_name`]) is treated as out of
+/// scope rather than panicking.
+fn id_in_scope(file_id: &str, scope_prefix: &str) -> bool {
+    file_id
+        .parse::<FileId>()
+        .map(|parsed| parsed.is_in_scope(scope_prefix))
+        .unwrap_or(false)
+}
+
+/// Compare two materialized label maps and report the net added, removed,
+/// and changed records going from `from` to `to`.
+fn diff_maps(
+    from: &HashMap<LabelKey, Bip329Record>,
+    to: &HashMap<LabelKey, Bip329Record>,
+) -> LabelDiff {
+    let mut diff = LabelDiff::default();
+
+    for (key, to_record) in to {
+        match from.get(key) {
+            None => diff.added.push(to_record.clone()),
+            Some(from_record) if from_record != to_record => {
+                diff.changed.push((from_record.clone(), to_record.clone()))
+            }
+            _ => {}
+        }
+    }
+    for (key, from_record) in from {
+        if !to.contains_key(key) {
+            diff.removed.push(from_record.clone());
+        }
+    }
+
+    diff
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::Mutex;
+
     use super::*;
     use crate::labels::jsonl::normalize_label_file_id;
+    use crate::labels::transport::DirEntry;
+
+    /// Minimal in-memory `Transport` so persistence tests don't need a
+    /// real filesystem.
+    #[derive(Default)]
+    struct InMemoryTransport {
+        files: Mutex<HashMap<std::path::PathBuf, Vec<u8>>>,
+    }
+
+    impl Transport for InMemoryTransport {
+        fn read_file(&self, path: &Path) -> Result<Vec<u8>, CoreError> {
+            self.files
+                .lock()
+                .expect("lock poisoned")
+                .get(path)
+                .cloned()
+                .ok_or_else(|| {
+                    CoreError::Io(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        path.display().to_string(),
+                    ))
+                })
+        }
+
+        fn write_file(&self, path: &Path, content: &[u8]) -> Result<(), CoreError> {
+            self.files
+                .lock()
+                .expect("lock poisoned")
+                .insert(path.to_path_buf(), content.to_vec());
+            Ok(())
+        }
+
+        fn list_dir(&self, _path: &Path) -> Result<Vec<DirEntry>, CoreError> {
+            Ok(Vec::new())
+        }
+
+        fn remove_file(&self, path: &Path) -> Result<(), CoreError> {
+            self.files.lock().expect("lock poisoned").remove(path);
+            Ok(())
+        }
+    }
 
     #[test]
     fn normalize_file_id_minimal() {
@@ -353,6 +1127,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn file_id_parses_scope_and_leaf() {
+        let parsed: FileId = "Exchanges/Binance Hot".parse().expect("parse");
+        assert_eq!(parsed.scope.as_deref(), Some("Exchanges"));
+        assert_eq!(parsed.leaf, "Binance Hot");
+        assert_eq!(parsed.full(), "Exchanges/Binance Hot");
+    }
+
+    #[test]
+    fn file_id_with_no_scope() {
+        let parsed: FileId = "My Wallet".parse().expect("parse");
+        assert_eq!(parsed.scope, None);
+        assert_eq!(parsed.leaf, "My Wallet");
+    }
+
+    #[test]
+    fn file_id_rejects_empty_segments() {
+        assert!(matches!(
+            "Exchanges//Binance Hot".parse::<FileId>(),
+            Err(LabelStoreError::InvalidFileId(_))
+        ));
+        assert!(matches!(
+            "".parse::<FileId>(),
+            Err(LabelStoreError::EmptyFileName)
+        ));
+    }
+
+    #[test]
+    fn file_id_is_in_scope_matches_nested_scopes() {
+        let parsed: FileId = "Exchanges/Sub/Leaf".parse().expect("parse");
+        assert!(parsed.is_in_scope("Exchanges"));
+        assert!(parsed.is_in_scope("Exchanges/Sub"));
+        assert!(!parsed.is_in_scope("Wallets"));
+
+        let scopeless: FileId = "My Wallet".parse().expect("parse");
+        assert!(!scopeless.is_in_scope("My"));
+    }
+
     #[test]
     fn browser_file_lifecycle_and_export() {
         let mut store = LabelStore::new();
@@ -411,6 +1223,9 @@ mod tests {
             editable: true,
             source_path: None,
             labels: rw_labels,
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
         });
 
         // Inject a PersistentRo file directly.
@@ -424,6 +1239,9 @@ mod tests {
             editable: false,
             source_path: None,
             labels: ro_labels,
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
         });
 
         let labels = store.get_all_labels_for(Bip329Type::Tx, "txid1");
@@ -478,6 +1296,9 @@ mod tests {
             editable: false,
             source_path: None,
             labels: ro_labels,
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
         });
 
         let result = store.set_label(
@@ -500,6 +1321,9 @@ mod tests {
             editable: true,
             source_path: None,
             labels: HashMap::new(),
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
         });
 
         let result = store.remove_browser_file("rw-file");
@@ -567,11 +1391,556 @@ mod tests {
     }
 
     #[test]
-    fn export_nonexistent_file_fails() {
+    fn set_label_flushes_persistent_rw_file_through_transport() {
+        let transport = Arc::new(InMemoryTransport::default());
+        let mut store = LabelStore::with_transport(transport.clone());
+
+        let path = Path::new("wallet.jsonl").to_path_buf();
+        store.persistent_rw_files.push(LabelFile {
+            id: "wallet".into(),
+            name: "wallet".into(),
+            kind: LabelFileKind::PersistentRw,
+            editable: true,
+            source_path: Some(path.clone()),
+            labels: HashMap::new(),
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
+        });
+
+        store
+            .set_label(
+                "wallet",
+                Bip329Type::Tx,
+                "txid1".to_string(),
+                "Label 1".to_string(),
+            )
+            .expect("set label should succeed");
+
+        let written = transport.read_file(&path).expect("file should be written");
+        assert!(String::from_utf8(written)
+            .expect("valid utf8")
+            .contains("\"label\":\"Label 1\""));
+    }
+
+    #[test]
+    fn export_nonexistent_file_fails() {
         let store = LabelStore::new();
         assert!(matches!(
             store.export_file("no-such-file"),
             Err(LabelStoreError::FileNotFound(_))
         ));
     }
+
+    #[test]
+    fn validate_reports_no_problems_for_a_clean_store() {
+        let mut store = LabelStore::new();
+        let file_id = store.create_browser_file("wallet").expect("create file");
+        store
+            .set_label(
+                &file_id,
+                Bip329Type::Tx,
+                "txid1".to_string(),
+                "Label 1".to_string(),
+            )
+            .expect("set label");
+
+        let stats = store.validate();
+        assert_eq!(stats.files_scanned, 1);
+        assert_eq!(stats.records_scanned, 1);
+        assert!(stats.is_clean());
+    }
+
+    #[test]
+    fn validate_detects_duplicate_refs_across_files() {
+        let mut store = LabelStore::new();
+
+        let rw_labels = parse_jsonl_records(r#"{"type":"tx","ref":"txid1","label":"From rw"}"#)
+            .expect("rw parse");
+        store.persistent_rw_files.push(LabelFile {
+            id: "rw-file".into(),
+            name: "rw-file".into(),
+            kind: LabelFileKind::PersistentRw,
+            editable: true,
+            source_path: None,
+            labels: rw_labels,
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
+        });
+
+        let ro_labels = parse_jsonl_records(r#"{"type":"tx","ref":"txid1","label":"From ro"}"#)
+            .expect("ro parse");
+        store.persistent_ro_files.push(LabelFile {
+            id: "ro-file".into(),
+            name: "ro-file".into(),
+            kind: LabelFileKind::PersistentRo,
+            editable: false,
+            source_path: None,
+            labels: ro_labels,
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
+        });
+
+        let stats = store.validate();
+        assert_eq!(stats.duplicate_refs.len(), 1);
+        assert!(!stats.is_clean());
+    }
+
+    #[test]
+    fn validate_detects_blank_origin_and_empty_label() {
+        let mut store = LabelStore::new();
+        let mut labels = HashMap::new();
+        labels.insert(
+            (Bip329Type::Tx, "txid1".to_string()),
+            Bip329Record {
+                label_type: Bip329Type::Tx,
+                ref_id: "txid1".to_string(),
+                label: "   ".to_string(),
+                origin: Some("  ".to_string()),
+                spendable: None,
+            },
+        );
+        store.browser_rw_files.push(LabelFile {
+            id: "wallet".into(),
+            name: "wallet".into(),
+            kind: LabelFileKind::BrowserRw,
+            editable: true,
+            source_path: None,
+            labels,
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
+        });
+
+        let stats = store.validate();
+        assert_eq!(stats.empty_labels.len(), 1);
+        assert_eq!(stats.malformed_origins.len(), 1);
+    }
+
+    #[test]
+    fn get_effective_label_picks_highest_precedence_and_shadows_rest() {
+        let mut store = LabelStore::new();
+
+        store
+            .import_browser_file(
+                "browser-file",
+                r#"{"type":"tx","ref":"txid1","label":"Browser label"}"#,
+            )
+            .expect("browser import");
+
+        let rw_labels = parse_jsonl_records(r#"{"type":"tx","ref":"txid1","label":"Rw label"}"#)
+            .expect("rw parse");
+        store.persistent_rw_files.push(LabelFile {
+            id: "rw-file".into(),
+            name: "rw-file".into(),
+            kind: LabelFileKind::PersistentRw,
+            editable: true,
+            source_path: None,
+            labels: rw_labels,
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
+        });
+
+        let resolved = store
+            .get_effective_label(Bip329Type::Tx, "txid1")
+            .expect("should resolve");
+        assert_eq!(resolved.record.label, "Rw label");
+        assert_eq!(resolved.file.kind, LabelFileKind::PersistentRw);
+        assert_eq!(resolved.shadowed.len(), 1);
+        assert_eq!(resolved.shadowed[0].1.label, "Browser label");
+    }
+
+    #[test]
+    fn get_effective_label_returns_none_when_unset() {
+        let store = LabelStore::new();
+        assert!(store.get_effective_label(Bip329Type::Tx, "nope").is_none());
+    }
+
+    #[test]
+    fn resolve_all_resolves_every_key_in_one_pass() {
+        let mut store = LabelStore::new();
+        let file_id = store.create_browser_file("wallet").expect("create file");
+        store
+            .set_label(
+                &file_id,
+                Bip329Type::Tx,
+                "a".to_string(),
+                "Label A".to_string(),
+            )
+            .expect("set label a");
+        store
+            .set_label(
+                &file_id,
+                Bip329Type::Tx,
+                "b".to_string(),
+                "Label B".to_string(),
+            )
+            .expect("set label b");
+
+        let resolved = store.resolve_all(&[(Bip329Type::Tx, "a"), (Bip329Type::Tx, "missing")]);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(
+            resolved[&(Bip329Type::Tx, "a".to_string())].record.label,
+            "Label A"
+        );
+    }
+
+    // -- revision -----------------------------------------------------------
+
+    #[test]
+    fn revision_bumps_on_label_mutation_but_not_on_read() {
+        let mut store = LabelStore::new();
+        let file_id = store.create_browser_file("wallet").expect("create file");
+        let after_create = store.revision();
+        assert!(after_create > 0);
+
+        let _ = store.get_all_labels_for(Bip329Type::Tx, "a");
+        assert_eq!(store.revision(), after_create);
+
+        store
+            .set_label(&file_id, Bip329Type::Tx, "a".into(), "Label A".into())
+            .expect("set label a");
+        assert!(store.revision() > after_create);
+    }
+
+    // -- undo/redo/diff/squash --------------------------------------------------
+
+    #[test]
+    fn undo_reverts_last_layer_and_redo_restores_it() {
+        let mut store = LabelStore::new();
+        let file_id = store.create_browser_file("wallet").expect("create file");
+        store
+            .set_label(&file_id, Bip329Type::Tx, "a".into(), "Label A".into())
+            .expect("set label a");
+        store
+            .set_label(&file_id, Bip329Type::Tx, "b".into(), "Label B".into())
+            .expect("set label b");
+
+        store.undo(&file_id).expect("undo");
+        let labels = store.get_all_labels_for(Bip329Type::Tx, "b");
+        assert!(labels.is_empty());
+        assert!(store
+            .get_all_labels_for(Bip329Type::Tx, "a")
+            .iter()
+            .any(|(_, r)| r.label == "Label A"));
+
+        store.redo(&file_id).expect("redo");
+        assert!(store
+            .get_all_labels_for(Bip329Type::Tx, "b")
+            .iter()
+            .any(|(_, r)| r.label == "Label B"));
+    }
+
+    #[test]
+    fn undo_with_no_history_fails() {
+        let mut store = LabelStore::new();
+        let file_id = store.create_browser_file("wallet").expect("create file");
+        assert!(matches!(
+            store.undo(&file_id),
+            Err(LabelStoreError::NothingToUndo(_))
+        ));
+    }
+
+    #[test]
+    fn undo_on_freshly_loaded_file_with_no_session_edits_fails() {
+        let mut store = LabelStore::new();
+
+        // Simulate a file loaded from disk with pre-existing labels and no
+        // `.ops.jsonl` sidecar: `log` starts empty, so the first `undo`
+        // seeds a baseline from `labels` via `ensure_log_seeded` before this
+        // fix. No `set_label`/`delete_label` has been called through this
+        // store instance yet.
+        let ro_labels =
+            parse_jsonl_records(r#"{"type":"tx","ref":"txid1","label":"Pre-existing label"}"#)
+                .expect("ro parse should succeed");
+        store.persistent_rw_files.push(LabelFile {
+            id: "rw-file".into(),
+            name: "rw-file".into(),
+            kind: LabelFileKind::PersistentRw,
+            editable: true,
+            source_path: None,
+            labels: ro_labels,
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
+        });
+
+        assert!(matches!(
+            store.undo("rw-file"),
+            Err(LabelStoreError::NothingToUndo(_))
+        ));
+        // The pre-existing label must survive untouched.
+        assert!(store
+            .get_all_labels_for(Bip329Type::Tx, "txid1")
+            .iter()
+            .any(|(_, r)| r.label == "Pre-existing label"));
+    }
+
+    #[test]
+    fn redo_with_nothing_undone_fails() {
+        let mut store = LabelStore::new();
+        let file_id = store.create_browser_file("wallet").expect("create file");
+        store
+            .set_label(&file_id, Bip329Type::Tx, "a".into(), "Label A".into())
+            .expect("set label a");
+        assert!(matches!(
+            store.redo(&file_id),
+            Err(LabelStoreError::NothingToRedo(_))
+        ));
+    }
+
+    #[test]
+    fn a_fresh_edit_clears_the_redo_stack() {
+        let mut store = LabelStore::new();
+        let file_id = store.create_browser_file("wallet").expect("create file");
+        store
+            .set_label(&file_id, Bip329Type::Tx, "a".into(), "Label A".into())
+            .expect("set label a");
+        store.undo(&file_id).expect("undo");
+
+        store
+            .set_label(&file_id, Bip329Type::Tx, "b".into(), "Label B".into())
+            .expect("set label b");
+        assert!(matches!(
+            store.redo(&file_id),
+            Err(LabelStoreError::NothingToRedo(_))
+        ));
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_records() {
+        let mut store = LabelStore::new();
+        let file_id = store.create_browser_file("wallet").expect("create file");
+        store
+            .set_label(&file_id, Bip329Type::Tx, "a".into(), "v1".into())
+            .expect("set a");
+        store
+            .set_label(&file_id, Bip329Type::Tx, "b".into(), "keep".into())
+            .expect("set b");
+        store
+            .set_label(&file_id, Bip329Type::Tx, "a".into(), "v2".into())
+            .expect("update a");
+        store
+            .delete_label(&file_id, Bip329Type::Tx, "b")
+            .expect("delete b");
+
+        let diff = store.diff(&file_id, 0, 4).expect("diff");
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].ref_id, "a");
+        assert_eq!(diff.added[0].label, "v2");
+        assert!(diff.removed.is_empty());
+        assert!(diff.changed.is_empty());
+
+        let diff = store.diff(&file_id, 1, 2).expect("diff");
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].ref_id, "b");
+
+        let diff = store.diff(&file_id, 2, 4).expect("diff");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].ref_id, "b");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].0.label, "v1");
+        assert_eq!(diff.changed[0].1.label, "v2");
+    }
+
+    #[test]
+    fn diff_out_of_range_version_fails() {
+        let mut store = LabelStore::new();
+        let file_id = store.create_browser_file("wallet").expect("create file");
+        store
+            .set_label(&file_id, Bip329Type::Tx, "a".into(), "v1".into())
+            .expect("set a");
+
+        assert!(matches!(
+            store.diff(&file_id, 0, 5),
+            Err(LabelStoreError::InvalidVersion(5, _, 1))
+        ));
+    }
+
+    #[test]
+    fn squash_collapses_layers_and_clears_redo_history() {
+        let mut store = LabelStore::new();
+        let file_id = store.create_browser_file("wallet").expect("create file");
+        store
+            .set_label(&file_id, Bip329Type::Tx, "a".into(), "v1".into())
+            .expect("set a");
+        store
+            .set_label(&file_id, Bip329Type::Tx, "a".into(), "v2".into())
+            .expect("update a");
+        store.undo(&file_id).expect("undo");
+
+        store.squash(&file_id).expect("squash");
+        let file = store.get_file(&file_id).expect("file exists");
+        assert_eq!(file.version_count(), 1);
+        assert!(matches!(
+            store.redo(&file_id),
+            Err(LabelStoreError::NothingToRedo(_))
+        ));
+        assert!(store
+            .get_all_labels_for(Bip329Type::Tx, "a")
+            .iter()
+            .any(|(_, r)| r.label == "v1"));
+    }
+
+    // -- scoped queries -----------------------------------------------------
+
+    #[test]
+    fn list_files_in_restricts_to_matching_scope() {
+        let mut store = LabelStore::new();
+        store
+            .create_browser_file("Exchanges/Binance Hot")
+            .expect("create");
+        store
+            .create_browser_file("Exchanges/Kraken Cold")
+            .expect("create");
+        store.create_browser_file("My Wallet").expect("create");
+
+        let in_scope = store.list_files_in("Exchanges");
+        assert_eq!(in_scope.len(), 2);
+        assert!(in_scope.iter().all(|f| f.id.starts_with("Exchanges/")));
+    }
+
+    #[test]
+    fn get_all_labels_for_in_ignores_files_outside_scope() {
+        let mut store = LabelStore::new();
+        let scoped = store
+            .create_browser_file("Exchanges/Binance Hot")
+            .expect("create");
+        store
+            .set_label(&scoped, Bip329Type::Tx, "txid1".into(), "Exchange label".into())
+            .expect("set label");
+
+        let other = store.create_browser_file("My Wallet").expect("create");
+        store
+            .set_label(&other, Bip329Type::Tx, "txid1".into(), "Wallet label".into())
+            .expect("set label");
+
+        let labels = store.get_all_labels_for_in("Exchanges", Bip329Type::Tx, "txid1");
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].1.label, "Exchange label");
+    }
+
+    // -- manifest sync --------------------------------------------------------
+
+    #[test]
+    fn manifest_has_one_entry_per_record_sorted_by_key() {
+        let mut store = LabelStore::new();
+        let file = store.create_browser_file("wallet").expect("create");
+        store
+            .set_label(&file, Bip329Type::Tx, "txid2".into(), "second".into())
+            .expect("set label");
+        store
+            .set_label(&file, Bip329Type::Tx, "txid1".into(), "first".into())
+            .expect("set label");
+
+        let manifest = store.manifest(&file).expect("manifest");
+        assert_eq!(manifest.len(), 2);
+        assert_eq!(manifest[0].ref_id, "txid1");
+        assert_eq!(manifest[1].ref_id, "txid2");
+        assert_ne!(manifest[0].hash, manifest[1].hash);
+    }
+
+    #[test]
+    fn sync_records_applies_missing_records() {
+        let mut store = LabelStore::new();
+        let file = store.create_browser_file("wallet").expect("create");
+
+        let outcome = store
+            .sync_records(
+                &file,
+                vec![Bip329Record {
+                    label_type: Bip329Type::Addr,
+                    ref_id: "bc1q...".into(),
+                    label: "Cold storage".into(),
+                    origin: None,
+                    spendable: None,
+                }],
+            )
+            .expect("sync");
+
+        assert_eq!(outcome.applied, vec![(Bip329Type::Addr, "bc1q...".to_string())]);
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(
+            store.get_effective_label(Bip329Type::Addr, "bc1q...").map(|r| r.record.label.clone()),
+            Some("Cold storage".to_string())
+        );
+    }
+
+    #[test]
+    fn sync_records_reports_conflicting_label_without_applying_it() {
+        let mut store = LabelStore::new();
+        let file = store.create_browser_file("wallet").expect("create");
+        store
+            .set_label(&file, Bip329Type::Addr, "bc1q...".into(), "Cold storage".into())
+            .expect("set label");
+
+        let outcome = store
+            .sync_records(
+                &file,
+                vec![Bip329Record {
+                    label_type: Bip329Type::Addr,
+                    ref_id: "bc1q...".into(),
+                    label: "Hot wallet".into(),
+                    origin: None,
+                    spendable: None,
+                }],
+            )
+            .expect("sync");
+
+        assert!(outcome.applied.is_empty());
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].local_label, "Cold storage");
+        assert_eq!(outcome.conflicts[0].incoming_label, "Hot wallet");
+        assert_eq!(
+            store.get_effective_label(Bip329Type::Addr, "bc1q...").map(|r| r.record.label.clone()),
+            Some("Cold storage".to_string())
+        );
+    }
+
+    #[test]
+    fn sync_records_is_a_noop_for_an_identical_record() {
+        let mut store = LabelStore::new();
+        let file = store.create_browser_file("wallet").expect("create");
+        store
+            .set_label(&file, Bip329Type::Addr, "bc1q...".into(), "Cold storage".into())
+            .expect("set label");
+
+        let outcome = store
+            .sync_records(
+                &file,
+                vec![Bip329Record {
+                    label_type: Bip329Type::Addr,
+                    ref_id: "bc1q...".into(),
+                    label: "Cold storage".into(),
+                    origin: None,
+                    spendable: None,
+                }],
+            )
+            .expect("sync");
+
+        assert!(outcome.applied.is_empty());
+        assert!(outcome.conflicts.is_empty());
+    }
+
+    #[test]
+    fn sync_records_rejects_read_only_file() {
+        let mut store = LabelStore::new();
+        store.persistent_ro_files.push(LabelFile {
+            id: "ro-file".into(),
+            name: "ro-file".into(),
+            kind: LabelFileKind::PersistentRo,
+            editable: false,
+            source_path: None,
+            labels: HashMap::new(),
+            log: Vec::new(),
+            undone: Vec::new(),
+            session_op_count: 0,
+        });
+
+        let result = store.sync_records("ro-file", Vec::new());
+        assert!(matches!(result, Err(LabelStoreError::ReadOnlyFile(_))));
+    }
 }
@@ -0,0 +1,104 @@
+//! Record-hash manifest sync for reconciling a label file across two Cory
+//! instances without re-transferring every record, adapted from Proxmox
+//! Backup Server's "known chunks" handshake: a receiver publishes what it
+//! already has, and a sender only needs to push what's missing.
+//!
+//! [`LabelStore::manifest`] lists every record's [`canonical_record_hash`],
+//! and [`LabelStore::sync_records`] applies a batch of records a remote
+//! peer determined (by diffing against that manifest) it was missing.
+//! Records whose `(type, ref)` already exists locally with a *different*
+//! `label` are never auto-applied — they come back as [`SyncConflict`]s for
+//! the caller to resolve.
+//!
+//! Requires the `unicode-normalization` crate for NFC normalization in
+//! [`canonical_record_hash`].
+
+use bitcoin::hashes::{sha256, Hash};
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+use super::types::{Bip329Record, Bip329Type};
+
+/// One entry in a [`super::LabelStore::manifest`] response: enough for a
+/// remote peer to both diff by hash and, on a mismatch, know which key it
+/// concerns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    #[serde(rename = "type")]
+    pub label_type: Bip329Type,
+    #[serde(rename = "ref")]
+    pub ref_id: String,
+    pub hash: String,
+}
+
+/// A `(type, ref)` present in both an incoming sync batch and the local
+/// store with a different `label`, left unapplied by
+/// [`super::LabelStore::sync_records`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncConflict {
+    #[serde(rename = "type")]
+    pub label_type: Bip329Type,
+    #[serde(rename = "ref")]
+    pub ref_id: String,
+    pub local_label: String,
+    pub incoming_label: String,
+}
+
+/// Result of [`super::LabelStore::sync_records`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncOutcome {
+    /// `(type, ref)` keys that were absent locally and have now been set.
+    pub applied: Vec<(Bip329Type, String)>,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// Stable hash of a record's canonical `(type, ref, label)` tuple: `ref`
+/// and `label` are trimmed and Unicode-NFC normalized first, so the same
+/// logical record hashes identically no matter which OS, editor, or input
+/// method produced the original bytes.
+pub(super) fn canonical_record_hash(record: &Bip329Record) -> String {
+    hash_fields(record.label_type, &record.ref_id, &record.label)
+}
+
+fn hash_fields(label_type: Bip329Type, ref_id: &str, label: &str) -> String {
+    let ref_id: String = ref_id.trim().nfc().collect();
+    let label: String = label.trim().nfc().collect();
+    let preimage = format!("{label_type}\0{ref_id}\0{label}");
+    sha256::Hash::hash(preimage.as_bytes()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(label_type: Bip329Type, ref_id: &str, label: &str) -> Bip329Record {
+        Bip329Record {
+            label_type,
+            ref_id: ref_id.to_string(),
+            label: label.to_string(),
+            origin: None,
+            spendable: None,
+        }
+    }
+
+    #[test]
+    fn hash_ignores_surrounding_whitespace() {
+        let a = canonical_record_hash(&record(Bip329Type::Addr, "bc1q...", "Cold storage"));
+        let b = canonical_record_hash(&record(Bip329Type::Addr, " bc1q... ", "  Cold storage  "));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn hash_differs_on_label_change() {
+        let a = canonical_record_hash(&record(Bip329Type::Addr, "bc1q...", "Cold storage"));
+        let b = canonical_record_hash(&record(Bip329Type::Addr, "bc1q...", "Hot wallet"));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn hash_differs_on_type_change() {
+        let a = canonical_record_hash(&record(Bip329Type::Addr, "abc", "same"));
+        let b = canonical_record_hash(&record(Bip329Type::Tx, "abc", "same"));
+        assert_ne!(a, b);
+    }
+}
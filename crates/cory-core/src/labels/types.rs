@@ -1,6 +1,7 @@
 //! BIP-329 record types, label file definitions, and store error definitions.
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
@@ -35,6 +36,22 @@ impl std::fmt::Display for Bip329Type {
     }
 }
 
+impl std::str::FromStr for Bip329Type {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tx" => Ok(Self::Tx),
+            "addr" => Ok(Self::Addr),
+            "pubkey" => Ok(Self::Pubkey),
+            "input" => Ok(Self::Input),
+            "output" => Ok(Self::Output),
+            "xpub" => Ok(Self::Xpub),
+            _ => Err(()),
+        }
+    }
+}
+
 /// A single BIP-329 label record, as defined by the specification.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Bip329Record {
@@ -56,8 +73,16 @@ pub struct Bip329Record {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum LabelFileKind {
-    Local,
-    Pack,
+    /// Loaded from a `--labels-rw` directory; editable and auto-flushed.
+    PersistentRw,
+    /// Created/imported via the UI; editable, and flushed through the
+    /// store's transport under a `browser/` source path.
+    BrowserRw,
+    /// Loaded from a `--labels-ro` directory; read-only.
+    PersistentRo,
+    /// Synthesized from a registered wallet descriptor/xpub match; not
+    /// backed by any label file, read-only.
+    WalletDerived,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -72,10 +97,28 @@ pub enum LabelStoreError {
     EmptyLabel,
 
     #[error("label file already exists: {0}")]
-    DuplicateLocalFile(String),
+    DuplicateFileId(String),
+
+    #[error("label file not found: {0}")]
+    FileNotFound(String),
+
+    #[error("label file is not a browser file: {0}")]
+    NotBrowserFile(String),
+
+    #[error("label file is read-only: {0}")]
+    ReadOnlyFile(String),
+
+    #[error("no mutation to undo for file: {0}")]
+    NothingToUndo(String),
 
-    #[error("local label file not found: {0}")]
-    LocalFileNotFound(String),
+    #[error("no undone mutation to redo for file: {0}")]
+    NothingToRedo(String),
+
+    #[error("version {0} is out of range for file `{1}` (has {2} layers)")]
+    InvalidVersion(usize, String, usize),
+
+    #[error("invalid file id `{0}`: scope and leaf segments must not be empty")]
+    InvalidFileId(String),
 
     #[error(transparent)]
     Core(#[from] CoreError),
@@ -84,13 +127,39 @@ pub enum LabelStoreError {
 /// Composite key for looking up labels: (type, ref_id).
 pub(super) type LabelKey = (Bip329Type, String);
 
-/// A loaded label file (local or pack).
+/// A loaded label file (persistent-rw, browser-rw, or persistent-ro).
 pub struct LabelFile {
     pub id: String,
     pub name: String,
     pub kind: LabelFileKind,
     pub editable: bool,
+    /// Path this file auto-flushes to on mutation: the on-disk path it was
+    /// loaded from for `PersistentRw`, `browser/<id>.jsonl` for
+    /// `BrowserRw`. `None` for `PersistentRo`.
+    pub(super) source_path: Option<PathBuf>,
+    /// Materialized view of `log` (or, for a file with no recorded ops yet,
+    /// the labels it was loaded with). Kept alongside `log` rather than
+    /// recomputed on every read since queries run far more often than
+    /// mutations.
     pub(super) labels: HashMap<LabelKey, Bip329Record>,
+    /// Append-only history of `set`/`delete` ops. Empty for a file that was
+    /// loaded from a flat JSONL file with no `.ops.jsonl` sidecar next to
+    /// it and has never been mutated since — see
+    /// [`super::oplog::effective_ops`].
+    pub(super) log: Vec<super::oplog::LabelOp>,
+    /// Ops popped off `log` by [`super::LabelStore::undo`], most-recently-undone
+    /// last, so [`super::LabelStore::redo`] can push them back in order.
+    /// Cleared by any new `set`/`delete`/merge, since redoing past a fresh
+    /// edit would silently discard it.
+    pub(super) undone: Vec<super::oplog::LabelOp>,
+    /// Number of ops pushed onto `log` by this `LabelStore` instance since it
+    /// was loaded/seeded, as opposed to ops that were already part of the
+    /// file on disk (or synthesized by [`super::store::ensure_log_seeded`]
+    /// from a pre-existing `labels` map). Reset to `0` whenever `log` is
+    /// reseeded or wholesale-replaced from a non-session source. Lets
+    /// [`super::LabelStore::undo`] refuse to pop past what this session
+    /// actually did, rather than popping into a freshly seeded baseline.
+    pub(super) session_op_count: usize,
 }
 
 impl LabelFile {
@@ -98,4 +167,85 @@ impl LabelFile {
     pub fn record_count(&self) -> usize {
         self.labels.len()
     }
+
+    /// Number of layers (recorded ops) in this file's history, the upper
+    /// bound accepted for `to_version`/`from_version` in
+    /// [`super::LabelStore::diff`].
+    pub fn version_count(&self) -> usize {
+        self.log.len()
+    }
+
+    /// Unix timestamp (seconds) of the most recent recorded mutation, for a
+    /// `Last-Modified` response header. `None` for a file that was loaded
+    /// from a flat JSONL file with no `.ops.jsonl` sidecar and has never
+    /// been mutated since, since no wall-clock time was ever recorded for
+    /// it — see [`super::oplog`]'s module docs for why the log, not this
+    /// method, is the authoritative state.
+    pub fn last_modified_unix_secs(&self) -> Option<u64> {
+        self.log.iter().map(|op| op.clock.millis / 1000).max()
+    }
+}
+
+// ==============================================================================
+// Diffing
+// ==============================================================================
+
+/// Net changes between two versions of a file, as produced by
+/// [`super::LabelStore::diff`]. `changed` pairs the old record with the new
+/// one so a caller can show both sides without a second lookup.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LabelDiff {
+    pub added: Vec<Bip329Record>,
+    pub removed: Vec<Bip329Record>,
+    pub changed: Vec<(Bip329Record, Bip329Record)>,
+}
+
+impl LabelDiff {
+    /// `true` if the two versions compared are identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+// ==============================================================================
+// Validation
+// ==============================================================================
+
+/// A single integrity problem found by [`super::LabelStore::validate`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidateProblem {
+    /// ID of the file the problem was found in.
+    pub file_id: String,
+    /// `type:ref` of the offending record, if the problem is record-scoped.
+    pub record: Option<String>,
+    pub message: String,
+}
+
+/// Integrity report produced by [`super::LabelStore::validate`].
+///
+/// Carries per-problem detail vectors alongside aggregate counts, so a
+/// caller can both surface a one-line summary ("3 files, 1204 labels, 2
+/// empty labels, 5 duplicate refs across files") and drill into specifics.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidateStats {
+    pub files_scanned: usize,
+    pub records_scanned: usize,
+    /// Records whose `ref_id` or `label` is empty or all whitespace.
+    pub empty_labels: Vec<ValidateProblem>,
+    /// Records whose `origin` is present but blank.
+    pub malformed_origins: Vec<ValidateProblem>,
+    /// `(type, ref)` keys that appear in more than one file.
+    pub duplicate_refs: Vec<ValidateProblem>,
+    /// File IDs that collide across the three file vectors.
+    pub id_collisions: Vec<ValidateProblem>,
+}
+
+impl ValidateStats {
+    /// `true` if no problems of any kind were found.
+    pub fn is_clean(&self) -> bool {
+        self.empty_labels.is_empty()
+            && self.malformed_origins.is_empty()
+            && self.duplicate_refs.is_empty()
+            && self.id_collisions.is_empty()
+    }
 }
@@ -0,0 +1,193 @@
+//! Live filesystem watching for `--labels-rw`/`--labels-ro` directories.
+//!
+//! [`LabelStore::load_rw_dir`]/`load_ro_dir` only ever run once at
+//! startup, and [`crate::jobs::rescan_dir`] only rescans on request —
+//! neither notices an edit made outside the app. [`LabelWatcher::watch`]
+//! instead monitors one base directory with the `notify` crate and
+//! incrementally applies create/modify/delete/rename events to a shared
+//! [`LabelStore`] as they happen, via
+//! [`LabelStore::apply_watched_file`]/[`LabelStore::remove_watched_file`].
+//! Rapid bursts (e.g. an editor's save-via-rename, or a `git checkout`)
+//! are debounced per path so a flurry of raw OS events collapses into a
+//! single apply once that path goes quiet. Every applied change is
+//! published on a caller-supplied [`broadcast::Sender`], mirroring
+//! [`crate::notify::ZmqNotifier`], so a server layer can expose them over
+//! SSE without polling.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::warn;
+
+use super::pack::relative_label_file_id;
+use crate::error::CoreError;
+use crate::labels::{LabelFileKind, LabelStore};
+
+/// How long a path must go quiet before its latest pending event is
+/// applied, collapsing bursts (e.g. an editor's save-via-rename writing
+/// several `.jsonl`/`.ops.jsonl` files back to back) into one update.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// A change already applied to a watched [`LabelStore`], broadcast for any
+/// number of subscribers (e.g. a server-sent-events endpoint).
+#[derive(Debug, Clone)]
+pub struct LabelChangeEvent {
+    pub kind: LabelFileKind,
+    pub file_id: String,
+    pub removed: bool,
+}
+
+/// Watches one base directory for `.jsonl` changes and incrementally
+/// applies them to a shared [`LabelStore`]. Dropping this value stops the
+/// background watch and debounce tasks.
+pub struct LabelWatcher {
+    _fs_watcher: RecommendedWatcher,
+    debounce_task: JoinHandle<()>,
+}
+
+impl LabelWatcher {
+    /// Start watching `base` (a `--labels-rw`/`--labels-ro` directory) for
+    /// `.jsonl` changes, applying them to `store` as `kind` and publishing
+    /// each applied change on `changes`.
+    pub fn watch(
+        base: PathBuf,
+        kind: LabelFileKind,
+        store: Arc<RwLock<LabelStore>>,
+        changes: broadcast::Sender<LabelChangeEvent>,
+    ) -> Result<Self, CoreError> {
+        let (raw_tx, raw_rx) = mpsc::unbounded_channel();
+        let base_for_log = base.clone();
+        let mut fs_watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            match res {
+                // Reads (`Access`) and anything `notify` can't classify
+                // (`Other`) never change a file's contents; skip them so
+                // they don't reset an unrelated path's debounce deadline.
+                Ok(event) if matches!(event.kind, EventKind::Access(_) | EventKind::Other) => {}
+                Ok(event) => {
+                    // Only fails once the receiver (owned by `debounce_and_apply`,
+                    // which only stops when this `LabelWatcher` is dropped
+                    // and aborts it) is gone, so there's nothing left to notify.
+                    let _ = raw_tx.send(event);
+                }
+                Err(e) => {
+                    warn!(base = %base_for_log.display(), error = %e, "label directory watch error");
+                }
+            }
+        })
+        .map_err(|e| CoreError::Watch(e.to_string()))?;
+        fs_watcher
+            .watch(&base, RecursiveMode::Recursive)
+            .map_err(|e| CoreError::Watch(e.to_string()))?;
+
+        let debounce_task = tokio::spawn(debounce_and_apply(raw_rx, base, kind, store, changes));
+
+        Ok(Self {
+            _fs_watcher: fs_watcher,
+            debounce_task,
+        })
+    }
+}
+
+impl Drop for LabelWatcher {
+    fn drop(&mut self) {
+        self.debounce_task.abort();
+    }
+}
+
+/// Coalesces raw `notify` events per path (so a burst of writes to the
+/// same file only triggers one [`LabelStore`] update, [`DEBOUNCE`] after
+/// the last one) and applies the settled result.
+async fn debounce_and_apply(
+    mut raw_rx: mpsc::UnboundedReceiver<Event>,
+    base: PathBuf,
+    kind: LabelFileKind,
+    store: Arc<RwLock<LabelStore>>,
+    changes: broadcast::Sender<LabelChangeEvent>,
+) {
+    // Per watched path, the deadline its latest pending event settles at.
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+
+    loop {
+        let sleep = match pending.values().min() {
+            Some(deadline) => tokio::time::sleep_until(*deadline),
+            None => tokio::time::sleep(Duration::from_secs(3600)),
+        };
+        tokio::pin!(sleep);
+
+        tokio::select! {
+            event = raw_rx.recv() => {
+                let Some(event) = event else { return };
+                for path in relevant_paths(&event) {
+                    pending.insert(path, Instant::now() + DEBOUNCE);
+                }
+            }
+            () = &mut sleep => {
+                let now = Instant::now();
+                let settled: Vec<PathBuf> = pending
+                    .iter()
+                    .filter(|(_, deadline)| **deadline <= now)
+                    .map(|(path, _)| path.clone())
+                    .collect();
+                for path in settled {
+                    pending.remove(&path);
+                    apply_settled(&base, &path, kind, &store, &changes).await;
+                }
+            }
+        }
+    }
+}
+
+/// `.jsonl` paths an event touches, ignoring `.ops.jsonl` sidecars and
+/// directories — mirrors [`super::pack::walk_label_dir`]'s own filtering,
+/// so a watcher only ever reacts to the same files a full rescan would
+/// load.
+fn relevant_paths(event: &Event) -> impl Iterator<Item = PathBuf> + '_ {
+    event.paths.iter().filter_map(|path| {
+        let is_jsonl = path.extension().is_some_and(|ext| ext == "jsonl");
+        let is_sidecar = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().ends_with(".ops.jsonl"));
+        (is_jsonl && !is_sidecar).then(|| path.clone())
+    })
+}
+
+/// Apply one settled path: reload it if it still exists (covers both
+/// create and modify — and the "create" half of a rename), or drop it
+/// from the store if it doesn't (delete, and the "remove" half of a
+/// rename away).
+async fn apply_settled(
+    base: &Path,
+    path: &Path,
+    kind: LabelFileKind,
+    store: &Arc<RwLock<LabelStore>>,
+    changes: &broadcast::Sender<LabelChangeEvent>,
+) {
+    let exists = tokio::fs::try_exists(path).await.unwrap_or(false);
+    let file_id = relative_label_file_id(base, path);
+    if file_id.is_empty() {
+        return;
+    }
+
+    let mut guard = store.write().await;
+    if exists {
+        if let Err(e) = guard.apply_watched_file(base, path, kind) {
+            warn!(path = %path.display(), error = %e, "failed to apply watched label file change");
+            return;
+        }
+    } else {
+        guard.remove_watched_file(base, path, kind);
+    }
+    drop(guard);
+
+    let _ = changes.send(LabelChangeEvent {
+        kind,
+        file_id,
+        removed: !exists,
+    });
+}
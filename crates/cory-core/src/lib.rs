@@ -9,9 +9,16 @@ pub mod cache;
 pub mod enrich;
 pub mod error;
 pub mod graph;
+pub mod history;
+pub mod jobs;
 pub mod labels;
+pub mod notify;
+pub mod prevout_store;
+mod proof;
 pub mod rpc;
+pub mod tip;
 pub mod types;
+pub mod wallet;
 
 #[cfg(test)]
 pub(crate) mod test_util;
@@ -0,0 +1,223 @@
+//! Push-based block/transaction notifications via Bitcoin Core's ZMQ
+//! publisher, complementing the poll-based [`crate::rpc`] module.
+//!
+//! Bitcoin Core's `-zmqpub*` options publish three-frame multipart
+//! messages (`topic`, `payload`, `sequence`) over ZMQ PUB sockets.
+//! [`ZmqNotifier::connect`] subscribes to one or more of these endpoints
+//! and decodes each message into a typed [`NotifyEvent`], broadcast to any
+//! number of consumers via [`ZmqNotifier::subscribe`]. A consumer can use
+//! a `NewBlockHash` event to proactively warm [`crate::rpc::HttpRpcClient`]'s
+//! header-height cache by calling [`crate::rpc::BitcoinRpc::get_block_header`]
+//! for the new hash, rather than waiting for the next graph traversal to
+//! need it.
+//!
+//! Each topic carries its own monotonically increasing sequence counter;
+//! a gap in that counter means Core dropped messages (e.g. the PUB socket's
+//! high-water mark was exceeded) and is surfaced as [`NotifyEvent::Gap`] so
+//! a consumer knows to resync state through the existing RPC path instead
+//! of assuming it saw every block/tx.
+
+use std::collections::HashMap;
+
+use bitcoin::hashes::Hash;
+use bitcoin::{Block, BlockHash, Transaction, Txid};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tokio_stream::{Stream, StreamExt};
+use tracing::{debug, warn};
+use zeromq::{Socket, SocketRecv, SubSocket};
+
+use crate::error::CoreError;
+
+/// ZMQ topics Bitcoin Core can publish, one per `-zmqpub<topic>=...` flag.
+const TOPICS: &[&str] = &["hashblock", "hashtx", "rawblock", "rawtx"];
+
+/// Capacity of the broadcast channel fanning events out to subscribers.
+/// A slow subscriber that falls this far behind the fastest one loses the
+/// oldest unread events and learns about it as a [`BroadcastStreamRecvError`]
+/// from [`ZmqNotifier::subscribe_stream`], independent of the per-topic
+/// [`NotifyEvent::Gap`] tracking (which only catches ZMQ itself dropping
+/// messages, not a slow in-process consumer).
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// A decoded event from Bitcoin Core's ZMQ publisher.
+#[derive(Debug, Clone)]
+pub enum NotifyEvent {
+    /// `hashblock`: a new block connected to the chain tip.
+    NewBlockHash(BlockHash),
+    /// `hashtx`: a new transaction entered the mempool (or a block).
+    NewTxid(Txid),
+    /// `rawblock`: a new block, fully decoded.
+    NewBlock(Block),
+    /// `rawtx`: a new transaction, fully decoded.
+    NewTx(Transaction),
+    /// `topic`'s sequence counter skipped `missed` messages, meaning Core
+    /// dropped them (usually a PUB socket high-water mark being hit under
+    /// load). Consumers that care about not missing anything should treat
+    /// this as "resync `topic`'s state through the RPC client."
+    Gap { topic: &'static str, missed: u32 },
+}
+
+/// Subscribes to Bitcoin Core's ZMQ publisher endpoints and fans out
+/// decoded events to any number of consumers.
+///
+/// Dropping this value stops the background receive task and closes every
+/// outstanding subscription.
+pub struct ZmqNotifier {
+    sender: broadcast::Sender<NotifyEvent>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl ZmqNotifier {
+    /// Connect to each of `endpoints` (e.g. `tcp://127.0.0.1:28332`) and
+    /// subscribe to all four notification topics on each. Matches Core's
+    /// usual deployment where a single `-zmqpub*` endpoint is reused for
+    /// every topic, but also works if topics are split across endpoints,
+    /// since subscribing to a topic a given PUB socket never publishes is
+    /// a no-op.
+    pub async fn connect(endpoints: &[&str]) -> Result<Self, CoreError> {
+        if endpoints.is_empty() {
+            return Err(CoreError::InvalidTxData(
+                "zmq notifier requires at least one endpoint".to_owned(),
+            ));
+        }
+
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let mut tasks = Vec::with_capacity(endpoints.len());
+        for endpoint in endpoints {
+            let mut socket = SubSocket::new();
+            socket
+                .connect(endpoint)
+                .await
+                .map_err(|e| CoreError::InvalidTxData(format!("zmq connect to {endpoint}: {e}")))?;
+            for topic in TOPICS {
+                socket.subscribe(topic).await.map_err(|e| {
+                    CoreError::InvalidTxData(format!("zmq subscribe to {topic} on {endpoint}: {e}"))
+                })?;
+            }
+
+            let endpoint = (*endpoint).to_owned();
+            let task_sender = sender.clone();
+            tasks.push(tokio::spawn(async move {
+                run_receive_loop(endpoint, socket, task_sender).await
+            }));
+        }
+
+        Ok(Self { sender, tasks })
+    }
+
+    /// Subscribe to the event broadcast. Each subscriber gets every event
+    /// published after this call; one that falls more than
+    /// [`CHANNEL_CAPACITY`] events behind loses the oldest unread ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<NotifyEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Like [`Self::subscribe`], but adapted into a [`Stream`] for callers
+    /// that want to combine it with other streams (e.g. via `select!` or
+    /// `StreamExt` combinators) rather than polling a channel directly.
+    /// A lagged subscriber sees a [`BroadcastStreamRecvError::Lagged`] item
+    /// rather than a silent gap.
+    pub fn subscribe_stream(
+        &self,
+    ) -> impl Stream<Item = Result<NotifyEvent, BroadcastStreamRecvError>> {
+        BroadcastStream::new(self.subscribe())
+    }
+}
+
+impl Drop for ZmqNotifier {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+async fn run_receive_loop(
+    endpoint: String,
+    mut socket: SubSocket,
+    sender: broadcast::Sender<NotifyEvent>,
+) {
+    let mut last_seq: HashMap<&'static str, u32> = HashMap::new();
+    loop {
+        let message = match socket.recv().await {
+            Ok(message) => message,
+            Err(e) => {
+                warn!(endpoint = %endpoint, error = %e, "zmq receive failed, stopping notifier task");
+                return;
+            }
+        };
+
+        let frames: Vec<Vec<u8>> = message.into_vec().into_iter().map(|b| b.to_vec()).collect();
+        let [topic, payload, seq] = <[Vec<u8>; 3]>::try_from(frames).unwrap_or_else(|frames| {
+            warn!(
+                endpoint = %endpoint,
+                frame_count = frames.len(),
+                "zmq message did not have the expected 3 frames; ignoring"
+            );
+            [Vec::new(), Vec::new(), Vec::new()]
+        });
+        if topic.is_empty() {
+            continue;
+        }
+
+        let Some(topic) = TOPICS.iter().find(|t| t.as_bytes() == topic.as_slice()) else {
+            debug!(endpoint = %endpoint, "zmq message for unrecognized topic; ignoring");
+            continue;
+        };
+        let Some(sequence) = seq
+            .get(..4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        else {
+            warn!(endpoint = %endpoint, topic = %topic, "zmq sequence frame was not 4 bytes; ignoring");
+            continue;
+        };
+
+        if let Some(previous) = last_seq.insert(topic, sequence) {
+            let expected = previous.wrapping_add(1);
+            if sequence != expected {
+                let missed = sequence.wrapping_sub(expected);
+                let _ = sender.send(NotifyEvent::Gap { topic, missed });
+            }
+        }
+
+        let event = match *topic {
+            "hashblock" => BlockHash::from_slice(&payload)
+                .ok()
+                .map(NotifyEvent::NewBlockHash),
+            "hashtx" => Txid::from_slice(&payload).ok().map(NotifyEvent::NewTxid),
+            "rawblock" => bitcoin::consensus::encode::deserialize(&payload)
+                .ok()
+                .map(NotifyEvent::NewBlock),
+            "rawtx" => bitcoin::consensus::encode::deserialize(&payload)
+                .ok()
+                .map(NotifyEvent::NewTx),
+            _ => None,
+        };
+
+        match event {
+            Some(event) => {
+                // No subscribers is a normal, common state (e.g. before the
+                // server's warmup task has subscribed yet); not an error.
+                let _ = sender.send(event);
+            }
+            None => warn!(
+                endpoint = %endpoint,
+                topic = %topic,
+                "failed to decode zmq payload; ignoring"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn connect_rejects_no_endpoints() {
+        let err = ZmqNotifier::connect(&[]).await.expect_err("must reject");
+        assert!(err.to_string().contains("at least one endpoint"));
+    }
+}
@@ -0,0 +1,327 @@
+//! Pluggable on-disk backing store for resolved prevout data.
+//!
+//! `crate::cache::Cache`'s prevout cache is in-memory and LRU-bounded, so
+//! it starts cold on every restart — repeat ancestry queries against
+//! overlapping histories pay for every prevout resolution again, even ones
+//! already seen in a previous run. A [`PrevoutStore`] persists resolved
+//! `(txid, vout) -> TxOutput` pairs to disk behind a trait, so
+//! `crate::graph`'s `fetch_and_convert`/`resolve_prevout_without_rpc`/
+//! `resolve_unresolved_prevouts` can consult a warm, restart-surviving
+//! store before ever falling back to RPC.
+//!
+//! Unlike holding the whole UTXO set in RAM, a `PrevoutStore` only ever
+//! holds the outputs the graph has actually touched, lazily populated as
+//! ancestry queries resolve prevouts — plus whatever [`PrevoutStore::warm_up`]
+//! is handed up front.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use bitcoin::{OutPoint, Txid};
+use serde::{Deserialize, Serialize};
+
+use crate::error::CoreError;
+use crate::types::TxOutput;
+
+/// Backing store for persisted prevout lookups.
+///
+/// Implementations are expected to be cheap to clone (e.g. hold an `Arc`
+/// internally), mirroring [`crate::labels::Transport`].
+pub trait PrevoutStore: Send + Sync {
+    /// Look up a persisted prevout for `outpoint`, if one was ever recorded.
+    fn get(&self, outpoint: OutPoint) -> Option<TxOutput>;
+
+    /// Persist a resolved prevout, evicting the oldest entry first if the
+    /// store is at capacity.
+    fn put(&self, outpoint: OutPoint, info: TxOutput) -> Result<(), CoreError>;
+
+    /// Bulk-import already-known outputs — e.g. from a wallet's own UTXO
+    /// set — ahead of any graph query, without a `put` round trip (and its
+    /// disk flush) per entry.
+    fn warm_up(&self, entries: Vec<(OutPoint, TxOutput)>) -> Result<(), CoreError> {
+        for (outpoint, info) in entries {
+            self.put(outpoint, info)?;
+        }
+        Ok(())
+    }
+
+    /// Number of entries currently persisted.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// ==============================================================================
+// File-Backed Store
+// ==============================================================================
+
+#[derive(Serialize, Deserialize)]
+struct PrevoutRecord {
+    txid: Txid,
+    vout: u32,
+    output: TxOutput,
+}
+
+struct Inner {
+    entries: HashMap<OutPoint, TxOutput>,
+    /// Insertion order, oldest first, used for FIFO eviction once
+    /// `max_entries` is exceeded. Not LRU: re-reading an entry via `get`
+    /// doesn't move it, so eviction order is stable across lookups and
+    /// cheap to maintain without extra bookkeeping per `get`.
+    order: VecDeque<OutPoint>,
+}
+
+/// [`PrevoutStore`] backed by a single JSONL file, fully loaded into memory
+/// on [`Self::open`].
+///
+/// Every [`Self::put`] rewrites the whole file from memory (after evicting
+/// the oldest entries once `max_entries` is exceeded), via the same
+/// write-then-rename pattern as [`crate::labels::LocalTransport`]; the file
+/// is never appended to in place.
+pub struct FilePrevoutStore {
+    path: PathBuf,
+    max_entries: usize,
+    inner: Mutex<Inner>,
+}
+
+impl FilePrevoutStore {
+    /// Open (or create) a store at `path`, keeping at most `max_entries`
+    /// entries. If `path` already holds more than `max_entries` records,
+    /// only the most recently appended `max_entries` are kept.
+    pub fn open(path: impl Into<PathBuf>, max_entries: usize) -> Result<Self, CoreError> {
+        let path = path.into();
+        let records = match std::fs::read_to_string(&path) {
+            Ok(content) => parse_jsonl(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(CoreError::Io(e)),
+        };
+
+        let mut entries = HashMap::new();
+        let mut order = VecDeque::new();
+        for record in records {
+            let outpoint = OutPoint::new(record.txid, record.vout);
+            if entries.insert(outpoint, record.output).is_none() {
+                order.push_back(outpoint);
+            }
+        }
+        while order.len() > max_entries {
+            if let Some(oldest) = order.pop_front() {
+                entries.remove(&oldest);
+            }
+        }
+
+        let store = Self {
+            path,
+            max_entries,
+            inner: Mutex::new(Inner { entries, order }),
+        };
+        // Rewrite compacted in case the on-disk file had more records than
+        // `max_entries`, so a restart without any further writes doesn't
+        // keep re-reading a file larger than it needs to be.
+        store.persist()?;
+        Ok(store)
+    }
+
+    fn persist(&self) -> Result<(), CoreError> {
+        let inner = self.inner.lock().expect("prevout store mutex poisoned");
+        let mut content = String::new();
+        for outpoint in &inner.order {
+            let output = inner
+                .entries
+                .get(outpoint)
+                .expect("order and entries stay in sync");
+            let record = PrevoutRecord {
+                txid: outpoint.txid,
+                vout: outpoint.vout,
+                output: output.clone(),
+            };
+            content.push_str(&serde_json::to_string(&record).expect("valid JSON"));
+            content.push('\n');
+        }
+        drop(inner);
+        write_file(&self.path, content.as_bytes())
+    }
+}
+
+impl PrevoutStore for FilePrevoutStore {
+    fn get(&self, outpoint: OutPoint) -> Option<TxOutput> {
+        let inner = self.inner.lock().expect("prevout store mutex poisoned");
+        inner.entries.get(&outpoint).cloned()
+    }
+
+    fn put(&self, outpoint: OutPoint, info: TxOutput) -> Result<(), CoreError> {
+        {
+            let mut inner = self.inner.lock().expect("prevout store mutex poisoned");
+            if inner.entries.insert(outpoint, info).is_none() {
+                inner.order.push_back(outpoint);
+            }
+            while inner.order.len() > self.max_entries {
+                if let Some(oldest) = inner.order.pop_front() {
+                    inner.entries.remove(&oldest);
+                }
+            }
+        }
+        self.persist()
+    }
+
+    fn warm_up(&self, entries: Vec<(OutPoint, TxOutput)>) -> Result<(), CoreError> {
+        {
+            let mut inner = self.inner.lock().expect("prevout store mutex poisoned");
+            for (outpoint, info) in entries {
+                if inner.entries.insert(outpoint, info).is_none() {
+                    inner.order.push_back(outpoint);
+                }
+            }
+            while inner.order.len() > self.max_entries {
+                if let Some(oldest) = inner.order.pop_front() {
+                    inner.entries.remove(&oldest);
+                }
+            }
+        }
+        self.persist()
+    }
+
+    fn len(&self) -> usize {
+        self.inner
+            .lock()
+            .expect("prevout store mutex poisoned")
+            .order
+            .len()
+    }
+}
+
+fn parse_jsonl(content: &str) -> Result<Vec<PrevoutRecord>, CoreError> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line.trim())
+                .map_err(|e| CoreError::InvalidTxData(format!("invalid prevout store record: {e}")))
+        })
+        .collect()
+}
+
+/// Write `content` to `path` atomically: write to a sibling `.tmp` file,
+/// then rename over the target, mirroring
+/// [`crate::labels::LocalTransport::write_file`].
+fn write_file(path: &Path, content: &[u8]) -> Result<(), CoreError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(CoreError::Io)?;
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp_path = path.with_file_name(format!("{file_name}.tmp"));
+
+    let mut tmp_file = std::fs::File::create(&tmp_path).map_err(CoreError::Io)?;
+    tmp_file.write_all(content).map_err(CoreError::Io)?;
+    tmp_file.sync_all().map_err(CoreError::Io)?;
+    drop(tmp_file);
+
+    std::fs::rename(&tmp_path, path).map_err(CoreError::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{make_output, txid_from_byte};
+
+    fn tmp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "cory-prevout-store-test-{}-{name}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("system time before unix epoch")
+                .as_nanos()
+        ))
+    }
+
+    #[test]
+    fn put_then_get_round_trips() {
+        let path = tmp_path("round-trip");
+        let store = FilePrevoutStore::open(&path, 10).expect("open");
+        let outpoint = OutPoint::new(txid_from_byte(1), 0);
+        let output = make_output(5000);
+
+        store.put(outpoint, output.clone()).expect("put");
+        let fetched = store.get(outpoint).expect("should be present");
+        assert_eq!(fetched.value, output.value);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn get_returns_none_for_unknown_outpoint() {
+        let path = tmp_path("unknown");
+        let store = FilePrevoutStore::open(&path, 10).expect("open");
+        assert!(store.get(OutPoint::new(txid_from_byte(1), 0)).is_none());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn reopening_recovers_persisted_entries() {
+        let path = tmp_path("reopen");
+        {
+            let store = FilePrevoutStore::open(&path, 10).expect("open");
+            store
+                .put(OutPoint::new(txid_from_byte(1), 0), make_output(1000))
+                .expect("put");
+        }
+
+        let reopened = FilePrevoutStore::open(&path, 10).expect("reopen");
+        assert_eq!(reopened.len(), 1);
+        assert!(reopened.get(OutPoint::new(txid_from_byte(1), 0)).is_some());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn exceeding_max_entries_evicts_oldest_first() {
+        let path = tmp_path("evict");
+        let store = FilePrevoutStore::open(&path, 2).expect("open");
+        let a = OutPoint::new(txid_from_byte(1), 0);
+        let b = OutPoint::new(txid_from_byte(2), 0);
+        let c = OutPoint::new(txid_from_byte(3), 0);
+
+        store.put(a, make_output(1000)).expect("put a");
+        store.put(b, make_output(2000)).expect("put b");
+        store.put(c, make_output(3000)).expect("put c");
+
+        assert!(store.get(a).is_none(), "oldest entry should be evicted");
+        assert!(store.get(b).is_some());
+        assert!(store.get(c).is_some());
+        assert_eq!(store.len(), 2);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn warm_up_bulk_imports_without_exceeding_capacity() {
+        let path = tmp_path("warm-up");
+        let store = FilePrevoutStore::open(&path, 2).expect("open");
+        let a = OutPoint::new(txid_from_byte(1), 0);
+        let b = OutPoint::new(txid_from_byte(2), 0);
+        let c = OutPoint::new(txid_from_byte(3), 0);
+
+        store
+            .warm_up(vec![
+                (a, make_output(1000)),
+                (b, make_output(2000)),
+                (c, make_output(3000)),
+            ])
+            .expect("warm_up");
+
+        assert_eq!(store.len(), 2);
+        assert!(store.get(a).is_none(), "oldest entry should be evicted");
+        assert!(store.get(c).is_some());
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,105 @@
+//! SPV-style Merkle-inclusion proof verification.
+//!
+//! Decodes the hex proof returned by `gettxoutproof` as a
+//! [`bitcoin::merkle_block::MerkleBlock`], recomputes the merkle root from
+//! the matched transactions via its partial merkle tree, and compares it
+//! against the containing block header's `merkleroot`, fetched
+//! independently via `getblockheader`. This catches a node that returns a
+//! proof whose internal header and partial tree agree with each other but
+//! not with the header it actually has on record for that block.
+
+use bitcoin::merkle_block::MerkleBlock;
+use bitcoin::{consensus::encode::deserialize, Txid};
+
+use crate::error::CoreError;
+use crate::rpc::BlockHeaderInfo;
+use crate::types::TxInclusionProof;
+
+/// Verify `txid`'s inclusion proof (`gettxoutproof` hex) against an
+/// independently-fetched block header.
+///
+/// Returns [`CoreError::TxNotIncluded`] if the proof's matched transactions
+/// don't include `txid`, and [`CoreError::InvalidProof`] if the proof's
+/// reconstructed merkle root disagrees with the header's.
+pub(crate) fn verify_tx_inclusion(
+    txid: Txid,
+    header: BlockHeaderInfo,
+    proof_hex: &str,
+) -> Result<TxInclusionProof, CoreError> {
+    let bytes = decode_hex(proof_hex)?;
+    let merkle_block: MerkleBlock = deserialize(&bytes)
+        .map_err(|e| CoreError::InvalidProof(format!("invalid txoutproof: {e}")))?;
+
+    let mut matches = Vec::new();
+    let mut indexes = Vec::new();
+    let computed_root = merkle_block
+        .extract_matches(&mut matches, &mut indexes)
+        .map_err(|e| CoreError::InvalidProof(format!("invalid merkle proof: {e}")))?;
+
+    if !matches.contains(&txid) {
+        return Err(CoreError::TxNotIncluded(txid));
+    }
+    if computed_root != header.merkle_root {
+        return Err(CoreError::InvalidProof(format!(
+            "reconstructed merkle root {computed_root} does not match block header's {}",
+            header.merkle_root
+        )));
+    }
+
+    Ok(TxInclusionProof {
+        txid,
+        block_hash: header.hash,
+        block_height: header.height,
+        merkle_root: computed_root,
+    })
+}
+
+/// Tiny hex-decoding helper to avoid adding a `hex` crate dependency for
+/// this single call site.
+fn decode_hex(s: &str) -> Result<Vec<u8>, CoreError> {
+    if s.len() % 2 != 0 {
+        return Err(CoreError::InvalidTxData(
+            "txoutproof hex has odd length".to_owned(),
+        ));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&s[i..i + 2], 16)
+                .map_err(|e| CoreError::InvalidTxData(format!("invalid txoutproof hex: {e}")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+    use bitcoin::BlockHash;
+
+    #[test]
+    fn decode_hex_roundtrips_bytes() {
+        assert_eq!(decode_hex("00ff10").unwrap(), vec![0x00, 0xff, 0x10]);
+    }
+
+    #[test]
+    fn decode_hex_rejects_odd_length() {
+        assert!(decode_hex("abc").is_err());
+    }
+
+    #[test]
+    fn decode_hex_rejects_non_hex() {
+        assert!(decode_hex("zz").is_err());
+    }
+
+    #[test]
+    fn verify_tx_inclusion_rejects_garbage_proof() {
+        let header = BlockHeaderInfo {
+            hash: BlockHash::all_zeros(),
+            height: 100,
+            merkle_root: bitcoin::TxMerkleNode::all_zeros(),
+        };
+        let txid = Txid::all_zeros();
+        assert!(verify_tx_inclusion(txid, header, "00112233").is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! A [`BitcoinRpc`] decorator that counts calls issued through it.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use bitcoin::{BlockHash, OutPoint, Txid};
+
+use crate::error::CoreError;
+use crate::types::{TxInclusionProof, TxNode};
+
+use super::{
+    BitcoinRpc, Block, BlockHeaderInfo, BlockId, ChainInfo, EstimateMode, TxOutInfo, TxSpend,
+};
+
+/// Wraps an `&dyn BitcoinRpc`, counting every call forwarded through it.
+///
+/// Every trait method is overridden to forward straight to `inner`'s own
+/// implementation, rather than falling back to the trait's default batching
+/// loops — so a batched `get_tx_outs` call still counts as one call, not one
+/// per outpoint, matching how many round-trips `inner` actually issues.
+/// Used by `cory`'s server to observe how many RPC calls one `get_graph`
+/// request costs (see `cory::server::graph::run_graph_build`).
+pub struct CountingRpc<'a> {
+    inner: &'a dyn BitcoinRpc,
+    calls: AtomicU64,
+}
+
+impl<'a> CountingRpc<'a> {
+    pub fn new(inner: &'a dyn BitcoinRpc) -> Self {
+        Self {
+            inner,
+            calls: AtomicU64::new(0),
+        }
+    }
+
+    /// Total calls forwarded to `inner` so far.
+    pub fn call_count(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    fn record(&self) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl<'a> BitcoinRpc for CountingRpc<'a> {
+    async fn get_transaction(&self, txid: &Txid) -> Result<TxNode, CoreError> {
+        self.record();
+        self.inner.get_transaction(txid).await
+    }
+
+    async fn get_transactions(&self, txids: &[Txid]) -> Result<Vec<TxNode>, CoreError> {
+        self.record();
+        self.inner.get_transactions(txids).await
+    }
+
+    async fn get_tx_out(
+        &self,
+        txid: &Txid,
+        vout: u32,
+        include_mempool: bool,
+    ) -> Result<Option<TxOutInfo>, CoreError> {
+        self.record();
+        self.inner.get_tx_out(txid, vout, include_mempool).await
+    }
+
+    async fn get_tx_outs(
+        &self,
+        outpoints: &[OutPoint],
+        include_mempool: bool,
+    ) -> Result<Vec<Option<TxOutInfo>>, CoreError> {
+        self.record();
+        self.inner.get_tx_outs(outpoints, include_mempool).await
+    }
+
+    async fn get_spend(&self, outpoint: OutPoint) -> Result<Option<TxSpend>, CoreError> {
+        self.record();
+        self.inner.get_spend(outpoint).await
+    }
+
+    async fn get_spends(&self, outpoints: &[OutPoint]) -> Result<Vec<Option<TxSpend>>, CoreError> {
+        self.record();
+        self.inner.get_spends(outpoints).await
+    }
+
+    async fn get_blockchain_info(&self) -> Result<ChainInfo, CoreError> {
+        self.record();
+        self.inner.get_blockchain_info().await
+    }
+
+    async fn get_tx_inclusion_proof(
+        &self,
+        txid: &Txid,
+        block_hash: &BlockHash,
+    ) -> Result<Option<TxInclusionProof>, CoreError> {
+        self.record();
+        self.inner.get_tx_inclusion_proof(txid, block_hash).await
+    }
+
+    async fn get_txout_proof(&self, txids: &[Txid]) -> Result<Option<String>, CoreError> {
+        self.record();
+        self.inner.get_txout_proof(txids).await
+    }
+
+    async fn get_block_header(&self, id: BlockId) -> Result<BlockHeaderInfo, CoreError> {
+        self.record();
+        self.inner.get_block_header(id).await
+    }
+
+    async fn get_block_hash(&self, id: BlockId) -> Result<BlockHash, CoreError> {
+        self.record();
+        self.inner.get_block_hash(id).await
+    }
+
+    async fn get_block(&self, id: BlockId) -> Result<Block, CoreError> {
+        self.record();
+        self.inner.get_block(id).await
+    }
+
+    async fn estimate_smart_fee(
+        &self,
+        conf_target: u16,
+        mode: EstimateMode,
+    ) -> Result<Option<f64>, CoreError> {
+        self.record();
+        self.inner.estimate_smart_fee(conf_target, mode).await
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bitcoin::{BlockHash, Network, OutPoint, TxMerkleNode, Txid};
+use futures::future::try_join_all;
+use reqwest::Url;
+
+use crate::error::CoreError;
+use crate::types::TxNode;
+
+use super::super::types::{
+    chain_to_network, genesis_hash_for_chain, Block, BlockHeaderInfo, BlockId, ChainInfo,
+    EstimateMode, TxOutInfo, TxSpend,
+};
+use super::super::BitcoinRpc;
+use super::parsing::{parse_esplora_tx, EsploraTx};
+
+/// Bitcoin transaction data source backed by an Esplora-style REST API.
+///
+/// Unlike [`HttpRpcClient`](super::super::HttpRpcClient), Esplora inlines
+/// each input's prevout value and scriptPubKey directly in the transaction
+/// response, so ancestry traversal works against a pruned node or a public
+/// block explorer with no `-txindex` required.
+pub struct EsploraClient {
+    client: reqwest::Client,
+    base_url: String,
+    /// Chain name reported in [`ChainInfo`], since Esplora has no
+    /// equivalent of `getblockchaininfo`'s `chain` field.
+    chain: String,
+}
+
+impl EsploraClient {
+    /// Create a new client for an Esplora base URL, e.g.
+    /// `https://blockstream.info/api` or `http://127.0.0.1:3000`.
+    ///
+    /// `chain` is surfaced verbatim in [`ChainInfo::chain`] and should match
+    /// one of Bitcoin Core's chain names (`main`, `test`, `signet`, `regtest`)
+    /// since Cory uses it to select the active network.
+    pub fn new(base_url: &str, chain: &str) -> Result<Self, CoreError> {
+        let parsed = Url::parse(base_url).map_err(|e| {
+            CoreError::InvalidTxData(format!("invalid esplora base url `{base_url}`: {e}"))
+        })?;
+        match parsed.scheme() {
+            "http" | "https" => {}
+            other => {
+                return Err(CoreError::InvalidTxData(format!(
+                    "unsupported esplora scheme `{other}`; expected http or https"
+                )))
+            }
+        }
+
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("reqwest client builder uses valid static config");
+
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            chain: chain.to_owned(),
+        })
+    }
+
+    async fn get_text(&self, path: &str) -> Result<String, CoreError> {
+        let url = format!("{}{path}", self.base_url);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(crate::error::RpcError::Transport)?;
+        if !response.status().is_success() {
+            return Err(CoreError::InvalidTxData(format!(
+                "esplora request to {url} failed: {}",
+                response.status()
+            )));
+        }
+        response
+            .text()
+            .await
+            .map_err(crate::error::RpcError::Transport)
+            .map_err(CoreError::Rpc)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, CoreError> {
+        let body = self.get_text(path).await?;
+        serde_json::from_str(&body).map_err(|e| {
+            CoreError::InvalidTxData(format!("decode esplora response: {e}; body={body}"))
+        })
+    }
+
+    async fn fetch_tx(&self, txid: &Txid) -> Result<TxNode, CoreError> {
+        let raw: EsploraTx = self
+            .get_json(&format!("/tx/{txid}"))
+            .await
+            .map_err(|err| normalize_esplora_not_found(txid, err))?;
+        parse_esplora_tx(raw, self.network()?)
+    }
+
+    /// Resolve `chain` (Esplora has no `getblockchaininfo` to report it) to
+    /// the [`Network`] used to derive addresses from scripts.
+    fn network(&self) -> Result<Network, CoreError> {
+        chain_to_network(&self.chain).ok_or_else(|| {
+            CoreError::InvalidTxData(format!(
+                "unrecognized chain name `{}` passed to EsploraClient::new",
+                self.chain
+            ))
+        })
+    }
+
+    /// Current chain tip height and block hash.
+    async fn chain_tip(&self) -> Result<(u64, BlockHash), CoreError> {
+        let height: u64 = self
+            .get_text("/blocks/tip/height")
+            .await?
+            .trim()
+            .parse()
+            .map_err(|e| CoreError::InvalidTxData(format!("invalid tip height: {e}")))?;
+        let hash_text = self.get_text("/blocks/tip/hash").await?;
+        let hash: BlockHash = hash_text
+            .trim()
+            .parse()
+            .map_err(|e| CoreError::InvalidTxData(format!("invalid tip hash: {e}")))?;
+        Ok((height, hash))
+    }
+}
+
+#[async_trait]
+impl BitcoinRpc for EsploraClient {
+    async fn get_transaction(&self, txid: &Txid) -> Result<TxNode, CoreError> {
+        self.fetch_tx(txid).await
+    }
+
+    async fn get_transactions(&self, txids: &[Txid]) -> Result<Vec<TxNode>, CoreError> {
+        // Esplora has no batch endpoint; issue requests concurrently instead.
+        try_join_all(txids.iter().map(|txid| self.fetch_tx(txid))).await
+    }
+
+    async fn get_tx_out(
+        &self,
+        txid: &Txid,
+        vout: u32,
+        include_mempool: bool,
+    ) -> Result<Option<TxOutInfo>, CoreError> {
+        let node = match self.fetch_tx(txid).await {
+            Ok(node) => node,
+            Err(CoreError::TxNotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if !include_mempool && node.block_height.is_none() {
+            return Ok(None);
+        }
+        let Some(output) = node.outputs.get(vout as usize) else {
+            return Ok(None);
+        };
+
+        let spent: EsploraOutspend = self
+            .get_json(&format!("/tx/{txid}/outspend/{vout}"))
+            .await?;
+        if spent.spent {
+            return Ok(None);
+        }
+
+        let (tip_height, bestblock) = self.chain_tip().await?;
+        let confirmations = node
+            .block_height
+            .map_or(0, |h| tip_height.saturating_sub(u64::from(h)) + 1);
+
+        // Esplora's own vout representation doesn't carry Core's
+        // `scriptPubKey.type`/`address` breakdown (see `EsploraVout`), so
+        // those are left empty here rather than guessed at.
+        Ok(Some(TxOutInfo {
+            output: output.clone(),
+            confirmations,
+            bestblock,
+            coinbase: node.is_coinbase(),
+            script_pub_key_type: None,
+            addresses: Vec::new(),
+        }))
+    }
+
+    async fn get_tx_outs(
+        &self,
+        outpoints: &[OutPoint],
+        include_mempool: bool,
+    ) -> Result<Vec<Option<TxOutInfo>>, CoreError> {
+        try_join_all(
+            outpoints
+                .iter()
+                .map(|outpoint| self.get_tx_out(&outpoint.txid, outpoint.vout, include_mempool)),
+        )
+        .await
+    }
+
+    async fn get_spend(&self, outpoint: OutPoint) -> Result<Option<TxSpend>, CoreError> {
+        let spend: EsploraOutspend = self
+            .get_json(&format!("/tx/{}/outspend/{}", outpoint.txid, outpoint.vout))
+            .await?;
+        if !spend.spent {
+            return Ok(None);
+        }
+        let (Some(spending_txid), Some(input_index)) = (spend.txid, spend.vin) else {
+            return Err(CoreError::InvalidTxData(format!(
+                "esplora reported outpoint {outpoint} spent but omitted the spending txid/vin"
+            )));
+        };
+        Ok(Some(TxSpend {
+            spending_txid,
+            input_index,
+        }))
+    }
+
+    async fn get_spends(&self, outpoints: &[OutPoint]) -> Result<Vec<Option<TxSpend>>, CoreError> {
+        try_join_all(outpoints.iter().map(|outpoint| self.get_spend(*outpoint))).await
+    }
+
+    async fn get_blockchain_info(&self) -> Result<ChainInfo, CoreError> {
+        let (height, best_block_hash) = self.chain_tip().await?;
+
+        Ok(ChainInfo {
+            chain: self.chain.clone(),
+            blocks: height,
+            best_block_hash,
+            // Esplora instances commonly serve pruned or archival nodes
+            // alike with no way to tell from the REST API; assume
+            // full-archive semantics since pruning only affects
+            // getrawtransaction-style access, which this client never uses.
+            pruned: false,
+        })
+    }
+
+    async fn get_txout_proof(&self, txids: &[Txid]) -> Result<Option<String>, CoreError> {
+        let [txid] = txids else {
+            return Err(CoreError::InvalidTxData(
+                "esplora backend only supports a proof for a single transaction at a time".into(),
+            ));
+        };
+
+        match self
+            .get_text(&format!("/tx/{txid}/merkleblock-proof"))
+            .await
+        {
+            Ok(hex) => Ok(Some(hex.trim().to_owned())),
+            Err(err) if is_esplora_not_found(&err) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn get_block_header(&self, id: BlockId) -> Result<BlockHeaderInfo, CoreError> {
+        let block_hash = self.get_block_hash(id).await?;
+        let block: EsploraBlock = self.get_json(&format!("/block/{block_hash}")).await?;
+        let merkle_root: TxMerkleNode = block
+            .merkle_root
+            .parse()
+            .map_err(|e| CoreError::InvalidTxData(format!("invalid merkle_root: {e}")))?;
+
+        Ok(BlockHeaderInfo {
+            hash: block_hash,
+            height: block.height,
+            merkle_root,
+        })
+    }
+
+    async fn get_block(&self, _id: BlockId) -> Result<Block, CoreError> {
+        // Esplora's `/block/:hash/txs` endpoint paginates 25 transactions
+        // at a time and omits prevout value/scriptPubKey for unconfirmed
+        // spends, so there's no single cheap call that matches Core's
+        // `getblock verbosity=2`. Callers treat this as "prefetch
+        // unavailable" and fall back to per-transaction fetches, which this
+        // client already serves efficiently via inlined prevout data.
+        Err(CoreError::InvalidTxData(
+            "the esplora backend has no bulk whole-block fetch; use get_transaction per txid"
+                .into(),
+        ))
+    }
+
+    async fn estimate_smart_fee(
+        &self,
+        conf_target: u16,
+        // Esplora's `/fee-estimates` has no economical/conservative
+        // distinction; Core's notion of mode doesn't apply here.
+        _mode: EstimateMode,
+    ) -> Result<Option<f64>, CoreError> {
+        let estimates: HashMap<String, f64> = self.get_json("/fee-estimates").await?;
+        // Keys are confirmation targets in blocks and the map doesn't
+        // necessarily contain every target, so fall back to the closest
+        // target no tighter than the one requested (the smallest key that
+        // is at least `conf_target`).
+        let feerate = estimates
+            .into_iter()
+            .filter_map(|(target, feerate)| target.parse::<u16>().ok().map(|t| (t, feerate)))
+            .filter(|(target, _)| *target >= conf_target)
+            .min_by_key(|(target, _)| *target)
+            .map(|(_, feerate)| feerate);
+        Ok(feerate)
+    }
+
+    async fn get_block_hash(&self, id: BlockId) -> Result<BlockHash, CoreError> {
+        match id {
+            BlockId::Hash(hash) => Ok(hash),
+            BlockId::Latest => {
+                let (_, hash) = self.chain_tip().await?;
+                Ok(hash)
+            }
+            BlockId::Earliest => genesis_hash_for_chain(&self.chain),
+            BlockId::Height(height) => {
+                let hash_text = self.get_text(&format!("/block-height/{height}")).await?;
+                hash_text
+                    .trim()
+                    .parse()
+                    .map_err(|e| CoreError::InvalidTxData(format!("invalid block hash: {e}")))
+            }
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct EsploraBlock {
+    height: u32,
+    merkle_root: String,
+}
+
+#[derive(serde::Deserialize)]
+struct EsploraOutspend {
+    spent: bool,
+    txid: Option<Txid>,
+    vin: Option<u32>,
+}
+
+/// Esplora returns a plain `"Transaction not found"` 404 body rather than a
+/// structured error, so we detect it by the request failure surfaced as an
+/// `InvalidTxData` whose message mentions the 404 status.
+fn normalize_esplora_not_found(txid: &Txid, err: CoreError) -> CoreError {
+    if is_esplora_not_found(&err) {
+        return CoreError::TxNotFound(*txid);
+    }
+    err
+}
+
+/// Whether a failed Esplora request was a plain 404 (see
+/// [`normalize_esplora_not_found`]).
+fn is_esplora_not_found(err: &CoreError) -> bool {
+    matches!(err, CoreError::InvalidTxData(message) if message.contains("404"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_rejects_non_http_scheme() {
+        let err = EsploraClient::new("ftp://example.com", "main").expect_err("must reject ftp");
+        assert!(err.to_string().contains("unsupported esplora scheme"));
+    }
+
+    #[test]
+    fn new_strips_trailing_slash_from_base_url() {
+        let client =
+            EsploraClient::new("https://blockstream.info/api/", "main").expect("must construct");
+        assert_eq!(client.base_url, "https://blockstream.info/api");
+    }
+}
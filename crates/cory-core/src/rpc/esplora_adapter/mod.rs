@@ -0,0 +1,12 @@
+//! Esplora-style REST client for nodes without a local Bitcoin Core RPC.
+//!
+//! Implements [`BitcoinRpc`](super::BitcoinRpc) over the Esplora HTTP API
+//! (as served by `blockstream.info`, `mempool.space`, and self-hosted
+//! `esplora` instances). Esplora inlines each input's prevout value and
+//! scriptPubKey directly in the transaction response, so graph traversal
+//! works without `-txindex` and needs only one request per transaction.
+
+mod client;
+mod parsing;
+
+pub use client::EsploraClient;
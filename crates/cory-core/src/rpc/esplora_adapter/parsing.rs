@@ -0,0 +1,235 @@
+use bitcoin::{Amount, BlockHash, Network, OutPoint, ScriptBuf, Txid};
+
+use crate::enrich::classify_script;
+use crate::error::CoreError;
+use crate::types::{TxInput, TxNode, TxOutput};
+
+/// Derive the address Core/Esplora would report for `script` on `network`,
+/// or `None` for scripts with no derivable address (e.g. `OP_RETURN`).
+fn address_from_script(
+    script: &ScriptBuf,
+    network: Network,
+) -> Option<bitcoin::Address<bitcoin::address::NetworkUnchecked>> {
+    bitcoin::Address::from_script(script.as_script(), network)
+        .ok()
+        .map(|addr| addr.as_unchecked().clone())
+}
+
+#[derive(serde::Deserialize)]
+pub(super) struct EsploraStatus {
+    confirmed: bool,
+    block_height: Option<u32>,
+    block_hash: Option<String>,
+    block_time: Option<u64>,
+}
+
+#[derive(serde::Deserialize)]
+pub(super) struct EsploraPrevout {
+    value: u64,
+    scriptpubkey: String,
+}
+
+#[derive(serde::Deserialize)]
+pub(super) struct EsploraVin {
+    txid: Option<String>,
+    vout: Option<u32>,
+    sequence: u32,
+    #[serde(default)]
+    is_coinbase: bool,
+    prevout: Option<EsploraPrevout>,
+}
+
+#[derive(serde::Deserialize)]
+pub(super) struct EsploraVout {
+    value: u64,
+    scriptpubkey: String,
+}
+
+#[derive(serde::Deserialize)]
+pub(super) struct EsploraTx {
+    txid: String,
+    version: i32,
+    locktime: u32,
+    size: u64,
+    weight: u64,
+    vin: Vec<EsploraVin>,
+    vout: Vec<EsploraVout>,
+    status: EsploraStatus,
+}
+
+pub(super) fn script_from_hex(hex_str: &str) -> Result<ScriptBuf, CoreError> {
+    ScriptBuf::from_hex(hex_str)
+        .map_err(|e| CoreError::InvalidTxData(format!("invalid scriptpubkey hex: {e}")))
+}
+
+fn parse_esplora_vin(vin: &EsploraVin, network: Network) -> Result<TxInput, CoreError> {
+    let prevout = if vin.is_coinbase {
+        None
+    } else {
+        let txid: Txid = vin
+            .txid
+            .as_deref()
+            .ok_or_else(|| CoreError::InvalidTxData("missing vin.txid".into()))?
+            .parse()
+            .map_err(|e| CoreError::InvalidTxData(format!("invalid vin.txid: {e}")))?;
+        let vout = vin
+            .vout
+            .ok_or_else(|| CoreError::InvalidTxData("missing vin.vout".into()))?;
+        Some(OutPoint::new(txid, vout))
+    };
+
+    let (value, script_type, address) = match &vin.prevout {
+        Some(prevout) => {
+            let script = script_from_hex(&prevout.scriptpubkey)?;
+            (
+                Some(Amount::from_sat(prevout.value)),
+                Some(classify_script(script.as_script())),
+                address_from_script(&script, network),
+            )
+        }
+        None => (None, None, None),
+    };
+
+    Ok(TxInput {
+        prevout,
+        sequence: vin.sequence,
+        value,
+        script_type,
+        address,
+        unresolved_reason: None,
+    })
+}
+
+fn parse_esplora_vout(vout: &EsploraVout, network: Network) -> Result<TxOutput, CoreError> {
+    let script_pub_key = script_from_hex(&vout.scriptpubkey)?;
+    let script_type = classify_script(script_pub_key.as_script());
+    let address = address_from_script(&script_pub_key, network);
+    Ok(TxOutput {
+        value: Amount::from_sat(vout.value),
+        script_pub_key,
+        script_type,
+        address,
+    })
+}
+
+/// Convert a decoded Esplora `/tx/:txid` response into a [`TxNode`].
+///
+/// Esplora does not report `vsize` directly; it is recovered from `weight`
+/// per BIP-141 (`vsize = ceil(weight / 4)`). `network` is used to derive
+/// each input/output's address from its `scriptPubKey`.
+pub(super) fn parse_esplora_tx(raw: EsploraTx, network: Network) -> Result<TxNode, CoreError> {
+    let txid: Txid = raw
+        .txid
+        .parse()
+        .map_err(|e| CoreError::InvalidTxData(format!("invalid txid: {e}")))?;
+    let block_hash = raw
+        .status
+        .block_hash
+        .as_deref()
+        .map(str::parse::<BlockHash>)
+        .transpose()
+        .map_err(|e| CoreError::InvalidTxData(format!("invalid status.block_hash: {e}")))?;
+    let block_height = if raw.status.confirmed {
+        raw.status.block_height
+    } else {
+        None
+    };
+
+    let inputs = raw
+        .vin
+        .iter()
+        .map(|vin| parse_esplora_vin(vin, network))
+        .collect::<Result<_, _>>()?;
+    let outputs = raw
+        .vout
+        .iter()
+        .map(|vout| parse_esplora_vout(vout, network))
+        .collect::<Result<_, _>>()?;
+
+    Ok(TxNode {
+        txid,
+        version: raw.version,
+        locktime: raw.locktime,
+        size: raw.size,
+        vsize: raw.weight.div_ceil(4),
+        weight: raw.weight,
+        block_hash,
+        block_height,
+        block_time: raw.status.block_time,
+        inputs,
+        outputs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_tx_json() -> serde_json::Value {
+        serde_json::json!({
+            "txid": "0000000000000000000000000000000000000000000000000000000000000001",
+            "version": 2,
+            "locktime": 0,
+            "size": 225,
+            "weight": 561,
+            "vin": [{
+                "txid": "0000000000000000000000000000000000000000000000000000000000000002",
+                "vout": 0,
+                "sequence": 4294967295,
+                "is_coinbase": false,
+                "prevout": {
+                    "value": 5000000000_u64,
+                    "scriptpubkey": "76a914000000000000000000000000000000000000000088ac",
+                }
+            }],
+            "vout": [{
+                "value": 4999990000_u64,
+                "scriptpubkey": "76a914000000000000000000000000000000000000000088ac",
+            }],
+            "status": {
+                "confirmed": true,
+                "block_height": 100,
+                "block_hash": "0000000000000000000000000000000000000000000000000000000000000003",
+                "block_time": 1700000000,
+            }
+        })
+    }
+
+    #[test]
+    fn parse_esplora_tx_includes_inline_prevout() {
+        let raw: EsploraTx = serde_json::from_value(sample_tx_json()).expect("must deserialize");
+        let node = parse_esplora_tx(raw, Network::Bitcoin).expect("must parse");
+
+        assert_eq!(node.block_height, Some(100));
+        assert_eq!(node.vsize, 141);
+        assert_eq!(node.inputs.len(), 1);
+        assert_eq!(node.inputs[0].value, Some(Amount::from_sat(5000000000)));
+        assert_eq!(node.outputs[0].value, Amount::from_sat(4999990000));
+    }
+
+    #[test]
+    fn parse_esplora_tx_unconfirmed_has_no_block_height() {
+        let mut json = sample_tx_json();
+        json["status"] = serde_json::json!({ "confirmed": false });
+        let raw: EsploraTx = serde_json::from_value(json).expect("must deserialize");
+        let node = parse_esplora_tx(raw, Network::Bitcoin).expect("must parse");
+
+        assert!(node.block_height.is_none());
+        assert!(node.block_hash.is_none());
+    }
+
+    #[test]
+    fn parse_esplora_tx_derives_addresses() {
+        let raw: EsploraTx = serde_json::from_value(sample_tx_json()).expect("must deserialize");
+        let node = parse_esplora_tx(raw, Network::Bitcoin).expect("must parse");
+
+        let output_address = node.outputs[0]
+            .checked_address(Network::Bitcoin)
+            .expect("p2pkh script must have an address");
+        assert_eq!(
+            output_address.to_string(),
+            "1111111111111111111114oLvT2"
+        );
+        assert!(node.inputs[0].checked_address(Network::Bitcoin).is_some());
+    }
+}
@@ -1,141 +1,767 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::num::{NonZeroU32, NonZeroUsize};
-use std::path::Path;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
-use bitcoin::{BlockHash, OutPoint, Txid};
+use bitcoin::{BlockHash, Network, OutPoint, Txid};
 use futures::future::try_join_all;
 use governor::clock::DefaultClock;
 use governor::state::{InMemoryState, NotKeyed};
 use governor::{Quota, RateLimiter};
 use lru::LruCache;
 use reqwest::header;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, Semaphore};
 use tracing::{debug, trace, warn};
 
-use crate::error::{CoreError, RpcError};
-use crate::types::{BlockHeight, TxNode, TxOutput};
+use crate::enrich::classify_script;
+use crate::error::{BitcoinRpcErrorCode, CoreError, RpcError, TimeoutPhase};
+use crate::types::{BlockHeight, TxInput, TxNode, TxOutput};
 
-use super::super::types::ChainInfo;
+use super::super::types::{
+    chain_to_network, genesis_hash_for_chain, Block, BlockHeaderInfo, BlockId, ChainInfo,
+    EstimateMode, TxOutInfo, TxSpend,
+};
 use super::super::BitcoinRpc;
 use super::connection::{parse_connection, resolve_auth};
 use super::parsing::{
-    parse_gettxout_result, parse_integer_optional, parse_integer_required, parse_opt_block_hash,
-    parse_txid, parse_vin, parse_vout,
+    address_from_script, parse_block, parse_gettxout_result, parse_integer_optional,
+    parse_integer_required, parse_merkle_root, parse_opt_block_hash, parse_txid, parse_vin,
+    parse_vout,
 };
 use super::protocol::{
     parse_batch_id, parse_jsonrpc_error, JsonRpcRequest, JsonRpcRequestOwned, JsonRpcResponse,
     JsonRpcResponseOwned,
 };
 
-/// Maximum number of block-hash → height entries cached in memory.
+/// Maximum number of block-hash → height entries cached in memory (and,
+/// symmetrically, the cap on canonical height → hash entries).
 const BLOCK_HEIGHT_CACHE_CAP: usize = 10_000;
 
+/// Minimum Bitcoin Core `getnetworkinfo` `version` [`HttpRpcClient::ensure_node_supported`]
+/// accepts (v0.20.0). Below this, the verbose `getblock`/`getrawtransaction`
+/// response shapes this client parses, and full segwit support, can't be
+/// relied on.
+const MIN_SUPPORTED_NODE_VERSION: i64 = 200_000;
+
+/// Highest Bitcoin Core `version` [`HttpRpcClient::ensure_node_supported`]
+/// accepts (v27.0.0). Bump this once a newer release has been verified
+/// compatible; a node ahead of it is refused rather than assumed fine, since
+/// an untested RPC response shape change would otherwise surface as a
+/// confusing downstream parse failure instead of this preflight's clear one.
+const MAX_SUPPORTED_NODE_VERSION: i64 = 270_000;
+
+/// Default `batch_chunk_size` used by [`HttpRpcClient::with_cookie_file`].
+const DEFAULT_BATCH_CHUNK_SIZE: usize = 10;
+
+/// Default `request_timeout_secs` used by [`HttpRpcClient::with_cookie_file`].
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Default `connect_timeout_secs` used by [`HttpRpcClient::with_cookie_file`].
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// Default maximum number of batch chunks [`HttpRpcClient::rpc_batch_chunked`]
+/// runs concurrently. Override with [`HttpRpcClient::with_max_concurrent_batch_chunks`].
+const DEFAULT_MAX_CONCURRENT_BATCH_CHUNKS: usize = 4;
+
+/// Bidirectional header-chain cache: confirmed `hash -> height` (LRU,
+/// entries never go stale since a confirmed height is immutable) and
+/// canonical `height -> hash` (evicted on reorg, since a height's
+/// canonical hash can change).
+struct HeaderChainCache {
+    hash_to_height: LruCache<BlockHash, BlockHeight>,
+    height_to_hash: BTreeMap<u32, BlockHash>,
+    /// Highest chain height observed via `getblockchaininfo`, used to
+    /// detect reorgs (a height regression) on the next observation.
+    best_height_seen: Option<u32>,
+}
+
+impl HeaderChainCache {
+    fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            hash_to_height: LruCache::new(capacity),
+            height_to_hash: BTreeMap::new(),
+            best_height_seen: None,
+        }
+    }
+
+    fn get_height(&mut self, hash: &BlockHash) -> Option<BlockHeight> {
+        self.hash_to_height.get(hash).copied()
+    }
+
+    fn get_hash(&self, height: u32) -> Option<BlockHash> {
+        self.height_to_hash.get(&height).copied()
+    }
+
+    fn insert(&mut self, hash: BlockHash, height: BlockHeight) {
+        self.hash_to_height.put(hash, height);
+        self.height_to_hash.insert(height.0, hash);
+        while self.height_to_hash.len() > self.hash_to_height.cap().get() {
+            let Some(&lowest) = self.height_to_hash.keys().next() else {
+                break;
+            };
+            self.height_to_hash.remove(&lowest);
+        }
+    }
+
+    /// Records a best-height observation from `getblockchaininfo`. If it's
+    /// lower than the highest height seen so far, a reorg happened below
+    /// the old tip: every cached height → hash entry at or above the new
+    /// height might now point at a replaced block, so all of them are
+    /// evicted. Hash → height entries are left alone, since they're only
+    /// ever recorded for heights Cory has independently confirmed.
+    fn note_best_height(&mut self, height: u32) {
+        if let Some(previous_best) = self.best_height_seen {
+            if height < previous_best {
+                self.height_to_hash.retain(|&h, _| h < height);
+            }
+        }
+        self.best_height_seen = Some(
+            self.best_height_seen
+                .map_or(height, |prev| prev.max(height)),
+        );
+    }
+}
+
+/// Default number of retries attempted after the first failed try (so up
+/// to `max_attempts + 1` total attempts per call/chunk). See [`RetryPolicy`].
+const RETRY_MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_BACKOFF: Duration = Duration::from_millis(100);
+const RETRY_MAX_BACKOFF: Duration = Duration::from_millis(2_000);
+const RETRY_FACTOR: f64 = 2.0;
+
+/// How long a failed endpoint is skipped in favor of a healthy one,
+/// doubling per consecutive failure up to [`ENDPOINT_PENALTY_CAP`]. Reset
+/// to zero as soon as the endpoint succeeds once. This is deliberately
+/// separate from [`RetryPolicy`]'s backoff, which paces retries of one
+/// logical call; this paces how long a *node* stays out of rotation.
+const ENDPOINT_PENALTY_BASE: Duration = Duration::from_secs(1);
+const ENDPOINT_PENALTY_CAP: Duration = Duration::from_secs(60);
+
 type DirectRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
 
+/// One Bitcoin Core RPC endpoint to try, with its own auth inputs. Passed
+/// to [`HttpRpcClient::new`] as a list so a client can fail over between
+/// redundant nodes instead of bubbling up every transport blip.
+#[derive(Debug, Clone)]
+pub struct RpcEndpoint {
+    /// An HTTP(S) JSON-RPC URL, e.g. `http://127.0.0.1:8332`.
+    pub connection: String,
+    pub user: Option<String>,
+    pub pass: Option<String>,
+    pub cookie_file: Option<PathBuf>,
+}
+
+impl RpcEndpoint {
+    /// Construct an endpoint with no auth (e.g. a node with `-rpcauth`
+    /// disabled, or a proxy that injects credentials itself).
+    pub fn new(connection: impl Into<String>) -> Self {
+        Self {
+            connection: connection.into(),
+            user: None,
+            pass: None,
+            cookie_file: None,
+        }
+    }
+
+    /// Set explicit `user`/`pass` auth, consuming-self builder style.
+    pub fn with_user_pass(mut self, user: impl Into<String>, pass: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self.pass = Some(pass.into());
+        self
+    }
+
+    /// Set a cookie-file auth source, consuming-self builder style.
+    pub fn with_cookie_file(mut self, cookie_file: impl Into<PathBuf>) -> Self {
+        self.cookie_file = Some(cookie_file.into());
+        self
+    }
+}
+
+/// Configures how [`HttpRpcClient`] retries a failed call: how many extra
+/// attempts, how long to back off between them, and which JSON-RPC error
+/// codes are worth retrying at all. Transport-level errors (connect/
+/// timeout), 5xx HTTP responses, and 401/403 auth rejections are always
+/// retryable regardless of this policy; `retryable_rpc_codes` only governs
+/// application-level JSON-RPC errors. RPC_INVALID_ADDRESS_OR_KEY (-5, "not
+/// found") is never retried no matter what this is set to, since retrying
+/// it just reproduces the same answer.
+///
+/// A 401/403 is worth retrying because [`HttpRpcClient::current_auth`]
+/// re-reads the endpoint's cookie file fresh on every attempt rather than
+/// caching it, so a rotated cookie (`bitcoind` rewrites it on every
+/// restart) is picked up automatically on the very next try. If the
+/// rejection isn't due to a stale cookie, retries are exhausted quickly and
+/// the caller sees a [`crate::error::RpcError::AuthRejected`] rather than a
+/// generic HTTP error.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Multiplier applied to `base_delay` per attempt before the `max_delay`
+    /// cap and jitter are applied (see [`Self::backoff_delay`]). `2.0`
+    /// (the default) doubles the delay each attempt; a `factor` of `1.0`
+    /// disables growth entirely, retrying at a flat `base_delay`.
+    pub factor: f64,
+    pub retryable_rpc_codes: Vec<BitcoinRpcErrorCode>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: RETRY_MAX_ATTEMPTS,
+            base_delay: RETRY_BASE_BACKOFF,
+            max_delay: RETRY_MAX_BACKOFF,
+            factor: RETRY_FACTOR,
+            retryable_rpc_codes: vec![BitcoinRpcErrorCode::InWarmup],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether a failed RPC call is worth retrying. See the type's docs
+    /// for what's always/never retryable independent of configuration.
+    fn is_retryable(&self, err: &CoreError) -> bool {
+        match err {
+            CoreError::Rpc(RpcError::Transport(e)) => e.is_timeout() || e.is_connect(),
+            CoreError::Rpc(RpcError::Timeout { .. }) => true,
+            CoreError::Rpc(RpcError::HttpStatus { status, .. }) => *status >= 500,
+            CoreError::Rpc(RpcError::AuthRejected { .. }) => true,
+            CoreError::Rpc(RpcError::ServerError { code, .. }) => {
+                *code != BitcoinRpcErrorCode::InvalidAddressOrKey
+                    && self.retryable_rpc_codes.contains(code)
+            }
+            _ => false,
+        }
+    }
+
+    /// "Full jitter" exponential backoff for retry attempt `attempt`
+    /// (0-indexed): `delay = min(max_delay, base_delay * factor^attempt)`,
+    /// then a uniformly random duration in `[0, delay]` — AWS's
+    /// recommended full-jitter scheme, which spreads retries out more than
+    /// a fixed-percentage jitter and so is less prone to many concurrent
+    /// callers clustering their retries together.
+    ///
+    /// The random draw is derived from the current time's sub-second
+    /// nanoseconds rather than a `rand` dependency, since a single call
+    /// site doesn't warrant one.
+    fn backoff_delay(&self, attempt: u32) -> Duration {
+        let exp_millis = self.base_delay.as_millis() as f64 * self.factor.powi(attempt as i32);
+        let delay = Duration::from_millis(exp_millis as u64).min(self.max_delay);
+
+        let millis = delay.as_millis() as u64;
+        if millis == 0 {
+            return delay;
+        }
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| u64::from(d.subsec_nanos()))
+            .unwrap_or(0);
+        Duration::from_millis(nanos % (millis + 1))
+    }
+}
+
+/// An [`RpcEndpoint`] plus the health bookkeeping [`HttpRpcClient`] uses to
+/// temporarily skip a flapping node in favor of a healthy one.
+struct EndpointState {
+    endpoint: RpcEndpoint,
+    consecutive_failures: AtomicU32,
+    /// Epoch millis until which this endpoint is skipped when a healthy
+    /// alternative exists. `0` means not currently penalized.
+    penalized_until_millis: AtomicU64,
+}
+
+impl EndpointState {
+    fn new(endpoint: RpcEndpoint) -> Self {
+        Self {
+            endpoint,
+            consecutive_failures: AtomicU32::new(0),
+            penalized_until_millis: AtomicU64::new(0),
+        }
+    }
+
+    fn is_penalized(&self, now_millis: u64) -> bool {
+        self.penalized_until_millis.load(Ordering::Relaxed) > now_millis
+    }
+
+    fn note_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        self.penalized_until_millis.store(0, Ordering::Relaxed);
+    }
+
+    fn note_failure(&self) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        let penalty = ENDPOINT_PENALTY_BASE
+            .saturating_mul(1u32 << failures.min(8).saturating_sub(1))
+            .min(ENDPOINT_PENALTY_CAP);
+        let until = now_millis().saturating_add(penalty.as_millis() as u64);
+        self.penalized_until_millis.store(until, Ordering::Relaxed);
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Classify a failed `send()` into [`RpcError::Timeout`] when it expired
+/// the connect or response deadline, or [`RpcError::Transport`] for any
+/// other transport failure (DNS, connection reset, TLS error, etc.).
+fn classify_send_error(err: reqwest::Error) -> RpcError {
+    if err.is_timeout() {
+        let phase = if err.is_connect() {
+            TimeoutPhase::Connect
+        } else {
+            TimeoutPhase::Response
+        };
+        RpcError::Timeout { phase }
+    } else {
+        RpcError::Transport(err)
+    }
+}
+
+/// Per-method credit costs for the rate limiter's default cost table.
+/// Unlisted methods (e.g. `getblockheader`, `gettxout`) cost 1 credit.
+/// Override via [`HttpRpcClient::with_method_cost`].
+fn default_method_cost(method: &str) -> u32 {
+    match method {
+        "getrawtransaction" => 2,
+        "getblockchaininfo" => 3,
+        _ => 1,
+    }
+}
+
+/// The most expensive cost in [`default_method_cost`]'s table. The rate
+/// limiter's credit capacity must be at least this large, or a call for
+/// that method would request more credits than the bucket can ever hold
+/// and block forever.
+const MAX_DEFAULT_METHOD_COST: u32 = 3;
+
 /// Bitcoin Core JSON-RPC client over HTTP(S).
 ///
-/// Supports both single and batched RPC calls. Maintains an LRU cache of
-/// block-hash-to-height mappings to avoid redundant `getblockheader` calls
-/// for confirmed transactions.
+/// Supports both single and batched RPC calls. Maintains a bidirectional
+/// header-chain cache ([`HeaderChainCache`]) to avoid redundant
+/// `getblockheader`/`getblockhash` calls for confirmed blocks.
 pub struct HttpRpcClient {
     client: reqwest::Client,
-    url: String,
-    auth: Option<(String, String)>,
+    /// One or more endpoints to call, in the order passed to [`Self::new`].
+    /// A call tries a healthy endpoint first, falling over to the next on
+    /// a retryable failure; see [`EndpointState`] and [`RetryPolicy`].
+    endpoints: Vec<EndpointState>,
+    /// Index of the current "healthy endpoint" to try first. Steady-state
+    /// calls just load this value (no scanning), so a healthy deployment
+    /// pays no probing cost; it only advances when [`Self::pick_endpoint_index`]
+    /// is asked to skip a just-failed endpoint.
+    next_endpoint: AtomicUsize,
+    retry_policy: RetryPolicy,
+    /// Credit/cost token bucket. `None` means rate limiting is disabled.
     limiter: Option<DirectRateLimiter>,
+    /// Bucket capacity in credits, kept alongside `limiter` so
+    /// [`Self::with_method_cost`] can validate overrides without reaching
+    /// into `governor`'s quota internals.
+    limiter_capacity: Option<u32>,
+    /// Per-method cost overrides, consulted before [`default_method_cost`].
+    method_cost_overrides: HashMap<String, u32>,
     batch_chunk_size: usize,
+    /// Caps the number of batch chunks in flight at once, so a batch of
+    /// thousands of calls (e.g. resolving many prevouts) can't open more
+    /// concurrent HTTP requests than the node can comfortably handle. The
+    /// credit bucket above separately paces the overall request rate; this
+    /// bounds concurrency regardless of rate.
+    batch_chunk_semaphore: Semaphore,
+    /// When set, [`Self::get_transaction`]/[`Self::get_transactions`] try
+    /// Bitcoin Core's binary REST interface (`/rest/tx/<txid>.bin`) first,
+    /// falling back to `getrawtransaction` only on a 404 (REST requires
+    /// `-rest=1` and, unlike JSON-RPC, serves no prevout or block context,
+    /// so its transactions always need downstream prevout resolution).
+    rest_enabled: bool,
     next_id: AtomicU64,
-    /// Bounded LRU cache mapping confirmed block hashes to their height.
-    /// Confirmed block heights are immutable, so entries never need
-    /// invalidation, only eviction under memory pressure.
-    block_height_cache: RwLock<LruCache<BlockHash, BlockHeight>>,
+    /// Bidirectional header-chain cache, used by [`Self::get_block_height`]
+    /// and [`Self::get_block_hash`] to avoid redundant `getblockheader`/
+    /// `getblockhash` round-trips.
+    header_chain_cache: RwLock<HeaderChainCache>,
+    /// Lazily resolved via [`Self::resolve_network`], since — unlike
+    /// [`super::super::EsploraClient`] — a Core node reports its own chain
+    /// rather than being told one, and it never changes for the lifetime of
+    /// the client, so a single `getblockchaininfo` round-trip is cached.
+    network_cache: RwLock<Option<Network>>,
+    /// Set once [`Self::ensure_node_supported`] has passed, so repeated
+    /// calls (e.g. once per `build_ancestry` run) don't repeat the
+    /// `getnetworkinfo`/`getindexinfo` round-trips. A failed check is never
+    /// cached, since it may just reflect a transient RPC error rather than
+    /// a genuinely incompatible node.
+    handshake_cache: RwLock<Option<()>>,
 }
 
 impl HttpRpcClient {
-    /// Create a new client for an HTTP URL.
-    ///
-    /// `connection` accepts one of:
-    /// - `http://...` or `https://...` for standard HTTP RPC
+    /// Create a new client for one or more HTTP(S) endpoints. `endpoints`
+    /// must be non-empty; extra entries beyond the first are only used for
+    /// failover, never load-balanced round-robin on their own (a healthy
+    /// first endpoint is always preferred).
     ///
-    /// Authentication precedence:
+    /// Each [`RpcEndpoint`] resolves its own auth, with the usual
+    /// precedence:
     /// 1. explicit `user` + `pass`
     /// 2. cookie file (`username:password`) from `cookie_file`
     /// 3. no auth
     ///
-    /// If `requests_per_second` is set, calls are rate-limited per outbound
-    /// HTTP request (batched calls count as one request).
+    /// If `credits_per_second` is set, calls are rate-limited through a
+    /// cost-weighted credit bucket: cheap calls (e.g. `getblockheader`,
+    /// `gettxout`) cost 1 credit, `getrawtransaction` costs 2, and
+    /// `getblockchaininfo` costs 3 (see [`default_method_cost`]; override
+    /// per method with [`Self::with_method_cost`]). A batched request
+    /// costs the sum of its calls' costs. The bucket holds up to
+    /// `credit_capacity` credits (defaulting to `credits_per_second`,
+    /// i.e. one second's worth of burst) and refills at `credits_per_second`
+    /// credits/second. The bucket is shared across all endpoints, since a
+    /// rate limit is normally a constraint on the caller, not on any one
+    /// node.
+    ///
+    /// `request_timeout_secs` bounds each individual HTTP request (a batch
+    /// chunk counts as one request), from just after the connection is
+    /// established through the last byte of the response body.
+    /// `connect_timeout_secs` separately bounds only the initial TCP/TLS
+    /// handshake, so an unreachable node can fail fast without waiting out
+    /// the full request budget; it's normally set well below
+    /// `request_timeout_secs`. Expiry of either is reported as
+    /// [`crate::error::RpcError::Timeout`], tagged with which phase
+    /// expired. Transient failures — connect/timeout errors, 5xx
+    /// responses, and "still warming up" RPC errors — are retried against
+    /// the next healthy endpoint with jittered exponential backoff, per
+    /// the default [`RetryPolicy`] (override with
+    /// [`Self::with_retry_policy`]). An endpoint that fails is penalized
+    /// for a short, doubling-per-failure window so a flapping node is
+    /// skipped rather than retried every single call.
     pub fn new(
-        connection: &str,
-        user: Option<&str>,
-        pass: Option<&str>,
-        cookie_file: Option<&Path>,
-        requests_per_second: Option<u32>,
+        endpoints: Vec<RpcEndpoint>,
+        credits_per_second: Option<u32>,
+        credit_capacity: Option<u32>,
         batch_chunk_size: usize,
+        request_timeout_secs: u64,
+        connect_timeout_secs: u64,
     ) -> Result<Self, CoreError> {
+        if endpoints.is_empty() {
+            return Err(CoreError::InvalidTxData(
+                "rpc client requires at least one endpoint".to_owned(),
+            ));
+        }
         if batch_chunk_size == 0 {
             return Err(CoreError::InvalidTxData(
                 "rpc batch chunk size must be at least 1".to_owned(),
             ));
         }
-        let auth = resolve_auth(user, pass, cookie_file)?;
-        let url = parse_connection(connection)?;
+
+        let mut endpoint_states = Vec::with_capacity(endpoints.len());
+        for endpoint in endpoints {
+            // Resolve once up front so misconfiguration (missing/malformed
+            // cookie file, partial user/pass) fails fast at startup, even
+            // though the resolved value itself is discarded here and
+            // re-resolved on every request from here on.
+            resolve_auth(
+                endpoint.user.as_deref(),
+                endpoint.pass.as_deref(),
+                endpoint.cookie_file.as_deref(),
+            )?;
+            let connection = parse_connection(&endpoint.connection)?;
+            endpoint_states.push(EndpointState::new(RpcEndpoint {
+                connection,
+                ..endpoint
+            }));
+        }
 
         let client = reqwest::Client::builder()
-            .connect_timeout(Duration::from_secs(10))
-            .timeout(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(connect_timeout_secs))
+            .timeout(Duration::from_secs(request_timeout_secs))
             .pool_max_idle_per_host(32)
             .tcp_nodelay(true)
             .build()
             .expect("reqwest client builder uses valid static config");
 
-        let limiter = match requests_per_second {
-            None => None,
-            Some(limit) => {
-                let limit = NonZeroU32::new(limit).ok_or_else(|| {
-                    CoreError::InvalidTxData("requests_per_second must be at least 1".to_owned())
+        let (limiter, limiter_capacity) = match credits_per_second {
+            None => (None, None),
+            Some(rate) => {
+                let rate = NonZeroU32::new(rate).ok_or_else(|| {
+                    CoreError::InvalidTxData("credits_per_second must be at least 1".to_owned())
                 })?;
-                Some(RateLimiter::direct(Quota::per_second(limit)))
+                let capacity = match credit_capacity {
+                    Some(capacity) => NonZeroU32::new(capacity).ok_or_else(|| {
+                        CoreError::InvalidTxData("credit_capacity must be at least 1".to_owned())
+                    })?,
+                    None => rate,
+                };
+                if capacity.get() < MAX_DEFAULT_METHOD_COST {
+                    return Err(CoreError::InvalidTxData(format!(
+                        "rpc credit capacity {capacity} is smaller than the costliest default \
+                         RPC method cost {MAX_DEFAULT_METHOD_COST}; calls for that method would \
+                         never acquire enough credits"
+                    )));
+                }
+                let period = Duration::from_secs_f64(1.0 / f64::from(rate.get()));
+                let quota = Quota::with_period(period)
+                    .expect("period derived from a nonzero rate is always positive")
+                    .allow_burst(capacity);
+                (Some(RateLimiter::direct(quota)), Some(capacity.get()))
             }
         };
 
         Ok(Self {
             client,
-            url,
-            auth,
+            endpoints: endpoint_states,
+            next_endpoint: AtomicUsize::new(0),
+            retry_policy: RetryPolicy::default(),
             limiter,
+            limiter_capacity,
+            method_cost_overrides: HashMap::new(),
             batch_chunk_size,
+            batch_chunk_semaphore: Semaphore::new(DEFAULT_MAX_CONCURRENT_BATCH_CHUNKS),
+            rest_enabled: false,
             next_id: AtomicU64::new(initial_request_id()),
-            block_height_cache: RwLock::new(LruCache::new(
+            header_chain_cache: RwLock::new(HeaderChainCache::new(
                 NonZeroUsize::new(BLOCK_HEIGHT_CACHE_CAP)
                     .expect("BLOCK_HEIGHT_CACHE_CAP is non-zero"),
             )),
+            network_cache: RwLock::new(None),
+            handshake_cache: RwLock::new(None),
         })
     }
 
+    /// Convenience constructor for the common case: a single node
+    /// authenticated via Bitcoin Core's rotating `.cookie` file, with no
+    /// rate limiting and default batching/timeout. Equivalent to
+    /// `Self::new(vec![RpcEndpoint::new(connection).with_cookie_file(cookie_file)], None, None, 10, 30, 10)`.
+    ///
+    /// For multiple failover endpoints, static user/pass auth, or
+    /// non-default rate limits/batching/timeouts, build a [`RpcEndpoint`]
+    /// and call [`Self::new`] directly.
+    pub fn with_cookie_file(
+        connection: impl Into<String>,
+        cookie_file: impl Into<PathBuf>,
+    ) -> Result<Self, CoreError> {
+        Self::new(
+            vec![RpcEndpoint::new(connection).with_cookie_file(cookie_file)],
+            None,
+            None,
+            DEFAULT_BATCH_CHUNK_SIZE,
+            DEFAULT_REQUEST_TIMEOUT_SECS,
+            DEFAULT_CONNECT_TIMEOUT_SECS,
+        )
+    }
+
+    /// Replace the default [`RetryPolicy`], consuming-self builder style.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Overrides the credit cost of `method`, replacing the
+    /// [`default_method_cost`] table entry for it. Fails if `cost` exceeds
+    /// the configured rate limiter's credit capacity, since such a call
+    /// could never acquire enough credits to proceed.
+    pub fn with_method_cost(mut self, method: &str, cost: u32) -> Result<Self, CoreError> {
+        if let Some(capacity) = self.limiter_capacity {
+            if cost > capacity {
+                return Err(CoreError::InvalidTxData(format!(
+                    "cost {cost} for method \"{method}\" exceeds rate limiter capacity {capacity}; \
+                     the call would never acquire enough credits"
+                )));
+            }
+        }
+        self.method_cost_overrides.insert(method.to_owned(), cost);
+        Ok(self)
+    }
+
+    /// Replace the default cap ([`DEFAULT_MAX_CONCURRENT_BATCH_CHUNKS`]) on
+    /// how many [`Self::rpc_batch_chunked`] chunks run concurrently,
+    /// consuming-self builder style.
+    pub fn with_max_concurrent_batch_chunks(mut self, max: usize) -> Result<Self, CoreError> {
+        if max == 0 {
+            return Err(CoreError::InvalidTxData(
+                "max concurrent batch chunks must be at least 1".to_owned(),
+            ));
+        }
+        self.batch_chunk_semaphore = Semaphore::new(max);
+        Ok(self)
+    }
+
+    /// Enable fetching transactions over Bitcoin Core's binary REST
+    /// interface (`-rest=1`) instead of `getrawtransaction`, consuming-self
+    /// builder style. REST payloads decode faster (no JSON, no prevout
+    /// verbosity) but carry no block context or prevout data, so every
+    /// input still needs the usual prevout resolution; a 404 (REST
+    /// disabled, or the node predates it) falls back to JSON-RPC per call.
+    pub fn with_rest_transport(mut self, enabled: bool) -> Self {
+        self.rest_enabled = enabled;
+        self
+    }
+
+    /// Start building a [`BatchRequest`] of JSON-RPC calls to dispatch
+    /// together in one or more batched HTTP requests, rather than one
+    /// round trip per call — a first-class alternative to the trait
+    /// methods' internal use of batching (see [`Self::rpc_batch_chunked`])
+    /// for callers (e.g. a bulk label-import command) that want to batch
+    /// calls the built-in trait methods don't already batch for them.
+    pub fn batch(&self) -> BatchRequest<'_> {
+        BatchRequest {
+            client: self,
+            calls: Vec::new(),
+        }
+    }
+
+    /// Credit cost of `method`: an override set via
+    /// [`Self::with_method_cost`] if present, otherwise
+    /// [`default_method_cost`].
+    fn method_cost(&self, method: &str) -> u32 {
+        self.method_cost_overrides
+            .get(method)
+            .copied()
+            .unwrap_or_else(|| default_method_cost(method))
+    }
+
+    /// Re-resolve `endpoint`'s auth credentials for the current request.
+    ///
+    /// When a cookie file is in use, this re-reads it from disk every
+    /// call so a `bitcoind` restart (which rewrites the cookie with a
+    /// fresh password) is picked up without restarting Cory. Explicit
+    /// `user`/`pass` always wins and involves no I/O.
+    fn current_auth(endpoint: &RpcEndpoint) -> Result<Option<(String, String)>, CoreError> {
+        resolve_auth(
+            endpoint.user.as_deref(),
+            endpoint.pass.as_deref(),
+            endpoint.cookie_file.as_deref(),
+        )
+    }
+
     /// Atomically reserve `count` consecutive request IDs for batch calls.
     fn reserve_request_ids(&self, count: u64) -> u64 {
         self.next_id.fetch_add(count, Ordering::Relaxed)
     }
 
-    async fn wait_for_rate_limit(&self) {
-        if let Some(limiter) = &self.limiter {
-            limiter.until_ready().await;
+    /// Picks which endpoint the next attempt should use.
+    ///
+    /// On the first attempt of a call (`exclude` is `None`) this is just an
+    /// atomic load of the current healthy endpoint — no scanning, so a
+    /// healthy steady-state deployment pays no probing cost. Only when
+    /// retrying after a failure (`exclude` is `Some`, naming the endpoint
+    /// that just failed) does this scan forward for the next non-penalized
+    /// endpoint and persist it as the new current endpoint, so later calls
+    /// start from there too. If every endpoint is currently penalized, falls
+    /// back to round-robin anyway rather than refusing to try at all — a
+    /// penalty is a guess, not a certainty, and a node may have recovered
+    /// despite it.
+    fn pick_endpoint_index(&self, exclude: Option<usize>) -> usize {
+        let len = self.endpoints.len();
+        let current = self.next_endpoint.load(Ordering::Relaxed) % len;
+        if exclude.is_none() {
+            return current;
+        }
+
+        let now = now_millis();
+        let idx = (0..len)
+            .map(|offset| (current + offset) % len)
+            .find(|&idx| Some(idx) != exclude && !self.endpoints[idx].is_penalized(now))
+            .or_else(|| {
+                (0..len)
+                    .map(|offset| (current + offset) % len)
+                    .find(|&idx| Some(idx) != exclude)
+            })
+            .unwrap_or(current);
+
+        self.next_endpoint.store(idx, Ordering::Relaxed);
+        idx
+    }
+
+    /// Run `f` against a selected endpoint, retrying transient failures
+    /// (per [`RetryPolicy::is_retryable`]) up to `retry_policy.max_attempts`
+    /// additional times with jittered exponential backoff, failing over to
+    /// the next healthy endpoint on each retry. `f` is called fresh on
+    /// every attempt, so each one reserves its own request id(s) — a late
+    /// response from an abandoned endpoint can never be mistaken for the
+    /// next attempt's.
+    async fn with_retry<F, Fut, T>(&self, label: &str, mut f: F) -> Result<T, CoreError>
+    where
+        F: FnMut(&EndpointState) -> Fut,
+        Fut: std::future::Future<Output = Result<T, CoreError>>,
+    {
+        let mut attempt = 0;
+        let mut endpoint_idx = self.pick_endpoint_index(None);
+        loop {
+            let endpoint = &self.endpoints[endpoint_idx];
+            match f(endpoint).await {
+                Ok(value) => {
+                    endpoint.note_success();
+                    return Ok(value);
+                }
+                Err(err)
+                    if attempt < self.retry_policy.max_attempts
+                        && self.retry_policy.is_retryable(&err) =>
+                {
+                    endpoint.note_failure();
+                    let delay = self.retry_policy.backoff_delay(attempt);
+                    warn!(
+                        rpc.label = label,
+                        rpc.endpoint = %endpoint.endpoint.connection,
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %err,
+                        "retrying rpc call after transient failure"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                    endpoint_idx = self.pick_endpoint_index(Some(endpoint_idx));
+                }
+                Err(err) => {
+                    endpoint.note_failure();
+                    return Err(err);
+                }
+            }
         }
     }
 
+    /// Awaits until the credit bucket holds at least `cost` credits, then
+    /// atomically deducts them. A no-op when rate limiting is disabled.
+    async fn acquire_credits(&self, cost: u32) -> Result<(), CoreError> {
+        let Some(limiter) = &self.limiter else {
+            return Ok(());
+        };
+        let cost = NonZeroU32::new(cost).unwrap_or_else(|| NonZeroU32::new(1).expect("1 != 0"));
+        limiter.until_n_ready(cost).await.map_err(|_| {
+            CoreError::InvalidTxData(format!(
+                "rpc call cost {cost} exceeds rate limiter capacity and can never be granted"
+            ))
+        })
+    }
+
+    /// Issue a single JSON-RPC call, retrying transient failures against
+    /// the next healthy endpoint with jittered exponential backoff. See
+    /// [`RetryPolicy::is_retryable`].
     async fn rpc_call(
         &self,
         method: &str,
         params: Vec<serde_json::Value>,
     ) -> Result<serde_json::Value, CoreError> {
-        self.wait_for_rate_limit().await;
+        self.with_retry(method, |endpoint| {
+            self.rpc_call_once(endpoint, method, params.clone())
+        })
+        .await
+    }
+
+    async fn rpc_call_once(
+        &self,
+        endpoint: &EndpointState,
+        method: &str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<serde_json::Value, CoreError> {
+        self.acquire_credits(self.method_cost(method)).await?;
         let id = self.reserve_request_ids(1);
         debug!(
             rpc.id = id,
             rpc.method = method,
             rpc.params = params.len(),
+            rpc.endpoint = %endpoint.endpoint.connection,
             "rpc call"
         );
         let req = JsonRpcRequest {
@@ -147,20 +773,34 @@ impl HttpRpcClient {
 
         let mut builder = self
             .client
-            .post(&self.url)
+            .post(&endpoint.endpoint.connection)
             .header(header::CONTENT_TYPE, "application/json")
             .json(&req);
-        if let Some((ref user, ref pass)) = self.auth {
+        if let Some((user, pass)) = Self::current_auth(&endpoint.endpoint)? {
             builder = builder.basic_auth(user, Some(pass));
         }
 
-        let response = builder.send().await.map_err(RpcError::Transport)?;
+        let response = builder.send().await.map_err(classify_send_error)?;
         let status = response.status();
 
         let body = response.text().await.map_err(RpcError::Transport)?;
         debug!(rpc.id = id, rpc.method = method, %status, body_len = body.len(), "rpc response");
         trace!(rpc.id = id, rpc.method = method, body = %body, "rpc response body");
 
+        if status.as_u16() == 401 || status.as_u16() == 403 {
+            return Err(RpcError::AuthRejected {
+                status: status.as_u16(),
+            }
+            .into());
+        }
+        if !status.is_success() {
+            return Err(RpcError::HttpStatus {
+                status: status.as_u16(),
+                body,
+            }
+            .into());
+        }
+
         let decoded: JsonRpcResponse = serde_json::from_str(&body).map_err(|e| {
             RpcError::InvalidResponse(format!("decode JSON-RPC response: {e}; body={body}"))
         })?;
@@ -172,15 +812,40 @@ impl HttpRpcClient {
         Ok(decoded.result.unwrap_or(serde_json::Value::Null))
     }
 
+    /// Issue a single JSON-RPC batch request, retrying transient failures
+    /// against the next healthy endpoint with jittered exponential
+    /// backoff. See [`RetryPolicy::is_retryable`].
+    ///
+    /// Returns one `Result` per call, in submission order, rather than a
+    /// single `Result` for the whole batch: a per-call JSON-RPC error
+    /// (e.g. "transaction not found") only fails that call's slot, so it
+    /// can't poison the others' results. A transient per-call error (per
+    /// [`RetryPolicy::is_retryable`], e.g. the node being in warmup) still
+    /// retries the whole batch, since that class of error means every
+    /// call in it is suspect, not just the one that happened to report it.
     async fn rpc_batch(
         &self,
         calls: &[(String, Vec<serde_json::Value>)],
-    ) -> Result<Vec<serde_json::Value>, CoreError> {
-        self.wait_for_rate_limit().await;
+    ) -> Result<Vec<Result<serde_json::Value, CoreError>>, CoreError> {
+        self.with_retry("batch", |endpoint| self.rpc_batch_once(endpoint, calls))
+            .await
+    }
+
+    async fn rpc_batch_once(
+        &self,
+        endpoint: &EndpointState,
+        calls: &[(String, Vec<serde_json::Value>)],
+    ) -> Result<Vec<Result<serde_json::Value, CoreError>>, CoreError> {
+        let batch_cost: u32 = calls
+            .iter()
+            .map(|(method, _)| self.method_cost(method))
+            .sum();
+        self.acquire_credits(batch_cost).await?;
         let start_id = self.reserve_request_ids(calls.len() as u64);
         debug!(
             rpc.batch_start_id = start_id,
             rpc.batch_size = calls.len(),
+            rpc.endpoint = %endpoint.endpoint.connection,
             "rpc batch call"
         );
         let requests: Vec<JsonRpcRequestOwned> = calls
@@ -196,14 +861,14 @@ impl HttpRpcClient {
 
         let mut builder = self
             .client
-            .post(&self.url)
+            .post(&endpoint.endpoint.connection)
             .header(header::CONTENT_TYPE, "application/json")
             .json(&requests);
-        if let Some((ref user, ref pass)) = self.auth {
+        if let Some((user, pass)) = Self::current_auth(&endpoint.endpoint)? {
             builder = builder.basic_auth(user, Some(pass));
         }
 
-        let response = builder.send().await.map_err(RpcError::Transport)?;
+        let response = builder.send().await.map_err(classify_send_error)?;
         let status = response.status();
 
         let body = response.text().await.map_err(RpcError::Transport)?;
@@ -221,6 +886,20 @@ impl HttpRpcClient {
             "rpc batch response body"
         );
 
+        if status.as_u16() == 401 || status.as_u16() == 403 {
+            return Err(RpcError::AuthRejected {
+                status: status.as_u16(),
+            }
+            .into());
+        }
+        if !status.is_success() {
+            return Err(RpcError::HttpStatus {
+                status: status.as_u16(),
+                body,
+            }
+            .into());
+        }
+
         let decoded: Vec<JsonRpcResponseOwned> = serde_json::from_str(&body).map_err(|e| {
             RpcError::InvalidResponse(format!("decode JSON-RPC batch response: {e}; body={body}"))
         })?;
@@ -228,17 +907,25 @@ impl HttpRpcClient {
         let mut by_id: HashMap<u64, JsonRpcResponseOwned> = HashMap::with_capacity(decoded.len());
         for item in decoded {
             let id = parse_batch_id(&item.id)?;
-            by_id.insert(id, item);
+            if by_id.insert(id, item).is_some() {
+                return Err(RpcError::DuplicateBatchItem { id }.into());
+            }
         }
 
         let mut ordered = Vec::with_capacity(calls.len());
         for id in start_id..(start_id + calls.len() as u64) {
             let item = by_id.remove(&id).ok_or(RpcError::MissingBatchItem { id })?;
 
-            if let Some(err) = item.error {
-                return Err(parse_jsonrpc_error(err));
+            match item.error {
+                Some(err) => {
+                    let err = parse_jsonrpc_error(err);
+                    if self.retry_policy.is_retryable(&err) {
+                        return Err(err);
+                    }
+                    ordered.push(Err(err));
+                }
+                None => ordered.push(Ok(item.result.unwrap_or(serde_json::Value::Null))),
             }
-            ordered.push(item.result.unwrap_or(serde_json::Value::Null));
         }
 
         Ok(ordered)
@@ -247,21 +934,110 @@ impl HttpRpcClient {
     async fn rpc_batch_chunked(
         &self,
         calls: &[(String, Vec<serde_json::Value>)],
-    ) -> Result<Vec<serde_json::Value>, CoreError> {
+    ) -> Result<Vec<Result<serde_json::Value, CoreError>>, CoreError> {
         if calls.is_empty() {
             return Ok(Vec::new());
         }
 
         // Keep each payload small enough for node/proxy limits while still
         // issuing chunks concurrently to avoid serial round-trip latency.
+        // Each chunk retries independently, so one flaky chunk doesn't
+        // force the whole batch to restart. In-flight chunks are capped by
+        // `batch_chunk_semaphore` so a huge batch (e.g. thousands of
+        // outpoints) can't open more concurrent requests than the node can
+        // comfortably handle; the credit bucket (see `acquire_credits`)
+        // separately paces the overall request rate regardless of how many
+        // chunks are in flight at once.
         let chunk_futures: Vec<_> = calls
             .chunks(self.batch_chunk_size)
-            .map(|chunk| self.rpc_batch(chunk))
+            .map(|chunk| async move {
+                let _permit = self
+                    .batch_chunk_semaphore
+                    .acquire()
+                    .await
+                    .expect("batch chunk semaphore is never closed");
+                self.rpc_batch(chunk).await
+            })
             .collect();
         let chunked = try_join_all(chunk_futures).await?;
         Ok(chunked.into_iter().flatten().collect())
     }
 
+    /// Resolve the node's [`Network`], used to derive addresses from
+    /// scripts. Unlike [`EsploraClient`](super::super::EsploraClient), Core
+    /// never has this configured up front — it reports its own chain via
+    /// `getblockchaininfo` — so the first call pays one round-trip and
+    /// caches the (immutable for the client's lifetime) result.
+    async fn resolve_network(&self) -> Result<Network, CoreError> {
+        if let Some(network) = *self.network_cache.read().await {
+            return Ok(network);
+        }
+
+        let info = self.get_blockchain_info().await?;
+        let network = chain_to_network(&info.chain).ok_or_else(|| {
+            CoreError::InvalidTxData(format!(
+                "unrecognized chain name `{}` from getblockchaininfo",
+                info.chain
+            ))
+        })?;
+        *self.network_cache.write().await = Some(network);
+        Ok(network)
+    }
+
+    /// One-time preflight validating the connected node's version and
+    /// indexing capability, meant to be called once before the first
+    /// `build_ancestry` run so an incompatible node fails fast with a
+    /// clear explanation instead of graph building eventually producing
+    /// confusing "missing transaction" errors partway through a deep walk.
+    ///
+    /// Checks `getnetworkinfo`'s `version` against
+    /// [`MIN_SUPPORTED_NODE_VERSION`]/[`MAX_SUPPORTED_NODE_VERSION`] and
+    /// returns [`RpcError::UnsupportedNode`] naming the detected version
+    /// and the supported range if it's outside it. Also checks
+    /// `getindexinfo` for a `txindex` entry, logging a warning (not an
+    /// error, since `-txindex` is only required for looking up arbitrary
+    /// historical/unconfirmed txids, not every call this client makes) if
+    /// it's absent or the RPC doesn't exist at all (pre-0.21 nodes).
+    ///
+    /// Successful results are cached for the client's lifetime; a failure
+    /// is not, so a transient RPC error during the handshake itself doesn't
+    /// permanently poison an otherwise-supported node.
+    pub async fn ensure_node_supported(&self) -> Result<(), CoreError> {
+        if self.handshake_cache.read().await.is_some() {
+            return Ok(());
+        }
+
+        let raw = self.rpc_call("getnetworkinfo", Vec::new()).await?;
+        let version = parse_integer_required::<i64, false>(raw.get("version"), "version")?;
+        if !(MIN_SUPPORTED_NODE_VERSION..=MAX_SUPPORTED_NODE_VERSION).contains(&version) {
+            return Err(RpcError::UnsupportedNode(format!(
+                "node reports version {version}, outside Cory's supported range \
+                 {MIN_SUPPORTED_NODE_VERSION}-{MAX_SUPPORTED_NODE_VERSION} \
+                 (Bitcoin Core v0.20.0 through v27.0.0)"
+            ))
+            .into());
+        }
+
+        match self.rpc_call("getindexinfo", Vec::new()).await {
+            Ok(indexes) if indexes.get("txindex").is_none() => {
+                warn!(
+                    "connected node has no txindex; getrawtransaction lookups for \
+                     unconfirmed or arbitrary (non-wallet) txids will fail"
+                );
+            }
+            Ok(_) => {}
+            Err(err) => {
+                // Pre-0.21 nodes have no `getindexinfo` at all; the version
+                // check above already covers the floor this client supports,
+                // so this is informational rather than disqualifying.
+                debug!(error = %err, "getindexinfo unavailable; skipping txindex check");
+            }
+        }
+
+        *self.handshake_cache.write().await = Some(());
+        Ok(())
+    }
+
     async fn parse_tx_node_from_raw(&self, raw: serde_json::Value) -> Result<TxNode, CoreError> {
         let txid = parse_txid(raw.get("txid"), "txid")?;
         let version = parse_integer_required::<i32, true>(raw.get("version"), "version")?;
@@ -291,8 +1067,9 @@ impl HttpRpcClient {
             .and_then(serde_json::Value::as_array)
             .ok_or_else(|| CoreError::InvalidTxData("missing vout array".into()))?;
 
-        let inputs = parse_vin(vin)?;
-        let outputs = parse_vout(vout)?;
+        let network = self.resolve_network().await?;
+        let inputs = parse_vin(vin, network)?;
+        let outputs = parse_vout(vout, network)?;
 
         Ok(TxNode {
             txid,
@@ -312,14 +1089,13 @@ impl HttpRpcClient {
         &self,
         block_hash: BlockHash,
     ) -> Result<Option<BlockHeight>, CoreError> {
-        // The LRU cache requires a write lock for `get` (it updates recency),
-        // but the lookup is fast so the write lock is acceptable.
+        // The cache requires a write lock even for a lookup (the LRU side
+        // updates recency), but the lookup is fast so this is acceptable.
         if let Some(height) = self
-            .block_height_cache
+            .header_chain_cache
             .write()
             .await
-            .get(&block_hash)
-            .copied()
+            .get_height(&block_hash)
         {
             return Ok(Some(height));
         }
@@ -335,18 +1111,215 @@ impl HttpRpcClient {
             .await?;
         let height = parse_integer_optional::<u32, false>(raw.get("height")).map(BlockHeight);
         if let Some(height) = height {
-            self.block_height_cache
+            self.header_chain_cache
                 .write()
                 .await
-                .put(block_hash, height);
+                .insert(block_hash, height);
         }
         Ok(height)
     }
+
+    /// Fetch `txid` over the REST interface, returning `Ok(None)` on a 404
+    /// (REST disabled, or the transaction isn't indexed there) so the
+    /// caller can fall back to JSON-RPC. Only a 404 is treated as absence —
+    /// any other failure (transport, 5xx) is retried/failed-over exactly
+    /// like a JSON-RPC call via [`Self::with_retry`].
+    async fn get_transaction_via_rest(&self, txid: &Txid) -> Result<Option<TxNode>, CoreError> {
+        let bytes = self
+            .with_retry("rest_tx", |endpoint| {
+                self.rest_fetch_tx_bytes(endpoint, txid)
+            })
+            .await?;
+        let Some(bytes) = bytes else {
+            return Ok(None);
+        };
+        let tx: bitcoin::Transaction = bitcoin::consensus::deserialize(&bytes).map_err(|e| {
+            CoreError::InvalidTxData(format!("invalid REST tx payload for {txid}: {e}"))
+        })?;
+        let network = self.resolve_network().await?;
+        Ok(Some(tx_node_from_rest_tx(tx, network)))
+    }
+
+    async fn rest_fetch_tx_bytes(
+        &self,
+        endpoint: &EndpointState,
+        txid: &Txid,
+    ) -> Result<Option<Vec<u8>>, CoreError> {
+        let url = format!("{}/rest/tx/{txid}.bin", endpoint.endpoint.connection);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(classify_send_error)?;
+
+        if response.status().as_u16() == 404 {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(RpcError::HttpStatus {
+                status: response.status().as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            }
+            .into());
+        }
+
+        let bytes = response.bytes().await.map_err(RpcError::Transport)?;
+        Ok(Some(bytes.to_vec()))
+    }
+
+    /// Fetch `hash`'s header over the REST interface, returning `Ok(None)`
+    /// when the height isn't already known (REST headers carry no height,
+    /// unlike `getblockheader`'s JSON response) or on a 404, so the caller
+    /// can fall back to JSON-RPC in either case.
+    async fn get_block_header_via_rest(
+        &self,
+        hash: BlockHash,
+    ) -> Result<Option<BlockHeaderInfo>, CoreError> {
+        let Some(height) = self.header_chain_cache.write().await.get_height(&hash) else {
+            return Ok(None);
+        };
+
+        let bytes = self
+            .with_retry("rest_header", |endpoint| {
+                self.rest_fetch_header_bytes(endpoint, &hash)
+            })
+            .await?;
+        let Some(bytes) = bytes else {
+            return Ok(None);
+        };
+
+        let header: bitcoin::block::Header =
+            bitcoin::consensus::deserialize(&bytes).map_err(|e| {
+                CoreError::InvalidTxData(format!("invalid REST header payload for {hash}: {e}"))
+            })?;
+
+        Ok(Some(BlockHeaderInfo {
+            hash,
+            height: height.0,
+            merkle_root: header.merkle_root,
+        }))
+    }
+
+    async fn rest_fetch_header_bytes(
+        &self,
+        endpoint: &EndpointState,
+        hash: &BlockHash,
+    ) -> Result<Option<Vec<u8>>, CoreError> {
+        let url = format!("{}/rest/headers/1/{hash}.bin", endpoint.endpoint.connection);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(classify_send_error)?;
+
+        if response.status().as_u16() == 404 {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(RpcError::HttpStatus {
+                status: response.status().as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            }
+            .into());
+        }
+
+        let bytes = response.bytes().await.map_err(RpcError::Transport)?;
+        Ok(Some(bytes.to_vec()))
+    }
+}
+
+/// Convert a consensus-decoded [`bitcoin::Transaction`], as served raw by
+/// the REST `/rest/tx/<txid>.bin` endpoint, into a [`TxNode`]. Unlike
+/// `getrawtransaction`, raw consensus bytes carry no block context or
+/// prevout values/scripts, so those are left `None`/unresolved here exactly
+/// as they would be for any other transaction whose prevouts haven't been
+/// resolved yet — see `graph::resolve_unresolved_prevouts`.
+fn tx_node_from_rest_tx(tx: bitcoin::Transaction, network: Network) -> TxNode {
+    let inputs = tx
+        .input
+        .iter()
+        .map(|txin| {
+            let prevout = (!txin.previous_output.is_null()).then_some(txin.previous_output);
+            TxInput {
+                prevout,
+                sequence: txin.sequence.to_consensus_u32(),
+                value: None,
+                script_type: None,
+                address: None,
+                unresolved_reason: None,
+            }
+        })
+        .collect();
+
+    let outputs = tx
+        .output
+        .iter()
+        .map(|txout| TxOutput {
+            value: txout.value,
+            script_pub_key: txout.script_pubkey.clone(),
+            script_type: classify_script(txout.script_pubkey.as_script()),
+            address: address_from_script(&txout.script_pubkey, network),
+        })
+        .collect();
+
+    TxNode {
+        txid: tx.txid(),
+        version: tx.version.0,
+        locktime: tx.lock_time.to_consensus_u32(),
+        size: tx.total_size() as u64,
+        vsize: tx.vsize() as u64,
+        weight: tx.weight().to_wu(),
+        block_hash: None,
+        block_height: None,
+        block_time: None,
+        inputs,
+        outputs,
+    }
+}
+
+/// A batch of JSON-RPC calls accumulated via [`HttpRpcClient::batch`] and
+/// dispatched together, split into `batch_chunk_size`-sized chunks sent
+/// concurrently (same machinery the trait methods use internally; see
+/// [`HttpRpcClient::rpc_batch_chunked`]).
+///
+/// [`Self::execute`] returns one `Result` per call, in submission order —
+/// matching [`BatchRequest::add`]'s returned index — rather than a single
+/// `Result` for the whole batch, so a failed `gettxout`/`getrawtransaction`
+/// call doesn't prevent the caller from seeing every other call's result.
+pub struct BatchRequest<'a> {
+    client: &'a HttpRpcClient,
+    calls: Vec<(String, Vec<serde_json::Value>)>,
+}
+
+impl<'a> BatchRequest<'a> {
+    /// Queue `method`/`params` as the next call in this batch, returning
+    /// its index in [`Self::execute`]'s result `Vec`.
+    pub fn add(&mut self, method: impl Into<String>, params: Vec<serde_json::Value>) -> usize {
+        self.calls.push((method.into(), params));
+        self.calls.len() - 1
+    }
+
+    /// Dispatch every queued call. An empty batch is a no-op that never
+    /// makes a request.
+    pub async fn execute(self) -> Result<Vec<Result<serde_json::Value, CoreError>>, CoreError> {
+        if self.calls.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.client.rpc_batch_chunked(&self.calls).await
+    }
 }
 
 #[async_trait]
 impl BitcoinRpc for HttpRpcClient {
     async fn get_transaction(&self, txid: &Txid) -> Result<TxNode, CoreError> {
+        if self.rest_enabled {
+            if let Some(node) = self.get_transaction_via_rest(txid).await? {
+                return Ok(node);
+            }
+        }
+
         let raw = self
             .rpc_call(
                 "getrawtransaction",
@@ -362,6 +1335,22 @@ impl BitcoinRpc for HttpRpcClient {
             return Ok(Vec::new());
         }
 
+        if self.rest_enabled {
+            // No batch REST endpoint exists, so fetch concurrently (capped
+            // by `batch_chunk_semaphore`, same as a JSON-RPC batch chunk
+            // would be); `get_transaction` falls back to JSON-RPC per txid
+            // on a 404, so a partially-indexed REST node still works.
+            let futures = txids.iter().map(|txid| async move {
+                let _permit = self
+                    .batch_chunk_semaphore
+                    .acquire()
+                    .await
+                    .expect("batch chunk semaphore is never closed");
+                self.get_transaction(txid).await
+            });
+            return try_join_all(futures).await;
+        }
+
         let calls: Vec<(String, Vec<serde_json::Value>)> = txids
             .iter()
             .map(|txid| {
@@ -391,30 +1380,48 @@ impl BitcoinRpc for HttpRpcClient {
 
         let parse_futures: Vec<_> = raw_results
             .into_iter()
-            .map(|raw| self.parse_tx_node_from_raw(raw))
+            .map(|raw| async move { self.parse_tx_node_from_raw(raw?).await })
             .collect();
         try_join_all(parse_futures).await
     }
 
-    async fn get_tx_out(&self, txid: &Txid, vout: u32) -> Result<Option<TxOutput>, CoreError> {
+    /// Always goes over JSON-RPC, even with `rest_enabled`: Core's REST
+    /// UTXO-set endpoint (`/rest/getutxos/...`) encodes its response with
+    /// Core's internal "Coin" compression format (a bespoke amount
+    /// compression scheme, a non-standard base-128 VARINT, and special-cased
+    /// script compression), which the `bitcoin` crate has no decoder for.
+    /// Falling back to the already-REST-backed `/rest/tx/<txid>.bin` plain
+    /// transaction fetch isn't a safe substitute either: `gettxout` reports
+    /// UTXO-set *membership*, and a historical transaction still decodes
+    /// (and still shows the output) long after that output has been spent,
+    /// which would silently turn prevout resolution wrong instead of merely
+    /// unoptimized.
+    async fn get_tx_out(
+        &self,
+        txid: &Txid,
+        vout: u32,
+        include_mempool: bool,
+    ) -> Result<Option<TxOutInfo>, CoreError> {
         let raw = self
             .rpc_call(
                 "gettxout",
                 vec![
                     serde_json::json!(txid.to_string()),
                     serde_json::json!(vout),
-                    serde_json::json!(true),
+                    serde_json::json!(include_mempool),
                 ],
             )
             .await?;
 
-        parse_gettxout_result(raw)
+        let network = self.resolve_network().await?;
+        parse_gettxout_result(raw, network)
     }
 
     async fn get_tx_outs(
         &self,
         outpoints: &[OutPoint],
-    ) -> Result<Vec<Option<TxOutput>>, CoreError> {
+        include_mempool: bool,
+    ) -> Result<Vec<Option<TxOutInfo>>, CoreError> {
         if outpoints.is_empty() {
             return Ok(Vec::new());
         }
@@ -427,14 +1434,30 @@ impl BitcoinRpc for HttpRpcClient {
                     vec![
                         serde_json::json!(outpoint.txid.to_string()),
                         serde_json::json!(outpoint.vout),
-                        serde_json::json!(true),
+                        serde_json::json!(include_mempool),
                     ],
                 )
             })
             .collect();
 
+        let network = self.resolve_network().await?;
         let raw_results = self.rpc_batch_chunked(&calls).await?;
-        raw_results.into_iter().map(parse_gettxout_result).collect()
+        raw_results
+            .into_iter()
+            .map(|raw| parse_gettxout_result(raw?, network))
+            .collect()
+    }
+
+    async fn get_spend(&self, outpoint: OutPoint) -> Result<Option<TxSpend>, CoreError> {
+        // Core's JSON-RPC has no "who spent this output" query without a
+        // third-party index (txindex only resolves a txid to its own
+        // transaction, not to whatever spent one of its outputs) — so
+        // there's nothing to call here. Descendant tracing needs a backend
+        // with an outpoint index, e.g. `EsploraClient`.
+        let _ = outpoint;
+        Err(CoreError::InvalidTxData(
+            "the core backend has no outpoint-spend index; descendant tracing requires the esplora backend".into(),
+        ))
     }
 
     async fn get_blockchain_info(&self) -> Result<ChainInfo, CoreError> {
@@ -442,8 +1465,140 @@ impl BitcoinRpc for HttpRpcClient {
         let info: ChainInfo = serde_json::from_value(raw).map_err(|e| {
             CoreError::InvalidTxData(format!("invalid getblockchaininfo result: {e}"))
         })?;
+        self.header_chain_cache
+            .write()
+            .await
+            .note_best_height(info.blocks as u32);
         Ok(info)
     }
+
+    async fn get_txout_proof(&self, txids: &[Txid]) -> Result<Option<String>, CoreError> {
+        let ids: Vec<serde_json::Value> = txids
+            .iter()
+            .map(|txid| serde_json::json!(txid.to_string()))
+            .collect();
+
+        match self
+            .rpc_call("gettxoutproof", vec![serde_json::json!(ids)])
+            .await
+        {
+            Ok(raw) => {
+                let hex = raw.as_str().ok_or_else(|| {
+                    CoreError::InvalidTxData("gettxoutproof did not return a hex string".into())
+                })?;
+                Ok(Some(hex.to_owned()))
+            }
+            Err(err) => normalize_gettxoutproof_error(err),
+        }
+    }
+
+    async fn get_block_header(&self, id: BlockId) -> Result<BlockHeaderInfo, CoreError> {
+        let block_hash = self.get_block_hash(id).await?;
+
+        if self.rest_enabled {
+            if let Some(info) = self.get_block_header_via_rest(block_hash).await? {
+                return Ok(info);
+            }
+        }
+
+        let raw = self
+            .rpc_call(
+                "getblockheader",
+                vec![
+                    serde_json::json!(block_hash.to_string()),
+                    serde_json::json!(true),
+                ],
+            )
+            .await?;
+
+        let height = parse_integer_required::<u32, false>(raw.get("height"), "height")?;
+        let merkle_root = parse_merkle_root(raw.get("merkleroot"))?;
+
+        self.header_chain_cache
+            .write()
+            .await
+            .insert(block_hash, BlockHeight(height));
+
+        Ok(BlockHeaderInfo {
+            hash: block_hash,
+            height,
+            merkle_root,
+        })
+    }
+
+    async fn get_block(&self, id: BlockId) -> Result<Block, CoreError> {
+        let block_hash = self.get_block_hash(id).await?;
+        let raw = self
+            .rpc_call(
+                "getblock",
+                vec![serde_json::json!(block_hash.to_string()), serde_json::json!(2)],
+            )
+            .await?;
+        let network = self.resolve_network().await?;
+        parse_block(raw, network)
+    }
+
+    async fn estimate_smart_fee(
+        &self,
+        conf_target: u16,
+        mode: EstimateMode,
+    ) -> Result<Option<f64>, CoreError> {
+        let raw = self
+            .rpc_call(
+                "estimatesmartfee",
+                vec![
+                    serde_json::json!(conf_target),
+                    serde_json::json!(mode.as_core_str()),
+                ],
+            )
+            .await?;
+
+        // Core omits `feerate` (and populates `errors` instead) when it
+        // doesn't have enough data yet, e.g. on a freshly started node.
+        let Some(feerate_btc_per_kvb) = raw.get("feerate").and_then(serde_json::Value::as_f64)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(feerate_btc_per_kvb * 100_000_000.0 / 1000.0))
+    }
+
+    async fn get_block_hash(&self, id: BlockId) -> Result<BlockHash, CoreError> {
+        match id {
+            BlockId::Hash(hash) => Ok(hash),
+            BlockId::Latest => {
+                let raw = self.rpc_call("getbestblockhash", Vec::new()).await?;
+                let hash = raw.as_str().ok_or_else(|| {
+                    CoreError::InvalidTxData("getbestblockhash did not return a string".into())
+                })?;
+                hash.parse()
+                    .map_err(|e| CoreError::InvalidTxData(format!("invalid best block hash: {e}")))
+            }
+            BlockId::Earliest => {
+                let info = self.get_blockchain_info().await?;
+                genesis_hash_for_chain(&info.chain)
+            }
+            BlockId::Height(height) => {
+                if let Some(hash) = self.header_chain_cache.read().await.get_hash(height) {
+                    return Ok(hash);
+                }
+
+                let raw = self
+                    .rpc_call("getblockhash", vec![serde_json::json!(height)])
+                    .await?;
+                let hash_str = raw.as_str().ok_or_else(|| {
+                    CoreError::InvalidTxData("getblockhash did not return a string".into())
+                })?;
+                let hash: BlockHash = hash_str
+                    .parse()
+                    .map_err(|e| CoreError::InvalidTxData(format!("invalid block hash: {e}")))?;
+                self.header_chain_cache
+                    .write()
+                    .await
+                    .insert(hash, BlockHeight(height));
+                Ok(hash)
+            }
+        }
+    }
 }
 
 fn initial_request_id() -> u64 {
@@ -459,12 +1614,14 @@ fn initial_request_id() -> u64 {
 
 /// Convert Bitcoin Core "missing tx" JSON-RPC responses into `TxNotFound`.
 ///
-/// This keeps not-found semantics strongly typed for upstream HTTP mapping,
-/// while preserving other RPC/transport failures as-is.
+/// `getrawtransaction` and `gettxout` both report a missing transaction as
+/// RPC_INVALID_ADDRESS_OR_KEY (-5), so we key off the classified code
+/// rather than matching on `message` text, which varies across Core
+/// versions and locales.
 fn normalize_getrawtransaction_error(txid: &Txid, err: CoreError) -> CoreError {
     match err {
-        CoreError::Rpc(RpcError::ServerError { code, message })
-            if is_tx_not_found_server_error(code, &message) =>
+        CoreError::Rpc(RpcError::ServerError { code, .. })
+            if code == BitcoinRpcErrorCode::InvalidAddressOrKey =>
         {
             CoreError::TxNotFound(*txid)
         }
@@ -472,20 +1629,98 @@ fn normalize_getrawtransaction_error(txid: &Txid, err: CoreError) -> CoreError {
     }
 }
 
-fn is_tx_not_found_server_error(code: i64, message: &str) -> bool {
-    if code != -5 {
-        return false;
+/// Convert Bitcoin Core `gettxoutproof` errors.
+///
+/// Unconfirmed transactions fail with RPC_INVALID_ADDRESS_OR_KEY (-5,
+/// "Transaction not yet in block"), which we key off the classified code
+/// and map to `Ok(None)` rather than an error, matching the trait's
+/// contract. A pruned node that has discarded the block's data fails with
+/// the generic RPC_MISC_ERROR (-1) and no dedicated code, so unlike the
+/// code-based checks elsewhere in this client, this one has to key off a
+/// `message` substring.
+fn normalize_gettxoutproof_error(err: CoreError) -> Result<Option<String>, CoreError> {
+    match err {
+        CoreError::Rpc(RpcError::ServerError { code, .. })
+            if code == BitcoinRpcErrorCode::InvalidAddressOrKey =>
+        {
+            Ok(None)
+        }
+        CoreError::Rpc(RpcError::ServerError { message, .. })
+            if message.to_ascii_lowercase().contains("pruned") =>
+        {
+            Err(CoreError::PrunedBlockData(message))
+        }
+        other => Err(other),
     }
-
-    let msg = message.to_ascii_lowercase();
-    msg.contains("not found") || msg.contains("no such mempool or blockchain transaction")
 }
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
     use super::*;
     use bitcoin::hashes::Hash;
 
+    #[test]
+    fn current_auth_picks_up_cookie_file_rotation() {
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time must be after unix epoch")
+            .as_nanos();
+        let cookie_path =
+            std::env::temp_dir().join(format!("cory-core-client-cookie-{unique}.txt"));
+        fs::write(&cookie_path, "__cookie__:first\n").expect("cookie file must be writable");
+
+        let client = HttpRpcClient::new(
+            vec![RpcEndpoint::new("http://127.0.0.1:8332").with_cookie_file(&cookie_path)],
+            None,
+            None,
+            10,
+            30,
+            10,
+        )
+        .expect("client must construct with a valid cookie file");
+        let endpoint = &client.endpoints[0].endpoint;
+
+        assert_eq!(
+            HttpRpcClient::current_auth(endpoint).expect("auth must resolve"),
+            Some(("__cookie__".to_owned(), "first".to_owned()))
+        );
+
+        // Simulate bitcoind rewriting the cookie file on restart.
+        fs::write(&cookie_path, "__cookie__:second\n").expect("cookie file must be rewritable");
+        assert_eq!(
+            HttpRpcClient::current_auth(endpoint).expect("auth must re-resolve"),
+            Some(("__cookie__".to_owned(), "second".to_owned()))
+        );
+
+        let _ = fs::remove_file(cookie_path);
+    }
+
+    #[test]
+    fn with_cookie_file_constructs_a_single_endpoint_client() {
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time must be after unix epoch")
+            .as_nanos();
+        let cookie_path =
+            std::env::temp_dir().join(format!("cory-core-with-cookie-file-{unique}.txt"));
+        fs::write(&cookie_path, "__cookie__:secret\n").expect("cookie file must be writable");
+
+        let client = HttpRpcClient::with_cookie_file("http://127.0.0.1:8332", &cookie_path)
+            .expect("client must construct with a valid cookie file");
+        assert_eq!(client.endpoints.len(), 1);
+
+        let endpoint = &client.endpoints[0].endpoint;
+        assert_eq!(
+            HttpRpcClient::current_auth(endpoint).expect("auth must resolve"),
+            Some(("__cookie__".to_owned(), "secret".to_owned()))
+        );
+
+        let _ = fs::remove_file(cookie_path);
+    }
+
     fn txid_1() -> Txid {
         Txid::from_slice(&[1; 32]).expect("static txid bytes must parse")
     }
@@ -494,7 +1729,7 @@ mod tests {
     fn normalize_getrawtransaction_not_found_maps_to_typed_error() {
         let txid = txid_1();
         let err = CoreError::Rpc(RpcError::ServerError {
-            code: -5,
+            code: BitcoinRpcErrorCode::InvalidAddressOrKey,
             message: "No such mempool or blockchain transaction".to_string(),
         });
 
@@ -506,17 +1741,126 @@ mod tests {
     fn normalize_getrawtransaction_other_server_error_preserved() {
         let txid = txid_1();
         let err = CoreError::Rpc(RpcError::ServerError {
-            code: -32603,
+            code: BitcoinRpcErrorCode::Other(-32603),
             message: "Internal error".to_string(),
         });
 
         let mapped = normalize_getrawtransaction_error(&txid, err);
         assert!(matches!(
             mapped,
-            CoreError::Rpc(RpcError::ServerError { code: -32603, .. })
+            CoreError::Rpc(RpcError::ServerError {
+                code: BitcoinRpcErrorCode::Other(-32603),
+                ..
+            })
         ));
     }
 
+    #[test]
+    fn default_method_cost_matches_the_documented_table() {
+        assert_eq!(default_method_cost("getblockheader"), 1);
+        assert_eq!(default_method_cost("gettxout"), 1);
+        assert_eq!(default_method_cost("getrawtransaction"), 2);
+        assert_eq!(default_method_cost("getblockchaininfo"), 3);
+    }
+
+    #[test]
+    fn new_rejects_a_credit_capacity_smaller_than_the_costliest_method() {
+        let err = HttpRpcClient::new(
+            vec![RpcEndpoint::new("http://127.0.0.1:8332")],
+            Some(10),
+            Some(2),
+            10,
+            30,
+            10,
+        )
+        .expect_err("capacity below getblockchaininfo's cost must be rejected");
+        assert!(matches!(err, CoreError::InvalidTxData(_)));
+    }
+
+    #[test]
+    fn with_method_cost_overrides_the_default_table() {
+        let client = HttpRpcClient::new(
+            vec![RpcEndpoint::new("http://127.0.0.1:8332")],
+            Some(10),
+            None,
+            10,
+            30,
+            10,
+        )
+        .expect("client must construct")
+        .with_method_cost("estimatesmartfee", 5)
+        .expect("5 is within the default capacity of 10");
+
+        assert_eq!(client.method_cost("estimatesmartfee"), 5);
+        assert_eq!(client.method_cost("gettxout"), 1);
+    }
+
+    #[test]
+    fn with_method_cost_rejects_a_cost_above_capacity() {
+        let client = HttpRpcClient::new(
+            vec![RpcEndpoint::new("http://127.0.0.1:8332")],
+            Some(5),
+            None,
+            10,
+            30,
+            10,
+        )
+        .expect("client must construct");
+
+        let err = client
+            .with_method_cost("estimatesmartfee", 6)
+            .expect_err("cost above capacity must be rejected");
+        assert!(matches!(err, CoreError::InvalidTxData(_)));
+    }
+
+    #[test]
+    fn tx_node_from_rest_tx_leaves_prevouts_unresolved() {
+        let funding_txid = Txid::from_byte_array([7u8; 32]);
+        let tx = bitcoin::Transaction {
+            version: bitcoin::transaction::Version(2),
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![bitcoin::TxIn {
+                previous_output: OutPoint::new(funding_txid, 0),
+                script_sig: bitcoin::ScriptBuf::new(),
+                sequence: bitcoin::Sequence::MAX,
+                witness: bitcoin::Witness::new(),
+            }],
+            output: vec![bitcoin::TxOut {
+                value: bitcoin::Amount::from_sat(1_000),
+                script_pubkey: bitcoin::ScriptBuf::new(),
+            }],
+        };
+
+        let node = tx_node_from_rest_tx(tx, Network::Bitcoin);
+
+        assert_eq!(node.inputs.len(), 1);
+        assert_eq!(node.inputs[0].prevout, Some(OutPoint::new(funding_txid, 0)));
+        assert_eq!(node.inputs[0].value, None);
+        assert_eq!(node.inputs[0].unresolved_reason, None);
+        assert_eq!(node.outputs[0].value, bitcoin::Amount::from_sat(1_000));
+        assert_eq!(node.outputs[0].address, None);
+        assert_eq!(node.block_hash, None);
+        assert_eq!(node.block_height, None);
+    }
+
+    #[test]
+    fn with_max_concurrent_batch_chunks_rejects_zero() {
+        let client = HttpRpcClient::new(
+            vec![RpcEndpoint::new("http://127.0.0.1:8332")],
+            None,
+            None,
+            10,
+            30,
+            10,
+        )
+        .expect("client must construct");
+
+        let err = client
+            .with_max_concurrent_batch_chunks(0)
+            .expect_err("zero concurrent chunks must be rejected");
+        assert!(matches!(err, CoreError::InvalidTxData(_)));
+    }
+
     #[test]
     fn normalize_getrawtransaction_non_rpc_error_preserved() {
         let txid = txid_1();
@@ -525,4 +1869,363 @@ mod tests {
         let mapped = normalize_getrawtransaction_error(&txid, err);
         assert!(matches!(mapped, CoreError::InvalidTxData(message) if message == "bad data"));
     }
+
+    #[test]
+    fn normalize_gettxoutproof_unconfirmed_maps_to_none() {
+        let err = CoreError::Rpc(RpcError::ServerError {
+            code: BitcoinRpcErrorCode::InvalidAddressOrKey,
+            message: "Transaction not yet in block".to_string(),
+        });
+
+        assert_eq!(
+            normalize_gettxoutproof_error(err).expect("must not error"),
+            None
+        );
+    }
+
+    #[test]
+    fn normalize_gettxoutproof_pruned_maps_to_typed_error() {
+        let err = CoreError::Rpc(RpcError::ServerError {
+            code: BitcoinRpcErrorCode::Other(-1),
+            message: "Block not available (pruned data)".to_string(),
+        });
+
+        assert!(matches!(
+            normalize_gettxoutproof_error(err),
+            Err(CoreError::PrunedBlockData(_))
+        ));
+    }
+
+    #[test]
+    fn normalize_gettxoutproof_other_server_error_preserved() {
+        let err = CoreError::Rpc(RpcError::ServerError {
+            code: BitcoinRpcErrorCode::Other(-32603),
+            message: "Internal error".to_string(),
+        });
+
+        assert!(matches!(
+            normalize_gettxoutproof_error(err),
+            Err(CoreError::Rpc(RpcError::ServerError {
+                code: BitcoinRpcErrorCode::Other(-32603),
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn is_retryable_classifies_transient_failures() {
+        let policy = RetryPolicy::default();
+        assert!(policy.is_retryable(&CoreError::Rpc(RpcError::HttpStatus {
+            status: 503,
+            body: String::new(),
+        })));
+        assert!(policy.is_retryable(&CoreError::Rpc(RpcError::ServerError {
+            code: BitcoinRpcErrorCode::InWarmup,
+            message: "Loading block index".to_string(),
+        })));
+    }
+
+    #[test]
+    fn is_retryable_rejects_permanent_failures() {
+        let policy = RetryPolicy::default();
+        assert!(!policy.is_retryable(&CoreError::Rpc(RpcError::HttpStatus {
+            status: 401,
+            body: String::new(),
+        })));
+        assert!(!policy.is_retryable(&CoreError::TxNotFound(txid_1())));
+    }
+
+    #[test]
+    fn is_retryable_always_retries_auth_rejections() {
+        let policy = RetryPolicy::default();
+        assert!(policy.is_retryable(&CoreError::Rpc(RpcError::AuthRejected { status: 401 })));
+        assert!(policy.is_retryable(&CoreError::Rpc(RpcError::AuthRejected { status: 403 })));
+    }
+
+    #[test]
+    fn is_retryable_always_retries_timeouts() {
+        let policy = RetryPolicy::default();
+        assert!(policy.is_retryable(&CoreError::Rpc(RpcError::Timeout {
+            phase: TimeoutPhase::Connect,
+        })));
+        assert!(policy.is_retryable(&CoreError::Rpc(RpcError::Timeout {
+            phase: TimeoutPhase::Response,
+        })));
+    }
+
+    #[test]
+    fn is_retryable_never_retries_invalid_address_or_key_even_if_configured() {
+        let policy = RetryPolicy {
+            retryable_rpc_codes: vec![BitcoinRpcErrorCode::InvalidAddressOrKey],
+            ..RetryPolicy::default()
+        };
+        assert!(!policy.is_retryable(&CoreError::Rpc(RpcError::ServerError {
+            code: BitcoinRpcErrorCode::InvalidAddressOrKey,
+            message: "not found".to_string(),
+        })));
+    }
+
+    #[test]
+    fn header_chain_cache_insert_is_bidirectional() {
+        let mut cache = HeaderChainCache::new(NonZeroUsize::new(10).expect("10 != 0"));
+        let hash = BlockHash::all_zeros();
+        cache.insert(hash, BlockHeight(42));
+
+        assert_eq!(cache.get_height(&hash), Some(BlockHeight(42)));
+        assert_eq!(cache.get_hash(42), Some(hash));
+    }
+
+    #[test]
+    fn header_chain_cache_evicts_height_entries_on_reorg() {
+        let mut cache = HeaderChainCache::new(NonZeroUsize::new(10).expect("10 != 0"));
+        cache.insert(BlockHash::all_zeros(), BlockHeight(10));
+        cache.note_best_height(10);
+
+        // A reorg drops the tip back to height 5: every cached height at or
+        // above the new best is no longer trustworthy.
+        cache.note_best_height(5);
+        assert_eq!(cache.get_hash(10), None);
+    }
+
+    #[test]
+    fn backoff_delay_is_full_jitter_up_to_the_exponential_delay_and_caps() {
+        let policy = RetryPolicy::default();
+        assert!(policy.backoff_delay(0) <= RETRY_BASE_BACKOFF);
+        assert!(policy.backoff_delay(10) <= RETRY_MAX_BACKOFF);
+    }
+
+    #[test]
+    fn backoff_delay_honors_a_custom_factor() {
+        // A factor of 1.0 disables growth: every attempt's upper bound is
+        // just `base_delay`, never growing toward `max_delay`.
+        let policy = RetryPolicy {
+            factor: 1.0,
+            ..RetryPolicy::default()
+        };
+        assert!(policy.backoff_delay(5) <= RETRY_BASE_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn with_retry_retries_transient_errors_then_succeeds() {
+        let client = HttpRpcClient::new(
+            vec![RpcEndpoint::new("http://127.0.0.1:8332")],
+            None,
+            None,
+            10,
+            30,
+            10,
+        )
+        .expect("client must construct");
+
+        let attempts = std::sync::atomic::AtomicU32::new(0);
+        let result: Result<u32, CoreError> = client
+            .with_retry("test", |_endpoint| {
+                let attempt = attempts.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    if attempt < 2 {
+                        Err(CoreError::Rpc(RpcError::HttpStatus {
+                            status: 503,
+                            body: String::new(),
+                        }))
+                    } else {
+                        Ok(42)
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(result.expect("must eventually succeed"), 42);
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn with_retry_picks_up_a_rotated_cookie_after_a_401() {
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time must be after unix epoch")
+            .as_nanos();
+        let cookie_path =
+            std::env::temp_dir().join(format!("cory-core-client-retry-cookie-{unique}.txt"));
+        fs::write(&cookie_path, "__cookie__:stale\n").expect("cookie file must be writable");
+
+        let client = HttpRpcClient::new(
+            vec![RpcEndpoint::new("http://127.0.0.1:8332").with_cookie_file(&cookie_path)],
+            None,
+            None,
+            10,
+            30,
+            10,
+        )
+        .expect("client must construct with a valid cookie file");
+
+        let attempts = std::sync::atomic::AtomicU32::new(0);
+        let result: Result<u32, CoreError> = client
+            .with_retry("test", |endpoint| {
+                let attempt = attempts.fetch_add(1, Ordering::Relaxed);
+                let auth = HttpRpcClient::current_auth(endpoint);
+                async move {
+                    let (_, password) = auth
+                        .expect("auth must resolve")
+                        .expect("cookie auth must be present");
+                    if attempt == 0 {
+                        assert_eq!(password, "stale", "first attempt must see the stale cookie");
+                        // Simulate bitcoind rewriting the cookie file on
+                        // restart right after it rejected the stale one.
+                        fs::write(&cookie_path, "__cookie__:fresh\n")
+                            .expect("cookie file must be rewritable");
+                        Err(CoreError::Rpc(RpcError::AuthRejected { status: 401 }))
+                    } else {
+                        assert_eq!(password, "fresh", "retry must re-read the rotated cookie");
+                        Ok(42)
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(result.expect("must succeed once the fresh cookie is read"), 42);
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+
+        let _ = fs::remove_file(cookie_path);
+    }
+
+    #[tokio::test]
+    async fn with_retry_does_not_retry_permanent_errors() {
+        let client = HttpRpcClient::new(
+            vec![RpcEndpoint::new("http://127.0.0.1:8332")],
+            None,
+            None,
+            10,
+            30,
+            10,
+        )
+        .expect("client must construct");
+
+        let attempts = std::sync::atomic::AtomicU32::new(0);
+        let result: Result<u32, CoreError> = client
+            .with_retry("test", |_endpoint| {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    Err(CoreError::Rpc(RpcError::HttpStatus {
+                        status: 401,
+                        body: String::new(),
+                    }))
+                }
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn with_retry_fails_over_to_the_next_endpoint() {
+        let client = HttpRpcClient::new(
+            vec![
+                RpcEndpoint::new("http://127.0.0.1:8332"),
+                RpcEndpoint::new("http://127.0.0.1:8333"),
+            ],
+            None,
+            None,
+            10,
+            30,
+            10,
+        )
+        .expect("client must construct");
+
+        let result: Result<u32, CoreError> = client
+            .with_retry("test", |endpoint| {
+                let connection = endpoint.endpoint.connection.clone();
+                async move {
+                    if connection.ends_with("8332") {
+                        Err(CoreError::Rpc(RpcError::HttpStatus {
+                            status: 503,
+                            body: String::new(),
+                        }))
+                    } else {
+                        Ok(42)
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(result.expect("must fail over to the healthy endpoint"), 42);
+    }
+
+    #[test]
+    fn pick_endpoint_index_is_sticky_until_a_failure() {
+        let client = HttpRpcClient::new(
+            vec![
+                RpcEndpoint::new("http://127.0.0.1:8332"),
+                RpcEndpoint::new("http://127.0.0.1:8333"),
+                RpcEndpoint::new("http://127.0.0.1:8334"),
+            ],
+            None,
+            None,
+            10,
+            30,
+            10,
+        )
+        .expect("client must construct");
+
+        let first = client.pick_endpoint_index(None);
+        for _ in 0..5 {
+            assert_eq!(
+                client.pick_endpoint_index(None),
+                first,
+                "steady-state calls must keep returning the same endpoint"
+            );
+        }
+
+        let next = client.pick_endpoint_index(Some(first));
+        assert_ne!(
+            next, first,
+            "a reported failure must rotate to another endpoint"
+        );
+        assert_eq!(
+            client.pick_endpoint_index(None),
+            next,
+            "the rotated endpoint becomes the new steady-state choice"
+        );
+    }
+
+    #[test]
+    fn batch_request_add_returns_submission_order_indices() {
+        let client = HttpRpcClient::new(
+            vec![RpcEndpoint::new("http://127.0.0.1:8332")],
+            None,
+            None,
+            10,
+            30,
+            10,
+        )
+        .expect("client must construct");
+
+        let mut batch = client.batch();
+        let first = batch.add(
+            "getrawtransaction",
+            vec![serde_json::json!(txid_1().to_string())],
+        );
+        let second = batch.add("gettxout", vec![serde_json::json!(txid_1().to_string())]);
+        assert_eq!((first, second), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn batch_request_execute_on_empty_batch_is_a_no_op() {
+        let client = HttpRpcClient::new(
+            vec![RpcEndpoint::new("http://127.0.0.1:8332")],
+            None,
+            None,
+            10,
+            30,
+            10,
+        )
+        .expect("client must construct");
+
+        let results = client
+            .batch()
+            .execute()
+            .await
+            .expect("an empty batch must never make a request");
+        assert!(results.is_empty());
+    }
 }
@@ -9,4 +9,6 @@ mod connection;
 mod parsing;
 mod protocol;
 
-pub use client::HttpRpcClient;
+pub use client::{BatchRequest, HttpRpcClient, RetryPolicy, RpcEndpoint};
+#[cfg(feature = "alloc")]
+pub use parsing::parse_amount_with_denomination;
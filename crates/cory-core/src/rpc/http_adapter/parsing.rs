@@ -1,17 +1,53 @@
-use bitcoin::{Amount, BlockHash, OutPoint, ScriptBuf, Txid};
+//! JSON parsers for Bitcoin Core RPC responses.
+//!
+//! Most of this module needs an allocator — it's decoding `serde_json`
+//! objects into owned `String`/`Vec` fields. Following rust-bitcoin's own
+//! split (`Amount` arithmetic works with no allocator; its string/float
+//! conversions don't), the `alloc` feature — on by default — gates the
+//! parts that build owned strings: [`parse_btc_amount`] and the
+//! `format!`-based detail in [`CoreError::InvalidTxData`]. With `alloc`
+//! disabled, [`parse_integer_required`], [`parse_integer_optional`], and
+//! [`parse_txid`] keep working (they only ever borrow their input), so a
+//! constrained signing context can still decode a `vin`'s `OutPoint`
+//! without pulling in allocation for the rest of the response.
+use bitcoin::address::NetworkUnchecked;
+use bitcoin::hashes::Hash;
+use bitcoin::{Address, Amount, BlockHash, Network, OutPoint, ScriptBuf, TxMerkleNode, Txid};
 
 use crate::enrich::classify_script;
 use crate::error::CoreError;
+#[cfg(feature = "alloc")]
+use crate::error::ParseDenominatedAmountError;
+use crate::rpc::{Block, BlockHeaderData, BlockTx, TxOutInfo};
 use crate::types::{TxInput, TxOutput};
 
+/// Derive the address Core would report for `script` on `network`, or
+/// `None` for scripts with no derivable address (e.g. `OP_RETURN`). Kept
+/// network-unchecked to match [`crate::types::TxOutput::address`] /
+/// [`crate::types::TxInput::address`].
+pub(super) fn address_from_script(
+    script: &ScriptBuf,
+    network: Network,
+) -> Option<Address<NetworkUnchecked>> {
+    Address::from_script(script.as_script(), network)
+        .ok()
+        .map(|addr| addr.as_unchecked().clone())
+}
+
 #[derive(serde::Deserialize)]
 struct TxOutResponse {
+    bestblock: String,
+    confirmations: u64,
     value: serde_json::Value,
     #[serde(rename = "scriptPubKey")]
     script_pubkey: serde_json::Value,
+    coinbase: bool,
 }
 
-pub(super) fn parse_gettxout_result(raw: serde_json::Value) -> Result<Option<TxOutput>, CoreError> {
+pub(super) fn parse_gettxout_result(
+    raw: serde_json::Value,
+    network: Network,
+) -> Result<Option<TxOutInfo>, CoreError> {
     if raw.is_null() {
         return Ok(None);
     }
@@ -22,24 +58,238 @@ pub(super) fn parse_gettxout_result(raw: serde_json::Value) -> Result<Option<TxO
     let value = parse_btc_amount(&response.value)?;
     let script_pub_key = parse_script_pubkey_from_json(&response.script_pubkey)?;
     let script_type = classify_script(script_pub_key.as_script());
-
-    Ok(Some(TxOutput {
-        value,
-        script_pub_key,
-        script_type,
+    let address = address_from_script(&script_pub_key, network);
+    let script_pub_key_type = response
+        .script_pubkey
+        .get("type")
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_owned);
+    let addresses = parse_script_pubkey_addresses(&response.script_pubkey);
+    let bestblock: BlockHash = response
+        .bestblock
+        .parse()
+        .map_err(|e| CoreError::InvalidTxData(format!("invalid gettxout bestblock: {e}")))?;
+
+    Ok(Some(TxOutInfo {
+        output: TxOutput {
+            value,
+            script_pub_key,
+            script_type,
+            address,
+        },
+        confirmations: response.confirmations,
+        bestblock,
+        coinbase: response.coinbase,
+        script_pub_key_type,
+        addresses,
     }))
 }
 
+/// Parse a verbose `getblockheader`/`getblock` result into a full consensus
+/// header plus its chain metadata.
+pub(super) fn parse_block_header(raw: serde_json::Value) -> Result<BlockHeaderData, CoreError> {
+    let version = bitcoin::block::Version::from_consensus(parse_integer_required::<i32, true>(
+        raw.get("version"),
+        "version",
+    )?);
+
+    let prev_blockhash = match raw.get("previousblockhash").and_then(serde_json::Value::as_str) {
+        Some(s) => s
+            .parse()
+            .map_err(|e| CoreError::InvalidTxData(format!("invalid previousblockhash: {e}")))?,
+        None => BlockHash::all_zeros(),
+    };
+
+    let merkle_root = parse_merkle_root(raw.get("merkleroot"))?;
+    let time = parse_integer_required::<u32, false>(raw.get("time"), "time")?;
+    let nonce = parse_integer_required::<u32, false>(raw.get("nonce"), "nonce")?;
+    let bits = parse_compact_target(raw.get("bits"))?;
+
+    let header = bitcoin::block::Header {
+        version,
+        prev_blockhash,
+        merkle_root,
+        time,
+        bits,
+        nonce,
+    };
+
+    let chainwork = parse_chainwork(raw.get("chainwork"))?;
+    let height = parse_integer_optional::<u32, false>(raw.get("height"));
+    let confirmations = parse_integer_optional::<u64, false>(raw.get("confirmations"));
+
+    Ok(BlockHeaderData {
+        header,
+        chainwork,
+        height,
+        confirmations,
+    })
+}
+
+/// Parse a `getblock verbose=2` response into a [`Block`]: the header (via
+/// [`parse_block_header`]) plus every transaction in `tx`, decoded via the
+/// same [`parse_vin`]/[`parse_vout`] used for a single `getrawtransaction`
+/// response. Transaction order is preserved from the RPC response.
+pub(super) fn parse_block(raw: serde_json::Value, network: Network) -> Result<Block, CoreError> {
+    let header = parse_block_header(raw.clone())?;
+
+    let tx_array = raw
+        .get("tx")
+        .and_then(serde_json::Value::as_array)
+        .ok_or_else(|| CoreError::InvalidTxData("missing tx array in block".into()))?;
+    let transactions = tx_array
+        .iter()
+        .map(|tx| parse_block_tx(tx, network))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Block {
+        header,
+        transactions,
+    })
+}
+
+/// Decode one entry of a `getblock verbose=2` response's `tx` array.
+fn parse_block_tx(raw: &serde_json::Value, network: Network) -> Result<BlockTx, CoreError> {
+    let txid = parse_txid(raw.get("txid"), "tx.txid")?;
+    let version = parse_integer_required::<i32, true>(raw.get("version"), "tx.version")?;
+    let locktime = parse_integer_required::<u32, false>(raw.get("locktime"), "tx.locktime")?;
+    let size = parse_integer_optional::<u64, false>(raw.get("size"));
+    let weight = parse_integer_optional::<u64, false>(raw.get("weight"));
+
+    let vin = raw
+        .get("vin")
+        .and_then(serde_json::Value::as_array)
+        .ok_or_else(|| CoreError::InvalidTxData("missing vin array in block tx".into()))?;
+    let vout = raw
+        .get("vout")
+        .and_then(serde_json::Value::as_array)
+        .ok_or_else(|| CoreError::InvalidTxData("missing vout array in block tx".into()))?;
+
+    Ok(BlockTx {
+        txid,
+        version,
+        locktime,
+        size,
+        weight,
+        inputs: parse_vin(vin, network)?,
+        outputs: parse_vout(vout, network)?,
+    })
+}
+
+/// Parse the `bits` field (a compact-target hex string, e.g.
+/// `"1d00ffff"`) into a [`bitcoin::pow::CompactTarget`].
+fn parse_compact_target(
+    value: Option<&serde_json::Value>,
+) -> Result<bitcoin::pow::CompactTarget, CoreError> {
+    let hex_str = value
+        .and_then(serde_json::Value::as_str)
+        .ok_or_else(|| CoreError::InvalidTxData("missing bits".into()))?;
+    let bits = u32::from_str_radix(hex_str, 16)
+        .map_err(|e| CoreError::InvalidTxData(format!("invalid bits `{hex_str}`: {e}")))?;
+    Ok(bitcoin::pow::CompactTarget::from_consensus(bits))
+}
+
+/// Parse Core's `chainwork` field — a 64-char big-endian hex string — into
+/// a 256-bit [`bitcoin::pow::Work`] value.
+fn parse_chainwork(value: Option<&serde_json::Value>) -> Result<bitcoin::pow::Work, CoreError> {
+    let hex_str = value
+        .and_then(serde_json::Value::as_str)
+        .ok_or_else(|| CoreError::InvalidTxData("missing chainwork".into()))?;
+
+    let mut digits = hex_str.trim().to_owned();
+    if digits.len() > 64 {
+        return Err(CoreError::InvalidTxData(format!(
+            "chainwork `{hex_str}` is longer than 32 bytes"
+        )));
+    }
+    // Core always emits a zero-padded 64-char string, but tolerate a
+    // shorter one by left-padding, matching how it reads as a big integer.
+    while digits.len() < 64 {
+        digits.insert(0, '0');
+    }
+
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        let start = i * 2;
+        *byte = u8::from_str_radix(&digits[start..start + 2], 16)
+            .map_err(|e| CoreError::InvalidTxData(format!("invalid chainwork `{hex_str}`: {e}")))?;
+    }
+
+    Ok(bitcoin::pow::Work::from_be_bytes(bytes))
+}
+
+/// Read the addresses Core inferred for a `scriptPubKey`, tolerating both
+/// the single `address` field Core 22+ reports and the deprecated plural
+/// `addresses` array older versions report, and returning an empty `Vec`
+/// rather than an error if neither is present (e.g. `OP_RETURN` outputs,
+/// or a bitcoind old enough to omit both).
+fn parse_script_pubkey_addresses(script_pubkey: &serde_json::Value) -> Vec<String> {
+    if let Some(address) = script_pubkey
+        .get("address")
+        .and_then(serde_json::Value::as_str)
+    {
+        return vec![address.to_owned()];
+    }
+
+    script_pubkey
+        .get("addresses")
+        .and_then(serde_json::Value::as_array)
+        .map(|addrs| {
+            addrs
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Error constructors shared by the parsers that stay available with the
+// `alloc` feature disabled (`parse_integer*`, `parse_txid`, and the
+// `OutPoint` decoding in `parse_vin`). Per-field detail needs a `format!`ed
+// `String`, so the `alloc` build keeps it and the no-`alloc` build falls
+// back to a fixed, static message via `CoreError::InvalidTxDataStatic`.
+#[cfg(feature = "alloc")]
+fn missing_field_error(field: &str) -> CoreError {
+    CoreError::InvalidTxData(format!("missing {field}"))
+}
+
+#[cfg(not(feature = "alloc"))]
+fn missing_field_error(_field: &str) -> CoreError {
+    CoreError::InvalidTxDataStatic("missing field")
+}
+
+#[cfg(feature = "alloc")]
+fn out_of_range_error(field: &str, n: impl std::fmt::Display) -> CoreError {
+    CoreError::InvalidTxData(format!("{field} out of range: {n}"))
+}
+
+#[cfg(not(feature = "alloc"))]
+fn out_of_range_error(_field: &str, _n: impl core::fmt::Display) -> CoreError {
+    CoreError::InvalidTxDataStatic("value out of range")
+}
+
+#[cfg(feature = "alloc")]
+fn invalid_field_error(field: &str, e: impl std::fmt::Display) -> CoreError {
+    CoreError::InvalidTxData(format!("invalid {field}: {e}"))
+}
+
+#[cfg(not(feature = "alloc"))]
+fn invalid_field_error(_field: &str, _e: impl core::fmt::Display) -> CoreError {
+    CoreError::InvalidTxDataStatic("invalid field")
+}
+
+/// Decode a `txid` field. Borrows the input `&str` and only ever builds a
+/// [`Txid`] out of it, so — unlike most of this module — it keeps working
+/// with the `alloc` feature disabled.
 pub(super) fn parse_txid(
     value: Option<&serde_json::Value>,
     field: &str,
 ) -> Result<Txid, CoreError> {
     let value = value
         .and_then(serde_json::Value::as_str)
-        .ok_or_else(|| CoreError::InvalidTxData(format!("missing {field}")))?;
-    value
-        .parse()
-        .map_err(|e| CoreError::InvalidTxData(format!("invalid {field}: {e}")))
+        .ok_or_else(|| missing_field_error(field))?;
+    value.parse().map_err(|e| invalid_field_error(field, e))
 }
 
 pub(super) fn parse_opt_block_hash(
@@ -54,6 +304,20 @@ pub(super) fn parse_opt_block_hash(
     }
 }
 
+pub(super) fn parse_merkle_root(
+    value: Option<&serde_json::Value>,
+) -> Result<TxMerkleNode, CoreError> {
+    let value = value
+        .and_then(serde_json::Value::as_str)
+        .ok_or_else(|| CoreError::InvalidTxData("missing merkleroot".into()))?;
+    value
+        .parse()
+        .map_err(|e| CoreError::InvalidTxData(format!("invalid merkleroot: {e}")))
+}
+
+/// Decode a required integer field. Only ever reads `value`/`field` by
+/// reference and returns a plain `T`, so — like [`parse_txid`] — it keeps
+/// working with the `alloc` feature disabled.
 pub(super) fn parse_integer_required<T, const SIGNED: bool>(
     value: Option<&serde_json::Value>,
     field: &str,
@@ -61,8 +325,7 @@ pub(super) fn parse_integer_required<T, const SIGNED: bool>(
 where
     T: TryFrom<i64> + TryFrom<u64>,
 {
-    parse_integer::<T, SIGNED, true>(value, field)?
-        .ok_or_else(|| CoreError::InvalidTxData(format!("missing {field}")))
+    parse_integer::<T, SIGNED, true>(value, field)?.ok_or_else(|| missing_field_error(field))
 }
 
 pub(super) fn parse_integer_optional<T, const SIGNED: bool>(
@@ -87,7 +350,7 @@ where
 {
     let missing_or_none = || {
         if REQUIRED {
-            Err(CoreError::InvalidTxData(format!("missing {field}")))
+            Err(missing_field_error(field))
         } else {
             Ok(None)
         }
@@ -103,18 +366,21 @@ where
         };
         T::try_from(n)
             .map(Some)
-            .map_err(|_| CoreError::InvalidTxData(format!("{field} out of range: {n}")))
+            .map_err(|_| out_of_range_error(field, n))
     } else {
         let Some(n) = value.as_u64() else {
             return missing_or_none();
         };
         T::try_from(n)
             .map(Some)
-            .map_err(|_| CoreError::InvalidTxData(format!("{field} out of range: {n}")))
+            .map_err(|_| out_of_range_error(field, n))
     }
 }
 
-pub(super) fn parse_vin(vin: &[serde_json::Value]) -> Result<Vec<TxInput>, CoreError> {
+pub(super) fn parse_vin(
+    vin: &[serde_json::Value],
+    network: Network,
+) -> Result<Vec<TxInput>, CoreError> {
     vin.iter()
         .map(|input| {
             let sequence = parse_integer_required::<u32, false>(input.get("sequence"), "sequence")?;
@@ -134,25 +400,36 @@ pub(super) fn parse_vin(vin: &[serde_json::Value]) -> Result<Vec<TxInput>, CoreE
                 .and_then(|p| p.get("value"))
                 .and_then(|v| parse_btc_amount(v).ok());
 
-            let script_type = input
+            let prevout_script = input
                 .get("prevout")
                 .and_then(|p| p.get("scriptPubKey"))
                 .and_then(|s| s.get("hex"))
                 .and_then(serde_json::Value::as_str)
-                .and_then(|hex_str| script_from_hex(hex_str).ok())
+                .and_then(|hex_str| script_from_hex(hex_str).ok());
+
+            let script_type = prevout_script
+                .as_ref()
                 .map(|script| classify_script(script.as_script()));
+            let address = prevout_script
+                .as_ref()
+                .and_then(|script| address_from_script(script, network));
 
             Ok(TxInput {
                 prevout,
                 sequence,
                 value: prevout_value,
                 script_type,
+                address,
+                unresolved_reason: None,
             })
         })
         .collect()
 }
 
-pub(super) fn parse_vout(vout: &[serde_json::Value]) -> Result<Vec<TxOutput>, CoreError> {
+pub(super) fn parse_vout(
+    vout: &[serde_json::Value],
+    network: Network,
+) -> Result<Vec<TxOutput>, CoreError> {
     vout.iter()
         .map(|output| {
             let value = parse_btc_amount(
@@ -166,6 +443,7 @@ pub(super) fn parse_vout(vout: &[serde_json::Value]) -> Result<Vec<TxOutput>, Co
                     CoreError::InvalidTxData("missing scriptPubKey in vout".into())
                 })?)?;
             let script_type = classify_script(script.as_script());
+            let address = address_from_script(&script, network);
             // We intentionally rely on array position for `vout` indexing.
             // TODO: Validate `vout.n` sequencing if we need stricter RPC checks.
 
@@ -173,6 +451,7 @@ pub(super) fn parse_vout(vout: &[serde_json::Value]) -> Result<Vec<TxOutput>, Co
                 value,
                 script_pub_key: script,
                 script_type,
+                address,
             })
         })
         .collect()
@@ -193,23 +472,182 @@ fn script_from_hex(hex_str: &str) -> Result<ScriptBuf, CoreError> {
 
 /// Parse a BTC amount from a JSON value.
 ///
-/// Number values are parsed via `Amount::from_float_in` to support scientific
-/// notation, while string values are parsed via `Amount::from_str_in`.
+/// Both branches go through [`Amount::from_str_in`] on a plain fixed-point
+/// decimal token rather than through `f64`, so values that aren't exactly
+/// representable in binary floating point (e.g. certain 8-decimal BTC
+/// amounts) don't get silently rounded to the wrong satoshi count. Numbers
+/// use `serde_json::Number`'s own decimal `Display` token, and any
+/// scientific-notation token (number or string, e.g. `6.6e-6`, `"1e-8"`) is
+/// normalized to fixed-point first via [`normalize_decimal_token`].
+///
+/// Requires the `alloc` feature: both the number-to-token and
+/// scientific-notation-normalization steps build an owned `String`. See
+/// [`parse_btc_amount`] below for the `alloc`-disabled stand-in.
+#[cfg(feature = "alloc")]
 pub(super) fn parse_btc_amount(value: &serde_json::Value) -> Result<Amount, CoreError> {
-    match value {
-        serde_json::Value::Number(n) => {
-            let parsed = n
-                .as_f64()
-                .ok_or_else(|| CoreError::InvalidTxData(format!("invalid BTC amount `{value}`")))?;
-            Amount::from_float_in(parsed, bitcoin::Denomination::Bitcoin)
-                .map_err(|e| CoreError::InvalidTxData(format!("invalid BTC amount `{value}`: {e}")))
+    let token = match value {
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => s.clone(),
+        _ => {
+            return Err(CoreError::InvalidTxData(format!(
+                "expected numeric BTC amount, got: {value}"
+            )))
         }
-        serde_json::Value::String(s) => Amount::from_str_in(s, bitcoin::Denomination::Bitcoin)
-            .map_err(|e| CoreError::InvalidTxData(format!("invalid BTC amount `{s}`: {e}"))),
-        _ => Err(CoreError::InvalidTxData(format!(
-            "expected numeric BTC amount, got: {value}"
-        ))),
+    };
+
+    let normalized = normalize_decimal_token(&token)
+        .ok_or_else(|| CoreError::InvalidTxData(format!("invalid BTC amount `{value}`")))?;
+
+    Amount::from_str_in(&normalized, bitcoin::Denomination::Bitcoin)
+        .map_err(|e| CoreError::InvalidTxData(format!("invalid BTC amount `{value}`: {e}")))
+}
+
+/// Parse an amount string carrying an explicit denomination suffix, e.g.
+/// `"0.5 BTC"`, `"500 mBTC"`, `"21 bits"`, or `"660 sat"` (the separating
+/// whitespace is optional and the suffix is matched case-insensitively).
+///
+/// Unlike [`parse_btc_amount`], which always assumes BTC, this lets
+/// downstream config/CLI layers feed user-entered amounts straight through
+/// without pre-normalizing to BTC first. Public so those layers can reach
+/// it as `cory_core::rpc::parse_amount_with_denomination`.
+#[cfg(feature = "alloc")]
+pub fn parse_amount_with_denomination(input: &str) -> Result<Amount, CoreError> {
+    let trimmed = input.trim();
+    let split_at = trimmed
+        .find(|c: char| c.is_ascii_alphabetic())
+        .ok_or_else(|| ParseDenominatedAmountError::UnknownDenomination(trimmed.to_owned()))?;
+    let (number, suffix) = trimmed.split_at(split_at);
+    let number = number.trim();
+    let suffix = suffix.trim();
+
+    let offset = denomination_offset(suffix)
+        .ok_or_else(|| ParseDenominatedAmountError::UnknownDenomination(suffix.to_owned()))?;
+
+    let sats = decimal_to_satoshis(number, offset)?;
+    Ok(Amount::from_sat(sats))
+}
+
+/// Decimal exponent from satoshi for each recognized denomination, e.g.
+/// `BTC` is `10^8` sat.
+#[cfg(feature = "alloc")]
+fn denomination_offset(suffix: &str) -> Option<u32> {
+    match suffix.to_ascii_lowercase().as_str() {
+        "btc" => Some(8),
+        "mbtc" => Some(5),
+        "ubtc" | "bits" => Some(2),
+        "sat" | "sats" => Some(0),
+        _ => None,
+    }
+}
+
+/// Convert a plain, non-negative decimal numeric token (no sign, no
+/// exponent) into a satoshi count, scaled by `10^offset` sats per unit.
+#[cfg(feature = "alloc")]
+fn decimal_to_satoshis(number: &str, offset: u32) -> Result<u64, CoreError> {
+    let (int_part, frac_part) = number.split_once('.').unwrap_or((number, ""));
+
+    if int_part.is_empty() && frac_part.is_empty()
+        || !int_part.chars().all(|c| c.is_ascii_digit())
+        || !frac_part.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(ParseDenominatedAmountError::InvalidNumber(number.to_owned()).into());
     }
+
+    let frac_len = frac_part.len() as u32;
+    if frac_len > offset {
+        return Err(ParseDenominatedAmountError::TooPrecise(
+            number.to_owned(),
+            format!("10^{offset}"),
+        )
+        .into());
+    }
+
+    let overflow = || ParseDenominatedAmountError::Overflow(number.to_owned());
+
+    let int_value: u64 = if int_part.is_empty() {
+        0
+    } else {
+        int_part.parse().map_err(|_| overflow())?
+    };
+    let frac_value: u64 = if frac_part.is_empty() {
+        0
+    } else {
+        frac_part.parse().map_err(|_| overflow())?
+    };
+
+    let scale = 10u64.checked_pow(offset).ok_or_else(overflow)?;
+    let frac_scale = 10u64.checked_pow(offset - frac_len).ok_or_else(overflow)?;
+
+    int_value
+        .checked_mul(scale)
+        .zip(frac_value.checked_mul(frac_scale))
+        .and_then(|(whole_sats, frac_sats)| whole_sats.checked_add(frac_sats))
+        .ok_or_else(|| overflow().into())
+}
+
+/// `alloc`-disabled stand-in for [`parse_btc_amount`] above.
+///
+/// There's no allocation-free way to reproduce the exact-decimal handling
+/// the `alloc` version does (it needs an owned, normalized token even for
+/// the plain-integer case), and falling back to `f64` would silently
+/// reintroduce the satoshi-rounding bug that version exists to avoid. So
+/// rather than parse amounts less correctly without `alloc`, this variant
+/// doesn't parse them at all — callers that need BTC amounts still need
+/// the `alloc` feature; only the `parse_integer`/`parse_txid`/`OutPoint`
+/// decoding above stays available either way.
+#[cfg(not(feature = "alloc"))]
+pub(super) fn parse_btc_amount(_value: &serde_json::Value) -> Result<Amount, CoreError> {
+    Err(CoreError::InvalidTxDataStatic(
+        "BTC amount parsing requires the `alloc` feature",
+    ))
+}
+
+/// Rewrite a scientific-notation decimal token (e.g. `"6.6e-6"`) into plain
+/// fixed-point (`"0.0000066"`) by shifting the decimal point by the
+/// exponent. Tokens with no `e`/`E` are returned unchanged. Returns `None`
+/// if the token isn't a well-formed decimal/scientific number.
+#[cfg(feature = "alloc")]
+fn normalize_decimal_token(token: &str) -> Option<String> {
+    let token = token.trim();
+    let Some(e_pos) = token.find(['e', 'E']) else {
+        return Some(token.to_owned());
+    };
+
+    let (mantissa, exp_str) = (&token[..e_pos], &token[e_pos + 1..]);
+    let exponent: i32 = exp_str.strip_prefix('+').unwrap_or(exp_str).parse().ok()?;
+
+    let negative = mantissa.starts_with('-');
+    let mantissa = mantissa.strip_prefix(['-', '+']).unwrap_or(mantissa);
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if int_part.is_empty() && frac_part.is_empty()
+        || !int_part.chars().all(|c| c.is_ascii_digit())
+        || !frac_part.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let digits = format!("{int_part}{frac_part}");
+    // Position of the decimal point, counted from the left of `digits`.
+    let point_pos = int_part.len() as i32 + exponent;
+
+    let mut out = String::new();
+    if negative {
+        out.push('-');
+    }
+    if point_pos <= 0 {
+        out.push_str("0.");
+        out.push_str(&"0".repeat((-point_pos) as usize));
+        out.push_str(&digits);
+    } else if (point_pos as usize) >= digits.len() {
+        out.push_str(&digits);
+        out.push_str(&"0".repeat(point_pos as usize - digits.len()));
+    } else {
+        let (whole, frac) = digits.split_at(point_pos as usize);
+        out.push_str(whole);
+        out.push('.');
+        out.push_str(frac);
+    }
+    Some(out)
 }
 
 #[cfg(test)]
@@ -262,13 +700,293 @@ mod tests {
     #[test]
     fn parse_btc_amount_scientific_string() {
         let val = serde_json::json!("1e-8");
-        assert!(parse_btc_amount(&val).is_err());
+        let amount = parse_btc_amount(&val).expect("should parse scientific string");
+        assert_eq!(amount, Amount::from_sat(1));
+    }
+
+    #[test]
+    fn parse_btc_amount_exact_no_float_rounding() {
+        // 20999999.9769 BTC isn't exactly representable in f64; routing it
+        // through `Amount::from_float_in` used to round the satoshi count.
+        let val = serde_json::json!(20999999.9769);
+        let amount = parse_btc_amount(&val).expect("should parse");
+        assert_eq!(amount, Amount::from_sat(2_099_999_997_690_000));
+    }
+
+    #[test]
+    fn parse_btc_amount_exact_string_no_float_rounding() {
+        let val = serde_json::json!("20999999.9769");
+        let amount = parse_btc_amount(&val).expect("should parse");
+        assert_eq!(amount, Amount::from_sat(2_099_999_997_690_000));
+    }
+
+    #[test]
+    fn parse_amount_with_denomination_btc() {
+        let amount = parse_amount_with_denomination("0.5 BTC").expect("should parse BTC");
+        assert_eq!(amount, Amount::from_btc(0.5).expect("valid"));
+    }
+
+    #[test]
+    fn parse_amount_with_denomination_mbtc() {
+        let amount = parse_amount_with_denomination("500 mBTC").expect("should parse mBTC");
+        assert_eq!(amount, Amount::from_btc(0.5).expect("valid"));
+    }
+
+    #[test]
+    fn parse_amount_with_denomination_bits() {
+        let amount = parse_amount_with_denomination("21 bits").expect("should parse bits");
+        assert_eq!(amount, Amount::from_sat(2100));
+    }
+
+    #[test]
+    fn parse_amount_with_denomination_sat_no_space_case_insensitive() {
+        let amount = parse_amount_with_denomination("660SAT").expect("should parse sat");
+        assert_eq!(amount, Amount::from_sat(660));
+    }
+
+    #[test]
+    fn parse_amount_with_denomination_unknown_unit() {
+        let err = parse_amount_with_denomination("5 moon").expect_err("must reject unknown unit");
+        assert!(matches!(
+            err,
+            CoreError::ParseDenominatedAmount(ParseDenominatedAmountError::UnknownDenomination(_))
+        ));
+    }
+
+    #[test]
+    fn parse_amount_with_denomination_too_precise() {
+        let err =
+            parse_amount_with_denomination("0.001 sat").expect_err("must reject fractional sat");
+        assert!(matches!(
+            err,
+            CoreError::ParseDenominatedAmount(ParseDenominatedAmountError::TooPrecise(_, _))
+        ));
+    }
+
+    #[test]
+    fn parse_amount_with_denomination_overflow() {
+        let err = parse_amount_with_denomination("100000000000000000000 BTC")
+            .expect_err("must reject an amount that overflows u64 satoshis");
+        assert!(matches!(
+            err,
+            CoreError::ParseDenominatedAmount(ParseDenominatedAmountError::Overflow(_))
+        ));
     }
 
     #[test]
     fn parse_gettxout_result_null() {
         let val = serde_json::Value::Null;
-        let result = parse_gettxout_result(val).expect("should parse null");
+        let result = parse_gettxout_result(val, Network::Bitcoin).expect("should parse null");
         assert!(result.is_none());
     }
+
+    #[test]
+    fn parse_gettxout_result_full() {
+        let val = serde_json::json!({
+            "bestblock": "0000000000000000000000000000000000000000000000000000000000000001",
+            "confirmations": 6,
+            "value": 0.5,
+            "scriptPubKey": {
+                "asm": "OP_DUP OP_HASH160 abcd OP_EQUALVERIFY OP_CHECKSIG",
+                "hex": "76a914000000000000000000000000000000000000000088ac",
+                "address": "1111111111111111111114oLvT2",
+                "type": "pubkeyhash",
+            },
+            "coinbase": false,
+        });
+        let info = parse_gettxout_result(val, Network::Bitcoin)
+            .expect("should parse")
+            .expect("must not be null");
+        assert_eq!(info.confirmations, 6);
+        assert!(!info.coinbase);
+        assert_eq!(info.script_pub_key_type.as_deref(), Some("pubkeyhash"));
+        assert_eq!(
+            info.addresses,
+            vec!["1111111111111111111114oLvT2".to_string()]
+        );
+        assert_eq!(
+            info.output
+                .checked_address(Network::Bitcoin)
+                .expect("p2pkh script must have an address")
+                .to_string(),
+            "1111111111111111111114oLvT2"
+        );
+    }
+
+    #[test]
+    fn parse_gettxout_result_tolerates_missing_address_and_type() {
+        let val = serde_json::json!({
+            "bestblock": "0000000000000000000000000000000000000000000000000000000000000001",
+            "confirmations": 0,
+            "value": 0.001,
+            "scriptPubKey": {
+                "hex": "6a00",
+            },
+            "coinbase": false,
+        });
+        let info = parse_gettxout_result(val, Network::Bitcoin)
+            .expect("should parse")
+            .expect("must not be null");
+        assert_eq!(info.script_pub_key_type, None);
+        assert!(info.addresses.is_empty());
+    }
+
+    #[test]
+    fn parse_gettxout_result_deprecated_addresses_array() {
+        let val = serde_json::json!({
+            "bestblock": "0000000000000000000000000000000000000000000000000000000000000001",
+            "confirmations": 1,
+            "value": 0.001,
+            "scriptPubKey": {
+                "hex": "a914000000000000000000000000000000000000000087",
+                "type": "scripthash",
+                "addresses": ["3P14159f73E4gFr7JterCCQh9QjiTjiZrG"],
+            },
+            "coinbase": false,
+        });
+        let info = parse_gettxout_result(val, Network::Bitcoin)
+            .expect("should parse")
+            .expect("must not be null");
+        assert_eq!(
+            info.addresses,
+            vec!["3P14159f73E4gFr7JterCCQh9QjiTjiZrG".to_string()]
+        );
+    }
+
+    // -- parse_block_header tests ----------------------------------------
+
+    fn sample_header_json() -> serde_json::Value {
+        serde_json::json!({
+            "hash": "000000000000000000007e2c723d0a8c2e6e8b1b1c4e9e3b2a1f0e0d0c0b0a09",
+            "confirmations": 6,
+            "height": 800000,
+            "version": 536870912,
+            "previousblockhash": "00000000000000000000593174ab8098ab67a1c9b1d45449f8f42df2f0000001",
+            "merkleroot": "04a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33",
+            "time": 1700000000,
+            "nonce": 123456789,
+            "bits": "1d00ffff",
+            "chainwork": "000000000000000000000000000000000000000000790e47e8d4f1a0b2c3d4e5",
+        })
+    }
+
+    #[test]
+    fn parse_block_header_full() {
+        let data = parse_block_header(sample_header_json()).expect("should parse");
+        assert_eq!(data.header.time, 1700000000);
+        assert_eq!(data.header.nonce, 123456789);
+        assert_eq!(data.header.version.to_consensus(), 536870912);
+        assert_eq!(data.height, Some(800000));
+        assert_eq!(data.confirmations, Some(6));
+    }
+
+    #[test]
+    fn parse_block_header_genesis_has_zero_prev_blockhash() {
+        let mut val = sample_header_json();
+        val.as_object_mut()
+            .expect("object")
+            .remove("previousblockhash");
+        let data = parse_block_header(val).expect("should parse");
+        assert_eq!(data.header.prev_blockhash, BlockHash::all_zeros());
+    }
+
+    #[test]
+    fn parse_block_header_decodes_chainwork_big_endian() {
+        let mut val = sample_header_json();
+        val["chainwork"] = serde_json::json!(
+            "0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        let data = parse_block_header(val).expect("should parse");
+        let mut expected = [0u8; 32];
+        expected[31] = 1;
+        assert_eq!(data.chainwork, bitcoin::pow::Work::from_be_bytes(expected));
+    }
+
+    #[test]
+    fn parse_block_header_tolerates_short_chainwork_hex() {
+        let mut val = sample_header_json();
+        val["chainwork"] = serde_json::json!("ff");
+        let data = parse_block_header(val).expect("should parse");
+        let mut expected = [0u8; 32];
+        expected[31] = 0xff;
+        assert_eq!(data.chainwork, bitcoin::pow::Work::from_be_bytes(expected));
+    }
+
+    #[test]
+    fn parse_block_header_rejects_missing_bits() {
+        let mut val = sample_header_json();
+        val.as_object_mut().expect("object").remove("bits");
+        assert!(parse_block_header(val).is_err());
+    }
+
+    // -- parse_block tests -------------------------------------------------
+
+    fn sample_block_json() -> serde_json::Value {
+        let mut val = sample_header_json();
+        val["tx"] = serde_json::json!([{
+            "txid": "0000000000000000000000000000000000000000000000000000000000000002",
+            "version": 2,
+            "locktime": 0,
+            "size": 225,
+            "weight": 561,
+            "vin": [{
+                "coinbase": "0341950a",
+                "sequence": 4294967295,
+            }],
+            "vout": [{
+                "value": 6.25,
+                "scriptPubKey": {
+                    "hex": "76a914000000000000000000000000000000000000000088ac",
+                },
+            }],
+        }]);
+        val
+    }
+
+    #[test]
+    fn parse_block_decodes_header_and_transactions() {
+        let block = parse_block(sample_block_json(), Network::Bitcoin).expect("should parse");
+
+        assert_eq!(block.header.height, Some(800000));
+        assert_eq!(block.transactions.len(), 1);
+
+        let tx = &block.transactions[0];
+        assert_eq!(tx.version, 2);
+        assert_eq!(tx.size, Some(225));
+        assert_eq!(tx.weight, Some(561));
+        assert!(tx.inputs[0].prevout.is_none(), "coinbase input");
+        assert_eq!(tx.outputs[0].value, Amount::from_btc(6.25).unwrap());
+        assert_eq!(
+            tx.outputs[0]
+                .checked_address(Network::Bitcoin)
+                .expect("p2pkh script must have an address")
+                .to_string(),
+            "1111111111111111111114oLvT2"
+        );
+    }
+
+    #[test]
+    fn parse_block_preserves_transaction_order() {
+        let mut val = sample_block_json();
+        let mut second = val["tx"][0].clone();
+        second["txid"] =
+            serde_json::json!("0000000000000000000000000000000000000000000000000000000000000003");
+        val["tx"] = serde_json::json!([val["tx"][0].clone(), second]);
+
+        let block = parse_block(val, Network::Bitcoin).expect("should parse");
+        assert_eq!(
+            block.transactions[0].txid.to_string(),
+            "0000000000000000000000000000000000000000000000000000000000000002"
+        );
+        assert_eq!(
+            block.transactions[1].txid.to_string(),
+            "0000000000000000000000000000000000000000000000000000000000000003"
+        );
+    }
+
+    #[test]
+    fn parse_block_rejects_missing_tx_array() {
+        let val = sample_header_json();
+        assert!(parse_block(val, Network::Bitcoin).is_err());
+    }
 }
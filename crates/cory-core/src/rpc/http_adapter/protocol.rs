@@ -1,4 +1,4 @@
-use crate::error::{CoreError, RpcError};
+use crate::error::{BitcoinRpcErrorCode, CoreError, RpcError};
 
 #[derive(serde::Serialize)]
 pub(super) struct JsonRpcRequest<'a> {
@@ -43,7 +43,7 @@ pub(super) fn parse_jsonrpc_error(err: serde_json::Value) -> CoreError {
 
     if let Ok(parsed) = serde_json::from_value::<JsonRpcError>(err.clone()) {
         CoreError::Rpc(RpcError::ServerError {
-            code: parsed.code,
+            code: BitcoinRpcErrorCode::from_code(parsed.code),
             message: parsed.message,
         })
     } else {
@@ -1,38 +1,171 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use bitcoin::hashes::Hash;
 use bitcoin::{BlockHash, OutPoint, Txid};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 use crate::error::CoreError;
 use crate::types::{ChainInfo, RawTxInfo, TxOutInfo};
 
-use super::BitcoinRpc;
+use super::{BitcoinRpc, Block, BlockHeaderInfo, BlockId, TxSpend};
 
 /// A mock Bitcoin RPC backend for testing. Returns canned transaction data
-/// from a `HashMap` populated via the builder pattern.
+/// from a `HashMap` populated via the builder pattern, and can optionally
+/// inject latency and faults so consumers' retry/timeout paths can be
+/// exercised deterministically.
 pub struct MockRpc {
     transactions: HashMap<Txid, RawTxInfo>,
-    chain_info: ChainInfo,
+    /// Canned `get_spend` answers, keyed by the spent outpoint, for tests
+    /// exercising [`crate::graph::build_descendants`].
+    spends: HashMap<OutPoint, TxSpend>,
+    /// Canned `get_block` answers, keyed by block hash, for tests exercising
+    /// [`crate::graph`]'s block-level prefetch.
+    blocks: HashMap<BlockHash, Block>,
+    chain_info: Mutex<ChainInfo>,
+    dynamic_confirmations: bool,
+    latency: Option<Duration>,
+    queued_errors: Mutex<VecDeque<CoreError>>,
+    errors_for_txid: Mutex<HashMap<Txid, CoreError>>,
+    failure_rate: Option<(f64, Mutex<StdRng>)>,
+    /// Per-txid `get_transaction` call counts, so tests can assert on
+    /// RPC fan-out (e.g. that a shared parent is only fetched once).
+    get_transaction_calls: Mutex<HashMap<Txid, u32>>,
+    /// Number of `get_block` calls so far, so tests can assert a block is
+    /// only ever fetched once regardless of how many of its transactions
+    /// are visited.
+    get_block_calls: Mutex<u32>,
 }
 
 impl MockRpc {
     pub fn builder() -> MockRpcBuilder {
         MockRpcBuilder {
             transactions: HashMap::new(),
+            spends: HashMap::new(),
+            blocks: HashMap::new(),
             chain_info: ChainInfo {
                 chain: "regtest".into(),
                 blocks: 100,
                 best_block_hash: BlockHash::all_zeros(),
                 pruned: false,
             },
+            dynamic_confirmations: false,
+            latency: None,
+            queued_errors: VecDeque::new(),
+            errors_for_txid: HashMap::new(),
+            failure_rate: None,
+        }
+    }
+
+    /// Advances the simulated chain tip by `n` blocks, synthesizing a new
+    /// best-block hash. Has no effect on stored transactions themselves —
+    /// in [`Self::dynamic_confirmations`] mode, their reported
+    /// confirmations are simply recomputed against the new tip.
+    pub fn advance_tip(&self, n: u64) {
+        let mut info = self.chain_info.lock().unwrap();
+        info.blocks += n;
+        info.best_block_hash = synthetic_block_hash(info.blocks);
+    }
+
+    /// Simulates a reorg: rewinds (or fast-forwards) the tip to `height`
+    /// with `new_best_hash` as the new best block. In
+    /// [`Self::dynamic_confirmations`] mode, transactions mined above the
+    /// new tip begin reporting as unconfirmed.
+    pub fn reorg_to(&self, height: u64, new_best_hash: BlockHash) {
+        let mut info = self.chain_info.lock().unwrap();
+        info.blocks = height;
+        info.best_block_hash = new_best_hash;
+    }
+
+    /// Sleeps for the configured latency (if any), then returns the next
+    /// applicable injected error: a per-txid error takes priority, followed
+    /// by the next queued error, followed by a random failure-rate roll.
+    /// Called at the top of every fault-injectable `BitcoinRpc` method.
+    async fn inject_fault(&self, txid: &Txid) -> Result<(), CoreError> {
+        if let Some(latency) = self.latency {
+            tokio::time::sleep(latency).await;
+        }
+        if let Some(err) = self.errors_for_txid.lock().unwrap().remove(txid) {
+            return Err(err);
         }
+        if let Some(err) = self.queued_errors.lock().unwrap().pop_front() {
+            return Err(err);
+        }
+        if let Some((rate, rng)) = &self.failure_rate {
+            if rng.lock().unwrap().gen::<f64>() < *rate {
+                return Err(CoreError::InvalidTxData(
+                    "mock rpc: injected random failure".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// In dynamic-confirmations mode, recomputes `confirmations` (and
+    /// clears `block_hash`, if the transaction is now past the tip) from
+    /// `tx.block_height` against the current chain tip. Leaves the
+    /// transaction untouched when dynamic confirmations are disabled.
+    fn apply_confirmation_view(&self, mut tx: RawTxInfo) -> RawTxInfo {
+        if !self.dynamic_confirmations {
+            return tx;
+        }
+        let tip = self.chain_info.lock().unwrap().blocks;
+        tx.confirmations = match tx.block_height {
+            Some(height) if u64::from(height) <= tip => Some(tip - u64::from(height) + 1),
+            _ => {
+                tx.block_hash = None;
+                Some(0)
+            }
+        };
+        tx
+    }
+
+    /// How many times `get_transaction` has been called for `txid` so far.
+    pub fn get_transaction_call_count(&self, txid: &Txid) -> u32 {
+        self.get_transaction_calls
+            .lock()
+            .unwrap()
+            .get(txid)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// How many times `get_block` has been called so far, across all block
+    /// hashes.
+    pub fn get_block_call_count(&self) -> u32 {
+        *self.get_block_calls.lock().unwrap()
     }
 }
 
+/// Derives a deterministic, distinguishable `BlockHash` for a simulated
+/// tip height, so tests can assert a reorg actually changed the best hash.
+fn synthetic_block_hash(height: u64) -> BlockHash {
+    let mut bytes = [0u8; 32];
+    bytes[..8].copy_from_slice(&height.to_le_bytes());
+    BlockHash::from_byte_array(bytes)
+}
+
+/// Reverses [`synthetic_block_hash`]: recovers the height a synthetic hash
+/// was derived from, so [`MockRpc::get_block_header`] can answer a
+/// `BlockId::Hash` lookup for any hash this mock itself produced.
+fn synthetic_block_height(hash: BlockHash) -> u64 {
+    let bytes = hash.as_byte_array();
+    u64::from_le_bytes(bytes[..8].try_into().expect("8-byte slice"))
+}
+
 pub struct MockRpcBuilder {
     transactions: HashMap<Txid, RawTxInfo>,
+    spends: HashMap<OutPoint, TxSpend>,
+    blocks: HashMap<BlockHash, Block>,
     chain_info: ChainInfo,
+    dynamic_confirmations: bool,
+    latency: Option<Duration>,
+    queued_errors: VecDeque<CoreError>,
+    errors_for_txid: HashMap<Txid, CoreError>,
+    failure_rate: Option<(f64, u64)>,
 }
 
 impl MockRpcBuilder {
@@ -41,15 +174,90 @@ impl MockRpcBuilder {
         self
     }
 
+    /// Registers `outpoint` as spent by `spending_txid`'s input at
+    /// `input_index`, for `get_spend`/`get_spends` — an outpoint with no
+    /// registration here is reported unspent.
+    pub fn with_spend(mut self, outpoint: OutPoint, spending_txid: Txid, input_index: u32) -> Self {
+        self.spends.insert(
+            outpoint,
+            TxSpend {
+                spending_txid,
+                input_index,
+            },
+        );
+        self
+    }
+
+    /// Registers `block` as the `get_block` answer for `hash` — a block not
+    /// registered here fails with [`CoreError::InvalidTxData`], mirroring a
+    /// real backend that has no data for an unknown hash.
+    pub fn with_block(mut self, hash: BlockHash, block: Block) -> Self {
+        self.blocks.insert(hash, block);
+        self
+    }
+
     pub fn with_chain_info(mut self, info: ChainInfo) -> Self {
         self.chain_info = info;
         self
     }
 
+    /// Derives each response's `confirmations` from `chain_info.blocks -
+    /// block_height + 1` instead of the static value stored on each
+    /// `RawTxInfo`, so [`MockRpc::advance_tip`] and [`MockRpc::reorg_to`]
+    /// actually move the confirmation count.
+    pub fn with_dynamic_confirmations(mut self) -> Self {
+        self.dynamic_confirmations = true;
+        self
+    }
+
+    /// Queues `error` to be returned, once, the next time `txid` is
+    /// requested via `get_transaction`, `get_tx_out`, or `get_spend` (for
+    /// an outpoint of `txid`) — after which
+    /// lookups for that txid succeed normally. Lets tests exercise a
+    /// single failed attempt followed by a successful retry.
+    pub fn with_error_for(mut self, txid: Txid, error: CoreError) -> Self {
+        self.errors_for_txid.insert(txid, error);
+        self
+    }
+
+    /// Queues `error` to be returned, once, on the next fault-injectable
+    /// call regardless of which txid it targets. Multiple calls queue
+    /// multiple errors, consumed in FIFO order.
+    pub fn with_queued_error(mut self, error: CoreError) -> Self {
+        self.queued_errors.push_back(error);
+        self
+    }
+
+    /// Injects an async delay (via `tokio::time::sleep`) before every
+    /// response, to exercise consumer timeout handling.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Fails a `rate` fraction of calls (0.0-1.0) with a seeded PRNG, so
+    /// runs are reproducible across test executions. Checked after queued
+    /// and per-txid errors are exhausted.
+    pub fn with_failure_rate(mut self, rate: f64, seed: u64) -> Self {
+        self.failure_rate = Some((rate, seed));
+        self
+    }
+
     pub fn build(self) -> MockRpc {
         MockRpc {
             transactions: self.transactions,
-            chain_info: self.chain_info,
+            spends: self.spends,
+            blocks: self.blocks,
+            chain_info: Mutex::new(self.chain_info),
+            dynamic_confirmations: self.dynamic_confirmations,
+            latency: self.latency,
+            queued_errors: Mutex::new(self.queued_errors),
+            errors_for_txid: Mutex::new(self.errors_for_txid),
+            failure_rate: self
+                .failure_rate
+                .map(|(rate, seed)| (rate, Mutex::new(StdRng::seed_from_u64(seed)))),
+            get_transaction_calls: Mutex::new(HashMap::new()),
+            get_block_calls: Mutex::new(0),
         }
     }
 }
@@ -57,16 +265,30 @@ impl MockRpcBuilder {
 #[async_trait]
 impl BitcoinRpc for MockRpc {
     async fn get_transaction(&self, txid: &Txid) -> Result<RawTxInfo, CoreError> {
+        *self
+            .get_transaction_calls
+            .lock()
+            .unwrap()
+            .entry(*txid)
+            .or_insert(0) += 1;
+        self.inject_fault(txid).await?;
         self.transactions
             .get(txid)
             .cloned()
+            .map(|tx| self.apply_confirmation_view(tx))
             .ok_or(CoreError::TxNotFound(*txid))
     }
 
-    async fn get_tx_out(&self, txid: &Txid, vout: u32) -> Result<Option<TxOutInfo>, CoreError> {
+    async fn get_tx_out(
+        &self,
+        txid: &Txid,
+        vout: u32,
+        _include_mempool: bool,
+    ) -> Result<Option<TxOutInfo>, CoreError> {
+        self.inject_fault(txid).await?;
         // Look up the transaction and return the output at the given index.
-        let tx = match self.transactions.get(txid) {
-            Some(tx) => tx,
+        let tx = match self.transactions.get(txid).cloned() {
+            Some(tx) => self.apply_confirmation_view(tx),
             None => return Ok(None),
         };
         let output = match tx.outputs.get(vout as usize) {
@@ -84,16 +306,74 @@ impl BitcoinRpc for MockRpc {
     async fn get_tx_outs(
         &self,
         outpoints: &[OutPoint],
+        include_mempool: bool,
     ) -> Result<Vec<Option<TxOutInfo>>, CoreError> {
         let mut results = Vec::with_capacity(outpoints.len());
         for outpoint in outpoints {
-            results.push(self.get_tx_out(&outpoint.txid, outpoint.vout).await?);
+            results.push(
+                self.get_tx_out(&outpoint.txid, outpoint.vout, include_mempool)
+                    .await?,
+            );
+        }
+        Ok(results)
+    }
+
+    async fn get_spend(&self, outpoint: OutPoint) -> Result<Option<TxSpend>, CoreError> {
+        self.inject_fault(&outpoint.txid).await?;
+        Ok(self.spends.get(&outpoint).copied())
+    }
+
+    async fn get_spends(&self, outpoints: &[OutPoint]) -> Result<Vec<Option<TxSpend>>, CoreError> {
+        let mut results = Vec::with_capacity(outpoints.len());
+        for outpoint in outpoints {
+            results.push(self.get_spend(*outpoint).await?);
         }
         Ok(results)
     }
 
     async fn get_blockchain_info(&self) -> Result<ChainInfo, CoreError> {
-        Ok(self.chain_info.clone())
+        Ok(self.chain_info.lock().unwrap().clone())
+    }
+
+    async fn get_block_hash(&self, id: BlockId) -> Result<BlockHash, CoreError> {
+        let height = match id {
+            BlockId::Hash(hash) => return Ok(hash),
+            BlockId::Earliest => 0,
+            BlockId::Latest => self.chain_info.lock().unwrap().blocks,
+            BlockId::Height(height) => u64::from(height),
+        };
+        Ok(synthetic_block_hash(height))
+    }
+
+    async fn get_block(&self, id: BlockId) -> Result<Block, CoreError> {
+        *self.get_block_calls.lock().unwrap() += 1;
+        let hash = self.get_block_hash(id).await?;
+        self.blocks.get(&hash).cloned().ok_or_else(|| {
+            CoreError::InvalidTxData(format!("mock rpc: no block registered for {hash}"))
+        })
+    }
+
+    async fn get_txout_proof(&self, _txids: &[Txid]) -> Result<Option<String>, CoreError> {
+        Err(CoreError::InvalidTxData(
+            "mock rpc: get_txout_proof is not implemented".to_string(),
+        ))
+    }
+
+    /// Resolves `id` to a height the same way [`Self::get_block_hash`]
+    /// does, with `merkle_root` always zeroed — no test using this mock
+    /// exercises Merkle-proof verification against it.
+    async fn get_block_header(&self, id: BlockId) -> Result<BlockHeaderInfo, CoreError> {
+        let height = match id {
+            BlockId::Earliest => 0,
+            BlockId::Latest => self.chain_info.lock().unwrap().blocks,
+            BlockId::Height(height) => u64::from(height),
+            BlockId::Hash(hash) => synthetic_block_height(hash),
+        };
+        Ok(BlockHeaderInfo {
+            hash: self.get_block_hash(id).await?,
+            height: height as u32,
+            merkle_root: bitcoin::TxMerkleNode::all_zeros(),
+        })
     }
 }
 
@@ -180,7 +460,7 @@ mod tests {
             OutPoint::new(txid, 1),
             OutPoint::new(txid, 99), // does not exist
         ];
-        let results = rpc.get_tx_outs(&outpoints).await.unwrap();
+        let results = rpc.get_tx_outs(&outpoints, true).await.unwrap();
         assert_eq!(results.len(), 3);
         assert!(results[0].is_some());
         assert_eq!(results[0].as_ref().unwrap().value, Amount::from_sat(5000));
@@ -188,4 +468,185 @@ mod tests {
         assert_eq!(results[1].as_ref().unwrap().value, Amount::from_sat(3000));
         assert!(results[2].is_none());
     }
+
+    #[tokio::test]
+    async fn with_error_for_fails_once_then_succeeds() {
+        let txid = txid_from_byte(2);
+        let tx = make_tx(txid, vec![], vec![simple_output(1000)]);
+        let rpc = MockRpc::builder()
+            .with_tx(tx)
+            .with_error_for(txid, CoreError::TxNotFound(txid))
+            .build();
+
+        let first = rpc.get_transaction(&txid).await;
+        assert!(matches!(first, Err(CoreError::TxNotFound(t)) if t == txid));
+
+        let second = rpc
+            .get_transaction(&txid)
+            .await
+            .expect("retry should succeed");
+        assert_eq!(second.txid, txid);
+    }
+
+    #[tokio::test]
+    async fn with_queued_error_is_consumed_in_fifo_order() {
+        let txid = txid_from_byte(3);
+        let tx = make_tx(txid, vec![], vec![simple_output(1000)]);
+        let rpc = MockRpc::builder()
+            .with_tx(tx)
+            .with_queued_error(CoreError::InvalidTxData("first".into()))
+            .with_queued_error(CoreError::InvalidTxData("second".into()))
+            .build();
+
+        let first = rpc.get_transaction(&txid).await;
+        assert!(matches!(first, Err(CoreError::InvalidTxData(m)) if m == "first"));
+        let second = rpc.get_transaction(&txid).await;
+        assert!(matches!(second, Err(CoreError::InvalidTxData(m)) if m == "second"));
+        let third = rpc
+            .get_transaction(&txid)
+            .await
+            .expect("queue must be drained");
+        assert_eq!(third.txid, txid);
+    }
+
+    #[tokio::test]
+    async fn get_tx_outs_propagates_first_failure() {
+        let txid = txid_from_byte(4);
+        let mut out0 = simple_output(5000);
+        out0.n = 0;
+        let tx = make_tx(txid, vec![], vec![out0]);
+        let rpc = MockRpc::builder()
+            .with_tx(tx)
+            .with_error_for(txid, CoreError::InvalidTxData("boom".into()))
+            .build();
+
+        let outpoints = vec![OutPoint::new(txid, 0), OutPoint::new(txid, 0)];
+        let err = rpc
+            .get_tx_outs(&outpoints, true)
+            .await
+            .expect_err("first outpoint's injected error must propagate");
+        assert!(matches!(err, CoreError::InvalidTxData(m) if m == "boom"));
+    }
+
+    #[tokio::test]
+    async fn with_failure_rate_is_reproducible_for_a_fixed_seed() {
+        let txid = txid_from_byte(5);
+        let tx = make_tx(txid, vec![], vec![simple_output(1000)]);
+
+        let run = || async {
+            let rpc = MockRpc::builder()
+                .with_tx(tx.clone())
+                .with_failure_rate(0.5, 42)
+                .build();
+            let mut outcomes = Vec::new();
+            for _ in 0..10 {
+                outcomes.push(rpc.get_transaction(&txid).await.is_ok());
+            }
+            outcomes
+        };
+
+        assert_eq!(run().await, run().await);
+    }
+
+    #[tokio::test]
+    async fn with_latency_delays_the_response() {
+        let txid = txid_from_byte(6);
+        let tx = make_tx(txid, vec![], vec![simple_output(1000)]);
+        let rpc = MockRpc::builder()
+            .with_tx(tx)
+            .with_latency(Duration::from_millis(20))
+            .build();
+
+        let start = std::time::Instant::now();
+        rpc.get_transaction(&txid)
+            .await
+            .expect("lookup must succeed");
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    fn tx_at_height(txid: Txid, height: u32) -> RawTxInfo {
+        let mut tx = make_tx(txid, vec![], vec![simple_output(1000)]);
+        tx.block_height = Some(height);
+        tx.block_hash = Some(BlockHash::all_zeros());
+        tx.confirmations = Some(1); // stale static value; dynamic mode must ignore it
+        tx
+    }
+
+    #[tokio::test]
+    async fn dynamic_confirmations_derive_from_tip_and_height() {
+        let txid = txid_from_byte(10);
+        let rpc = MockRpc::builder()
+            .with_tx(tx_at_height(txid, 95))
+            .with_chain_info(ChainInfo {
+                chain: "regtest".into(),
+                blocks: 100,
+                best_block_hash: BlockHash::all_zeros(),
+                pruned: false,
+            })
+            .with_dynamic_confirmations()
+            .build();
+
+        let tx = rpc
+            .get_transaction(&txid)
+            .await
+            .expect("lookup must succeed");
+        assert_eq!(tx.confirmations, Some(6));
+    }
+
+    #[tokio::test]
+    async fn advance_tip_increases_confirmations() {
+        let txid = txid_from_byte(11);
+        let rpc = MockRpc::builder()
+            .with_tx(tx_at_height(txid, 100))
+            .with_chain_info(ChainInfo {
+                chain: "regtest".into(),
+                blocks: 100,
+                best_block_hash: BlockHash::all_zeros(),
+                pruned: false,
+            })
+            .with_dynamic_confirmations()
+            .build();
+
+        let before = rpc.get_transaction(&txid).await.unwrap();
+        assert_eq!(before.confirmations, Some(1));
+
+        rpc.advance_tip(5);
+        let after = rpc.get_transaction(&txid).await.unwrap();
+        assert_eq!(after.confirmations, Some(6));
+
+        let info = rpc.get_blockchain_info().await.unwrap();
+        assert_eq!(info.blocks, 105);
+    }
+
+    #[tokio::test]
+    async fn reorg_to_unconfirms_transactions_above_new_tip() {
+        let txid = txid_from_byte(12);
+        let rpc = MockRpc::builder()
+            .with_tx(tx_at_height(txid, 100))
+            .with_chain_info(ChainInfo {
+                chain: "regtest".into(),
+                blocks: 100,
+                best_block_hash: BlockHash::all_zeros(),
+                pruned: false,
+            })
+            .with_dynamic_confirmations()
+            .build();
+
+        let before = rpc.get_transaction(&txid).await.unwrap();
+        assert_eq!(before.confirmations, Some(1));
+        assert!(before.block_hash.is_some());
+
+        let mut new_hash_bytes = [0u8; 32];
+        new_hash_bytes[0] = 0xAB;
+        let new_best_hash = BlockHash::from_byte_array(new_hash_bytes);
+        rpc.reorg_to(90, new_best_hash);
+
+        let after = rpc.get_transaction(&txid).await.unwrap();
+        assert_eq!(after.confirmations, Some(0));
+        assert!(after.block_hash.is_none());
+
+        let info = rpc.get_blockchain_info().await.unwrap();
+        assert_eq!(info.blocks, 90);
+        assert_eq!(info.best_block_hash, new_best_hash);
+    }
 }
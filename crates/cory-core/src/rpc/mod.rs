@@ -1,21 +1,32 @@
 //! Bitcoin Core RPC abstraction layer.
 //!
-//! Defines the [`BitcoinRpc`] trait and provides an HTTP JSON-RPC
-//! implementation ([`HttpRpcClient`]) plus a test mock (`mock::MockRpc`).
+//! Defines the [`BitcoinRpc`] trait and provides two implementations — an
+//! HTTP JSON-RPC client for a local Bitcoin Core node ([`HttpRpcClient`])
+//! and a REST client for Esplora-style block explorers ([`EsploraClient`]),
+//! for users without a full node — plus a test mock (`mock::MockRpc`).
 
+mod counting;
+mod esplora_adapter;
 mod http_adapter;
 #[cfg(test)]
 pub mod mock;
 pub mod types;
 
-pub use http_adapter::HttpRpcClient;
-pub use types::ChainInfo;
+pub use counting::CountingRpc;
+pub use esplora_adapter::EsploraClient;
+pub use http_adapter::{BatchRequest, HttpRpcClient, RetryPolicy, RpcEndpoint};
+#[cfg(feature = "alloc")]
+pub use http_adapter::parse_amount_with_denomination;
+pub use types::{
+    Block, BlockHeaderData, BlockHeaderInfo, BlockId, BlockTx, ChainInfo, EstimateMode, TxOutInfo,
+    TxSpend,
+};
 
 use async_trait::async_trait;
-use bitcoin::{OutPoint, Txid};
+use bitcoin::{BlockHash, OutPoint, Txid};
 
 use crate::error::CoreError;
-use crate::types::{TxNode, TxOutput};
+use crate::types::{TxInclusionProof, TxNode};
 
 /// Minimal trait covering the Bitcoin Core RPC methods that Cory needs.
 ///
@@ -36,23 +47,129 @@ pub trait BitcoinRpc: Send + Sync {
         Ok(results)
     }
 
-    /// Fetch a specific unspent output (for prevout resolution).
-    /// Returns `None` if the output has been spent or does not exist.
-    async fn get_tx_out(&self, txid: &Txid, vout: u32) -> Result<Option<TxOutput>, CoreError>;
+    /// Fetch a specific unspent output (for prevout resolution), along with
+    /// the confirmation/coinbase/scriptPubKey context `gettxout` reports
+    /// alongside it. Returns `None` if the output has been spent or does
+    /// not exist.
+    ///
+    /// `include_mempool` controls whether an output that's only in the
+    /// mempool (not yet confirmed) counts as unspent, matching
+    /// `gettxout`'s own third parameter — set it so wallet-UTXO inspection
+    /// tooling can see an output the moment it's broadcast, or unset it to
+    /// only ever see confirmed state.
+    async fn get_tx_out(
+        &self,
+        txid: &Txid,
+        vout: u32,
+        include_mempool: bool,
+    ) -> Result<Option<TxOutInfo>, CoreError>;
 
     /// Fetch many outpoints efficiently. Implementations may batch these
     /// requests into a single HTTP JSON-RPC call.
     async fn get_tx_outs(
         &self,
         outpoints: &[OutPoint],
-    ) -> Result<Vec<Option<TxOutput>>, CoreError> {
+        include_mempool: bool,
+    ) -> Result<Vec<Option<TxOutInfo>>, CoreError> {
         let mut results = Vec::with_capacity(outpoints.len());
         for outpoint in outpoints {
-            results.push(self.get_tx_out(&outpoint.txid, outpoint.vout).await?);
+            results.push(
+                self.get_tx_out(&outpoint.txid, outpoint.vout, include_mempool)
+                    .await?,
+            );
+        }
+        Ok(results)
+    }
+
+    /// Look up whoever spends `outpoint`, for forward (descendant)
+    /// traversal in [`crate::graph::build_descendants`]. Returns `None` if
+    /// the output is unspent (or doesn't exist).
+    ///
+    /// Unlike prevout resolution, this has no generic fallback: Core's RPC
+    /// has no "who spent this" lookup without a third-party index, so
+    /// implementations that can't answer this (e.g. [`HttpRpcClient`])
+    /// should return [`CoreError::InvalidTxData`] explaining that
+    /// descendant tracing needs an address/outpoint-index backend.
+    async fn get_spend(&self, outpoint: OutPoint) -> Result<Option<TxSpend>, CoreError>;
+
+    /// Fetch many outpoints' spends efficiently. Implementations may batch
+    /// these requests into a single HTTP call.
+    async fn get_spends(&self, outpoints: &[OutPoint]) -> Result<Vec<Option<TxSpend>>, CoreError> {
+        let mut results = Vec::with_capacity(outpoints.len());
+        for outpoint in outpoints {
+            results.push(self.get_spend(*outpoint).await?);
         }
         Ok(results)
     }
 
     /// Fetch basic chain info (network, block count, pruning status).
     async fn get_blockchain_info(&self) -> Result<ChainInfo, CoreError>;
+
+    /// Fetch and verify a transaction's Merkle-inclusion proof, so the UI
+    /// can prove a transaction is really in a block without trusting the
+    /// node's self-reported `confirmations` count.
+    ///
+    /// Returns `None` for unconfirmed transactions, since no proof exists
+    /// for a transaction that isn't yet in a block. The default
+    /// implementation combines [`Self::get_txout_proof`] and
+    /// [`Self::get_block_header`]; implementations normally only need to
+    /// provide those two lower-level methods.
+    async fn get_tx_inclusion_proof(
+        &self,
+        txid: &Txid,
+        block_hash: &BlockHash,
+    ) -> Result<Option<TxInclusionProof>, CoreError> {
+        let Some(proof_hex) = self.get_txout_proof(std::slice::from_ref(txid)).await? else {
+            return Ok(None);
+        };
+        let header = self.get_block_header(BlockId::Hash(*block_hash)).await?;
+        Ok(Some(crate::proof::verify_tx_inclusion(
+            *txid, header, &proof_hex,
+        )?))
+    }
+
+    /// Fetch the raw hex-encoded Merkle proof that `txids` are included in
+    /// a block, as returned by `gettxoutproof`. Returns `None` if any of
+    /// the given transactions is unconfirmed — no proof exists yet.
+    async fn get_txout_proof(&self, txids: &[Txid]) -> Result<Option<String>, CoreError>;
+
+    /// Fetch a block header's height and merkle root, used to
+    /// independently cross-check Merkle-inclusion proofs. Accepts any
+    /// [`BlockId`], not just a bare hash, so callers can look up "the
+    /// current tip's header" or "the header at height N" in one call
+    /// instead of resolving the hash themselves first.
+    async fn get_block_header(&self, id: BlockId) -> Result<BlockHeaderInfo, CoreError>;
+
+    /// Resolve a [`BlockId`] to a concrete block hash: `Hash` is returned
+    /// unchanged, `Latest`/`Earliest` resolve the chain tip/genesis, and
+    /// `Height(n)` resolves the hash that was canonical for height `n` as
+    /// of this call.
+    async fn get_block_hash(&self, id: BlockId) -> Result<BlockHash, CoreError>;
+
+    /// Fetch a whole block in verbose form (`getblock` verbosity=2): the
+    /// header plus every contained transaction, decoded the same way as
+    /// [`Self::get_transaction`]. Lets a caller that needs several
+    /// transactions known to share a block fetch them all in one round
+    /// trip instead of one `getrawtransaction` per transaction — see
+    /// [`crate::graph::build_ancestry`]'s block-level prefetch, which uses
+    /// this to collapse dense, same-block ancestry chains.
+    ///
+    /// Implementations that have no cheap way to serve a whole block (e.g.
+    /// an Esplora-style REST backend without a bulk block-transactions
+    /// endpoint) should return [`CoreError::InvalidTxData`] explaining
+    /// that whole-block fetches aren't supported; callers treat this as
+    /// "prefetch unavailable" and fall back to per-transaction fetches.
+    async fn get_block(&self, id: BlockId) -> Result<Block, CoreError>;
+
+    /// Estimate the feerate, in sat/vB, needed to confirm within
+    /// `conf_target` blocks, so a transaction's historical fee can be
+    /// contextualized against current network conditions.
+    ///
+    /// Returns `None` if the backend doesn't have enough data to produce
+    /// an estimate yet (e.g. a freshly started node).
+    async fn estimate_smart_fee(
+        &self,
+        conf_target: u16,
+        mode: EstimateMode,
+    ) -> Result<Option<f64>, CoreError>;
 }
@@ -3,7 +3,8 @@
 //! representations — not the enriched domain types exposed by the
 //! public API.
 
-use bitcoin::{Amount, BlockHash, ScriptBuf, Txid};
+use bitcoin::{Amount, BlockHash, ScriptBuf, TxMerkleNode, Txid};
+use serde::{Deserialize, Serialize};
 
 use crate::types::BlockHeight;
 
@@ -50,15 +51,6 @@ pub struct RawOutputInfo {
 // UTXO and Chain Info
 // ==============================================================================
 
-/// UTXO information from `gettxout`.
-#[derive(Debug, Clone)]
-pub struct TxOutInfo {
-    pub value: Amount,
-    pub script_pub_key: ScriptBuf,
-    pub confirmations: u64,
-    pub coinbase: bool,
-}
-
 /// Basic chain information from `getblockchaininfo`.
 #[derive(Debug, Clone)]
 pub struct ChainInfo {
@@ -67,3 +59,164 @@ pub struct ChainInfo {
     pub best_block_hash: BlockHash,
     pub pruned: bool,
 }
+
+/// Block header metadata from `getblockheader`, used to independently
+/// verify Merkle-inclusion proofs.
+#[derive(Debug, Clone)]
+pub struct BlockHeaderInfo {
+    pub hash: BlockHash,
+    pub height: u32,
+    pub merkle_root: TxMerkleNode,
+}
+
+/// Full consensus header decoded from a verbose `getblockheader`/`getblock`
+/// response, plus the chain metadata Core reports alongside it. Kept
+/// distinct from [`BlockHeaderInfo`], which only carries the fields needed
+/// to verify Merkle-inclusion proofs.
+#[derive(Debug, Clone)]
+pub struct BlockHeaderData {
+    pub header: bitcoin::block::Header,
+    /// Cumulative chain work up to and including this block, decoded from
+    /// Core's 64-char big-endian hex `chainwork` field.
+    pub chainwork: bitcoin::pow::Work,
+    /// Height, if the caller asked Core for it (absent for headers reached
+    /// by walking `previousblockhash` rather than by direct RPC lookup).
+    pub height: Option<u32>,
+    /// Confirmations Core reports as of the call, or `-1` mapped to `None`
+    /// for headers on a side branch that isn't part of the best chain.
+    pub confirmations: Option<u64>,
+}
+
+/// A full block decoded from a `getblock verbose=2` response: the header
+/// plus every transaction's inputs/outputs, already script-classified.
+/// Lets a caller that already has the verbose block JSON (e.g. from a block
+/// explorer crawl) index an entire block in one pass instead of issuing one
+/// `getrawtransaction` per contained `Txid`.
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub header: BlockHeaderData,
+    pub transactions: Vec<BlockTx>,
+}
+
+/// A single transaction decoded while parsing a full [`Block`]. Carries
+/// only what `getblock verbose=2`'s `tx` array reports directly — unlike
+/// [`crate::types::TxNode`], it has no block-context fields (those are
+/// already on the containing `Block`), and `size`/`weight` are `None` when
+/// the RPC response omits them (pre-segwit `getblock` output).
+#[derive(Debug, Clone)]
+pub struct BlockTx {
+    pub txid: Txid,
+    pub version: i32,
+    pub locktime: u32,
+    pub size: Option<u64>,
+    pub weight: Option<u64>,
+    pub inputs: Vec<crate::types::TxInput>,
+    pub outputs: Vec<crate::types::TxOutput>,
+}
+
+/// Full `gettxout` response: the UTXO itself plus the context Core reports
+/// alongside it. Kept distinct from [`crate::types::TxOutput`] so callers
+/// that only need the output's value/script aren't forced to carry the
+/// extra fields around, while ancestry building can still tell matured
+/// from immature coinbase outputs without a second round-trip, and
+/// wallet-UTXO tooling can inspect the full `scriptPubKey` breakdown and
+/// query unconfirmed outputs (see [`super::BitcoinRpc::get_tx_out`]'s
+/// `include_mempool`) without a second query.
+#[derive(Debug, Clone)]
+pub struct TxOutInfo {
+    pub output: crate::types::TxOutput,
+    pub confirmations: u64,
+    pub bestblock: BlockHash,
+    pub coinbase: bool,
+    /// Core's own `scriptPubKey.type` string (e.g. `"witness_v1_taproot"`),
+    /// kept verbatim alongside `output.script_type`'s classification since
+    /// Core's vocabulary is more granular (e.g. distinguishes bare
+    /// multisig from nonstandard). `None` on bitcoind versions that omit
+    /// the field.
+    pub script_pub_key_type: Option<String>,
+    /// Addresses Core inferred for this scriptPubKey (`address` on Core
+    /// 22+, or the deprecated `addresses` array on older versions).
+    /// Empty for scripts with no derivable address (e.g. `OP_RETURN`) or
+    /// on bitcoind versions that omit the field entirely.
+    pub addresses: Vec<String>,
+}
+
+// ==============================================================================
+// Spend Lookup
+// ==============================================================================
+
+/// The transaction (and input) that spends a given outpoint, as reported
+/// by an address/outpoint-index backend — e.g. Esplora's
+/// `/tx/:txid/outspend/:vout`. Used to expand [`crate::graph`]'s descendant
+/// traversal forward from an output instead of backward from an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxSpend {
+    pub spending_txid: Txid,
+    pub input_index: u32,
+}
+
+// ==============================================================================
+// Block Addressing
+// ==============================================================================
+
+/// Addresses a block by position in the chain or by hash, mirroring how
+/// light-client header chains resolve block lookups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockId {
+    /// The chain's genesis block.
+    Earliest,
+    /// The current chain tip.
+    Latest,
+    /// A specific height. Only guaranteed canonical as of the call that
+    /// resolved it — a later reorg can replace what a given height maps to.
+    Height(u32),
+    /// A specific block hash, returned unchanged by
+    /// [`BitcoinRpc::get_block_hash`](super::BitcoinRpc::get_block_hash).
+    Hash(BlockHash),
+}
+
+/// Maps a `getblockchaininfo`-style chain name to its [`bitcoin::Network`].
+pub fn chain_to_network(chain: &str) -> Option<bitcoin::Network> {
+    match chain {
+        "main" => Some(bitcoin::Network::Bitcoin),
+        "test" => Some(bitcoin::Network::Testnet),
+        "signet" => Some(bitcoin::Network::Signet),
+        "regtest" => Some(bitcoin::Network::Regtest),
+        _ => None,
+    }
+}
+
+/// The genesis block hash for `chain` (a `getblockchaininfo`-style chain
+/// name), used to resolve [`BlockId::Earliest`].
+pub fn genesis_hash_for_chain(chain: &str) -> Result<BlockHash, crate::error::CoreError> {
+    let network = chain_to_network(chain).ok_or_else(|| {
+        crate::error::CoreError::InvalidTxData(format!(
+            "unrecognized chain name `{chain}` from getblockchaininfo"
+        ))
+    })?;
+    Ok(bitcoin::blockdata::constants::genesis_block(network).block_hash())
+}
+
+// ==============================================================================
+// Fee Estimation
+// ==============================================================================
+
+/// Fee-estimation mode passed to `estimatesmartfee`, trading off a tighter
+/// feerate estimate (`Economical`) against one padded to stay safe across
+/// fee spikes (`Conservative`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateMode {
+    Economical,
+    Conservative,
+}
+
+impl EstimateMode {
+    /// The string Bitcoin Core's `estimatesmartfee` RPC expects.
+    pub fn as_core_str(self) -> &'static str {
+        match self {
+            Self::Economical => "ECONOMICAL",
+            Self::Conservative => "CONSERVATIVE",
+        }
+    }
+}
@@ -73,13 +73,31 @@ pub fn spending_input(funding_txid: Txid, vout: u32) -> RawInputInfo {
     }
 }
 
+/// A spending input referencing `funding_txid:vout`, with the prevout's
+/// value and scriptPubKey already inlined — as an Esplora-style backend's
+/// `/tx/:txid` response or Core's `getrawtransaction` verbosity=2 would
+/// supply it, without a separate fetch of the funding transaction.
+pub fn spending_input_with_prevout(
+    funding_txid: Txid,
+    vout: u32,
+    value: Amount,
+    script_pub_key: bitcoin::ScriptBuf,
+) -> RawInputInfo {
+    RawInputInfo {
+        prevout: Some(bitcoin::OutPoint::new(funding_txid, vout)),
+        sequence: 0xFFFFFFFE,
+        prevout_value: Some(value),
+        prevout_script: Some(script_pub_key),
+    }
+}
+
 /// A minimal valid P2WPKH output with the given satoshi value.
 pub fn simple_output(sats: u64) -> RawOutputInfo {
     // Minimal valid P2WPKH scriptPubKey: OP_0 PUSH20 <20-byte-hash>.
     let script_bytes = [
         0x00, 0x14, // OP_0, PUSH20
-        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
-        0x0f, 0x10, 0x11, 0x12, 0x13, 0x14,
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+        0x10, 0x11, 0x12, 0x13, 0x14,
     ];
     RawOutputInfo {
         value: Amount::from_sat(sats),
@@ -95,26 +113,26 @@ pub fn simple_output(sats: u64) -> RawOutputInfo {
 /// Build a `TxInput` for domain-level tests. `value` is in satoshis.
 pub fn make_input(value: Option<u64>, sequence: u32) -> TxInput {
     TxInput {
-        prevout: Some(bitcoin::OutPoint::new(
-            Txid::from_byte_array([0u8; 32]),
-            0,
-        )),
+        prevout: Some(bitcoin::OutPoint::new(Txid::from_byte_array([0u8; 32]), 0)),
         sequence,
         value: value.map(Amount::from_sat),
         script_type: Some(ScriptType::P2wpkh),
+        address: None,
+        unresolved_reason: None,
     }
 }
 
 /// Build a `TxOutput` with a P2WPKH script for domain-level tests.
 pub fn make_output(sats: u64) -> TxOutput {
     let script_bytes = [
-        0x00, 0x14, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
-        0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14,
+        0x00, 0x14, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+        0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14,
     ];
     TxOutput {
         value: Amount::from_sat(sats),
         script_pub_key: bitcoin::ScriptBuf::from_bytes(script_bytes.to_vec()),
         script_type: ScriptType::P2wpkh,
+        address: None,
     }
 }
 
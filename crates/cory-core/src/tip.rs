@@ -0,0 +1,172 @@
+//! Poll-based chain-tip change detection, complementing [`crate::notify`]'s
+//! push-based ZMQ watcher for nodes that don't have `-zmqpub*` configured
+//! (or for the Esplora backend, which has no ZMQ equivalent at all).
+//!
+//! [`TipWatcher::watch`] polls any [`BitcoinRpc`] implementation on a fixed
+//! interval via `getblockchaininfo` and only emits a [`TipEvent`] when the
+//! best block hash actually changes, so idle polling costs one RPC call per
+//! interval and nothing else.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bitcoin::BlockHash;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tokio_stream::Stream;
+use tracing::warn;
+
+use crate::rpc::{BitcoinRpc, ChainInfo};
+
+/// Capacity of the broadcast channel fanning events out to subscribers.
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// A detected chain-tip change.
+#[derive(Debug, Clone)]
+pub enum TipEvent {
+    /// The tip advanced normally to a new best block.
+    NewTip(ChainInfo),
+    /// The chain reorganized: `from` was the last observed tip and is no
+    /// longer part of the best chain, replaced by `to`. Consumers should
+    /// invalidate any derived state built on `from` (or its ancestors back
+    /// to the fork point).
+    ///
+    /// Detected by height, not by `previousblockhash`: Core's
+    /// `getblockchaininfo` doesn't expose a block's parent hash, so a tip
+    /// whose height isn't exactly one more than the last observed height
+    /// is treated as a reorg. This catches same-height-different-hash
+    /// reorgs and multi-block reorgs alike, but can't distinguish a normal
+    /// append from a reorg that happens to land back at the same height
+    /// plus one.
+    Reorg { from: BlockHash, to: BlockHash },
+}
+
+/// Polls a [`BitcoinRpc`] backend for chain-tip changes and fans them out
+/// to any number of consumers.
+///
+/// Dropping this value stops the background polling task and closes every
+/// outstanding subscription.
+pub struct TipWatcher {
+    sender: broadcast::Sender<TipEvent>,
+    task: JoinHandle<()>,
+}
+
+impl TipWatcher {
+    /// Start polling `rpc` for the chain tip every `interval`, only
+    /// emitting when the best block hash changes.
+    pub fn watch(rpc: Arc<dyn BitcoinRpc>, interval: Duration) -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let task_sender = sender.clone();
+        let task = tokio::spawn(async move { run_poll_loop(rpc, interval, task_sender).await });
+        Self { sender, task }
+    }
+
+    /// Subscribe to the event broadcast. Each subscriber gets every event
+    /// published after this call; one that falls more than
+    /// [`CHANNEL_CAPACITY`] events behind loses the oldest unread ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<TipEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Like [`Self::subscribe`], but adapted into a [`Stream`] for callers
+    /// that want to combine it with other streams (e.g. via `select!` or
+    /// `StreamExt` combinators) rather than polling a channel directly.
+    pub fn subscribe_stream(
+        &self,
+    ) -> impl Stream<Item = Result<TipEvent, BroadcastStreamRecvError>> {
+        BroadcastStream::new(self.subscribe())
+    }
+}
+
+impl Drop for TipWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn run_poll_loop(
+    rpc: Arc<dyn BitcoinRpc>,
+    interval: Duration,
+    sender: broadcast::Sender<TipEvent>,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    let mut last: Option<(BlockHash, u64)> = None;
+    loop {
+        ticker.tick().await;
+
+        let info = match rpc.get_blockchain_info().await {
+            Ok(info) => info,
+            Err(e) => {
+                warn!(error = %e, "tip watcher: get_blockchain_info failed; retrying next interval");
+                continue;
+            }
+        };
+
+        if last.is_some_and(|(hash, _)| hash == info.best_block_hash) {
+            continue;
+        }
+
+        if let Some((last_hash, last_height)) = last {
+            if info.blocks != last_height + 1 {
+                let _ = sender.send(TipEvent::Reorg {
+                    from: last_hash,
+                    to: info.best_block_hash,
+                });
+            }
+        }
+
+        last = Some((info.best_block_hash, info.blocks));
+        let _ = sender.send(TipEvent::NewTip(info));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bitcoin::hashes::Hash;
+    use bitcoin::BlockHash;
+
+    use crate::rpc::mock::MockRpc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn watch_emits_new_tip_on_hash_change() {
+        let mock = Arc::new(MockRpc::builder().build());
+        let rpc: Arc<dyn BitcoinRpc> = mock.clone();
+        let watcher = TipWatcher::watch(rpc, Duration::from_millis(5));
+        let mut events = watcher.subscribe();
+
+        mock.advance_tip(1);
+
+        let event = tokio::time::timeout(Duration::from_secs(1), events.recv())
+            .await
+            .expect("must receive an event before timeout")
+            .expect("channel must not close");
+        assert!(matches!(event, TipEvent::NewTip(_)));
+    }
+
+    #[tokio::test]
+    async fn watch_emits_reorg_on_height_regression() {
+        let mock = Arc::new(MockRpc::builder().build());
+        let rpc: Arc<dyn BitcoinRpc> = mock.clone();
+        let watcher = TipWatcher::watch(rpc, Duration::from_millis(5));
+        let mut events = watcher.subscribe();
+
+        mock.advance_tip(1);
+        let first = tokio::time::timeout(Duration::from_secs(1), events.recv())
+            .await
+            .expect("must receive first event before timeout")
+            .expect("channel must not close");
+        assert!(matches!(first, TipEvent::NewTip(_)));
+
+        mock.reorg_to(1, BlockHash::all_zeros());
+        let second = tokio::time::timeout(Duration::from_secs(1), events.recv())
+            .await
+            .expect("must receive second event before timeout")
+            .expect("channel must not close");
+        assert!(matches!(second, TipEvent::Reorg { .. }));
+    }
+}
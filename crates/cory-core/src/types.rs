@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
-use bitcoin::{Amount, BlockHash, ScriptBuf, Txid};
+use bitcoin::address::NetworkUnchecked;
+use bitcoin::{Address, Amount, BlockHash, Network, ScriptBuf, TxMerkleNode, Txid};
 use serde::{Deserialize, Serialize};
 
 // ==============================================================================
@@ -68,6 +69,39 @@ impl TxNode {
     pub fn is_coinbase(&self) -> bool {
         self.inputs.len() == 1 && self.inputs[0].prevout.is_none()
     }
+
+    /// A transaction is final when every input's sequence is `0xFFFFFFFF`,
+    /// i.e. none of them opt in to RBF or enforce a relative locktime, and
+    /// `locktime` itself goes unenforced regardless of its value.
+    pub fn is_final(&self) -> bool {
+        self.inputs.iter().all(|input| input.sequence == 0xFFFFFFFF)
+    }
+
+    /// Decode `locktime`, given whether it's actually enforced (see
+    /// [`Self::is_final`]). See [`crate::enrich::locktime_info`] for the
+    /// decoding rules.
+    pub fn locktime_info(&self) -> crate::enrich::LocktimeInfo {
+        crate::enrich::locktime_info(self.locktime, !self.is_final())
+    }
+
+    /// Whether any input enforces a BIP-68 relative locktime. Central to
+    /// spotting CSV-based contracts (Lightning channels, vaults, etc.)
+    /// surfaced in the ancestry graph.
+    pub fn has_relative_locktime(&self) -> bool {
+        self.inputs
+            .iter()
+            .any(|input| input.relative_locktime().is_some())
+    }
+
+    /// Every input (by index) that enforces a relative locktime, paired
+    /// with the decoded constraint.
+    pub fn relative_locktime_inputs(&self) -> Vec<(usize, crate::enrich::RelativeLocktime)> {
+        self.inputs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, input)| input.relative_locktime().map(|lt| (index, lt)))
+            .collect()
+    }
 }
 
 /// A transaction input. For coinbase inputs, `prevout` is `None`.
@@ -81,6 +115,34 @@ pub struct TxInput {
     pub value: Option<Amount>,
     /// Script type of the spent output.
     pub script_type: Option<ScriptType>,
+    /// Address derived from the spent output's `scriptPubKey`, if the RPC
+    /// provided it and the script has one (e.g. not `OP_RETURN`). Kept
+    /// network-unchecked since `TxInput` is (de)serialized independent of
+    /// which network produced it, mirroring how wallets keep addresses
+    /// unchecked until a network is confirmed; call
+    /// [`Self::checked_address`] with the expected network to use it.
+    pub address: Option<Address<NetworkUnchecked>>,
+    /// Why `value`/`script_type` are still `None`, if known. `None` here
+    /// means either the input resolved fine, or it didn't and the cause
+    /// wasn't diagnosed.
+    pub unresolved_reason: Option<TruncationReason>,
+}
+
+impl TxInput {
+    /// Decode `sequence` as a BIP-68 relative locktime. Returns `None` when
+    /// the disable flag (bit 31) is set, i.e. this input enforces no
+    /// relative timelock. See [`crate::enrich::relative_locktime`] for the
+    /// decoding rules.
+    pub fn relative_locktime(&self) -> Option<crate::enrich::RelativeLocktime> {
+        crate::enrich::relative_locktime(self.sequence)
+    }
+
+    /// Recover [`Self::address`], asserting it belongs to `network`.
+    /// Returns `None` if there's no address, or if it doesn't match
+    /// `network`.
+    pub fn checked_address(&self, network: Network) -> Option<Address> {
+        self.address.clone()?.require_network(network).ok()
+    }
 }
 
 /// A transaction output.
@@ -89,6 +151,38 @@ pub struct TxOutput {
     pub value: Amount,
     pub script_pub_key: ScriptBuf,
     pub script_type: ScriptType,
+    /// Address derived from `script_pub_key`, if the script has one (e.g.
+    /// not `OP_RETURN`). Kept network-unchecked since `TxOutput` is
+    /// (de)serialized independent of which network produced it; call
+    /// [`Self::checked_address`] with the expected network to use it.
+    pub address: Option<Address<NetworkUnchecked>>,
+}
+
+impl TxOutput {
+    /// Recover [`Self::address`], asserting it belongs to `network`.
+    /// Returns `None` if there's no address, or if it doesn't match
+    /// `network`.
+    pub fn checked_address(&self, network: Network) -> Option<Address> {
+        self.address.clone()?.require_network(network).ok()
+    }
+}
+
+/// A verified Merkle-inclusion proof for a confirmed transaction.
+///
+/// Proves a transaction is really included in its claimed block without
+/// trusting the node's self-reported `confirmations` count: `merkle_root`
+/// is recomputed locally from the partial merkle tree returned by
+/// `gettxoutproof`, then checked against the containing block header's
+/// `merkleroot`, fetched independently. Since construction fails with
+/// [`crate::error::CoreError::TxNotIncluded`] or
+/// [`crate::error::CoreError::InvalidProof`] when that check doesn't pass,
+/// every value of this type represents a proof that did verify.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxInclusionProof {
+    pub txid: Txid,
+    pub block_hash: BlockHash,
+    pub block_height: u32,
+    pub merkle_root: TxMerkleNode,
 }
 
 // ==============================================================================
@@ -125,6 +219,39 @@ pub struct GraphStats {
     pub node_count: usize,
     pub edge_count: usize,
     pub max_depth_reached: usize,
+    /// Number of inputs across the graph left unresolved because the
+    /// connected node is pruned and has discarded the funding block, i.e.
+    /// `TxInput.unresolved_reason == Some(TruncationReason::Pruned)`.
+    pub pruned_unresolved_inputs: usize,
+    /// Combined fee across every *unconfirmed* transaction in the graph
+    /// (`block_height.is_none()`), mirroring mempool ancestor-package
+    /// accounting. `None` when the graph has no unconfirmed transactions,
+    /// or when any unconfirmed transaction's fee couldn't be computed (an
+    /// unresolved input).
+    pub ancestor_package_fee: Option<Amount>,
+    /// Combined vsize across every unconfirmed transaction in the graph.
+    /// `None` under the same conditions as [`Self::ancestor_package_fee`].
+    pub ancestor_package_vsize: Option<u64>,
+    /// `ancestor_package_fee` / `ancestor_package_vsize`, in sat/vB: the
+    /// package feerate a miner actually realizes by including the whole
+    /// unconfirmed ancestor set together, which can be far higher than a
+    /// low-feerate parent's own feerate when a child is bumping it (CPFP).
+    pub ancestor_package_feerate: Option<f64>,
+    /// Number of funding transactions excluded from the graph because their
+    /// `block_height` was after the pinned height of a
+    /// [`crate::graph::build_ancestry_as_of`] call — treated as
+    /// not-yet-existent at that point in history rather than followed
+    /// further. Always `0` for an ordinary (non-as-of) build.
+    pub excluded_after_as_of: usize,
+}
+
+/// Why a [`TxInput`]'s prevout could not be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TruncationReason {
+    /// The connected node reported itself as pruned and the funding block's
+    /// data was gone, rather than the transaction genuinely not existing.
+    Pruned,
 }
 
 /// Configurable limits for ancestry graph expansion.
@@ -133,6 +260,8 @@ pub struct GraphLimits {
     pub max_depth: usize,
     pub max_nodes: usize,
     pub max_edges: usize,
+    /// Traversal order to apply once a limit forces the graph to be cut short.
+    pub strategy: GraphStrategy,
 }
 
 impl Default for GraphLimits {
@@ -141,10 +270,28 @@ impl Default for GraphLimits {
             max_depth: 50,
             max_nodes: 500,
             max_edges: 2000,
+            strategy: GraphStrategy::default(),
         }
     }
 }
 
+/// Expansion order for ancestry graph traversal.
+///
+/// Only matters once a [`GraphLimits`] bound cuts the graph short: it
+/// decides which unexpanded outpoints get the remaining node/edge budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphStrategy {
+    /// Expand level-by-level from the root, fetching each frontier in
+    /// parallel. Simple and fast, but truncation falls out of insertion
+    /// order rather than relevance.
+    #[default]
+    BreadthFirst,
+    /// Always expand the highest-value unexpanded outpoint next, so a
+    /// high-value funding chain is kept over dust when a limit is hit.
+    ValueWeighted,
+}
+
 // ==============================================================================
 // RPC Intermediate Types
 // ==============================================================================
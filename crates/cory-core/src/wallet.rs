@@ -0,0 +1,247 @@
+//! Wallet-ownership tracking via registered output descriptors or xpubs.
+//!
+//! Lets operators register a BDK-style descriptor (an extended public key
+//! plus a script type) so the ancestry graph can recognize addresses
+//! derived from that wallet and flag them as self-owned, without the user
+//! having to hand-label every address it touches.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use bitcoin::bip32::{ChildNumber, DerivationPath, Xpub};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::{Network, PublicKey, Script, ScriptBuf};
+
+use crate::error::CoreError;
+
+/// Script template a descriptor derives addresses as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DescriptorKind {
+    /// `pkh(xpub)` — legacy P2PKH.
+    Pkh,
+    /// `wpkh(xpub)` — native segwit P2WPKH.
+    Wpkh,
+    /// `sh(wpkh(xpub))` — wrapped segwit P2SH-P2WPKH.
+    ShWpkh,
+}
+
+/// A parsed single-sig descriptor: an extended public key plus the script
+/// type it derives. Covers the common BDK-style forms (`pkh`, `wpkh`,
+/// `sh(wpkh(...))`) wrapping one xpub; multisig and taproot descriptors are
+/// not supported.
+#[derive(Debug, Clone)]
+pub struct WalletDescriptor {
+    xpub: Xpub,
+    kind: DescriptorKind,
+}
+
+impl WalletDescriptor {
+    /// Parses `pkh(<xpub>)`, `wpkh(<xpub>)`, or `sh(wpkh(<xpub>))`. Key
+    /// origin info (`[fingerprint/path]`) and multipath/wildcard suffixes
+    /// (`/0/*`) are not supported — both derivation chains (external and
+    /// internal) are always derived regardless.
+    pub fn parse(descriptor: &str) -> Result<Self, CoreError> {
+        let descriptor = descriptor.trim();
+        let (kind, inner) = if let Some(inner) = descriptor
+            .strip_prefix("sh(wpkh(")
+            .and_then(|s| s.strip_suffix("))"))
+        {
+            (DescriptorKind::ShWpkh, inner)
+        } else if let Some(inner) = descriptor
+            .strip_prefix("wpkh(")
+            .and_then(|s| s.strip_suffix(")"))
+        {
+            (DescriptorKind::Wpkh, inner)
+        } else if let Some(inner) = descriptor
+            .strip_prefix("pkh(")
+            .and_then(|s| s.strip_suffix(")"))
+        {
+            (DescriptorKind::Pkh, inner)
+        } else {
+            return Err(CoreError::InvalidTxData(format!(
+                "unsupported descriptor syntax: {descriptor}"
+            )));
+        };
+
+        let xpub = Xpub::from_str(inner)
+            .map_err(|e| CoreError::InvalidTxData(format!("invalid xpub in descriptor: {e}")))?;
+        Ok(Self { xpub, kind })
+    }
+
+    /// Parses a bare xpub (no descriptor wrapper) as a native segwit
+    /// (`wpkh`) wallet, the default script type for new single-sig wallets.
+    pub fn from_xpub(xpub_str: &str) -> Result<Self, CoreError> {
+        let xpub = Xpub::from_str(xpub_str.trim())
+            .map_err(|e| CoreError::InvalidTxData(format!("invalid xpub: {e}")))?;
+        Ok(Self {
+            xpub,
+            kind: DescriptorKind::Wpkh,
+        })
+    }
+
+    /// Derives the scriptPubKeys for both the external (chain `0`) and
+    /// internal/change (chain `1`) address chains, indices `0..gap_limit`,
+    /// on `network`.
+    pub fn derive_scripts(&self, network: Network, gap_limit: u32) -> HashSet<ScriptBuf> {
+        let secp = Secp256k1::verification_only();
+        let mut scripts = HashSet::new();
+
+        for chain in [0u32, 1u32] {
+            let Ok(chain_path) = DerivationPath::from_str(&format!("m/{chain}")) else {
+                continue;
+            };
+            let Ok(chain_xpub) = self.xpub.derive_pub(&secp, &chain_path) else {
+                continue;
+            };
+            for index in 0..gap_limit {
+                let Ok(child_number) = ChildNumber::from_normal_idx(index) else {
+                    continue;
+                };
+                let Ok(child) = chain_xpub.derive_pub(&secp, &[child_number]) else {
+                    continue;
+                };
+                let pubkey = PublicKey::new(child.public_key);
+                if let Some(address) = self.derive_address(&pubkey, network) {
+                    scripts.insert(address.script_pubkey());
+                }
+            }
+        }
+        scripts
+    }
+
+    fn derive_address(&self, pubkey: &PublicKey, network: Network) -> Option<bitcoin::Address> {
+        match self.kind {
+            DescriptorKind::Pkh => Some(bitcoin::Address::p2pkh(pubkey, network)),
+            DescriptorKind::Wpkh => {
+                let compressed = bitcoin::CompressedPublicKey::try_from(*pubkey).ok()?;
+                Some(bitcoin::Address::p2wpkh(&compressed, network))
+            }
+            DescriptorKind::ShWpkh => {
+                let compressed = bitcoin::CompressedPublicKey::try_from(*pubkey).ok()?;
+                Some(bitcoin::Address::p2shwpkh(&compressed, network))
+            }
+        }
+    }
+}
+
+/// Registry of wallet descriptors whose derived addresses should be
+/// flagged as self-owned in the ancestry graph. Descriptors are derived
+/// eagerly on registration, up to a fixed gap limit, into a flat script
+/// set so lookups during graph scanning are a plain hash-set membership
+/// check.
+pub struct WalletRegistry {
+    network: Network,
+    gap_limit: u32,
+    owned_scripts: HashSet<ScriptBuf>,
+}
+
+impl WalletRegistry {
+    pub fn new(network: Network, gap_limit: u32) -> Self {
+        Self {
+            network,
+            gap_limit,
+            owned_scripts: HashSet::new(),
+        }
+    }
+
+    /// Parses and registers a descriptor, deriving its owned scripts
+    /// immediately.
+    pub fn register_descriptor(&mut self, descriptor: &str) -> Result<(), CoreError> {
+        let parsed = WalletDescriptor::parse(descriptor)?;
+        self.owned_scripts
+            .extend(parsed.derive_scripts(self.network, self.gap_limit));
+        Ok(())
+    }
+
+    /// Parses and registers a bare xpub (treated as `wpkh`), deriving its
+    /// owned scripts immediately.
+    pub fn register_xpub(&mut self, xpub: &str) -> Result<(), CoreError> {
+        let parsed = WalletDescriptor::from_xpub(xpub)?;
+        self.owned_scripts
+            .extend(parsed.derive_scripts(self.network, self.gap_limit));
+        Ok(())
+    }
+
+    /// Whether `script` was derived from any registered descriptor.
+    #[must_use]
+    pub fn owns_script(&self, script: &Script) -> bool {
+        self.owned_scripts.contains(script)
+    }
+
+    /// Whether no descriptors have been registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.owned_scripts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A well-known BIP-32 test vector xpub (from the BIP-32 test suite),
+    // used purely as a structurally valid xpub; no real funds involved.
+    const TEST_XPUB: &str = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+
+    #[test]
+    fn parse_rejects_unsupported_syntax() {
+        let err = WalletDescriptor::parse("tr(nope)").expect_err("taproot must be rejected");
+        assert!(matches!(err, CoreError::InvalidTxData(_)));
+    }
+
+    #[test]
+    fn parse_wpkh_descriptor() {
+        let descriptor = WalletDescriptor::parse(&format!("wpkh({TEST_XPUB})"))
+            .expect("must parse wpkh descriptor");
+        assert_eq!(descriptor.kind, DescriptorKind::Wpkh);
+    }
+
+    #[test]
+    fn parse_sh_wpkh_descriptor() {
+        let descriptor = WalletDescriptor::parse(&format!("sh(wpkh({TEST_XPUB}))"))
+            .expect("must parse sh(wpkh(..)) descriptor");
+        assert_eq!(descriptor.kind, DescriptorKind::ShWpkh);
+    }
+
+    #[test]
+    fn from_xpub_defaults_to_wpkh() {
+        let descriptor = WalletDescriptor::from_xpub(TEST_XPUB).expect("must parse bare xpub");
+        assert_eq!(descriptor.kind, DescriptorKind::Wpkh);
+    }
+
+    #[test]
+    fn derive_scripts_covers_both_chains_up_to_gap_limit() {
+        let descriptor = WalletDescriptor::from_xpub(TEST_XPUB).expect("must parse");
+        let scripts = descriptor.derive_scripts(Network::Bitcoin, 5);
+        // 2 chains (external/internal) * 5 indices, all distinct scripts.
+        assert_eq!(scripts.len(), 10);
+    }
+
+    #[test]
+    fn registry_owns_scripts_from_registered_descriptor() {
+        let mut registry = WalletRegistry::new(Network::Bitcoin, 5);
+        assert!(registry.is_empty());
+
+        registry
+            .register_descriptor(&format!("wpkh({TEST_XPUB})"))
+            .expect("must register");
+        assert!(!registry.is_empty());
+
+        let descriptor = WalletDescriptor::from_xpub(TEST_XPUB).expect("must parse");
+        let scripts = descriptor.derive_scripts(Network::Bitcoin, 5);
+        let any_script = scripts
+            .iter()
+            .next()
+            .expect("must derive at least one script");
+        assert!(registry.owns_script(any_script));
+    }
+
+    #[test]
+    fn registry_does_not_own_unrelated_scripts() {
+        let mut registry = WalletRegistry::new(Network::Bitcoin, 5);
+        registry.register_xpub(TEST_XPUB).expect("must register");
+
+        let unrelated = bitcoin::ScriptBuf::from_bytes(vec![0x6a, 0x04, 0xde, 0xad, 0xbe, 0xef]);
+        assert!(!registry.owns_script(&unrelated));
+    }
+}
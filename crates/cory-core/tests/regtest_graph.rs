@@ -4,9 +4,9 @@ use std::{env, fs};
 
 use bitcoin::Txid;
 use cory_core::cache::Cache;
-use cory_core::graph::build_ancestry;
-use cory_core::rpc::HttpRpcClient;
-use cory_core::types::GraphLimits;
+use cory_core::graph::{build_ancestry, build_ancestry_as_of};
+use cory_core::rpc::{BlockId, HttpRpcClient};
+use cory_core::types::{GraphLimits, GraphStrategy};
 use serde::Deserialize;
 
 static TRACING_INIT: Once = Once::new();
@@ -45,6 +45,15 @@ struct GraphScenario {
     expected_exact_edge_count: Option<usize>,
     #[serde(default)]
     expected_unresolved_input_count: Option<usize>,
+    /// When set, the scenario is built via [`build_ancestry_as_of`] pinned
+    /// to this height instead of [`build_ancestry`], exercising the
+    /// point-in-time reconstruction mode.
+    #[serde(default)]
+    as_of_height: Option<u32>,
+    /// Expected [`cory_core::types::GraphStats::excluded_after_as_of`] for
+    /// an `as_of_height` scenario.
+    #[serde(default)]
+    expected_excluded_after_as_of: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -98,18 +107,36 @@ async fn regtest_graph_builder_handles_functional_and_stress_scenarios() {
             max_depth: scenario.limits.max_depth,
             max_nodes: scenario.limits.max_nodes,
             max_edges: scenario.limits.max_edges,
+            strategy: GraphStrategy::BreadthFirst,
         };
 
         // Use a fresh cache per scenario so assertions are independent.
         let cache = Cache::new();
 
         eprintln!(
-            "[itest][graph] scenario={} root={} limits=({}, {}, {})",
-            scenario.name, root_txid, limits.max_depth, limits.max_nodes, limits.max_edges
+            "[itest][graph] scenario={} root={} limits=({}, {}, {}) as_of_height={:?}",
+            scenario.name,
+            root_txid,
+            limits.max_depth,
+            limits.max_nodes,
+            limits.max_edges,
+            scenario.as_of_height
         );
-        let graph = build_ancestry(&rpc, &cache, root_txid, &limits, 8)
+        let graph = match scenario.as_of_height {
+            Some(as_of_height) => build_ancestry_as_of(
+                &rpc,
+                &cache,
+                root_txid,
+                &limits,
+                8,
+                BlockId::Height(as_of_height),
+            )
             .await
-            .expect("regtest graph build must succeed");
+            .expect("regtest as-of graph build must succeed"),
+            None => build_ancestry(&rpc, &cache, root_txid, &limits, 8)
+                .await
+                .expect("regtest graph build must succeed"),
+        };
 
         assert_eq!(
             graph.root_txid, root_txid,
@@ -245,6 +272,28 @@ async fn regtest_graph_builder_handles_functional_and_stress_scenarios() {
                 scenario.name
             );
         }
+
+        if let Some(as_of_height) = scenario.as_of_height {
+            for node in graph.nodes.values() {
+                if let Some(block_height) = node.block_height {
+                    assert!(
+                        block_height <= as_of_height,
+                        "scenario={} node {} confirmed at height {} after as_of_height {}",
+                        scenario.name,
+                        node.txid,
+                        block_height,
+                        as_of_height
+                    );
+                }
+            }
+            if let Some(expected_excluded) = scenario.expected_excluded_after_as_of {
+                assert_eq!(
+                    graph.stats.excluded_after_as_of, expected_excluded,
+                    "scenario={} excluded_after_as_of mismatch",
+                    scenario.name
+                );
+            }
+        }
     }
 
     eprintln!("[itest][graph] integration test completed");
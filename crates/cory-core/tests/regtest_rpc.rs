@@ -2,7 +2,7 @@ use std::sync::Once;
 use std::{env, fs};
 
 use bitcoin::{OutPoint, Txid};
-use cory_core::rpc::{BitcoinRpc, HttpRpcClient};
+use cory_core::rpc::{BitcoinRpc, HttpRpcClient, RpcEndpoint};
 
 static TRACING_INIT: Once = Once::new();
 
@@ -30,8 +30,15 @@ async fn regtest_rpc_client_parses_blockchain_info_and_transactions() {
     let outpoints_file =
         env::var("CORY_TEST_OUTPOINTS_FILE").expect("CORY_TEST_OUTPOINTS_FILE must be set");
 
-    let rpc = HttpRpcClient::new(&rpc_url, Some(&rpc_user), Some(&rpc_pass), None, None, 10)
-        .expect("rpc client must construct");
+    let rpc = HttpRpcClient::new(
+        vec![RpcEndpoint::new(&rpc_url).with_user_pass(&rpc_user, &rpc_pass)],
+        None,
+        None,
+        10,
+        30,
+        10,
+    )
+    .expect("rpc client must construct");
 
     eprintln!("[itest] checking get_blockchain_info against {rpc_url}");
     let info = rpc
@@ -102,7 +109,7 @@ async fn regtest_rpc_client_parses_blockchain_info_and_transactions() {
         outpoints.len()
     );
     let batch = rpc
-        .get_tx_outs(&outpoints)
+        .get_tx_outs(&outpoints, true)
         .await
         .expect("regtest get_tx_outs must succeed for fixture outpoints");
     assert_eq!(
@@ -115,11 +122,11 @@ async fn regtest_rpc_client_parses_blockchain_info_and_transactions() {
             .as_ref()
             .expect("fixture outpoint in batch result must still be unspent");
         assert!(
-            txout.value.to_sat() > 0,
+            txout.output.value.to_sat() > 0,
             "fixture outpoint value must be positive"
         );
         assert!(
-            !txout.script_pub_key.is_empty(),
+            !txout.output.script_pub_key.is_empty(),
             "fixture outpoint script must not be empty"
         );
     }
@@ -128,16 +135,16 @@ async fn regtest_rpc_client_parses_blockchain_info_and_transactions() {
     eprintln!("[itest] validating subset via get_tx_out (single)");
     for outpoint in outpoints.iter().take(3) {
         let txout = rpc
-            .get_tx_out(&outpoint.txid, outpoint.vout)
+            .get_tx_out(&outpoint.txid, outpoint.vout, true)
             .await
             .expect("regtest get_tx_out must succeed for fixture outpoint");
         let txout = txout.expect("fixture outpoint must still be unspent");
         assert!(
-            txout.value.to_sat() > 0,
+            txout.output.value.to_sat() > 0,
             "fixture outpoint value must be positive"
         );
         assert!(
-            !txout.script_pub_key.is_empty(),
+            !txout.output.script_pub_key.is_empty(),
             "fixture outpoint script must not be empty"
         );
     }
@@ -7,11 +7,11 @@
 // or a build step fails, we continue compiling the server and rely on
 // existing embedded assets (if any).
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use ed25519_dalek::Signer;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha512};
 
 /// All build-script output goes through `cargo:warning=` because that's the
@@ -55,6 +55,7 @@ fn main() {
     // files. If the npm project files are absent, skip npm steps entirely.
     if !has_npm_project {
         let hash = compute_dist_hash(&dist);
+        emit_manifest(&dist, &version, &hash);
         log_ui_warning(&[
             "Using PRE-COMPILED and PRE-BUILT UI.",
             &format!("cory package version: {}", version),
@@ -73,18 +74,26 @@ fn main() {
     }
 
     // Skip the npm build if UI source files haven't changed since the last
-    // successful build. We hash all watched files and compare against a
-    // cached marker in target/.
+    // successful build. We content-hash all watched files and compare
+    // against a cached marker in target/, so the check is reproducible
+    // across clones/CI (unlike an mtime-based check) and a touch that
+    // leaves file contents unchanged never forces a rebuild.
     let hash_marker =
         Path::new(&std::env::var("OUT_DIR").unwrap_or_default()).join("ui-build-hash");
-    let current_hash = hash_ui_sources(ui_dir);
 
     let mut needs_build = true;
+    let mut current_cache = None;
     if dist.join("index.html").exists() {
-        if let Ok(cached) = std::fs::read_to_string(&hash_marker) {
-            if cached.trim() == current_hash {
-                log!("UI sources unchanged: skipping npm build");
-                needs_build = false;
+        if let Some(cached) = read_cached_source_state(&hash_marker) {
+            // Fast path: a changed file count or size proves the sources
+            // changed without reading any file contents.
+            if quick_lengths_match(ui_dir, &cached.files) {
+                let current = hash_ui_sources(ui_dir);
+                if current.digest == cached.digest {
+                    log!("UI sources unchanged: skipping npm build");
+                    needs_build = false;
+                }
+                current_cache = Some(current);
             }
         }
     }
@@ -125,11 +134,13 @@ fn main() {
         log!("`npm run build` done, UI assets ready in ui/dist/");
 
         // Write the hash marker so subsequent builds can skip npm.
-        let _ = std::fs::write(&hash_marker, &current_hash);
+        let current = current_cache.unwrap_or_else(|| hash_ui_sources(ui_dir));
+        write_cached_source_state(&hash_marker, &current);
     }
 
     // Always compute and print the final SHA-512 of the resulting dist/ folder.
     let hash = compute_dist_hash(&dist);
+    emit_manifest(&dist, &version, &hash);
     log!("Pre-built UI SHA-512 for cory v{}: {}", version, hash);
 }
 
@@ -197,39 +208,115 @@ fn emit_rerun_directives(ui_dir: &Path) -> usize {
     count
 }
 
-/// Compute a hash of all UI source and config files for change detection.
-/// Uses file modification times rather than content for speed.
-fn hash_ui_sources(ui_dir: &Path) -> String {
-    let mut hasher = DefaultHasher::new();
+/// Per-file metadata persisted in the `ui-build-hash` marker alongside the
+/// content digest, so a later run can rule out changes with a cheap
+/// length comparison before re-reading any file content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedFileEntry {
+    path: String,
+    len: u64,
+}
 
-    for name in UI_CONFIG_FILES {
-        let path = ui_dir.join(name);
-        if let Ok(meta) = std::fs::metadata(&path) {
-            path.display().to_string().hash(&mut hasher);
-            if let Ok(modified) = meta.modified() {
-                modified.hash(&mut hasher);
-            }
-            meta.len().hash(&mut hasher);
-        }
-    }
+/// Cached state written to the `ui-build-hash` marker after a successful
+/// build, and compared against on the next run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UiSourceCache {
+    /// SHA-512 of every watched file's relative path and content, in
+    /// sorted-path order. Deterministic across clones/CI, unlike a hash
+    /// built from modification times.
+    digest: String,
+    files: Vec<CachedFileEntry>,
+}
+
+/// All UI config, source, and public files currently on disk, in
+/// deterministic sorted-path order.
+fn watched_file_list(ui_dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = UI_CONFIG_FILES
+        .iter()
+        .map(|name| ui_dir.join(name))
+        .filter(|path| path.exists())
+        .collect();
 
-    // Hash all source and public files.
     for dir_name in &["src", "public"] {
         let dir = ui_dir.join(dir_name);
         if dir.exists() {
-            for path in collect_files(&dir) {
-                if let Ok(meta) = std::fs::metadata(&path) {
-                    path.display().to_string().hash(&mut hasher);
-                    if let Ok(modified) = meta.modified() {
-                        modified.hash(&mut hasher);
-                    }
-                    meta.len().hash(&mut hasher);
-                }
-            }
+            files.extend(collect_files(&dir));
         }
     }
 
-    format!("{:016x}", hasher.finish())
+    files.sort();
+    files
+}
+
+/// `path` relative to `ui_dir`, with `/` separators regardless of platform,
+/// so the digest and cache are stable across Windows/Unix checkouts.
+fn relative_ui_path(ui_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(ui_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Fast path: compares the current watched file list against `cached`
+/// using only file count and byte length (via `stat`, no content reads).
+/// Returns `false` the moment anything differs, so callers can skip the
+/// expensive content hash entirely when a rebuild is obviously needed.
+fn quick_lengths_match(ui_dir: &Path, cached: &[CachedFileEntry]) -> bool {
+    let current = watched_file_list(ui_dir);
+    if current.len() != cached.len() {
+        return false;
+    }
+
+    current.iter().zip(cached).all(|(path, entry)| {
+        let Ok(meta) = std::fs::metadata(path) else {
+            return false;
+        };
+        relative_ui_path(ui_dir, path) == entry.path && meta.len() == entry.len
+    })
+}
+
+/// Compute a deterministic, content-addressed digest of all UI source and
+/// config files for change detection: each watched file's relative path
+/// and raw contents are fed into a single running SHA-512 hash, in
+/// sorted-path order, the same approach `compute_dist_hash` uses for
+/// `dist/`. Unlike an mtime-based hash, two checkouts of the same commit
+/// always produce the same digest, and a content-preserving touch never
+/// triggers a spurious rebuild.
+fn hash_ui_sources(ui_dir: &Path) -> UiSourceCache {
+    let files = watched_file_list(ui_dir);
+    let mut hasher = Sha512::new();
+    let mut entries = Vec::with_capacity(files.len());
+
+    for path in files {
+        let relative = relative_ui_path(ui_dir, &path);
+        let content = std::fs::read(&path).unwrap_or_default();
+
+        hasher.update(relative.as_bytes());
+        hasher.update(&content);
+
+        entries.push(CachedFileEntry {
+            path: relative,
+            len: content.len() as u64,
+        });
+    }
+
+    UiSourceCache {
+        digest: hex::encode(hasher.finalize()),
+        files: entries,
+    }
+}
+
+/// Reads and parses a previously written `ui-build-hash` marker, if any.
+fn read_cached_source_state(marker_path: &Path) -> Option<UiSourceCache> {
+    let content = std::fs::read_to_string(marker_path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Writes the `ui-build-hash` marker for the next run to compare against.
+fn write_cached_source_state(marker_path: &Path, cache: &UiSourceCache) {
+    if let Ok(json) = serde_json::to_string(cache) {
+        let _ = std::fs::write(marker_path, json);
+    }
 }
 
 /// Recursively collects all files in a directory, sorted by path.
@@ -272,6 +359,118 @@ fn compute_dist_hash(dist_dir: &Path) -> String {
     hex::encode(hasher.finalize())
 }
 
+// ==============================================================================
+// Integrity Manifest
+// ==============================================================================
+
+/// A machine-readable record of exactly which files make up the embedded
+/// UI, so downstream tooling can verify them individually instead of
+/// trusting a single opaque `compute_dist_hash` folder digest.
+#[derive(Debug, Serialize)]
+struct UiManifest {
+    version: String,
+    aggregate_sha512: String,
+    checksums: Vec<ManifestChecksum>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestChecksum {
+    path: String,
+    size: u64,
+    sha512: String,
+}
+
+/// Build the manifest describing every file under `dist_dir`.
+fn build_manifest(dist_dir: &Path, version: &str, aggregate_sha512: &str) -> UiManifest {
+    let mut checksums = Vec::new();
+
+    for path in collect_files(dist_dir) {
+        let Ok(relative) = path.strip_prefix(dist_dir) else {
+            continue;
+        };
+        let Ok(content) = std::fs::read(&path) else {
+            continue;
+        };
+
+        let mut hasher = Sha512::new();
+        hasher.update(&content);
+
+        checksums.push(ManifestChecksum {
+            path: relative.to_string_lossy().replace('\\', "/"),
+            size: content.len() as u64,
+            sha512: hex::encode(hasher.finalize()),
+        });
+    }
+
+    UiManifest {
+        version: version.to_string(),
+        aggregate_sha512: aggregate_sha512.to_string(),
+        checksums,
+    }
+}
+
+/// Writes `dist/manifest.json`, gated behind `CORY_UI_MANIFEST=1` so normal
+/// builds don't pay the extra per-file hashing cost. When
+/// `CORY_UI_SIGNING_KEY` also points at a raw 32-byte ed25519 seed, the
+/// manifest bytes are additionally signed to `manifest.json.sig`.
+fn emit_manifest(dist_dir: &Path, version: &str, aggregate_sha512: &str) {
+    if std::env::var("CORY_UI_MANIFEST").as_deref() != Ok("1") {
+        return;
+    }
+
+    let manifest = build_manifest(dist_dir, version, aggregate_sha512);
+    let json = match serde_json::to_vec_pretty(&manifest) {
+        Ok(json) => json,
+        Err(e) => {
+            log!("failed to serialize UI manifest: {e}");
+            return;
+        }
+    };
+
+    let manifest_path = dist_dir.join("manifest.json");
+    if let Err(e) = std::fs::write(&manifest_path, &json) {
+        log!("failed to write {}: {e}", manifest_path.display());
+        return;
+    }
+    log!("wrote {}", manifest_path.display());
+
+    sign_manifest(&manifest_path, &json);
+}
+
+/// If `CORY_UI_SIGNING_KEY` points at a raw 32-byte ed25519 seed, signs
+/// `manifest_bytes` and writes the detached signature next to
+/// `manifest_path` as `manifest.json.sig`.
+fn sign_manifest(manifest_path: &Path, manifest_bytes: &[u8]) {
+    let Ok(key_path) = std::env::var("CORY_UI_SIGNING_KEY") else {
+        return;
+    };
+
+    let key_bytes = match std::fs::read(&key_path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log!("failed to read CORY_UI_SIGNING_KEY at {key_path}: {e}");
+            return;
+        }
+    };
+
+    let seed: [u8; 32] = match key_bytes.as_slice().try_into() {
+        Ok(seed) => seed,
+        Err(_) => {
+            log!("CORY_UI_SIGNING_KEY must contain a raw 32-byte ed25519 seed");
+            return;
+        }
+    };
+
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&seed);
+    let signature = signing_key.sign(manifest_bytes);
+
+    let sig_path = manifest_path.with_extension("json.sig");
+    match std::fs::write(&sig_path, signature.to_bytes()) {
+        Ok(()) => log!("wrote {}", sig_path.display()),
+        Err(e) => log!("failed to write {}: {e}", sig_path.display()),
+    }
+}
+
 /// Recursively emits `cargo:rerun-if-changed` for every file and directory
 /// under `dir`. Returns the number of paths emitted.
 fn walk_rerun(dir: &Path) -> usize {
@@ -296,3 +495,177 @@ fn walk_rerun(dir: &Path) -> usize {
 
     count
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::*;
+
+    /// Creates a unique synthetic `ui/` tree with a watched config file
+    /// and a nested source file. Returns its path; callers must remove it
+    /// when done.
+    fn make_synthetic_ui_dir() -> PathBuf {
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time must be after unix epoch")
+            .as_nanos();
+        let ui_dir = std::env::temp_dir().join(format!("cory-ui-sources-test-{unique}"));
+        std::fs::create_dir_all(ui_dir.join("src")).expect("ui dir must be creatable");
+        std::fs::write(ui_dir.join("package.json"), b"{}").expect("file must be writable");
+        std::fs::write(ui_dir.join("src/main.tsx"), b"console.log(1);")
+            .expect("file must be writable");
+        ui_dir
+    }
+
+    #[test]
+    fn hash_ui_sources_is_deterministic_across_calls() {
+        let ui_dir = make_synthetic_ui_dir();
+
+        let first = hash_ui_sources(&ui_dir);
+        let second = hash_ui_sources(&ui_dir);
+        assert_eq!(first.digest, second.digest);
+
+        let _ = std::fs::remove_dir_all(ui_dir);
+    }
+
+    #[test]
+    fn hash_ui_sources_detects_content_change_with_same_length() {
+        let ui_dir = make_synthetic_ui_dir();
+        let before = hash_ui_sources(&ui_dir);
+
+        // Same byte length, different content: the length-only quick check
+        // still matches, so only the content digest can tell them apart.
+        std::fs::write(ui_dir.join("src/main.tsx"), b"console.log(2);")
+            .expect("file must be overwritable");
+        let after = hash_ui_sources(&ui_dir);
+
+        assert_ne!(before.digest, after.digest);
+        assert!(quick_lengths_match(&ui_dir, &before.files));
+
+        let _ = std::fs::remove_dir_all(ui_dir);
+    }
+
+    #[test]
+    fn quick_lengths_match_detects_added_file() {
+        let ui_dir = make_synthetic_ui_dir();
+        let before = hash_ui_sources(&ui_dir);
+
+        std::fs::write(ui_dir.join("src/extra.tsx"), b"export {};").expect("file must be writable");
+
+        assert!(!quick_lengths_match(&ui_dir, &before.files));
+
+        let _ = std::fs::remove_dir_all(ui_dir);
+    }
+
+    #[test]
+    fn quick_lengths_match_detects_size_change() {
+        let ui_dir = make_synthetic_ui_dir();
+        let before = hash_ui_sources(&ui_dir);
+
+        std::fs::write(
+            ui_dir.join("src/main.tsx"),
+            b"console.log('much longer now');",
+        )
+        .expect("file must be overwritable");
+
+        assert!(!quick_lengths_match(&ui_dir, &before.files));
+
+        let _ = std::fs::remove_dir_all(ui_dir);
+    }
+
+    #[test]
+    fn cached_source_state_round_trips_through_marker_file() {
+        let ui_dir = make_synthetic_ui_dir();
+        let cache = hash_ui_sources(&ui_dir);
+
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time must be after unix epoch")
+            .as_nanos();
+        let marker = std::env::temp_dir().join(format!("cory-ui-build-hash-test-{unique}"));
+
+        write_cached_source_state(&marker, &cache);
+        let read_back = read_cached_source_state(&marker).expect("marker must parse back");
+        assert_eq!(read_back.digest, cache.digest);
+        assert_eq!(read_back.files.len(), cache.files.len());
+
+        let _ = std::fs::remove_file(marker);
+        let _ = std::fs::remove_dir_all(ui_dir);
+    }
+
+    /// Creates a unique synthetic `dist/` tree with a couple of nested
+    /// files. Returns its path; callers must remove it when done.
+    fn make_synthetic_dist() -> PathBuf {
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time must be after unix epoch")
+            .as_nanos();
+        let dist = std::env::temp_dir().join(format!("cory-ui-manifest-test-{unique}"));
+        std::fs::create_dir_all(dist.join("assets")).expect("dist dir must be creatable");
+        std::fs::write(dist.join("index.html"), b"<html></html>").expect("file must be writable");
+        std::fs::write(dist.join("assets/app.js"), b"console.log('hi');")
+            .expect("file must be writable");
+        dist
+    }
+
+    #[test]
+    fn build_manifest_lists_every_file_with_size_and_hash() {
+        let dist = make_synthetic_dist();
+        let aggregate = compute_dist_hash(&dist);
+
+        let manifest = build_manifest(&dist, "1.2.3", &aggregate);
+
+        assert_eq!(manifest.version, "1.2.3");
+        assert_eq!(manifest.aggregate_sha512, aggregate);
+        assert_eq!(manifest.checksums.len(), 2);
+
+        let index = manifest
+            .checksums
+            .iter()
+            .find(|c| c.path == "index.html")
+            .expect("index.html must be present");
+        assert_eq!(index.size, b"<html></html>".len() as u64);
+
+        let mut hasher = Sha512::new();
+        hasher.update(b"<html></html>");
+        assert_eq!(index.sha512, hex::encode(hasher.finalize()));
+
+        let app_js = manifest
+            .checksums
+            .iter()
+            .find(|c| c.path == "assets/app.js")
+            .expect("assets/app.js must be present");
+        assert_eq!(app_js.size, b"console.log('hi');".len() as u64);
+
+        let _ = std::fs::remove_dir_all(dist);
+    }
+
+    #[test]
+    fn build_manifest_serializes_to_json() {
+        let dist = make_synthetic_dist();
+        let aggregate = compute_dist_hash(&dist);
+
+        let manifest = build_manifest(&dist, "1.2.3", &aggregate);
+        let json = serde_json::to_string(&manifest).expect("manifest must serialize");
+
+        assert!(json.contains("\"version\":\"1.2.3\""));
+        assert!(json.contains("\"aggregate_sha512\""));
+        assert!(json.contains("\"checksums\""));
+        assert!(json.contains("index.html"));
+
+        let _ = std::fs::remove_dir_all(dist);
+    }
+
+    #[test]
+    fn emit_manifest_is_gated_behind_env_var() {
+        let dist = make_synthetic_dist();
+        let aggregate = compute_dist_hash(&dist);
+
+        std::env::remove_var("CORY_UI_MANIFEST");
+        emit_manifest(&dist, "1.2.3", &aggregate);
+        assert!(!dist.join("manifest.json").exists());
+
+        let _ = std::fs::remove_dir_all(dist);
+    }
+}
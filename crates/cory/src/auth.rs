@@ -1,16 +1,26 @@
-use eyre::{Context, Result};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use eyre::{eyre, Context, Result};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use axum::{
     extract::Request,
-    http::StatusCode,
     middleware::Next,
     response::{IntoResponse, Response},
 };
 
+mod error;
+mod extractor;
+mod session;
+
+pub use error::AuthError;
+pub use extractor::{CookieExtractor, HeaderExtractor, TokenExtractor};
+pub use session::{InMemorySessionStore, RedisSessionStore, SessionRecord, SessionStore};
+
 pub const JWT_COOKIE_NAME: &str = "cory_refresh_token";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -32,45 +42,243 @@ pub struct Claims {
     pub session_id: String,
     /// Token type (access or refresh)
     pub token_type: TokenType,
+    /// Unique id of this specific token, so a single issued token (not
+    /// just the whole session) could in principle be targeted, even
+    /// though revocation today only acts at the `session_id` level.
+    pub jti: String,
+    /// Roles/scopes granted to this session, checked by [`RequireRole`].
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Expected issuer, checked by [`JwtManager::validate_token`] when
+    /// [`JwtConfig::issuer`] is set.
+    #[serde(rename = "iss", skip_serializing_if = "Option::is_none", default)]
+    pub issuer: Option<String>,
+    /// Expected audience, checked by [`JwtManager::validate_token`] when
+    /// [`JwtConfig::audience`] is set.
+    #[serde(rename = "aud", skip_serializing_if = "Option::is_none", default)]
+    pub audience: Option<String>,
+}
+
+/// Configuration for [`JwtManager::with_config`]: the signing algorithm —
+/// fixed explicitly rather than accepted from the token, to avoid the
+/// classic "alg: none" / algorithm-confusion pitfall — plus the issuer and
+/// audience claims to assert on every validated token.
+#[derive(Debug, Clone)]
+pub struct JwtConfig {
+    pub algorithm: Algorithm,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: Algorithm::HS256,
+            issuer: None,
+            audience: None,
+        }
+    }
+}
+
+/// Key id of the secret passed to [`JwtManager::new`]/[`JwtManager::with_config`].
+const DEFAULT_KID: &str = "default";
+
+/// A signing keyring: every key [`JwtManager::validate_token`] may need to
+/// verify an outstanding token against, plus which one new tokens are
+/// signed with.
+struct KeyState {
+    keys: HashMap<String, Vec<u8>>,
+    active_kid: String,
 }
 
 pub struct JwtManager {
-    secret: Vec<u8>,
+    /// Signing keys indexed by `kid`, so a token's header tells
+    /// [`Self::validate_token`] which key to verify it against while new
+    /// tokens are always signed with the active one.
+    keys: RwLock<KeyState>,
+    algorithm: Algorithm,
+    issuer: Option<String>,
+    audience: Option<String>,
     access_token_lifetime: Duration,
     refresh_token_lifetime: Duration,
+    /// Tracks active sessions so [`Self::sign_out`] can invalidate a
+    /// session's outstanding access and refresh tokens before their JWT
+    /// expiry — something a stateless JWT can't do on its own.
+    session_store: Arc<dyn SessionStore>,
+    /// Tried in order by [`Self::extract_token`] until one finds a token,
+    /// so the same API works from a CLI (`Authorization` header) and from
+    /// a browser (cookie) without separate routes.
+    token_extractors: Vec<Arc<dyn TokenExtractor>>,
 }
 
 impl JwtManager {
-    /// Creates a new JWT manager with the provided secret key.
+    /// Creates a new JWT manager with the provided secret key and an
+    /// in-memory session store. Use [`Self::with_session_store`] for a
+    /// shared store (e.g. [`RedisSessionStore`]) across server instances,
+    /// or [`Self::with_config`] to pin the algorithm, require `iss`/`aud`,
+    /// or seed a keyring for rotation.
     pub fn new(secret: Vec<u8>) -> Self {
+        Self::with_config(secret, JwtConfig::default())
+    }
+
+    /// Creates a new JWT manager whose secret is registered under
+    /// [`DEFAULT_KID`] and validated against `config`'s algorithm and
+    /// expected issuer/audience. Additional keys can be registered with
+    /// [`Self::add_key`] and promoted with [`Self::rotate_active_key`] for
+    /// zero-downtime secret rotation.
+    pub fn with_config(secret: Vec<u8>, config: JwtConfig) -> Self {
+        let mut keys = HashMap::new();
+        keys.insert(DEFAULT_KID.to_string(), secret);
+
         Self {
-            secret,
+            keys: RwLock::new(KeyState {
+                keys,
+                active_kid: DEFAULT_KID.to_string(),
+            }),
+            algorithm: config.algorithm,
+            issuer: config.issuer,
+            audience: config.audience,
             access_token_lifetime: Duration::from_secs(15 * 60), // 15 minutes
             refresh_token_lifetime: Duration::from_secs(7 * 24 * 60 * 60), // 7 days
+            session_store: Arc::new(InMemorySessionStore::new()),
+            token_extractors: default_token_extractors(),
         }
     }
 
+    /// Create a JWT manager backed by a custom [`SessionStore`] instead of
+    /// the in-memory default, e.g. a Redis-backed one shared across
+    /// multiple server instances.
+    pub fn with_session_store(secret: Vec<u8>, session_store: Arc<dyn SessionStore>) -> Self {
+        Self {
+            session_store,
+            ..Self::new(secret)
+        }
+    }
+
+    /// Create a JWT manager that tries `token_extractors` in order to pull
+    /// a token off an incoming request, instead of the default chain (the
+    /// `Authorization` header, then the [`JWT_COOKIE_NAME`] cookie).
+    pub fn with_token_extractors(
+        secret: Vec<u8>,
+        token_extractors: Vec<Arc<dyn TokenExtractor>>,
+    ) -> Self {
+        Self {
+            token_extractors,
+            ..Self::new(secret)
+        }
+    }
+
+    /// Registers `secret` under `kid` without making it the active signing
+    /// key, so tokens already issued under it keep validating via
+    /// [`Self::validate_token`] while new tokens continue to be signed with
+    /// the current active key. Call [`Self::rotate_active_key`] once the
+    /// new key should start signing.
+    pub fn add_key(&self, kid: impl Into<String>, secret: Vec<u8>) {
+        self.keys
+            .write()
+            .expect("jwt key state lock poisoned")
+            .keys
+            .insert(kid.into(), secret);
+    }
+
+    /// Promotes an already-registered `kid` (see [`Self::add_key`]) to the
+    /// active signing key. Existing tokens signed under the previous active
+    /// key keep validating as long as that `kid` remains registered.
+    pub fn rotate_active_key(&self, kid: &str) -> Result<()> {
+        let mut state = self.keys.write().expect("jwt key state lock poisoned");
+        if !state.keys.contains_key(kid) {
+            return Err(eyre!("unknown key id {kid}"));
+        }
+        state.active_kid = kid.to_string();
+        Ok(())
+    }
+
+    /// Tries each configured [`TokenExtractor`] in order and returns the
+    /// first token found.
+    pub fn extract_token(&self, headers: &axum::http::HeaderMap) -> Option<String> {
+        self.token_extractors
+            .iter()
+            .find_map(|extractor| extractor.extract(headers))
+    }
+
     /// Generates a new access token for a session (15 minute expiry).
-    pub fn generate_access_token(&self, session_id: String) -> Result<String> {
-        self.generate_token_with_lifetime(session_id, TokenType::Access, self.access_token_lifetime)
+    pub fn generate_access_token(&self, session_id: String, roles: Vec<String>) -> Result<String> {
+        let (token, _claims) = self.generate_token_with_lifetime(
+            session_id,
+            roles,
+            TokenType::Access,
+            self.access_token_lifetime,
+        )?;
+        Ok(token)
     }
 
     /// Generates a new refresh token for a session (7 day expiry).
-    pub fn generate_refresh_token(&self, session_id: String) -> Result<String> {
-        self.generate_token_with_lifetime(
+    pub fn generate_refresh_token(
+        &self,
+        session_id: String,
+        roles: Vec<String>,
+    ) -> Result<String> {
+        let (token, _claims) = self.generate_token_with_lifetime(
             session_id,
+            roles,
+            TokenType::Refresh,
+            self.refresh_token_lifetime,
+        )?;
+        Ok(token)
+    }
+
+    /// Issues a fresh access/refresh token pair for `session_id`, carrying
+    /// `roles` on both tokens, and records the session in the
+    /// [`SessionStore`] so [`Self::sign_out`] can later revoke both tokens
+    /// together before their JWT expiry.
+    pub async fn issue_token_pair(
+        &self,
+        session_id: String,
+        roles: Vec<String>,
+    ) -> Result<(String, String)> {
+        let access_token = self.generate_access_token(session_id.clone(), roles.clone())?;
+        let (refresh_token, refresh_claims) = self.generate_token_with_lifetime(
+            session_id.clone(),
+            roles,
             TokenType::Refresh,
             self.refresh_token_lifetime,
-        )
+        )?;
+
+        self.session_store
+            .record(
+                &session_id,
+                SessionRecord {
+                    jti: refresh_claims.jti,
+                    issued_at: refresh_claims.issued_at_time,
+                },
+            )
+            .await
+            .context("record session")?;
+
+        Ok((access_token, refresh_token))
     }
 
-    /// Generates a JWT token with the specified lifetime.
+    /// Revokes `session_id`, so any access or refresh token still carrying
+    /// it is rejected by [`Self::validate_token`] even though the JWTs
+    /// themselves remain cryptographically valid until they expire.
+    pub async fn sign_out(&self, session_id: &str) -> Result<()> {
+        self.session_store
+            .revoke(session_id)
+            .await
+            .context("revoke session")
+    }
+
+    /// Generates a JWT token with the specified lifetime, stamped with a
+    /// fresh `jti`. Returns the claims alongside the encoded token so
+    /// callers that need the `jti` (e.g. [`Self::issue_token_pair`]) don't
+    /// have to decode the token again.
     fn generate_token_with_lifetime(
         &self,
         session_id: String,
+        roles: Vec<String>,
         token_type: TokenType,
         lifetime: Duration,
-    ) -> Result<String> {
+    ) -> Result<(String, Claims)> {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .context("get current time")?
@@ -81,31 +289,96 @@ impl JwtManager {
             issued_at_time: now,
             session_id,
             token_type,
+            jti: generate_jti(),
+            roles,
+            issuer: self.issuer.clone(),
+            audience: self.audience.clone(),
         };
 
-        let token = encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(&self.secret),
-        )
-        .context("encode JWT token")?;
+        let (active_kid, secret) = {
+            let state = self.keys.read().expect("jwt key state lock poisoned");
+            let secret = state
+                .keys
+                .get(&state.active_kid)
+                .expect("active kid always present in keyring")
+                .clone();
+            (state.active_kid.clone(), secret)
+        };
 
-        Ok(token)
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(active_kid);
+
+        let token = encode(&header, &claims, &EncodingKey::from_secret(&secret))
+            .context("encode JWT token")?;
+
+        Ok((token, claims))
     }
 
-    /// Validates a JWT token and returns the claims if valid.
-    pub fn validate_token(&self, token: &str) -> Result<Claims> {
-        let token_data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(&self.secret),
-            &Validation::default(),
-        )
-        .context("decode and validate JWT token")?;
+    /// Validates a JWT token, returning the claims if it is well-formed,
+    /// signed by a key still present in the keyring, unexpired, matches the
+    /// configured issuer/audience (if set), and its session has not been
+    /// revoked via [`Self::sign_out`]. Returns a typed [`AuthError`] rather
+    /// than an opaque error so callers can tell an expired token (worth a
+    /// refresh) apart from a revoked one (must re-authenticate).
+    pub async fn validate_token(&self, token: &str) -> Result<Claims, AuthError> {
+        let header = decode_header(token).map_err(|_| AuthError::MalformedHeader)?;
+        let kid = header.kid.as_deref().unwrap_or(DEFAULT_KID);
+        let secret = self
+            .keys
+            .read()
+            .expect("jwt key state lock poisoned")
+            .keys
+            .get(kid)
+            .cloned()
+            .ok_or(AuthError::MalformedHeader)?;
 
-        Ok(token_data.claims)
+        let mut validation = Validation::new(self.algorithm);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if let Some(audience) = &self.audience {
+            validation.set_audience(&[audience]);
+        }
+
+        let token_data =
+            decode::<Claims>(token, &DecodingKey::from_secret(&secret), &validation).map_err(
+                |e| match e.kind() {
+                    jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::Expired,
+                    _ => AuthError::Invalid(e.to_string()),
+                },
+            )?;
+
+        let claims = token_data.claims;
+        let active = self
+            .session_store
+            .is_active(&claims.session_id)
+            .await
+            .map_err(|e| AuthError::Internal(e.to_string()))?;
+        if !active {
+            return Err(AuthError::Revoked);
+        }
+
+        Ok(claims)
     }
 }
 
+/// The default extractor chain: the `Authorization: Bearer` header first
+/// (CLI/API clients), falling back to the [`JWT_COOKIE_NAME`] cookie
+/// (browser clients).
+fn default_token_extractors() -> Vec<Arc<dyn TokenExtractor>> {
+    vec![
+        Arc::new(HeaderExtractor),
+        Arc::new(CookieExtractor::new(JWT_COOKIE_NAME)),
+    ]
+}
+
+/// Generates a random, URL-safe token id for [`Claims::jti`].
+fn generate_jti() -> String {
+    use rand::Rng;
+    let bytes: [u8; 16] = rand::thread_rng().gen();
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
 /// Generates a cryptographically secure random secret for JWT signing.
 pub fn generate_jwt_secret() -> Vec<u8> {
     use rand::Rng;
@@ -114,11 +387,13 @@ pub fn generate_jwt_secret() -> Vec<u8> {
     secret
 }
 
-/// Axum middleware that automatically validates JWT access tokens from the Authorization header.
+/// Axum middleware that automatically validates JWT access tokens.
 ///
-/// This middleware extracts the JWT access token from the Authorization header (Bearer scheme),
-/// validates it, and attaches the validated claims as an extension to the request.
-/// If validation fails, it returns a 401 Unauthorized.
+/// This middleware pulls a token off the request via the manager's
+/// [`TokenExtractor`] chain (by default the `Authorization: Bearer` header,
+/// then the [`JWT_COOKIE_NAME`] cookie), validates it, and attaches the
+/// validated claims as an extension to the request. If validation fails,
+/// it returns a 401 Unauthorized.
 ///
 /// Protected routes can access the claims via the `AuthenticatedUser` extractor.
 pub async fn jwt_auth_middleware(
@@ -129,51 +404,20 @@ pub async fn jwt_auth_middleware(
     let uri = request.uri().to_string();
     tracing::debug!("[JWT] Middleware invoked for: {}", uri);
 
-    // Attempt to extract the JWT token from the Authorization header.
-    let token = match request.headers().get(axum::http::header::AUTHORIZATION) {
-        Some(header_value) => match header_value.to_str() {
-            Ok(header_str) => {
-                if let Some(token) = header_str.strip_prefix("Bearer ") {
-                    token.to_string()
-                } else {
-                    tracing::warn!(
-                        "[JWT] Invalid Authorization header format for route: {}",
-                        uri
-                    );
-                    return (
-                        StatusCode::UNAUTHORIZED,
-                        "Invalid Authorization header format",
-                    )
-                        .into_response();
-                }
-            }
-            Err(_) => {
-                tracing::warn!(
-                    "[JWT] Invalid Authorization header encoding for route: {}",
-                    uri
-                );
-                return (
-                    StatusCode::UNAUTHORIZED,
-                    "Invalid Authorization header encoding",
-                )
-                    .into_response();
-            }
-        },
-        None => {
-            tracing::warn!("[JWT] Missing Authorization header for route: {}", uri);
-            return (StatusCode::UNAUTHORIZED, "Missing Authorization header").into_response();
-        }
+    let Some(token) = jwt_manager.extract_token(request.headers()) else {
+        tracing::warn!("[JWT] No token found (header or cookie) for route: {}", uri);
+        return AuthError::MissingToken.into_response();
     };
 
     // Validate the JWT token.
-    match jwt_manager.validate_token(&token) {
+    match jwt_manager.validate_token(&token).await {
         Ok(claims) => {
             if claims.token_type != TokenType::Access {
                 tracing::warn!(
                     "[JWT] Non-access token used for API access on route: {}",
                     uri
                 );
-                return (StatusCode::UNAUTHORIZED, "Invalid token type").into_response();
+                return AuthError::WrongTokenType.into_response();
             }
 
             tracing::debug!(
@@ -186,7 +430,7 @@ pub async fn jwt_auth_middleware(
         }
         Err(e) => {
             tracing::warn!("[JWT] Token invalid for route: {} — {}", uri, e);
-            (StatusCode::UNAUTHORIZED, "Invalid or expired access token").into_response()
+            e.into_response()
         }
     }
 }
@@ -200,7 +444,7 @@ impl<S> axum::extract::FromRequestParts<S> for AuthenticatedUser
 where
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, &'static str);
+    type Rejection = AuthError;
 
     async fn from_request_parts(
         parts: &mut axum::http::request::Parts,
@@ -211,10 +455,49 @@ where
             .get::<Claims>()
             .cloned()
             .map(AuthenticatedUser)
-            .ok_or((
-                StatusCode::UNAUTHORIZED,
-                "Missing authentication - JWT middleware not applied",
-            ))
+            .ok_or(AuthError::MissingToken)
+    }
+}
+
+/// Identifies a single role/scope string checked by [`RequireRole`].
+/// Implement this for a unit marker type per role so each protected route
+/// can pin its exact requirement at the type level, e.g.
+/// `RequireRole<Admin>`.
+pub trait Role {
+    const NAME: &'static str;
+}
+
+/// The `admin` role.
+pub struct Admin;
+
+impl Role for Admin {
+    const NAME: &'static str = "admin";
+}
+
+/// Extractor that requires the authenticated session to carry `R::NAME`
+/// among its [`Claims::roles`], rejecting with 403 Forbidden (not 401)
+/// when it doesn't — the request was authenticated, just not authorized
+/// for this route.
+pub struct RequireRole<R>(pub Claims, std::marker::PhantomData<R>);
+
+impl<S, R> axum::extract::FromRequestParts<S> for RequireRole<R>
+where
+    S: Send + Sync,
+    R: Role,
+{
+    type Rejection = AuthError;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let AuthenticatedUser(claims) = AuthenticatedUser::from_request_parts(parts, state).await?;
+
+        if claims.roles.iter().any(|role| role == R::NAME) {
+            Ok(Self(claims, std::marker::PhantomData))
+        } else {
+            Err(AuthError::InsufficientRole)
+        }
     }
 }
 
@@ -222,50 +505,196 @@ where
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_access_token_generation_and_validation() {
+    #[tokio::test]
+    async fn test_access_token_generation_and_validation() {
         let manager = JwtManager::new(generate_jwt_secret());
         let session_id = "test_session".to_string();
 
-        let token = manager.generate_access_token(session_id.clone()).unwrap();
-        let claims = manager.validate_token(&token).unwrap();
+        let (access_token, _refresh_token) = manager
+            .issue_token_pair(session_id.clone(), Vec::new())
+            .await
+            .unwrap();
+        let claims = manager.validate_token(&access_token).await.unwrap();
 
         assert_eq!(claims.session_id, session_id);
         assert_eq!(claims.token_type, TokenType::Access);
         assert!(claims.expiry_time > claims.issued_at_time);
     }
 
-    #[test]
-    fn test_refresh_token_generation_and_validation() {
+    #[tokio::test]
+    async fn test_refresh_token_generation_and_validation() {
         let manager = JwtManager::new(generate_jwt_secret());
         let session_id = "test_session".to_string();
 
-        let token = manager.generate_refresh_token(session_id.clone()).unwrap();
-        let claims = manager.validate_token(&token).unwrap();
+        let (_access_token, refresh_token) = manager
+            .issue_token_pair(session_id.clone(), Vec::new())
+            .await
+            .unwrap();
+        let claims = manager.validate_token(&refresh_token).await.unwrap();
 
         assert_eq!(claims.session_id, session_id);
         assert_eq!(claims.token_type, TokenType::Refresh);
         assert!(claims.expiry_time > claims.issued_at_time);
     }
 
-    #[test]
-    fn test_invalid_token_rejected() {
+    #[tokio::test]
+    async fn test_invalid_token_rejected() {
         let manager = JwtManager::new(generate_jwt_secret());
-        let result = manager.validate_token("invalid_token");
+        let result = manager.validate_token("invalid_token").await;
 
         assert!(result.is_err());
     }
 
-    #[test]
-    fn test_wrong_secret_rejected() {
+    #[tokio::test]
+    async fn test_wrong_secret_rejected() {
         let manager1 = JwtManager::new(generate_jwt_secret());
         let manager2 = JwtManager::new(generate_jwt_secret());
 
-        let token = manager1
-            .generate_access_token("session_test".to_string())
+        let (access_token, _refresh_token) = manager1
+            .issue_token_pair("session_test".to_string(), Vec::new())
+            .await
+            .unwrap();
+        let result = manager2.validate_token(&access_token).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sign_out_revokes_session() {
+        let manager = JwtManager::new(generate_jwt_secret());
+        let session_id = "test_session".to_string();
+
+        let (access_token, _refresh_token) = manager
+            .issue_token_pair(session_id.clone(), Vec::new())
+            .await
+            .unwrap();
+        manager.sign_out(&session_id).await.unwrap();
+
+        let result = manager.validate_token(&access_token).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_require_role_rejects_session_without_role() {
+        let manager = JwtManager::new(generate_jwt_secret());
+        let (access_token, _refresh_token) = manager
+            .issue_token_pair("session_test".to_string(), vec!["viewer".to_string()])
+            .await
+            .unwrap();
+        let claims = manager.validate_token(&access_token).await.unwrap();
+
+        assert!(!claims.roles.iter().any(|role| role == Admin::NAME));
+    }
+
+    #[tokio::test]
+    async fn test_require_role_accepts_session_with_role() {
+        let manager = JwtManager::new(generate_jwt_secret());
+        let (access_token, _refresh_token) = manager
+            .issue_token_pair("session_test".to_string(), vec!["admin".to_string()])
+            .await
+            .unwrap();
+        let claims = manager.validate_token(&access_token).await.unwrap();
+
+        assert!(claims.roles.iter().any(|role| role == Admin::NAME));
+    }
+
+    #[tokio::test]
+    async fn test_issuer_and_audience_stamped_and_checked() {
+        let manager = JwtManager::with_config(
+            generate_jwt_secret(),
+            JwtConfig {
+                algorithm: Algorithm::HS256,
+                issuer: Some("cory".to_string()),
+                audience: Some("cory-ui".to_string()),
+            },
+        );
+
+        let (access_token, _refresh_token) = manager
+            .issue_token_pair("session_test".to_string(), Vec::new())
+            .await
+            .unwrap();
+        let claims = manager.validate_token(&access_token).await.unwrap();
+
+        assert_eq!(claims.issuer.as_deref(), Some("cory"));
+        assert_eq!(claims.audience.as_deref(), Some("cory-ui"));
+    }
+
+    #[tokio::test]
+    async fn test_wrong_audience_rejected() {
+        let secret = generate_jwt_secret();
+        let issuer = JwtManager::with_config(
+            secret.clone(),
+            JwtConfig {
+                algorithm: Algorithm::HS256,
+                issuer: None,
+                audience: Some("cory-ui".to_string()),
+            },
+        );
+        let (access_token, _refresh_token) = issuer
+            .issue_token_pair("session_test".to_string(), Vec::new())
+            .await
             .unwrap();
-        let result = manager2.validate_token(&token);
+
+        let verifier = JwtManager::with_config(
+            secret,
+            JwtConfig {
+                algorithm: Algorithm::HS256,
+                issuer: None,
+                audience: Some("other-app".to_string()),
+            },
+        );
+        let result = verifier.validate_token(&access_token).await;
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_key_rotation_validates_old_and_new_tokens() {
+        let manager = JwtManager::new(generate_jwt_secret());
+        let (old_access_token, _old_refresh_token) = manager
+            .issue_token_pair("session_test".to_string(), Vec::new())
+            .await
+            .unwrap();
+
+        manager.add_key("v2", generate_jwt_secret());
+        manager.rotate_active_key("v2").unwrap();
+
+        let (new_access_token, _new_refresh_token) = manager
+            .issue_token_pair("session_test".to_string(), Vec::new())
+            .await
+            .unwrap();
+
+        assert!(manager.validate_token(&old_access_token).await.is_ok());
+        assert!(manager.validate_token(&new_access_token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rotate_to_unknown_key_rejected() {
+        let manager = JwtManager::new(generate_jwt_secret());
+        assert!(manager.rotate_active_key("does-not-exist").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_revoked_session_returns_revoked_error() {
+        let manager = JwtManager::new(generate_jwt_secret());
+        let session_id = "test_session".to_string();
+        let (access_token, _refresh_token) = manager
+            .issue_token_pair(session_id.clone(), Vec::new())
+            .await
+            .unwrap();
+        manager.sign_out(&session_id).await.unwrap();
+
+        let result = manager.validate_token(&access_token).await;
+
+        assert!(matches!(result, Err(AuthError::Revoked)));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_token_returns_malformed_header_error() {
+        let manager = JwtManager::new(generate_jwt_secret());
+
+        let result = manager.validate_token("not-a-jwt").await;
+
+        assert!(matches!(result, Err(AuthError::MalformedHeader)));
+    }
 }
@@ -0,0 +1,94 @@
+//! Structured errors for the JWT auth stack.
+//!
+//! [`super::jwt_auth_middleware`], [`super::AuthenticatedUser`],
+//! [`super::RequireRole`], and the `/api/v1/auth/refresh` handler all used
+//! to return ad-hoc `(StatusCode, &'static str)` tuples. [`AuthError`]
+//! replaces those with a typed variant per failure mode and a consistent
+//! JSON body, so a client can tell an expired access token (refreshable via
+//! `/api/v1/auth/refresh`) apart from a revoked one (must re-authenticate)
+//! instead of guessing from a freeform message string.
+
+use axum::response::{IntoResponse, Response};
+use axum::{http::StatusCode, Json};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    /// No token was found via the request's [`super::TokenExtractor`] chain.
+    #[error("missing authentication token")]
+    MissingToken,
+
+    /// The JWT's header (`alg`/`kid`) couldn't be parsed, or named a `kid`
+    /// not present in [`super::JwtManager`]'s keyring.
+    #[error("malformed authentication token header")]
+    MalformedHeader,
+
+    /// The token failed signature, issuer, or audience validation.
+    #[error("invalid authentication token: {0}")]
+    Invalid(String),
+
+    /// The token's `exp` claim is in the past. Kept distinct from
+    /// [`Self::Invalid`] so a client can attempt
+    /// `/api/v1/auth/refresh` instead of forcing a full re-login.
+    #[error("authentication token has expired")]
+    Expired,
+
+    /// A refresh token was presented where an access token was expected,
+    /// or vice versa.
+    #[error("wrong token type for this operation")]
+    WrongTokenType,
+
+    /// The token is well-formed and unexpired, but its session was
+    /// invalidated via [`super::JwtManager::sign_out`] (or superseded by a
+    /// refresh). Kept distinct from [`Self::Expired`] so a client knows
+    /// retrying via refresh won't help — the user must re-authenticate.
+    #[error("session has been revoked")]
+    Revoked,
+
+    /// The session authenticated fine but lacks a role required by
+    /// [`super::RequireRole`].
+    #[error("missing required role")]
+    InsufficientRole,
+
+    /// An unexpected failure unrelated to the presented token itself (e.g.
+    /// the session store or clock failed).
+    #[error("internal authentication error: {0}")]
+    Internal(String),
+}
+
+impl AuthError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::InsufficientRole => StatusCode::FORBIDDEN,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    /// Machine-readable identifier, stable across message wording changes,
+    /// so clients can switch on the failure kind (e.g. refresh on
+    /// `"EXPIRED"`, redirect to login on `"REVOKED"`) instead of parsing
+    /// the human-readable `error` message.
+    fn code(&self) -> &'static str {
+        match self {
+            Self::MissingToken => "MISSING_TOKEN",
+            Self::MalformedHeader => "MALFORMED_HEADER",
+            Self::Invalid(_) => "INVALID_TOKEN",
+            Self::Expired => "EXPIRED",
+            Self::WrongTokenType => "WRONG_TOKEN_TYPE",
+            Self::Revoked => "REVOKED",
+            Self::InsufficientRole => "INSUFFICIENT_ROLE",
+            Self::Internal(_) => "INTERNAL",
+        }
+    }
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let body = Json(serde_json::json!({
+            "error": self.to_string(),
+            "code": self.code(),
+        }));
+        (status, body).into_response()
+    }
+}
@@ -0,0 +1,90 @@
+//! Pluggable extraction of a bearer token from an incoming request.
+//!
+//! [`JwtManager`](super::JwtManager) tries its configured extractors in
+//! order and uses the first one that finds a token, so the same API can be
+//! driven from a CLI (`Authorization: Bearer ...`) and from a browser (a
+//! cookie) without separate routes for each.
+
+use axum::http::HeaderMap;
+
+/// Extracts a raw JWT string from request headers, or returns `None` if
+/// this extractor's source wasn't present.
+pub trait TokenExtractor: Send + Sync {
+    fn extract(&self, headers: &HeaderMap) -> Option<String>;
+}
+
+/// Extracts a token from `Authorization: Bearer <token>`.
+pub struct HeaderExtractor;
+
+impl TokenExtractor for HeaderExtractor {
+    fn extract(&self, headers: &HeaderMap) -> Option<String> {
+        let header_str = headers
+            .get(axum::http::header::AUTHORIZATION)?
+            .to_str()
+            .ok()?;
+        header_str.strip_prefix("Bearer ").map(str::to_string)
+    }
+}
+
+/// Extracts a token from a named cookie in the `Cookie` header.
+pub struct CookieExtractor {
+    cookie_name: String,
+}
+
+impl CookieExtractor {
+    pub fn new(cookie_name: impl Into<String>) -> Self {
+        Self {
+            cookie_name: cookie_name.into(),
+        }
+    }
+}
+
+impl TokenExtractor for CookieExtractor {
+    fn extract(&self, headers: &HeaderMap) -> Option<String> {
+        let cookie_header = headers.get(axum::http::header::COOKIE)?.to_str().ok()?;
+        cookie_header.split(';').find_map(|pair| {
+            let (key, value) = pair.trim().split_once('=')?;
+            (key == self.cookie_name).then(|| value.to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers_with(name: &'static str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn header_extractor_reads_bearer_token() {
+        let headers = headers_with("authorization", "Bearer abc.def.ghi");
+        assert_eq!(
+            HeaderExtractor.extract(&headers),
+            Some("abc.def.ghi".to_string())
+        );
+    }
+
+    #[test]
+    fn header_extractor_ignores_non_bearer_scheme() {
+        let headers = headers_with("authorization", "Basic abc");
+        assert_eq!(HeaderExtractor.extract(&headers), None);
+    }
+
+    #[test]
+    fn cookie_extractor_finds_named_cookie_among_several() {
+        let headers = headers_with("cookie", "foo=bar; cory_refresh_token=xyz; baz=qux");
+        let extractor = CookieExtractor::new("cory_refresh_token");
+        assert_eq!(extractor.extract(&headers), Some("xyz".to_string()));
+    }
+
+    #[test]
+    fn cookie_extractor_returns_none_when_absent() {
+        let headers = headers_with("cookie", "foo=bar");
+        let extractor = CookieExtractor::new("cory_refresh_token");
+        assert_eq!(extractor.extract(&headers), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! Server-side session tracking for [`super::JwtManager`].
+//!
+//! A JWT is validated without touching the server once it's issued, which
+//! means there's no ordinary way to invalidate one before it expires. This
+//! module adds a thin server-side side-channel keyed by `session_id`: every
+//! issued token pair records its issue time and the `jti` of the refresh
+//! token, and [`JwtManager::sign_out`](super::JwtManager::sign_out) removes
+//! that record, so [`JwtManager::validate_token`](super::JwtManager::validate_token)
+//! can reject an otherwise-valid token whose session has been revoked.
+
+use async_trait::async_trait;
+use eyre::Result;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// A recorded session: when it was issued, and the `jti` of the refresh
+/// token it was issued alongside (for future single-token introspection).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionRecord {
+    pub jti: String,
+    pub issued_at: u64,
+}
+
+/// Storage for active sessions, abstracted so a single-instance deployment
+/// can use [`InMemorySessionStore`] while a multi-instance one shares state
+/// via [`RedisSessionStore`].
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// Record (or refresh) an active session.
+    async fn record(&self, session_id: &str, record: SessionRecord) -> Result<()>;
+
+    /// Whether `session_id` is still active, i.e. has not been revoked.
+    async fn is_active(&self, session_id: &str) -> Result<bool>;
+
+    /// Revoke a session, e.g. on sign-out.
+    async fn revoke(&self, session_id: &str) -> Result<()>;
+}
+
+/// Default [`SessionStore`] backed by an in-process map. Sessions are lost
+/// on restart, which is fine for a single-instance deployment; use
+/// [`RedisSessionStore`] when running more than one server instance behind
+/// a shared session store.
+#[derive(Debug, Default)]
+pub struct InMemorySessionStore {
+    sessions: RwLock<HashMap<String, SessionRecord>>,
+}
+
+impl InMemorySessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SessionStore for InMemorySessionStore {
+    async fn record(&self, session_id: &str, record: SessionRecord) -> Result<()> {
+        self.sessions
+            .write()
+            .expect("session store lock poisoned")
+            .insert(session_id.to_string(), record);
+        Ok(())
+    }
+
+    async fn is_active(&self, session_id: &str) -> Result<bool> {
+        Ok(self
+            .sessions
+            .read()
+            .expect("session store lock poisoned")
+            .contains_key(session_id))
+    }
+
+    async fn revoke(&self, session_id: &str) -> Result<()> {
+        self.sessions
+            .write()
+            .expect("session store lock poisoned")
+            .remove(session_id);
+        Ok(())
+    }
+}
+
+/// [`SessionStore`] backed by Redis, for deployments running more than one
+/// server instance behind a shared session view. Sessions are stored as
+/// plain keys (`cory:session:{session_id}`) with a TTL matching the
+/// refresh-token lifetime, so a crashed instance's sessions still expire on
+/// their own instead of leaking forever.
+pub struct RedisSessionStore {
+    client: redis::Client,
+    ttl_seconds: u64,
+}
+
+impl RedisSessionStore {
+    /// Connects to `redis_url` (e.g. `redis://127.0.0.1/`), storing session
+    /// records with the given TTL (typically the refresh-token lifetime).
+    pub fn new(redis_url: &str, ttl_seconds: u64) -> Result<Self> {
+        let client = redis::Client::open(redis_url)?;
+        Ok(Self { client, ttl_seconds })
+    }
+
+    fn key(session_id: &str) -> String {
+        format!("cory:session:{session_id}")
+    }
+}
+
+#[async_trait]
+impl SessionStore for RedisSessionStore {
+    async fn record(&self, session_id: &str, record: SessionRecord) -> Result<()> {
+        use redis::AsyncCommands;
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let value = format!("{}:{}", record.jti, record.issued_at);
+        conn.set_ex::<_, _, ()>(Self::key(session_id), value, self.ttl_seconds)
+            .await?;
+        Ok(())
+    }
+
+    async fn is_active(&self, session_id: &str) -> Result<bool> {
+        use redis::AsyncCommands;
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        Ok(conn.exists(Self::key(session_id)).await?)
+    }
+
+    async fn revoke(&self, session_id: &str) -> Result<()> {
+        use redis::AsyncCommands;
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        conn.del::<_, ()>(Self::key(session_id)).await?;
+        Ok(())
+    }
+}
@@ -1,4 +1,35 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+
+/// Which backend to fetch transaction data from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "lowercase")]
+pub enum RpcBackend {
+    /// Bitcoin Core JSON-RPC, requires `-txindex` for confirmed ancestry.
+    Core,
+    /// Esplora-style REST API; works without a full node.
+    Esplora,
+}
+
+/// Traversal order for ancestry graph expansion once a limit cuts it short.
+/// Mirrors `cory_core::types::GraphStrategy`; kept as a separate CLI-facing
+/// enum so `cory-core` doesn't need a `clap` dependency.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum GraphStrategyArg {
+    /// Expand level-by-level, fetching each frontier in parallel.
+    BreadthFirst,
+    /// Expand the highest-value unexpanded outpoint first.
+    ValueWeighted,
+}
+
+impl From<GraphStrategyArg> for cory_core::types::GraphStrategy {
+    fn from(arg: GraphStrategyArg) -> Self {
+        match arg {
+            GraphStrategyArg::BreadthFirst => Self::BreadthFirst,
+            GraphStrategyArg::ValueWeighted => Self::ValueWeighted,
+        }
+    }
+}
 
 fn parse_nonzero_usize(s: &str) -> Result<usize, String> {
     let n: usize = s.parse().map_err(|e| format!("{e}"))?;
@@ -16,11 +47,29 @@ fn parse_nonzero_u32(s: &str) -> Result<u32, String> {
     Ok(n)
 }
 
+fn parse_nonzero_u64(s: &str) -> Result<u64, String> {
+    let n: u64 = s.parse().map_err(|e| format!("{e}"))?;
+    if n == 0 {
+        return Err("value must be at least 1".to_string());
+    }
+    Ok(n)
+}
+
 /// Cory — local Bitcoin transaction ancestry explorer with BIP-329 label editing.
 #[derive(Parser)]
 #[command(version, about)]
 pub struct Cli {
-    /// Bitcoin RPC connection target (HTTP URL).
+    /// Which backend to fetch transaction data from.
+    #[arg(
+        long,
+        default_value = "core",
+        env = "CORY_BACKEND",
+        help_heading = "RPC"
+    )]
+    pub backend: RpcBackend,
+
+    /// Bitcoin RPC connection target. An HTTP(S) Bitcoin Core RPC URL for
+    /// `--backend core`, or an Esplora API base URL for `--backend esplora`.
     #[arg(
         long,
         default_value = "http://127.0.0.1:8332",
@@ -41,14 +90,54 @@ pub struct Cli {
     #[arg(long, env = "CORY_RPC_COOKIE_FILE", help_heading = "RPC")]
     pub rpc_cookie_file: Option<std::path::PathBuf>,
 
-    /// Optional RPC request rate limit in requests/second (must be >= 1).
+    /// Additional Bitcoin Core RPC endpoints to fail over to if
+    /// `--connection` stops responding, e.g. a redundant node behind the
+    /// same reverse proxy. Only consulted for `--backend core`; reuses
+    /// `--rpc-user`/`--rpc-pass`/`--rpc-cookie-file` for every endpoint.
     #[arg(
         long,
-        env = "CORY_RPC_REQUESTS_PER_SECOND",
+        value_delimiter = ',',
+        env = "CORY_RPC_FALLBACK_CONNECTIONS",
+        help_heading = "RPC"
+    )]
+    pub rpc_fallback_connections: Vec<String>,
+
+    /// Chain Cory is connecting to. Only consulted for `--backend esplora`,
+    /// since the Esplora REST API has no equivalent of `getblockchaininfo`
+    /// to report it; Core backend always reports its own chain.
+    #[arg(
+        long,
+        default_value = "main",
+        env = "CORY_NETWORK",
+        help_heading = "RPC"
+    )]
+    pub network: String,
+
+    /// Optional RPC rate limit in credits/second (must be >= 1). Calls are
+    /// metered by a cost-weighted credit bucket rather than one credit per
+    /// request: cheap calls like `getblockheader`/`gettxout` cost 1,
+    /// `getrawtransaction` costs 2, `getblockchaininfo` costs 3, and a
+    /// batched request costs the sum of its calls' costs.
+    #[arg(
+        long,
+        env = "CORY_RPC_CREDITS_PER_SECOND",
+        value_parser = parse_nonzero_u32,
+        help_heading = "RPC"
+    )]
+    pub rpc_credits_per_second: Option<u32>,
+
+    /// Maximum burst size of the RPC credit bucket, in credits (must be
+    /// >= 1). Defaults to `--rpc-credits-per-second`, i.e. one second's
+    /// worth of burst. Must be at least as large as the costliest RPC
+    /// method's cost (3, for `getblockchaininfo`), or calls for that
+    /// method could never acquire enough credits.
+    #[arg(
+        long,
+        env = "CORY_RPC_CREDIT_CAPACITY",
         value_parser = parse_nonzero_u32,
         help_heading = "RPC"
     )]
-    pub rpc_requests_per_second: Option<u32>,
+    pub rpc_credit_capacity: Option<u32>,
 
     /// Maximum number of RPC calls per JSON-RPC batch chunk (must be >= 1).
     #[arg(
@@ -60,6 +149,26 @@ pub struct Cli {
     )]
     pub rpc_batch_chunk_size: usize,
 
+    /// Maximum number of JSON-RPC batch chunks in flight at once (must be
+    /// at least 1). Separate from `--rpc-credits-per-second`: the credit
+    /// bucket paces the overall request rate, while this bounds how many
+    /// requests can be outstanding at the same time.
+    #[arg(
+        long,
+        default_value = "4",
+        env = "CORY_RPC_BATCH_CONCURRENCY",
+        value_parser = parse_nonzero_usize,
+        help_heading = "RPC"
+    )]
+    pub rpc_batch_concurrency: usize,
+
+    /// Fetch transactions over Bitcoin Core's binary REST interface
+    /// (requires `-rest=1` on the node) instead of `getrawtransaction`,
+    /// falling back to JSON-RPC automatically when REST doesn't have a
+    /// transaction. Ignored for the Esplora backend.
+    #[arg(long, env = "CORY_RPC_REST_TRANSPORT", help_heading = "RPC")]
+    pub rpc_rest_transport: bool,
+
     /// Maximum concurrent RPC calls (must be at least 1).
     #[arg(
         long,
@@ -69,6 +178,33 @@ pub struct Cli {
     )]
     pub rpc_concurrency: usize,
 
+    /// Per-request RPC timeout in seconds (must be at least 1), covering
+    /// the time from sending a request to finishing reading its response.
+    /// On expiry, `build_ancestry` and friends see a
+    /// `CoreError::Rpc(RpcError::Timeout { phase: TimeoutPhase::Response })`.
+    #[arg(
+        long,
+        default_value = "30",
+        env = "CORY_RPC_TIMEOUT_SECS",
+        value_parser = parse_nonzero_u64,
+        help_heading = "RPC"
+    )]
+    pub rpc_timeout_secs: u64,
+
+    /// RPC TCP/TLS connect timeout in seconds (must be at least 1), kept
+    /// shorter than `--rpc-timeout-secs` by default so an unreachable node
+    /// (nothing answering the handshake) fails fast instead of waiting out
+    /// the full per-request budget. On expiry, callers see a
+    /// `CoreError::Rpc(RpcError::Timeout { phase: TimeoutPhase::Connect })`.
+    #[arg(
+        long,
+        default_value = "10",
+        env = "CORY_RPC_CONNECT_TIMEOUT_SECS",
+        value_parser = parse_nonzero_u64,
+        help_heading = "RPC"
+    )]
+    pub rpc_connect_timeout_secs: u64,
+
     /// Address to bind the web server to.
     #[arg(long, default_value = "127.0.0.1", help_heading = "Server")]
     pub bind: String,
@@ -77,6 +213,110 @@ pub struct Cli {
     #[arg(long, default_value = "3080", help_heading = "Server")]
     pub port: u16,
 
+    /// Additional origin allowed to make cross-origin requests (repeatable),
+    /// on top of the server's own `http://{bind}:{port}` origin, e.g.
+    /// `--cors-allowed-origin https://wallet.example.com`. The host
+    /// component may contain `*` globs, e.g.
+    /// `--cors-allowed-origin https://*.example.com`; the scheme and port
+    /// are still matched exactly.
+    #[arg(long, help_heading = "Server")]
+    pub cors_allowed_origin: Vec<String>,
+
+    /// Suffix an origin's host may end with to be allowed cross-origin,
+    /// e.g. `.example.com` allows `https://wallet.example.com`. Unset by
+    /// default, since a suffix match is broader than an exact allowlist.
+    #[arg(long, help_heading = "Server")]
+    pub cors_allowed_origin_suffix: Option<String>,
+
+    /// Reflect any `Origin` back in `Access-Control-Allow-Origin`, ignoring
+    /// `--cors-allowed-origin`/`--cors-allowed-origin-suffix`. Refused at
+    /// startup when combined with `--cors-allow-credentials`, since the
+    /// CORS spec forbids pairing credentialed responses with a wildcard or
+    /// reflect-all origin.
+    #[arg(long, help_heading = "Server")]
+    pub cors_allow_any_origin: bool,
+
+    /// Emit `Access-Control-Allow-Credentials: true`, letting cross-origin
+    /// requests carry cookies or the `Authorization` header. Requires a
+    /// concrete origin allowlist; see `--cors-allow-any-origin`.
+    #[arg(long, help_heading = "Server")]
+    pub cors_allow_credentials: bool,
+
+    /// How long, in seconds, a browser may cache a CORS preflight response
+    /// before re-checking it. Pass `0` to omit `Access-Control-Max-Age`
+    /// entirely, forcing a preflight on every cross-origin request.
+    #[arg(long, default_value = "3600", help_heading = "Server")]
+    pub cors_max_age_secs: u64,
+
+    /// Response header exposed to browser JavaScript via
+    /// `Access-Control-Expose-Headers` (repeatable), e.g. a rate-limit or
+    /// request-id header. Defaults to `etag`, used by the label file
+    /// export/download endpoints.
+    #[arg(long, default_value = "etag", help_heading = "Server")]
+    pub cors_exposed_header: Vec<String>,
+
+    /// `Host` header value allowed to reach the API (repeatable), guarding
+    /// against DNS-rebinding attacks. Supports the same `*` host globs as
+    /// `--cors-allowed-origin`, e.g. `localhost:*` or `127.0.0.1:*`; a bare
+    /// `*` allows any host. Defaults to just the server's own bound
+    /// `--bind:--port` address.
+    #[arg(long, help_heading = "Server")]
+    pub allowed_host: Vec<String>,
+
+    /// JSONL file to persist search history to (repeated searches overwrite
+    /// their prior entry). If unset, history is kept in memory only and
+    /// lost on restart.
+    #[arg(long, help_heading = "Server")]
+    pub history_file: Option<std::path::PathBuf>,
+
+    /// Maximum number of search history entries to retain; the oldest are
+    /// evicted first.
+    #[arg(
+        long,
+        default_value = "1000",
+        value_parser = parse_nonzero_usize,
+        help_heading = "Server"
+    )]
+    pub history_max_entries: usize,
+
+    /// Minimum response body size, in bytes, before gzip/deflate
+    /// compression is applied. Responses smaller than this (and already
+    /// compressed formats like `labels.zip`) are sent uncompressed.
+    #[arg(long, default_value = "1024", help_heading = "Server")]
+    pub compression_min_bytes: u16,
+
+    /// Domain to provision an ACME/Let's Encrypt certificate for. When set,
+    /// the server terminates TLS itself and serves HTTPS on `--port`
+    /// instead of plaintext HTTP; leave unset and put a reverse proxy in
+    /// front of `cory` if that's already how TLS is handled. Requires
+    /// `--tls-contact-email`.
+    #[arg(long, help_heading = "TLS")]
+    pub tls_domain: Option<String>,
+
+    /// Contact email passed to the ACME directory when creating the
+    /// account, e.g. for Let's Encrypt's certificate-expiry notices.
+    /// Required when `--tls-domain` is set.
+    #[arg(long, help_heading = "TLS")]
+    pub tls_contact_email: Option<String>,
+
+    /// ACME directory URL to request certificates from. Defaults to Let's
+    /// Encrypt's production directory; point this at Let's Encrypt's
+    /// staging directory (or a local Pebble instance) to test the ACME
+    /// flow without hitting production rate limits.
+    #[arg(
+        long,
+        default_value = "https://acme-v02.api.letsencrypt.org/directory",
+        help_heading = "TLS"
+    )]
+    pub tls_acme_directory_url: String,
+
+    /// Directory the ACME account key and issued certificate/key are
+    /// cached in, keyed by domain. Reused on restart so a still-valid
+    /// certificate isn't re-ordered, and reused across renewals so the
+    /// account doesn't need to be recreated.
+    #[arg(long, default_value = "./acme-cache", help_heading = "TLS")]
+    pub tls_cache_dir: std::path::PathBuf,
+
     /// Editable label directories (repeatable). Labels loaded from these
     /// directories are editable in the UI and auto-flushed to disk.
     #[arg(long, help_heading = "Labels")]
@@ -87,6 +327,79 @@ pub struct Cli {
     #[arg(long, help_heading = "Labels")]
     pub labels_ro: Vec<std::path::PathBuf>,
 
+    /// Maximum number of `--labels-rw`/`--labels-ro` rescans
+    /// (`POST /api/v1/label/jobs/rescan`) run concurrently.
+    #[arg(long, default_value = "2", help_heading = "Labels")]
+    pub max_concurrent_rescans: usize,
+
+    /// How long, in seconds, a finished rescan job's report is kept before
+    /// being evicted from the job store.
+    #[arg(long, default_value = "300", help_heading = "Labels")]
+    pub rescan_job_ttl_secs: u64,
+
+    /// Watch every `--labels-rw`/`--labels-ro` directory for external
+    /// changes (another process editing, `git checkout`, a sync tool) and
+    /// incrementally reload them, instead of requiring a manual
+    /// `POST /api/v1/label/jobs/rescan`. Applied changes are published on
+    /// `GET /api/v1/label/events`.
+    #[arg(long, help_heading = "Labels")]
+    pub watch_labels: bool,
+
+    /// S3-compatible bucket to persist browser-created label files to,
+    /// instead of the local filesystem, e.g. `cory-labels`. Unset by
+    /// default, which keeps browser-created label files on local disk.
+    #[arg(long, env = "CORY_LABEL_S3_BUCKET", help_heading = "Labels")]
+    pub label_s3_bucket: Option<String>,
+
+    /// Endpoint of the S3-compatible store, e.g.
+    /// `https://s3.us-east-1.amazonaws.com` or a self-hosted MinIO URL.
+    /// Required if `--label-s3-bucket` is set.
+    #[arg(long, env = "CORY_LABEL_S3_ENDPOINT", help_heading = "Labels")]
+    pub label_s3_endpoint: Option<String>,
+
+    /// Key prefix under the bucket to namespace this instance's label
+    /// files, e.g. `prod` if the bucket is shared with other instances.
+    #[arg(
+        long,
+        default_value = "",
+        env = "CORY_LABEL_S3_PREFIX",
+        help_heading = "Labels"
+    )]
+    pub label_s3_prefix: String,
+
+    /// SigV4 region to sign requests with. Self-hosted stores that ignore
+    /// the region still require some value to sign against.
+    #[arg(
+        long,
+        default_value = "us-east-1",
+        env = "CORY_LABEL_S3_REGION",
+        help_heading = "Labels"
+    )]
+    pub label_s3_region: String,
+
+    /// Access key ID for the S3-compatible store. Required if
+    /// `--label-s3-bucket` is set.
+    #[arg(long, env = "CORY_LABEL_S3_ACCESS_KEY_ID", help_heading = "Labels")]
+    pub label_s3_access_key_id: Option<String>,
+
+    /// Secret access key for the S3-compatible store. Required if
+    /// `--label-s3-bucket` is set.
+    #[arg(long, env = "CORY_LABEL_S3_SECRET_ACCESS_KEY", help_heading = "Labels")]
+    pub label_s3_secret_access_key: Option<String>,
+
+    /// Use path-style addressing (`{endpoint}/{bucket}/{key}`) instead of
+    /// virtual-host-style (`{bucket}.{endpoint}/{key}`). Most self-hosted
+    /// S3-compatible stores (MinIO, Ceph RGW) require this.
+    #[arg(long, env = "CORY_LABEL_S3_PATH_STYLE", help_heading = "Labels")]
+    pub label_s3_path_style: bool,
+
+    /// Treat the S3-compatible bucket as read-only, e.g. when
+    /// `--label-s3-access-key-id` only has `GetObject`/`ListBucket`
+    /// permissions. Label files loaded from it come back non-editable
+    /// instead of failing the first time a save is attempted.
+    #[arg(long, env = "CORY_LABEL_S3_READ_ONLY", help_heading = "Labels")]
+    pub label_s3_read_only: bool,
+
     /// Maximum ancestry graph depth.
     #[arg(long, default_value = "50", help_heading = "Graph Limits")]
     pub max_depth: usize,
@@ -99,6 +412,26 @@ pub struct Cli {
     #[arg(long, default_value = "2000", help_heading = "Graph Limits")]
     pub max_edges: usize,
 
+    /// Traversal order to apply once a graph limit cuts expansion short.
+    #[arg(
+        long,
+        value_enum,
+        default_value = "breadth-first",
+        help_heading = "Graph Limits"
+    )]
+    pub graph_strategy: GraphStrategyArg,
+
+    /// Maximum number of ancestry graph builds the async job queue
+    /// (`POST /api/v1/graph/tx/{txid}/job`) runs concurrently, independent
+    /// of `--rpc-concurrency` which bounds RPC fan-out within one build.
+    #[arg(long, default_value = "4", help_heading = "Graph Limits")]
+    pub max_concurrent_graph_jobs: usize,
+
+    /// How long, in seconds, a finished graph-build job's result is kept
+    /// before being evicted from the job store.
+    #[arg(long, default_value = "300", help_heading = "Graph Limits")]
+    pub graph_job_ttl_secs: u64,
+
     /// Maximum number of transactions to keep in the in-memory cache.
     /// Older entries are evicted in LRU order.
     #[arg(long, default_value = "10000", help_heading = "Cache")]
@@ -107,4 +440,24 @@ pub struct Cli {
     /// Maximum number of prevout entries to keep in the in-memory cache.
     #[arg(long, default_value = "50000", help_heading = "Cache")]
     pub cache_prevout_cap: usize,
+
+    /// Output descriptors to register as owned by this wallet (repeatable).
+    /// Supports `pkh(<xpub>)`, `wpkh(<xpub>)`, and `sh(wpkh(<xpub>))`.
+    #[arg(long, help_heading = "Wallet")]
+    pub wallet_descriptor: Vec<String>,
+
+    /// Bare extended public keys to register as owned by this wallet
+    /// (repeatable), treated as native segwit (`wpkh`) wallets.
+    #[arg(long, help_heading = "Wallet")]
+    pub wallet_xpub: Vec<String>,
+
+    /// Number of addresses to derive per chain (external/internal) for
+    /// each registered wallet descriptor/xpub.
+    #[arg(
+        long,
+        default_value = "100",
+        value_parser = parse_nonzero_u32,
+        help_heading = "Wallet"
+    )]
+    pub wallet_gap_limit: u32,
 }
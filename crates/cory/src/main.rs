@@ -1,5 +1,7 @@
+mod auth;
 mod cli;
 mod server;
+mod tls;
 
 use std::sync::Arc;
 
@@ -7,7 +9,7 @@ use bitcoin::Network;
 use clap::Parser;
 use eyre::{eyre, WrapErr};
 
-use cory_core::labels::LabelStore;
+use cory_core::labels::{LabelStore, S3Config, S3Transport, S3UrlStyle};
 
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
@@ -30,23 +32,69 @@ async fn main() -> eyre::Result<()> {
         hex_encode(bytes)
     };
 
-    // Connect to Bitcoin Core RPC and verify the connection succeeds
-    // before starting the server.
-    let rpc: Arc<dyn cory_core::rpc::BitcoinRpc> = Arc::new(cory_core::rpc::HttpRpcClient::new(
-        &args.rpc_url,
-        args.rpc_user.as_deref(),
-        args.rpc_pass.as_deref(),
-    ));
+    // Connect to the configured RPC backend and verify the connection
+    // succeeds before starting the server.
+    let rpc: Arc<dyn cory_core::rpc::BitcoinRpc> = match args.backend {
+        cli::RpcBackend::Core => {
+            let mut endpoint = cory_core::rpc::RpcEndpoint::new(&args.connection);
+            if let (Some(user), Some(pass)) = (args.rpc_user.as_deref(), args.rpc_pass.as_deref()) {
+                endpoint = endpoint.with_user_pass(user, pass);
+            }
+            if let Some(cookie_file) = args.rpc_cookie_file.as_deref() {
+                endpoint = endpoint.with_cookie_file(cookie_file);
+            }
+            let mut endpoints = vec![endpoint];
+            for fallback in &args.rpc_fallback_connections {
+                let mut fallback_endpoint = cory_core::rpc::RpcEndpoint::new(fallback);
+                if let (Some(user), Some(pass)) =
+                    (args.rpc_user.as_deref(), args.rpc_pass.as_deref())
+                {
+                    fallback_endpoint = fallback_endpoint.with_user_pass(user, pass);
+                }
+                if let Some(cookie_file) = args.rpc_cookie_file.as_deref() {
+                    fallback_endpoint = fallback_endpoint.with_cookie_file(cookie_file);
+                }
+                endpoints.push(fallback_endpoint);
+            }
+
+            let client = cory_core::rpc::HttpRpcClient::new(
+                endpoints,
+                args.rpc_credits_per_second,
+                args.rpc_credit_capacity,
+                args.rpc_batch_chunk_size,
+                args.rpc_timeout_secs,
+                args.rpc_connect_timeout_secs,
+            )
+            .and_then(|client| client.with_max_concurrent_batch_chunks(args.rpc_batch_concurrency))
+            .map(|client| client.with_rest_transport(args.rpc_rest_transport))
+            .context("construct Bitcoin Core RPC client")?;
+
+            // Fail fast on a node whose version/capabilities Cory hasn't
+            // been validated against, before any graph build gets far
+            // enough to produce a confusing "missing transaction" error.
+            client
+                .ensure_node_supported()
+                .await
+                .context("verify Bitcoin Core node version and capabilities")?;
+
+            Arc::new(client)
+        }
+        cli::RpcBackend::Esplora => Arc::new(
+            cory_core::rpc::EsploraClient::new(&args.connection, &args.network)
+                .context("construct Esplora RPC client")?,
+        ),
+    };
 
     let chain_info = rpc.get_blockchain_info().await.map_err(|err| {
-        let message = format_rpc_connect_error(&args.rpc_url, &err.to_string());
-        eyre!(message).wrap_err("while attempting to connect to Bitcoin Core RPC")
+        let message = format_rpc_connect_error(&args.connection, &err.to_string());
+        eyre!(message).wrap_err("while attempting to connect to the RPC backend")
     })?;
 
     tracing::info!(
+        backend = ?args.backend,
         chain = %chain_info.chain,
         blocks = chain_info.blocks,
-        "connected to Bitcoin Core"
+        "connected to RPC backend"
     );
     if chain_info.pruned {
         tracing::warn!("node is pruned — fetching old transactions may fail");
@@ -54,8 +102,10 @@ async fn main() -> eyre::Result<()> {
 
     // Verify txindex is available by attempting to fetch a confirmed transaction.
     // Without txindex, getrawtransaction only works for mempool transactions,
-    // making graph traversal fail on confirmed ancestors.
-    if chain_info.blocks > 0 {
+    // making graph traversal fail on confirmed ancestors. Esplora always
+    // returns full prevout data regardless of txindex, so this only
+    // applies to the Core backend.
+    if args.backend == cli::RpcBackend::Core && chain_info.blocks > 0 {
         check_txindex_available(rpc.as_ref()).await;
     }
 
@@ -64,61 +114,190 @@ async fn main() -> eyre::Result<()> {
         args.cache_tx_cap,
         args.cache_prevout_cap,
     ));
-    let mut label_store = match &args.label_dir {
-        Some(dir) => {
-            let store =
-                LabelStore::with_persistence(dir).context("load persisted label directory")?;
-            tracing::info!(path = %dir.display(), "loaded persisted label store");
-            store
+    let mut label_store = match build_label_s3_transport(&args)? {
+        Some(transport) => {
+            tracing::info!(
+                bucket = %args.label_s3_bucket.as_deref().unwrap_or_default(),
+                "persisting browser-created label files to S3-compatible store"
+            );
+            LabelStore::with_transport(transport)
         }
         None => LabelStore::new(),
     };
 
-    // Load label pack directories.
-    for dir in &args.label_pack_dir {
+    // Load the configured editable and read-only label directories.
+    for dir in &args.labels_rw {
         label_store
-            .load_pack_dir(dir)
-            .context("load label pack directory")?;
-        tracing::info!(path = %dir.display(), "loaded label pack");
+            .load_rw_dir(dir)
+            .context("load editable label directory")?;
+        tracing::info!(path = %dir.display(), "loaded editable label directory");
+    }
+    for dir in &args.labels_ro {
+        label_store
+            .load_ro_dir(dir)
+            .context("load read-only label directory")?;
+        tracing::info!(path = %dir.display(), "loaded read-only label directory");
     }
 
     let graph_limits = cory_core::GraphLimits {
         max_depth: args.max_depth,
         max_nodes: args.max_nodes,
         max_edges: args.max_edges,
+        strategy: args.graph_strategy.into(),
+    };
+
+    let network = map_chain_to_network(&chain_info.chain)?;
+
+    let mut history_store = cory_core::history::HistoryStore::new(args.history_max_entries);
+    if let Some(history_file) = &args.history_file {
+        history_store
+            .load_file(history_file)
+            .context("load persisted search history")?;
+        tracing::info!(path = %history_file.display(), "loaded persisted search history");
+    }
+
+    let mut wallet_registry =
+        cory_core::wallet::WalletRegistry::new(network, args.wallet_gap_limit);
+    for descriptor in &args.wallet_descriptor {
+        wallet_registry
+            .register_descriptor(descriptor)
+            .context("register wallet descriptor")?;
+    }
+    for xpub in &args.wallet_xpub {
+        wallet_registry
+            .register_xpub(xpub)
+            .context("register wallet xpub")?;
+    }
+
+    let labels = Arc::new(tokio::sync::RwLock::new(label_store));
+    let (label_changes, _) = tokio::sync::broadcast::channel(1024);
+    let label_watchers = if args.watch_labels {
+        server::spawn_label_watchers(
+            labels.clone(),
+            &args.labels_rw,
+            &args.labels_ro,
+            label_changes.clone(),
+        )
+    } else {
+        Vec::new()
     };
 
     let state = server::AppState {
         rpc,
         cache,
-        labels: Arc::new(tokio::sync::RwLock::new(label_store)),
+        labels,
         api_token: api_token.clone(),
         default_limits: graph_limits,
         rpc_concurrency: args.rpc_concurrency,
-        network: map_chain_to_network(&chain_info.chain)?,
+        network,
+        history: Arc::new(tokio::sync::RwLock::new(history_store)),
+        wallet_registry: Arc::new(wallet_registry),
+        jwt_manager: Arc::new(auth::JwtManager::new(auth::generate_jwt_secret())),
+        metrics: Arc::new(server::Metrics::new()),
+        compression_min_bytes: args.compression_min_bytes,
+        jobs: Arc::new(server::JobStore::new(
+            args.max_concurrent_graph_jobs,
+            std::time::Duration::from_secs(args.graph_job_ttl_secs),
+        )),
+        previews: Arc::new(server::PreviewStore::new()),
+        rescan_jobs: Arc::new(server::RescanJobStore::new(
+            args.max_concurrent_rescans,
+            std::time::Duration::from_secs(args.rescan_job_ttl_secs),
+        )),
+        labels_rw_dirs: args.labels_rw.clone(),
+        labels_ro_dirs: args.labels_ro.clone(),
+        label_changes,
+        label_watchers,
+    };
+
+    let tls_config = match &args.tls_domain {
+        Some(domain) => {
+            let contact_email = args
+                .tls_contact_email
+                .clone()
+                .ok_or_else(|| eyre!("--tls-contact-email is required when --tls-domain is set"))?;
+            Some(tls::TlsConfig {
+                domain: domain.clone(),
+                contact_email,
+                acme_directory_url: args.tls_acme_directory_url.clone(),
+                cache_dir: args.tls_cache_dir.clone(),
+            })
+        }
+        None => None,
     };
 
     let bind_addr = format!("{}:{}", args.bind, args.port);
-    let origin = format!("http://{}:{}", args.bind, args.port);
-    let router = server::build_router(state, &origin);
+    let origin = format!(
+        "{}://{}:{}",
+        if tls_config.is_some() { "https" } else { "http" },
+        args.bind,
+        args.port
+    );
+    let origins = if args.cors_allow_any_origin {
+        server::AllowedOrigins::All
+    } else if args.cors_allowed_origin.is_empty() && args.cors_allowed_origin_suffix.is_none() {
+        server::AllowedOrigins::None
+    } else {
+        server::AllowedOrigins::Some {
+            extra_origins: args.cors_allowed_origin.clone(),
+            allowed_origin_suffix: args.cors_allowed_origin_suffix.clone(),
+        }
+    };
+    let cors_config = server::CorsConfig {
+        origins,
+        allow_credentials: args.cors_allow_credentials,
+        max_age_secs: (args.cors_max_age_secs > 0).then_some(args.cors_max_age_secs),
+        exposed_headers: args.cors_exposed_header.clone(),
+    };
+    let host_config = server::HostConfig {
+        allowed_hosts: args.allowed_host.clone(),
+    };
+    let router = server::build_router(state, &origin, cors_config, host_config)
+        .wrap_err("failed to build the web server's router")?;
 
     if args.bind == "0.0.0.0" {
         tracing::warn!("server is bound to 0.0.0.0 — it is accessible from the network");
     }
 
-    println!();
-    println!("  Cory is running:");
-    println!("    URL:       http://{bind_addr}?token={api_token}");
-    println!();
+    match tls_config {
+        Some(tls_config) => {
+            let pending = tls::PendingChallenges::default();
+            let router = router.merge(tls::challenge_router(pending.clone()));
 
-    let listener = tokio::net::TcpListener::bind(&bind_addr)
-        .await
-        .context("bind TCP listener")?;
+            tracing::info!(domain = %tls_config.domain, "provisioning TLS certificate via ACME");
+            let rustls_config = tls::provision(&tls_config, &pending)
+                .await
+                .wrap_err("failed to provision TLS certificate")?;
+            tls::spawn_renewal_task(tls_config, rustls_config.clone(), pending);
 
-    tracing::info!("listening on {bind_addr}");
-    axum::serve(listener, router)
-        .await
-        .context("run HTTP server")?;
+            println!();
+            println!("  Cory is running:");
+            println!("    URL:       https://{bind_addr}?token={api_token}");
+            println!();
+
+            tracing::info!("listening on {bind_addr} (TLS)");
+            let socket_addr = bind_addr.parse().context("parse TLS bind address")?;
+            axum_server::bind_rustls(socket_addr, rustls_config)
+                .serve(router.into_make_service())
+                .await
+                .context("run HTTPS server")?;
+        }
+        None => {
+            println!();
+            println!("  Cory is running:");
+            println!("    URL:       http://{bind_addr}?token={api_token}");
+            println!();
+
+            let listener = tokio::net::TcpListener::bind(&bind_addr)
+                .await
+                .context("bind TCP listener")?;
+
+            tracing::info!("listening on {bind_addr}");
+            axum::serve(listener, router)
+                .await
+                .context("run HTTP server")?;
+        }
+    }
 
     Ok(())
 }
@@ -135,8 +314,12 @@ fn hex_encode(bytes: impl AsRef<[u8]>) -> String {
 /// but works on regtest/testnet/signet).
 async fn check_txindex_available(rpc: &dyn cory_core::rpc::BitcoinRpc) {
     // Use a zero txid as a probe — it will always fail without txindex.
-    // We specifically care about the error type: "No such mempool or blockchain
-    // transaction" means txindex is missing, other errors are unrelated.
+    // We specifically care about the error *code*: `getrawtransaction`
+    // reports both "not found" and "txindex disabled" as
+    // RPC_INVALID_ADDRESS_OR_KEY (-5), so we key off that rather than
+    // matching on message text, which is fragile across Core versions
+    // and locales (and this client already normalizes -5 to
+    // `CoreError::TxNotFound`).
     let probe_txid: bitcoin::Txid =
         "0000000000000000000000000000000000000000000000000000000000000001"
             .parse()
@@ -146,19 +329,16 @@ async fn check_txindex_available(rpc: &dyn cory_core::rpc::BitcoinRpc) {
         Ok(_) => {
             // Unexpectedly succeeded — txindex is definitely available.
         }
-        Err(e) => {
-            let msg = e.to_string();
-            // "No such mempool or blockchain transaction" is the Bitcoin Core
-            // error when txindex is disabled and the tx is not in mempool.
+        Err(cory_core::CoreError::TxNotFound(_)) => {
             // We can't distinguish "not found because txindex is off" from
             // "not found because the txid doesn't exist", so we emit an
             // info-level message rather than an error.
-            if msg.contains("No such mempool") || msg.contains("not found") {
-                tracing::info!(
-                    "txindex probe inconclusive — if graph queries fail for confirmed \
-                     transactions, ensure bitcoind is running with -txindex=1"
-                );
-            }
+            tracing::info!(
+                "txindex probe inconclusive — if graph queries fail for confirmed \
+                 transactions, ensure bitcoind is running with -txindex=1"
+            );
+        }
+        Err(_) => {
             // Other errors (network, auth) are already covered by the
             // initial getblockchaininfo check.
         }
@@ -199,6 +379,52 @@ fn format_rpc_connect_error(rpc_url: &str, source_error: &str) -> String {
     lines.join("\n")
 }
 
+/// Builds an `S3Transport` from `--label-s3-*` args if `--label-s3-bucket`
+/// is set, or `Ok(None)` if browser-created label files should stay on
+/// local disk. Returns an `Arc<dyn Transport>` rather than the concrete
+/// type so the caller doesn't need to know which backend was selected.
+fn build_label_s3_transport(
+    args: &cli::Cli,
+) -> eyre::Result<Option<Arc<dyn cory_core::labels::Transport>>> {
+    let Some(bucket) = args.label_s3_bucket.clone() else {
+        return Ok(None);
+    };
+
+    let endpoint = args
+        .label_s3_endpoint
+        .clone()
+        .ok_or_else(|| eyre!("--label-s3-endpoint is required when --label-s3-bucket is set"))?;
+    let access_key_id = args.label_s3_access_key_id.clone().ok_or_else(|| {
+        eyre!("--label-s3-access-key-id is required when --label-s3-bucket is set")
+    })?;
+    let secret_access_key = args.label_s3_secret_access_key.clone().ok_or_else(|| {
+        eyre!("--label-s3-secret-access-key is required when --label-s3-bucket is set")
+    })?;
+
+    let config = S3Config {
+        endpoint,
+        bucket,
+        prefix: args.label_s3_prefix.clone(),
+        region: args.label_s3_region.clone(),
+        access_key_id,
+        secret_access_key,
+        url_style: if args.label_s3_path_style {
+            S3UrlStyle::Path
+        } else {
+            S3UrlStyle::VirtualHost
+        },
+    };
+
+    let transport = S3Transport::new(config);
+    if args.label_s3_read_only {
+        Ok(Some(Arc::new(cory_core::labels::ReadOnlyTransport::new(
+            transport,
+        ))))
+    } else {
+        Ok(Some(Arc::new(transport)))
+    }
+}
+
 fn map_chain_to_network(chain: &str) -> eyre::Result<Network> {
     match chain {
         "main" => Ok(Network::Bitcoin),
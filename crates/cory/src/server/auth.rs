@@ -1,17 +1,104 @@
-use axum::http::HeaderMap;
+use axum::extract::State;
+use axum::http::{HeaderMap, HeaderValue};
+use axum::Json;
+use serde::Serialize;
+
+use std::sync::atomic::Ordering;
+
+use crate::auth::{AuthError, TokenType, JWT_COOKIE_NAME};
 
 use super::error::AppError;
+use super::{Metrics, SharedState};
 
-pub(super) fn check_auth(expected_token: &str, headers: &HeaderMap) -> Result<(), AppError> {
-    let token = headers
+/// Accepts either the legacy `X-API-Token` header or a standard
+/// `Authorization: Bearer <token>` header as an equivalent credential.
+pub(super) fn check_auth(
+    expected_token: &str,
+    headers: &HeaderMap,
+    metrics: &Metrics,
+) -> Result<(), AppError> {
+    let presented = headers
         .get("x-api-token")
         .and_then(|v| v.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(axum::http::header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+        })
         .unwrap_or("");
 
-    if token != expected_token {
+    if presented != expected_token {
+        metrics.auth_failures_total.fetch_add(1, Ordering::Relaxed);
         return Err(AppError::Unauthorized(
-            "invalid or missing X-API-Token".to_string(),
+            "invalid or missing API token; send it as X-API-Token or Authorization: Bearer <token>"
+                .to_string(),
         ));
     }
     Ok(())
 }
+
+// ==============================================================================
+// Refresh
+// ==============================================================================
+
+#[derive(Serialize)]
+pub(super) struct RefreshResponse {
+    access_token: String,
+}
+
+/// `POST /api/v1/auth/refresh`: exchanges the `cory_refresh_token` cookie
+/// for a new access/refresh pair.
+///
+/// The old refresh token's session is revoked before the new pair is
+/// issued, so a refresh token can only ever be used once — a stolen token
+/// replayed after the legitimate client has already rotated it is
+/// rejected, since by then its session no longer validates.
+pub(super) async fn refresh_token(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Result<(HeaderMap, Json<RefreshResponse>), AuthError> {
+    let refresh_token = read_cookie(&headers, JWT_COOKIE_NAME).ok_or(AuthError::MissingToken)?;
+
+    let claims = state.jwt_manager.validate_token(&refresh_token).await?;
+
+    if claims.token_type != TokenType::Refresh {
+        return Err(AuthError::WrongTokenType);
+    }
+
+    // Invalidate the session the presented token belongs to before issuing
+    // its replacement, so the old refresh token can't be replayed.
+    state
+        .jwt_manager
+        .sign_out(&claims.session_id)
+        .await
+        .map_err(|e| AuthError::Internal(e.to_string()))?;
+
+    // The replacement pair gets a fresh session id: re-recording the revoked
+    // one would make the old tokens validate again.
+    let new_session_id = uuid::Uuid::new_v4().to_string();
+    let (access_token, new_refresh_token) = state
+        .jwt_manager
+        .issue_token_pair(new_session_id, claims.roles)
+        .await
+        .map_err(|e| AuthError::Internal(e.to_string()))?;
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        axum::http::header::SET_COOKIE,
+        HeaderValue::from_str(&format!(
+            "{JWT_COOKIE_NAME}={new_refresh_token}; HttpOnly; Path=/; SameSite=Strict"
+        ))
+        .map_err(|e| AuthError::Internal(e.to_string()))?,
+    );
+
+    Ok((response_headers, Json(RefreshResponse { access_token })))
+}
+
+fn read_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    let cookie_header = headers.get(axum::http::header::COOKIE)?.to_str().ok()?;
+    cookie_header.split(';').find_map(|pair| {
+        let (key, value) = pair.trim().split_once('=')?;
+        (key == name).then(|| value.to_string())
+    })
+}
@@ -11,22 +11,76 @@ use cory_core::labels::LabelStoreError;
 pub(crate) enum AppError {
     BadRequest(String),
     Unauthorized(String),
+    /// The request was well-formed and authenticated but is refused outright
+    /// — e.g. a `Host` header outside the configured allowlist. Kept
+    /// distinct from [`Self::Unauthorized`] since it isn't about missing or
+    /// invalid credentials.
+    Forbidden(String),
     NotFound(String),
     Conflict(String),
+    /// The upstream Bitcoin RPC backend failed or refused the request
+    /// (transport error, node still warming up, pruned data missing).
+    BadGateway(String),
     Internal(String),
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let is_unauthorized = matches!(self, Self::Unauthorized(_));
         let (status, message) = match self {
             Self::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             Self::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            Self::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             Self::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             Self::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            Self::BadGateway(msg) => (StatusCode::BAD_GATEWAY, msg),
             Self::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
         };
 
-        (status, Json(serde_json::json!({ "error": message }))).into_response()
+        let mut response = (status, Json(serde_json::json!({ "error": message }))).into_response();
+        // RFC 7235 requires a challenge alongside a 401, so generated
+        // clients know which scheme to retry with.
+        if is_unauthorized {
+            response.headers_mut().insert(
+                axum::http::header::WWW_AUTHENTICATE,
+                axum::http::HeaderValue::from_static("Bearer"),
+            );
+        }
+        response
+    }
+}
+
+impl AppError {
+    /// The human-readable message carried by any variant, discarding which
+    /// HTTP status it maps to — used by the job queue (see `super::jobs`)
+    /// to record a `Failed { msg }` job status, which has no HTTP response
+    /// of its own to carry the status code.
+    pub(super) fn into_message(self) -> String {
+        match self {
+            Self::BadRequest(msg)
+            | Self::Unauthorized(msg)
+            | Self::Forbidden(msg)
+            | Self::NotFound(msg)
+            | Self::Conflict(msg)
+            | Self::BadGateway(msg)
+            | Self::Internal(msg) => msg,
+        }
+    }
+
+    /// The status this variant maps to in [`Self::into_response`] — exposed
+    /// separately so callers that report per-item results inline (see
+    /// `super::labels::batch_update_local_label_file`) can surface it
+    /// without wrapping the whole response in an HTTP error.
+    pub(super) fn status_code(&self) -> StatusCode {
+        match self {
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::BadGateway(_) => StatusCode::BAD_GATEWAY,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
     }
 }
 
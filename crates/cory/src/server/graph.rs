@@ -1,15 +1,18 @@
 use std::collections::HashMap;
 
 use axum::extract::{Path, Query, State};
-use axum::http::HeaderMap;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use serde::{Deserialize, Serialize};
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
 use cory_core::enrich;
+use cory_core::history::HistoryEntry;
 use cory_core::labels::{Bip329Record, Bip329Type, LabelFile, LabelFileKind, LabelStore};
-use cory_core::types::GraphLimits;
+use cory_core::types::{GraphLimits, GraphStrategy};
+use cory_core::wallet::WalletRegistry;
 use cory_core::AncestryGraph;
 
 use super::auth::check_auth;
@@ -17,7 +20,11 @@ use super::error::AppError;
 use super::limits::{HARD_MAX_DEPTH, HARD_MAX_EDGES, HARD_MAX_NODES};
 use super::SharedState;
 
-const MAX_HISTORY_ENTRIES: usize = 1000;
+/// Synthetic label file identity used for wallet-derived ownership labels
+/// injected by [`build_graph_enrichments`] — not backed by any real
+/// `LabelFile` in the label store.
+const WALLET_DERIVED_FILE_ID: &str = "wallet-derived";
+const WALLET_DERIVED_LABEL: &str = "your wallet";
 
 // ==============================================================================
 // DTOs
@@ -25,44 +32,57 @@ const MAX_HISTORY_ENTRIES: usize = 1000;
 
 #[derive(Deserialize)]
 pub(super) struct GraphQuery {
-    max_depth: Option<usize>,
-    max_nodes: Option<usize>,
-    max_edges: Option<usize>,
+    pub(super) max_depth: Option<usize>,
+    pub(super) max_nodes: Option<usize>,
+    pub(super) max_edges: Option<usize>,
+    /// Traversal order once a limit cuts expansion short. Defaults to the
+    /// server's configured `default_limits.strategy`.
+    pub(super) strategy: Option<GraphStrategy>,
+    /// Toggles the common-input-ownership clustering pass. Defaults to on.
+    pub(super) cluster: Option<bool>,
 }
 
 /// Graph response extends the core `AncestryGraph` with enrichment data
 /// (fees, RBF signaling, locktime info) and labels for each node.
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub(super) struct GraphResponse {
     #[serde(flatten)]
-    graph: AncestryGraph,
-    enrichments: HashMap<String, TxEnrichment>,
-    labels_by_type: GraphLabelsByType,
+    pub(super) graph: AncestryGraph,
+    pub(super) enrichments: HashMap<String, TxEnrichment>,
+    pub(super) labels_by_type: GraphLabelsByType,
     input_address_refs: HashMap<String, String>,
     output_address_refs: HashMap<String, String>,
     address_occurrences: HashMap<String, Vec<String>>,
+    /// Address → cluster id, from the common-input-ownership heuristic.
+    /// Addresses that were never grouped with another still get their own
+    /// singleton cluster id. Empty when `?cluster=false` was requested.
+    clusters: HashMap<String, u32>,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub(super) struct TxEnrichment {
-    fee_sats: Option<u64>,
-    feerate_sat_vb: Option<f64>,
-    rbf_signaling: bool,
-    locktime: enrich::LocktimeInfo,
+    pub(super) fee_sats: Option<u64>,
+    pub(super) feerate_sat_vb: Option<f64>,
+    pub(super) rbf_signaling: bool,
+    pub(super) locktime: enrich::LocktimeInfo,
+    /// Whether any of this transaction's outputs were derived from a
+    /// registered wallet descriptor/xpub, so the UI can highlight "your
+    /// coins" in the ancestry graph.
+    pub(super) self_owned: bool,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub(super) struct LabelEntry {
     file_id: String,
     file_name: String,
     file_kind: LabelFileKind,
     editable: bool,
-    label: String,
+    pub(super) label: String,
 }
 
-#[derive(Default, Serialize)]
+#[derive(Clone, Default, Serialize)]
 pub(super) struct GraphLabelsByType {
-    tx: HashMap<String, Vec<LabelEntry>>,
+    pub(super) tx: HashMap<String, Vec<LabelEntry>>,
     input: HashMap<String, Vec<LabelEntry>>,
     output: HashMap<String, Vec<LabelEntry>>,
     addr: HashMap<String, Vec<LabelEntry>>,
@@ -78,8 +98,100 @@ pub(super) async fn get_graph(
     Path(txid_str): Path<String>,
     Query(query): Query<GraphQuery>,
 ) -> Result<Json<GraphResponse>, AppError> {
-    check_auth(&state.api_token, &headers)?;
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+
+    let (txid, limits, graph) = fetch_graph(&state, &txid_str, &query).await?;
+
+    // Record successful ancestry searches in the durable history store.
+    // Repeated txids overwrite their prior entry instead of creating
+    // duplicates.
+    let searched_at = OffsetDateTime::now_utc()
+        .format(&Rfc3339)
+        .map_err(|e| AppError::Internal(format!("format search timestamp: {e}")))?;
+    let mut history = state.history.write().await;
+    history
+        .record(HistoryEntry {
+            txid: txid.to_string(),
+            searched_at,
+            network: state.network.to_string(),
+            limits,
+            node_count: graph.stats.node_count,
+            edge_count: graph.stats.edge_count,
+        })
+        .map_err(|e| AppError::Internal(format!("record search history: {e}")))?;
+    drop(history);
+
+    let response = build_graph_response(&state, graph, query.cluster.unwrap_or(true)).await;
+    Ok(Json(response))
+}
+
+/// Streams the full BIP-329 label set discovered while traversing a
+/// transaction's ancestry graph as newline-delimited JSON, including labels
+/// attached to addresses derived from output scripts during traversal (not
+/// just the directly-labeled tx/input/output refs). Unlike
+/// `/api/v1/label/{file_id}/export`, which exports one label file verbatim,
+/// this flattens labels merged across every loaded file for the refs that
+/// actually appear in the graph, so the export reflects what the UI shows.
+pub(super) async fn export_graph_labels(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(txid_str): Path<String>,
+    Query(query): Query<GraphQuery>,
+) -> Result<Response, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+
+    let (txid, _limits, graph) = fetch_graph(&state, &txid_str, &query).await?;
+
+    let label_store = state.labels.read().await;
+    let enrichments = build_graph_enrichments(
+        &graph,
+        &label_store,
+        state.network,
+        &state.wallet_registry,
+        false,
+    );
+
+    let content = labels_by_type_to_jsonl(&enrichments.labels_by_type);
 
+    let mut response = (StatusCode::OK, content).into_response();
+    response.headers_mut().insert(
+        axum::http::header::CONTENT_TYPE,
+        axum::http::HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    let disposition = format!("attachment; filename=\"{txid}-labels.jsonl\"");
+    let disposition_header = axum::http::HeaderValue::from_str(&disposition)
+        .map_err(|e| AppError::Internal(format!("invalid content disposition header: {e}")))?;
+    response
+        .headers_mut()
+        .insert(axum::http::header::CONTENT_DISPOSITION, disposition_header);
+    Ok(response)
+}
+
+/// Parses the txid, validates/resolves the requested limits, and builds the
+/// ancestry graph. Shared by [`get_graph`] and [`export_graph_labels`] so
+/// both endpoints apply the same bounds and build the same graph for a
+/// given txid/query.
+async fn fetch_graph(
+    state: &SharedState,
+    txid_str: &str,
+    query: &GraphQuery,
+) -> Result<(bitcoin::Txid, GraphLimits, AncestryGraph), AppError> {
+    let (txid, limits) = resolve_graph_request(state, txid_str, query)?;
+    let graph = run_graph_build(state, txid, &limits, None).await?;
+    Ok((txid, limits, graph))
+}
+
+/// Parses `txid_str` and resolves the effective [`GraphLimits`] for a graph
+/// request: a requested `max_depth`/`max_nodes`/`max_edges` of 0 or above
+/// the server's hard ceiling is rejected, and an unset one falls back to
+/// the configured default capped at that ceiling. Split out from
+/// [`run_graph_build`] so the job queue in `super::jobs` can validate a
+/// request synchronously, before enqueuing a background build.
+pub(super) fn resolve_graph_request(
+    state: &SharedState,
+    txid_str: &str,
+    query: &GraphQuery,
+) -> Result<(bitcoin::Txid, GraphLimits), AppError> {
     let txid: bitcoin::Txid = txid_str
         .parse()
         .map_err(|e| AppError::BadRequest(format!("invalid txid: {e}")))?;
@@ -101,42 +213,103 @@ pub(super) async fn get_graph(
             .max_edges
             .unwrap_or(state.default_limits.max_edges)
             .min(HARD_MAX_EDGES),
+        strategy: query.strategy.unwrap_or(state.default_limits.strategy),
     };
 
-    let graph = cory_core::graph::build_ancestry(
-        state.rpc.as_ref(),
-        &state.cache,
-        txid,
-        &limits,
-        state.rpc_concurrency,
-    )
-    .await
-    .map_err(|e| map_graph_build_error(txid, e))?;
-
-    // Record successful ancestry searches for the server-lifetime history panel.
-    // Repeated txids overwrite their timestamp instead of creating duplicates.
-    let searched_at = OffsetDateTime::now_utc()
-        .format(&Rfc3339)
-        .map_err(|e| AppError::Internal(format!("format search timestamp: {e}")))?;
-    let mut history = state.history.write().await;
-    record_search_history(
-        &mut history,
-        txid.to_string(),
-        searched_at,
-        MAX_HISTORY_ENTRIES,
-    );
+    Ok((txid, limits))
+}
 
+/// Builds the ancestry graph for an already-resolved `txid`/`limits` pair,
+/// recording the same build-duration/node/edge metrics regardless of
+/// whether the build runs inline (`progress: None`) or as a background job
+/// (`progress: Some(..)`, see [`super::jobs`]).
+pub(super) async fn run_graph_build(
+    state: &SharedState,
+    txid: bitcoin::Txid,
+    limits: &GraphLimits,
+    progress: Option<&dyn cory_core::graph::BuildProgress>,
+) -> Result<AncestryGraph, AppError> {
+    let counting_rpc = cory_core::rpc::CountingRpc::new(state.rpc.as_ref());
+
+    let build_started = std::time::Instant::now();
+    let result = match progress {
+        Some(progress) => {
+            cory_core::graph::build_ancestry_with_progress(
+                &counting_rpc,
+                &state.cache,
+                txid,
+                limits,
+                state.rpc_concurrency,
+                progress,
+            )
+            .await
+        }
+        None => {
+            cory_core::graph::build_ancestry(
+                &counting_rpc,
+                &state.cache,
+                txid,
+                limits,
+                state.rpc_concurrency,
+            )
+            .await
+        }
+    };
+    state
+        .metrics
+        .graph_build_duration_seconds
+        .observe(build_started.elapsed().as_secs_f64());
+    state
+        .metrics
+        .graph_rpc_calls
+        .observe(counting_rpc.call_count() as f64);
+
+    match result {
+        Ok(graph) => {
+            state.metrics.graph_requests_total.inc("ok");
+            state
+                .metrics
+                .graph_traversal_nodes
+                .observe(graph.stats.node_count as f64);
+            state
+                .metrics
+                .graph_traversal_edges
+                .observe(graph.stats.edge_count as f64);
+            Ok(graph)
+        }
+        Err(e) => {
+            state.metrics.graph_requests_total.inc("error");
+            Err(map_graph_build_error(txid, e))
+        }
+    }
+}
+
+/// Runs the enrichment pipeline over a built graph and assembles the final
+/// [`GraphResponse`]. Shared by [`get_graph`] and the job-result handler in
+/// [`super::jobs`] so both return identically shaped responses.
+pub(super) async fn build_graph_response(
+    state: &SharedState,
+    graph: AncestryGraph,
+    cluster_addresses: bool,
+) -> GraphResponse {
     let label_store = state.labels.read().await;
-    let enrichments = build_graph_enrichments(&graph, &label_store, state.network);
+    let enrichments = build_graph_enrichments(
+        &graph,
+        &label_store,
+        state.network,
+        &state.wallet_registry,
+        cluster_addresses,
+    );
 
-    Ok(Json(GraphResponse {
+    GraphResponse {
         graph,
         enrichments: enrichments.tx_enrichments,
         labels_by_type: enrichments.labels_by_type,
         input_address_refs: enrichments.input_address_refs,
         output_address_refs: enrichments.output_address_refs,
         address_occurrences: enrichments.address_occurrences,
-    }))
+        clusters: enrichments.clusters,
+    }
 }
 
 // ==============================================================================
@@ -168,32 +341,6 @@ fn map_graph_build_error(txid: bitcoin::Txid, err: cory_core::CoreError) -> AppE
     }
 }
 
-fn record_search_history(
-    history: &mut HashMap<String, String>,
-    txid: String,
-    searched_at: String,
-    max_entries: usize,
-) {
-    if let Some(existing) = history.get_mut(&txid) {
-        *existing = searched_at;
-        return;
-    }
-
-    if history.len() >= max_entries {
-        // RFC3339 UTC strings sort chronologically; removing the smallest
-        // timestamp evicts the oldest entry.
-        if let Some(oldest_txid) = history
-            .iter()
-            .min_by(|a, b| a.1.cmp(b.1))
-            .map(|(existing_txid, _)| existing_txid.clone())
-        {
-            history.remove(&oldest_txid);
-        }
-    }
-
-    history.insert(txid, searched_at);
-}
-
 // ==============================================================================
 // Enrichment Pipeline
 // ==============================================================================
@@ -205,14 +352,19 @@ struct GraphEnrichments {
     input_address_refs: HashMap<String, String>,
     output_address_refs: HashMap<String, String>,
     address_occurrences: HashMap<String, Vec<String>>,
+    clusters: HashMap<String, u32>,
 }
 
 /// Walks every node and edge in the graph to compute fee/RBF enrichments,
 /// collect labels by type, and derive address references for inputs/outputs.
+/// When `cluster_addresses` is set, also runs the common-input-ownership
+/// clustering pass over the addresses discovered during the scan.
 fn build_graph_enrichments(
     graph: &AncestryGraph,
     label_store: &LabelStore,
     network: bitcoin::Network,
+    wallet_registry: &WalletRegistry,
+    cluster_addresses: bool,
 ) -> GraphEnrichments {
     let mut tx_enrichments = HashMap::new();
     let mut labels_by_type = GraphLabelsByType::default();
@@ -225,6 +377,10 @@ fn build_graph_enrichments(
         let fee = enrich::compute_fee(node);
         let feerate = fee.map(|f| enrich::compute_feerate(f, node.vsize));
         let has_non_final = node.inputs.iter().any(|i| i.sequence < 0xFFFFFFFF);
+        let self_owned = node
+            .outputs
+            .iter()
+            .any(|o| wallet_registry.owns_script(o.script_pub_key.as_script()));
 
         tx_enrichments.insert(
             txid_str.clone(),
@@ -233,6 +389,7 @@ fn build_graph_enrichments(
                 feerate_sat_vb: feerate,
                 rbf_signaling: enrich::is_rbf_signaling(node),
                 locktime: enrich::locktime_info(node.locktime, has_non_final),
+                self_owned,
             },
         );
 
@@ -255,11 +412,14 @@ fn build_graph_enrichments(
 
         for (vout, output) in node.outputs.iter().enumerate() {
             let output_ref = format!("{txid_str}:{vout}");
+            let owned = wallet_registry.owns_script(output.script_pub_key.as_script());
             let output_labels = label_store.get_all_labels_for(Bip329Type::Output, &output_ref);
-            if !output_labels.is_empty() {
-                labels_by_type
-                    .output
-                    .insert(output_ref.clone(), to_label_entries(output_labels));
+            if !output_labels.is_empty() || owned {
+                let mut entries = to_label_entries(output_labels);
+                if owned {
+                    entries.push(wallet_derived_label_entry());
+                }
+                labels_by_type.output.insert(output_ref.clone(), entries);
             }
 
             if let Ok(address) =
@@ -277,8 +437,12 @@ fn build_graph_enrichments(
                 {
                     let addr_labels =
                         label_store.get_all_labels_for(Bip329Type::Addr, &address_ref);
-                    if !addr_labels.is_empty() {
-                        entry.insert(to_label_entries(addr_labels));
+                    if !addr_labels.is_empty() || owned {
+                        let mut entries = to_label_entries(addr_labels);
+                        if owned {
+                            entries.push(wallet_derived_label_entry());
+                        }
+                        entry.insert(entries);
                     }
                 }
             }
@@ -317,19 +481,192 @@ fn build_graph_enrichments(
         input_address_refs.insert(input_ref, address.to_string());
     }
 
+    let clusters = if cluster_addresses {
+        cluster_addresses_by_common_input_ownership(
+            graph,
+            &input_address_refs,
+            &address_occurrences,
+        )
+    } else {
+        HashMap::new()
+    };
+
     GraphEnrichments {
         tx_enrichments,
         labels_by_type,
         input_address_refs,
         output_address_refs,
         address_occurrences,
+        clusters,
     }
 }
 
+// ==============================================================================
+// Address Clustering (common-input-ownership heuristic)
+// ==============================================================================
+
+/// Disjoint-set structure over a fixed universe of `0..n` indices, with path
+/// compression but no union-by-rank — the sets here are small (bounded by
+/// the graph's address count), so the simpler implementation is plenty fast.
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, x: usize) -> usize {
+        if self.parent[x] != x {
+            self.parent[x] = self.find(self.parent[x]);
+        }
+        self.parent[x]
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a != root_b {
+            self.parent[root_a] = root_b;
+        }
+    }
+}
+
+/// Groups addresses likely controlled by one entity using the common-input-
+/// ownership heuristic: every address spent as an input of the same
+/// transaction is assumed to share an owner, so all of a transaction's
+/// spending-input addresses (resolved via `input_address_refs`, the edge map
+/// built from funding outputs) are unioned into one set. Transactions that
+/// look like CoinJoins are skipped entirely, since the heuristic's core
+/// assumption doesn't hold for them and applying it would merge unrelated
+/// participants into one cluster.
+///
+/// Returns every address that appeared in the graph mapped to a cluster id;
+/// an address that was never unioned with another still gets its own
+/// singleton cluster.
+fn cluster_addresses_by_common_input_ownership(
+    graph: &AncestryGraph,
+    input_address_refs: &HashMap<String, String>,
+    address_occurrences: &HashMap<String, Vec<String>>,
+) -> HashMap<String, u32> {
+    // Sorting first makes index assignment (and therefore the resulting
+    // cluster ids) deterministic across calls, rather than depending on
+    // HashMap iteration order.
+    let mut addresses: Vec<&String> = address_occurrences.keys().collect();
+    addresses.sort();
+
+    let mut index_of: HashMap<&str, usize> = HashMap::with_capacity(addresses.len());
+    for (index, address) in addresses.iter().enumerate() {
+        index_of.insert(address.as_str(), index);
+    }
+
+    let mut union_find = UnionFind::new(addresses.len());
+    for (txid, node) in &graph.nodes {
+        if looks_like_coinjoin(node) {
+            continue;
+        }
+
+        let txid_str = txid.to_string();
+        let mut input_indices = Vec::new();
+        for vin in 0..node.inputs.len() {
+            let input_ref = format!("{txid_str}:{vin}");
+            if let Some(index) = input_address_refs
+                .get(&input_ref)
+                .and_then(|address| index_of.get(address.as_str()))
+            {
+                input_indices.push(*index);
+            }
+        }
+
+        for pair in input_indices.windows(2) {
+            union_find.union(pair[0], pair[1]);
+        }
+    }
+
+    let mut cluster_id_of_root: HashMap<usize, u32> = HashMap::new();
+    let mut clusters = HashMap::with_capacity(addresses.len());
+    for (index, address) in addresses.into_iter().enumerate() {
+        let root = union_find.find(index);
+        let next_id = cluster_id_of_root.len() as u32;
+        let cluster_id = *cluster_id_of_root.entry(root).or_insert(next_id);
+        clusters.insert(address.clone(), cluster_id);
+    }
+    clusters
+}
+
+/// Heuristic CoinJoin detector: a transaction with at least 3 inputs and at
+/// least 3 outputs, where at least half of the outputs share one output value, is
+/// assumed to be an equal-output CoinJoin (Whirlpool, JoinMarket, and
+/// similar), which breaks the common-input-ownership assumption.
+fn looks_like_coinjoin(node: &cory_core::types::TxNode) -> bool {
+    if node.inputs.len() < 3 || node.outputs.len() < 3 {
+        return false;
+    }
+
+    let mut value_counts: HashMap<bitcoin::Amount, usize> = HashMap::new();
+    for output in &node.outputs {
+        *value_counts.entry(output.value).or_insert(0) += 1;
+    }
+    let Some(&max_count) = value_counts.values().max() else {
+        return false;
+    };
+
+    max_count as f64 / node.outputs.len() as f64 >= 0.5
+}
+
 // ==============================================================================
 // Helpers
 // ==============================================================================
 
+/// A synthetic, non-editable label marking an address/output as derived
+/// from a registered wallet descriptor or xpub.
+fn wallet_derived_label_entry() -> LabelEntry {
+    LabelEntry {
+        file_id: WALLET_DERIVED_FILE_ID.to_string(),
+        file_name: WALLET_DERIVED_FILE_ID.to_string(),
+        file_kind: LabelFileKind::WalletDerived,
+        editable: false,
+        label: WALLET_DERIVED_LABEL.to_string(),
+    }
+}
+
+/// Flattens a graph's merged labels into sorted, newline-delimited BIP-329
+/// JSON records (`{"type", "ref", "label"}`), one record per label entry.
+/// Refs within each type are sorted for deterministic output; a ref with
+/// multiple labels (e.g. shadowed by precedence, or wallet-derived
+/// alongside a real label) emits one record per label.
+fn labels_by_type_to_jsonl(labels_by_type: &GraphLabelsByType) -> String {
+    let typed_maps = [
+        (Bip329Type::Tx, &labels_by_type.tx),
+        (Bip329Type::Input, &labels_by_type.input),
+        (Bip329Type::Output, &labels_by_type.output),
+        (Bip329Type::Addr, &labels_by_type.addr),
+    ];
+
+    let mut output = String::new();
+    for (label_type, entries_by_ref) in typed_maps {
+        let mut refs: Vec<&String> = entries_by_ref.keys().collect();
+        refs.sort();
+        for ref_id in refs {
+            for entry in &entries_by_ref[ref_id] {
+                let record = Bip329Record {
+                    label_type,
+                    ref_id: ref_id.clone(),
+                    label: entry.label.clone(),
+                    origin: None,
+                    spendable: None,
+                };
+                let line = serde_json::to_string(&record).expect("valid JSON");
+                output.push_str(&line);
+                output.push('\n');
+            }
+        }
+    }
+    output
+}
+
 fn to_label_entries(labels: Vec<(&LabelFile, &Bip329Record)>) -> Vec<LabelEntry> {
     labels
         .into_iter()
@@ -363,55 +700,4 @@ mod tests {
         let response = err.into_response();
         assert_eq!(response.status(), axum::http::StatusCode::BAD_REQUEST);
     }
-
-    #[test]
-    fn record_search_history_evicts_oldest_when_full() {
-        let mut history = HashMap::new();
-        record_search_history(
-            &mut history,
-            "old".to_string(),
-            "2024-01-01T00:00:00Z".to_string(),
-            2,
-        );
-        record_search_history(
-            &mut history,
-            "newer".to_string(),
-            "2024-01-02T00:00:00Z".to_string(),
-            2,
-        );
-        record_search_history(
-            &mut history,
-            "latest".to_string(),
-            "2024-01-03T00:00:00Z".to_string(),
-            2,
-        );
-
-        assert_eq!(history.len(), 2);
-        assert!(!history.contains_key("old"));
-        assert!(history.contains_key("newer"));
-        assert!(history.contains_key("latest"));
-    }
-
-    #[test]
-    fn record_search_history_updates_existing_entry_without_growth() {
-        let mut history = HashMap::new();
-        record_search_history(
-            &mut history,
-            "same".to_string(),
-            "2024-01-01T00:00:00Z".to_string(),
-            2,
-        );
-        record_search_history(
-            &mut history,
-            "same".to_string(),
-            "2024-01-03T00:00:00Z".to_string(),
-            2,
-        );
-
-        assert_eq!(history.len(), 1);
-        assert_eq!(
-            history.get("same").expect("existing key must be present"),
-            "2024-01-03T00:00:00Z"
-        );
-    }
 }
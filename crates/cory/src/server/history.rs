@@ -1,7 +1,10 @@
-use axum::extract::State;
+use axum::extract::{Query, State};
 use axum::http::HeaderMap;
 use axum::Json;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use cory_core::history::HistoryEntry as CoreHistoryEntry;
+use cory_core::types::GraphLimits;
 
 use super::auth::check_auth;
 use super::error::AppError;
@@ -11,15 +14,44 @@ use super::SharedState;
 // DTOs
 // ==============================================================================
 
+const DEFAULT_PAGE_LIMIT: usize = 50;
+const MAX_PAGE_LIMIT: usize = 500;
+
+#[derive(Deserialize)]
+pub(super) struct HistoryQuery {
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
 #[derive(Clone, Serialize)]
 pub(super) struct HistoryEntry {
     txid: String,
     searched_at: String,
+    network: String,
+    limits: GraphLimits,
+    node_count: usize,
+    edge_count: usize,
 }
 
+impl From<CoreHistoryEntry> for HistoryEntry {
+    fn from(entry: CoreHistoryEntry) -> Self {
+        Self {
+            txid: entry.txid,
+            searched_at: entry.searched_at,
+            network: entry.network,
+            limits: entry.limits,
+            node_count: entry.node_count,
+            edge_count: entry.edge_count,
+        }
+    }
+}
+
+/// Paginated, newest-first view of the durable search history.
 #[derive(Serialize)]
 pub(super) struct HistoryResponse {
     entries: Vec<HistoryEntry>,
+    offset: usize,
+    total: usize,
 }
 
 // ==============================================================================
@@ -29,20 +61,27 @@ pub(super) struct HistoryResponse {
 pub(super) async fn get_history(
     State(state): State<SharedState>,
     headers: HeaderMap,
+    Query(query): Query<HistoryQuery>,
 ) -> Result<Json<HistoryResponse>, AppError> {
-    check_auth(&state.api_token, &headers)?;
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+
+    let offset = query.offset.unwrap_or(0);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_LIMIT)
+        .min(MAX_PAGE_LIMIT);
 
     let history = state.history.read().await;
-    let mut entries: Vec<HistoryEntry> = history
-        .iter()
-        .map(|(txid, searched_at)| HistoryEntry {
-            txid: txid.clone(),
-            searched_at: searched_at.clone(),
-        })
+    let total = history.list().len();
+    let entries = history
+        .list_page(offset, limit)
+        .into_iter()
+        .map(HistoryEntry::from)
         .collect();
 
-    // RFC3339 UTC strings are lexicographically sortable by recency.
-    entries.sort_by(|a, b| b.searched_at.cmp(&a.searched_at));
-
-    Ok(Json(HistoryResponse { entries }))
+    Ok(Json(HistoryResponse {
+        entries,
+        offset,
+        total,
+    }))
 }
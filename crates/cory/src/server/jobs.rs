@@ -0,0 +1,289 @@
+//! Asynchronous ancestry-graph build job queue.
+//!
+//! [`super::graph::get_graph`] builds the whole graph synchronously inside
+//! the request handler, holding the HTTP connection open for the entire
+//! traversal — fine for shallow graphs, but one bumping up against
+//! `HARD_MAX_DEPTH`/`HARD_MAX_NODES` can take long enough that a client
+//! would rather poll. `POST .../job` enqueues the build on a background
+//! Tokio task instead, bounded by [`JobStore`]'s own semaphore (independent
+//! of `rpc_concurrency`, which bounds RPC fan-out *within* one build);
+//! `GET .../jobs/{id}` polls its [`JobStatus`], and
+//! `GET .../jobs/{id}/result` fetches the finished [`GraphResponse`].
+//! Finished jobs are swept out of the store after [`JobStore`]'s TTL so a
+//! client that never cleans up after itself doesn't leak memory.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use serde::Serialize;
+use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use cory_core::graph::BuildProgress;
+
+use super::auth::check_auth;
+use super::error::AppError;
+use super::graph::{build_graph_response, resolve_graph_request, GraphQuery, GraphResponse};
+use super::SharedState;
+
+pub(super) type JobId = Uuid;
+
+// ==============================================================================
+// Job Status
+// ==============================================================================
+
+/// Current state of one enqueued graph build.
+#[derive(Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub(super) enum JobStatus {
+    Queued,
+    Running {
+        nodes_so_far: usize,
+        edges_so_far: usize,
+    },
+    Done,
+    Failed {
+        msg: String,
+    },
+}
+
+struct JobEntry {
+    status: JobStatus,
+    result: Option<GraphResponse>,
+    /// Set once the job reaches `Done`/`Failed`, so [`JobStore::sweep_expired`]
+    /// can evict it once its TTL elapses. `None` while queued or running.
+    finished_at: Option<Instant>,
+    /// Aborted on [`JobStore::cancel`] if the build is still in flight.
+    handle: Option<JoinHandle<()>>,
+}
+
+/// What [`JobStore::result`] found for a given job id.
+pub(super) enum JobResult {
+    NotFound,
+    NotReady,
+    Ready(GraphResponse),
+}
+
+// ==============================================================================
+// Job Store
+// ==============================================================================
+
+/// Holds every in-flight and recently-finished graph-build job.
+///
+/// Bounds concurrent background builds via `build_permits`, independent of
+/// `rpc_concurrency` (which bounds RPC fan-out *within* a single build), and
+/// evicts `Done`/`Failed` jobs older than `ttl` so a client that never polls
+/// `DELETE .../jobs/{id}` doesn't leak memory. Eviction happens
+/// opportunistically on each status or result lookup rather than via a
+/// background sweep task, since the store is already locked for the lookup.
+pub struct JobStore {
+    jobs: RwLock<HashMap<JobId, JobEntry>>,
+    build_permits: Arc<Semaphore>,
+    ttl: Duration,
+}
+
+impl JobStore {
+    pub fn new(max_concurrent_builds: usize, ttl: Duration) -> Self {
+        Self {
+            jobs: RwLock::new(HashMap::new()),
+            build_permits: Arc::new(Semaphore::new(max_concurrent_builds)),
+            ttl,
+        }
+    }
+
+    /// Enqueues a build for `txid`/`limits`, spawning it on a background
+    /// task that waits for a free `build_permits` permit before actually
+    /// running. Returns the new job's id immediately.
+    async fn spawn_build(
+        self: &Arc<Self>,
+        state: SharedState,
+        txid: bitcoin::Txid,
+        limits: cory_core::types::GraphLimits,
+    ) -> JobId {
+        let job_id = Uuid::new_v4();
+        self.jobs.write().await.insert(
+            job_id,
+            JobEntry {
+                status: JobStatus::Queued,
+                result: None,
+                finished_at: None,
+                handle: None,
+            },
+        );
+
+        let store = Arc::clone(self);
+        let permits = Arc::clone(&self.build_permits);
+        let handle = tokio::spawn(async move {
+            let _permit = permits
+                .acquire_owned()
+                .await
+                .expect("build_permits semaphore is never closed");
+
+            let progress = JobProgress {
+                store: Arc::clone(&store),
+                job_id,
+            };
+            match super::graph::run_graph_build(&state, txid, &limits, Some(&progress)).await {
+                Ok(graph) => {
+                    let response = build_graph_response(&state, graph, true).await;
+                    store.finish(job_id, response).await;
+                }
+                Err(err) => store.fail(job_id, err.into_message()).await,
+            }
+        });
+
+        if let Some(entry) = self.jobs.write().await.get_mut(&job_id) {
+            entry.handle = Some(handle);
+        }
+        job_id
+    }
+
+    async fn finish(&self, job_id: JobId, result: GraphResponse) {
+        let mut jobs = self.jobs.write().await;
+        if let Some(entry) = jobs.get_mut(&job_id) {
+            entry.status = JobStatus::Done;
+            entry.result = Some(result);
+            entry.finished_at = Some(Instant::now());
+        }
+    }
+
+    async fn fail(&self, job_id: JobId, msg: String) {
+        let mut jobs = self.jobs.write().await;
+        if let Some(entry) = jobs.get_mut(&job_id) {
+            entry.status = JobStatus::Failed { msg };
+            entry.finished_at = Some(Instant::now());
+        }
+    }
+
+    async fn status(&self, job_id: JobId) -> Option<JobStatus> {
+        let mut jobs = self.jobs.write().await;
+        Self::sweep_expired(&mut jobs, self.ttl);
+        jobs.get(&job_id).map(|entry| entry.status.clone())
+    }
+
+    async fn result(&self, job_id: JobId) -> JobResult {
+        let mut jobs = self.jobs.write().await;
+        Self::sweep_expired(&mut jobs, self.ttl);
+        match jobs.get(&job_id) {
+            None => JobResult::NotFound,
+            Some(entry) => match &entry.result {
+                Some(response) => JobResult::Ready(response.clone()),
+                None => JobResult::NotReady,
+            },
+        }
+    }
+
+    /// Drops the job and aborts its background task if still running.
+    /// Returns `false` if no job with this id exists.
+    async fn cancel(&self, job_id: JobId) -> bool {
+        let mut jobs = self.jobs.write().await;
+        let Some(entry) = jobs.remove(&job_id) else {
+            return false;
+        };
+        if let Some(handle) = entry.handle {
+            handle.abort();
+        }
+        true
+    }
+
+    fn sweep_expired(jobs: &mut HashMap<JobId, JobEntry>, ttl: Duration) {
+        jobs.retain(|_, entry| match entry.finished_at {
+            Some(finished_at) => finished_at.elapsed() < ttl,
+            None => true,
+        });
+    }
+}
+
+/// Reports a running build's progress back into its job entry. Best-effort:
+/// `on_progress` is called synchronously from inside the build's traversal
+/// loop, so it uses `try_write` and simply skips the update on lock
+/// contention rather than blocking the traversal.
+struct JobProgress {
+    store: Arc<JobStore>,
+    job_id: JobId,
+}
+
+impl BuildProgress for JobProgress {
+    fn on_progress(&self, nodes_so_far: usize, edges_so_far: usize) {
+        if let Ok(mut jobs) = self.store.jobs.try_write() {
+            if let Some(entry) = jobs.get_mut(&self.job_id) {
+                entry.status = JobStatus::Running {
+                    nodes_so_far,
+                    edges_so_far,
+                };
+            }
+        }
+    }
+}
+
+// ==============================================================================
+// Handlers
+// ==============================================================================
+
+#[derive(Serialize)]
+pub(super) struct EnqueueJobResponse {
+    job_id: JobId,
+}
+
+pub(super) async fn enqueue_graph_job(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(txid_str): Path<String>,
+    Query(query): Query<GraphQuery>,
+) -> Result<Json<EnqueueJobResponse>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+
+    let (txid, limits) = resolve_graph_request(&state, &txid_str, &query)?;
+    let job_id = state
+        .jobs
+        .spawn_build(Arc::clone(&state), txid, limits)
+        .await;
+    Ok(Json(EnqueueJobResponse { job_id }))
+}
+
+pub(super) async fn get_job_status(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(job_id): Path<JobId>,
+) -> Result<Json<JobStatus>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    state
+        .jobs
+        .status(job_id)
+        .await
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("job not found: {job_id}")))
+}
+
+pub(super) async fn get_job_result(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(job_id): Path<JobId>,
+) -> Result<Json<GraphResponse>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    match state.jobs.result(job_id).await {
+        JobResult::Ready(response) => Ok(Json(response)),
+        JobResult::NotReady => Err(AppError::Conflict(format!(
+            "job {job_id} has not finished yet"
+        ))),
+        JobResult::NotFound => Err(AppError::NotFound(format!("job not found: {job_id}"))),
+    }
+}
+
+pub(super) async fn delete_job(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(job_id): Path<JobId>,
+) -> Result<StatusCode, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    if state.jobs.cancel(job_id).await {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NotFound(format!("job not found: {job_id}")))
+    }
+}
@@ -1,19 +1,24 @@
 use std::collections::HashSet;
-use std::io::Write;
+use std::io::{Read, Write};
 
 use axum::extract::rejection::JsonRejection;
-use axum::extract::{Path, Query, State};
-use axum::http::{HeaderMap, StatusCode};
-use axum::response::{IntoResponse, Response};
+use axum::extract::{Multipart, Path, Query, State};
+use axum::http::HeaderMap;
+use axum::response::Response;
 use axum::Json;
 use serde::{Deserialize, Serialize};
 use zip::write::SimpleFileOptions;
-use zip::{CompressionMethod, ZipWriter};
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
 
-use cory_core::labels::{Bip329Type, LabelFile, LabelFileKind};
+use cory_core::labels::{
+    normalize_label_file_id, Bip329Record, Bip329Type, LabelFile, LabelFileKind, LabelStore,
+    LabelStoreError, ManifestEntry, SyncOutcome,
+};
+use cory_core::CoreError;
 
 use super::auth::check_auth;
 use super::error::{map_label_store_error, AppError};
+use super::range::serve_with_range_and_etag;
 use super::SharedState;
 
 // ==============================================================================
@@ -80,6 +85,158 @@ pub(super) struct DeleteLabelQuery {
     ref_id: String,
 }
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct BatchLabelRequest {
+    operations: Vec<BatchLabelOperation>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub(super) enum BatchLabelOperation {
+    Upsert {
+        #[serde(rename = "type")]
+        label_type: Bip329Type,
+        #[serde(rename = "ref")]
+        ref_id: String,
+        label: String,
+    },
+    Delete {
+        #[serde(rename = "type")]
+        label_type: Bip329Type,
+        #[serde(rename = "ref")]
+        ref_id: String,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub(super) enum BatchLabelOpResult {
+    Ok,
+    Error { code: u16, message: String },
+}
+
+#[derive(Serialize)]
+pub(super) struct BatchLabelResponse {
+    results: Vec<BatchLabelOpResult>,
+    summary: LabelFileSummary,
+}
+
+#[derive(Serialize)]
+pub(super) struct LabelManifestResponse {
+    manifest: Vec<ManifestEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct SyncLabelsRequest {
+    /// The manifest this store returned from a prior `GET .../manifest`
+    /// call, echoed back so the caller doesn't need a second round trip
+    /// just to learn what it already has. Unused beyond that: the set
+    /// difference is always recomputed against this store's live state,
+    /// never trusted from the request, so a sync is safe even if the
+    /// manifest has gone stale in the meantime.
+    #[allow(dead_code)]
+    manifest: Vec<ManifestEntry>,
+    /// Records the caller determined, by diffing its own manifest against
+    /// `manifest`, that this store is missing.
+    records: Vec<Bip329Record>,
+}
+
+#[derive(Serialize)]
+pub(super) struct SyncLabelsResponse {
+    #[serde(flatten)]
+    outcome: SyncOutcome,
+    summary: LabelFileSummary,
+}
+
+/// One editable label file as carried by `GET /api/v1/label/export-all` and
+/// `POST /api/v1/label/import-all`. `id` round-trips the canonical id a
+/// re-imported file would otherwise have to re-derive from `name`; `name` is
+/// what import actually keys off, so an archive can still be edited by hand
+/// before being re-imported.
+#[derive(Serialize, Deserialize)]
+pub(super) struct LabelArchiveEntry {
+    id: String,
+    name: String,
+    content: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct LabelArchiveManifest {
+    files: Vec<LabelArchiveEntry>,
+}
+
+/// How `POST /api/v1/label/import-all` should handle an archive entry whose
+/// name collides with an existing label file.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum ImportConflictMode {
+    /// Leave the existing file untouched; the entry is reported as skipped.
+    #[default]
+    Skip,
+    /// Import under a suffixed name (`"Wallet (2)"`, `"Wallet (3)"`, ...)
+    /// instead of the one in the archive.
+    Rename,
+    /// Replace the existing file's content in place. Only valid when the
+    /// existing file is a browser-created (`BrowserRw`) file; overwriting
+    /// any other kind, including an editable persistent file, fails for
+    /// that one entry.
+    Overwrite,
+}
+
+#[derive(Deserialize)]
+pub(super) struct ImportAllQuery {
+    conflict: Option<ImportConflictMode>,
+}
+
+#[derive(Serialize)]
+pub(super) struct RenamedLabelFile {
+    from: String,
+    to: String,
+}
+
+#[derive(Serialize)]
+pub(super) struct ImportLabelFileError {
+    name: String,
+    message: String,
+}
+
+#[derive(Default, Serialize)]
+pub(super) struct ImportAllSummary {
+    created: Vec<String>,
+    skipped: Vec<String>,
+    renamed: Vec<RenamedLabelFile>,
+    overwritten: Vec<String>,
+    errors: Vec<ImportLabelFileError>,
+}
+
+/// Per-entry outcome of `POST /api/v1/label/import-zip`. A bad entry only
+/// ever produces one of these, never an error response for the whole
+/// upload — one corrupt `.jsonl` in a large archive shouldn't lose the
+/// rest of it.
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub(super) enum ZipImportEntryStatus {
+    Imported { id: String },
+    SkippedDuplicate { id: String },
+    SkippedNotJsonl,
+    ParseError { message: String },
+    Rejected { message: String },
+}
+
+#[derive(Serialize)]
+pub(super) struct ZipImportEntryResult {
+    entry: String,
+    #[serde(flatten)]
+    status: ZipImportEntryStatus,
+}
+
+#[derive(Default, Serialize)]
+pub(super) struct ZipImportSummary {
+    results: Vec<ZipImportEntryResult>,
+}
+
 // ==============================================================================
 // Handlers
 // ==============================================================================
@@ -88,7 +245,7 @@ pub(super) async fn list_label_files(
     State(state): State<SharedState>,
     headers: HeaderMap,
 ) -> Result<Json<Vec<LabelFileSummary>>, AppError> {
-    check_auth(&state.api_token, &headers)?;
+    check_auth(&state.api_token, &headers, &state.metrics)?;
     let store = state.labels.read().await;
     Ok(Json(
         store
@@ -104,7 +261,7 @@ pub(super) async fn create_or_import_local_label_file(
     headers: HeaderMap,
     req: Result<Json<CreateOrImportLabelFileRequest>, JsonRejection>,
 ) -> Result<Json<LabelFileSummary>, AppError> {
-    check_auth(&state.api_token, &headers)?;
+    check_auth(&state.api_token, &headers, &state.metrics)?;
     let Json(req) = req.map_err(|e| AppError::BadRequest(e.to_string()))?;
 
     let mut store = state.labels.write().await;
@@ -121,6 +278,7 @@ pub(super) async fn create_or_import_local_label_file(
         .map(label_file_to_summary)
         .ok_or_else(|| AppError::Internal("created label file was not found".to_string()))?;
 
+    state.metrics.label_ops_total.inc("create_or_import");
     Ok(Json(summary))
 }
 
@@ -130,7 +288,7 @@ pub(super) async fn upsert_or_replace_local_label_file(
     Path(file_id): Path<String>,
     req: Result<Json<UpsertOrReplaceLabelFileRequest>, JsonRejection>,
 ) -> Result<Json<LabelFileSummary>, AppError> {
-    check_auth(&state.api_token, &headers)?;
+    check_auth(&state.api_token, &headers, &state.metrics)?;
     let Json(req) = req.map_err(|e| AppError::BadRequest(e.to_string()))?;
 
     let mut store = state.labels.write().await;
@@ -149,6 +307,7 @@ pub(super) async fn upsert_or_replace_local_label_file(
         .map(label_file_to_summary)
         .ok_or_else(|| AppError::Internal("updated label file was not found".to_string()))?;
 
+    state.metrics.label_ops_total.inc("upsert_or_replace");
     Ok(Json(summary))
 }
 
@@ -157,12 +316,13 @@ pub(super) async fn delete_local_label_file(
     headers: HeaderMap,
     Path(file_id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    check_auth(&state.api_token, &headers)?;
+    check_auth(&state.api_token, &headers, &state.metrics)?;
     let mut store = state.labels.write().await;
     store
         .remove_browser_file(&file_id)
         .map_err(map_label_store_error)?;
 
+    state.metrics.label_ops_total.inc("delete_file");
     Ok(Json(serde_json::json!({ "status": "deleted" })))
 }
 
@@ -172,7 +332,7 @@ pub(super) async fn delete_local_label_entry(
     Path(file_id): Path<String>,
     Query(query): Query<DeleteLabelQuery>,
 ) -> Result<Json<LabelFileSummary>, AppError> {
-    check_auth(&state.api_token, &headers)?;
+    check_auth(&state.api_token, &headers, &state.metrics)?;
     let mut store = state.labels.write().await;
     store
         .delete_label(&file_id, query.label_type, &query.ref_id)
@@ -182,46 +342,142 @@ pub(super) async fn delete_local_label_entry(
         .get_file(&file_id)
         .map(label_file_to_summary)
         .ok_or_else(|| AppError::Internal("updated label file was not found".to_string()))?;
+    state.metrics.label_ops_total.inc("delete_entry");
     Ok(Json(summary))
 }
 
+/// `POST /api/v1/label/{file_id}/batch`: applies an ordered list of
+/// upsert/delete operations under a single write-lock acquisition, so
+/// tagging dozens of nodes costs one round-trip instead of one per label.
+///
+/// A failing operation (e.g. a ref that turns out to be empty) doesn't
+/// abort the batch — its slot in `results` records the error and every
+/// later operation still runs, mirroring how [`map_label_store_error`]
+/// reports a single-label failure.
+pub(super) async fn batch_update_local_label_file(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(file_id): Path<String>,
+    req: Result<Json<BatchLabelRequest>, JsonRejection>,
+) -> Result<Json<BatchLabelResponse>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    let Json(req) = req.map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut store = state.labels.write().await;
+    let results = req
+        .operations
+        .into_iter()
+        .map(|op| {
+            let outcome = match op {
+                BatchLabelOperation::Upsert {
+                    label_type,
+                    ref_id,
+                    label,
+                } => store.set_label(&file_id, label_type, ref_id, label),
+                BatchLabelOperation::Delete { label_type, ref_id } => {
+                    store.delete_label(&file_id, label_type, &ref_id)
+                }
+            };
+            match outcome {
+                Ok(()) => BatchLabelOpResult::Ok,
+                Err(err) => {
+                    let app_err = map_label_store_error(err);
+                    let code = app_err.status_code().as_u16();
+                    BatchLabelOpResult::Error {
+                        code,
+                        message: app_err.into_message(),
+                    }
+                }
+            }
+        })
+        .collect();
+
+    let summary = store
+        .get_file(&file_id)
+        .map(label_file_to_summary)
+        .ok_or_else(|| AppError::Internal("updated label file was not found".to_string()))?;
+
+    state.metrics.label_ops_total.inc("batch");
+    Ok(Json(BatchLabelResponse { results, summary }))
+}
+
+/// `GET /api/v1/label/{file_id}/manifest`: per-record content hashes for
+/// this file, for a remote peer to diff against its own records and work
+/// out what it needs to push via `POST .../sync`.
+pub(super) async fn get_label_manifest(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(file_id): Path<String>,
+) -> Result<Json<LabelManifestResponse>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    let store = state.labels.read().await;
+    let manifest = store.manifest(&file_id).map_err(map_label_store_error)?;
+    Ok(Json(LabelManifestResponse { manifest }))
+}
+
+/// `POST /api/v1/label/{file_id}/sync`: delta-sync records a remote peer
+/// determined (from a prior `GET .../manifest`) this store is missing.
+/// Records whose `(type, ref)` already exists locally with a different
+/// `label` come back as conflicts rather than being overwritten — see
+/// [`cory_core::labels::LabelStore::sync_records`].
+pub(super) async fn sync_label_file(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(file_id): Path<String>,
+    req: Result<Json<SyncLabelsRequest>, JsonRejection>,
+) -> Result<Json<SyncLabelsResponse>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    let Json(req) = req.map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut store = state.labels.write().await;
+    let outcome = store
+        .sync_records(&file_id, req.records)
+        .map_err(map_label_store_error)?;
+
+    let summary = store
+        .get_file(&file_id)
+        .map(label_file_to_summary)
+        .ok_or_else(|| AppError::Internal("synced label file was not found".to_string()))?;
+
+    state.metrics.label_ops_total.inc("sync");
+    Ok(Json(SyncLabelsResponse { outcome, summary }))
+}
+
 pub(super) async fn export_local_label_file(
     State(state): State<SharedState>,
     headers: HeaderMap,
     Path(file_id): Path<String>,
 ) -> Result<Response, AppError> {
-    check_auth(&state.api_token, &headers)?;
+    check_auth(&state.api_token, &headers, &state.metrics)?;
     let store = state.labels.read().await;
     let file = store
         .get_file(&file_id)
         .ok_or_else(|| AppError::NotFound(format!("label file not found: {file_id}")))?;
     let content = store.export_file(&file_id).map_err(map_label_store_error)?;
-
-    let mut response = (StatusCode::OK, content).into_response();
-    response.headers_mut().insert(
-        axum::http::header::CONTENT_TYPE,
-        axum::http::HeaderValue::from_static("text/plain; charset=utf-8"),
-    );
     let disposition = format!("attachment; filename=\"{}.jsonl\"", file.name);
-    let disposition_header = axum::http::HeaderValue::from_str(&disposition)
-        .map_err(|e| AppError::Internal(format!("invalid content disposition header: {e}")))?;
-    response
-        .headers_mut()
-        .insert(axum::http::header::CONTENT_DISPOSITION, disposition_header);
-    Ok(response)
+    let last_modified = file.last_modified_unix_secs();
+
+    serve_with_range_and_etag(
+        &headers,
+        content.into_bytes(),
+        "text/plain; charset=utf-8",
+        Some(disposition),
+        last_modified,
+    )
 }
 
 pub(super) async fn zip_browser_labels(
     State(state): State<SharedState>,
     headers: HeaderMap,
 ) -> Result<Response, AppError> {
-    check_auth(&state.api_token, &headers)?;
+    check_auth(&state.api_token, &headers, &state.metrics)?;
     let store = state.labels.read().await;
 
     // Export only BrowserRw files and package them into one archive for
     // one-click persistence from the UI.
     let mut used_names = HashSet::new();
     let mut entries = Vec::new();
+    let mut last_modified = None;
     for file in store
         .list_files()
         .into_iter()
@@ -231,6 +487,7 @@ pub(super) async fn zip_browser_labels(
         let base_name = sanitize_zip_base_name(&file.name);
         let entry_name = unique_zip_entry_name(&base_name, &mut used_names);
         entries.push((entry_name, content.into_bytes()));
+        last_modified = last_modified.max(file.last_modified_unix_secs());
     }
 
     if entries.is_empty() {
@@ -240,16 +497,278 @@ pub(super) async fn zip_browser_labels(
     }
 
     let zip_bytes = build_zip(entries)?;
-    let mut response = (StatusCode::OK, zip_bytes).into_response();
-    response.headers_mut().insert(
-        axum::http::header::CONTENT_TYPE,
-        axum::http::HeaderValue::from_static("application/zip"),
-    );
-    response.headers_mut().insert(
-        axum::http::header::CONTENT_DISPOSITION,
-        axum::http::HeaderValue::from_static("attachment; filename=\"labels.zip\""),
-    );
-    Ok(response)
+    serve_with_range_and_etag(
+        &headers,
+        zip_bytes,
+        "application/zip",
+        Some("attachment; filename=\"labels.zip\"".to_string()),
+        last_modified,
+    )
+}
+
+/// `GET /api/v1/label/export-all`: bundles every editable label file
+/// (persistent-rw and browser-rw, not read-only or wallet-derived entries)
+/// into a single JSON manifest, for backing up or migrating a whole working
+/// set in one request instead of one `export` call per file.
+pub(super) async fn export_all_local_label_files(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    let store = state.labels.read().await;
+
+    let mut files = Vec::new();
+    for file in store.list_files().into_iter().filter(|file| file.editable) {
+        let content = store.export_file(&file.id).map_err(map_label_store_error)?;
+        files.push(LabelArchiveEntry {
+            id: file.id.clone(),
+            name: file.name.clone(),
+            content,
+        });
+    }
+
+    if files.is_empty() {
+        return Err(AppError::NotFound(
+            "no editable label files to export".to_string(),
+        ));
+    }
+
+    let body = serde_json::to_vec(&LabelArchiveManifest { files })
+        .map_err(|e| AppError::Internal(format!("failed to serialize label archive: {e}")))?;
+
+    serve_with_range_and_etag(
+        &headers,
+        body,
+        "application/json",
+        Some("attachment; filename=\"labels-export.json\"".to_string()),
+        None,
+    )
+}
+
+/// `POST /api/v1/label/import-all`: recreates every entry in a
+/// [`LabelArchiveManifest`] (as produced by [`export_all_local_label_files`])
+/// under a single write-lock acquisition, so a full restore is one atomic
+/// round-trip instead of one `create_or_import` call per file racing
+/// against concurrent edits. `?conflict=` selects how a name collision with
+/// an existing file is resolved; see [`ImportConflictMode`].
+pub(super) async fn import_all_local_label_files(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(query): Query<ImportAllQuery>,
+    req: Result<Json<LabelArchiveManifest>, JsonRejection>,
+) -> Result<Json<ImportAllSummary>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    let Json(manifest) = req.map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let conflict = query.conflict.unwrap_or_default();
+
+    let mut store = state.labels.write().await;
+    let mut summary = ImportAllSummary::default();
+
+    for entry in manifest.files {
+        let id = normalize_label_file_id(&entry.name);
+        if store.get_file(&id).is_none() {
+            match store.import_browser_file(&entry.name, &entry.content) {
+                Ok(created) => summary.created.push(created),
+                Err(err) => summary.errors.push(ImportLabelFileError {
+                    name: entry.name,
+                    message: err.to_string(),
+                }),
+            }
+            continue;
+        }
+
+        match conflict {
+            ImportConflictMode::Skip => summary.skipped.push(entry.name),
+            ImportConflictMode::Overwrite => {
+                match store.replace_browser_file_content(&id, &entry.content) {
+                    Ok(()) => summary.overwritten.push(id),
+                    Err(err) => summary.errors.push(ImportLabelFileError {
+                        name: entry.name,
+                        message: err.to_string(),
+                    }),
+                }
+            }
+            ImportConflictMode::Rename => {
+                let renamed_name = unique_import_name(&entry.name, &store);
+                match store.import_browser_file(&renamed_name, &entry.content) {
+                    Ok(created) => summary.renamed.push(RenamedLabelFile {
+                        from: entry.name,
+                        to: created,
+                    }),
+                    Err(err) => summary.errors.push(ImportLabelFileError {
+                        name: entry.name,
+                        message: err.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+
+    state.metrics.label_ops_total.inc("import_all");
+    Ok(Json(summary))
+}
+
+/// `POST /api/v1/label/import-zip`: the inverse of [`zip_browser_labels`].
+/// Accepts a single multipart file field holding a `.zip` archive (as
+/// `zip_browser_labels` itself produces) and imports every `*.jsonl`
+/// entry as a new `BrowserRw` file, using the entry's file stem as the
+/// proposed name. A bad entry (duplicate id, unparseable JSONL, or a
+/// zip-slip attempt) is recorded in that entry's result rather than
+/// failing the whole upload — see [`ZipImportEntryStatus`]. Requires
+/// axum's `multipart` feature.
+pub(super) async fn import_zip_labels(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Result<Json<ZipImportSummary>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+
+    // Bounds the sum of entries' *uncompressed* size, independent of the
+    // compressed upload body (already capped by `LABEL_BODY_LIMIT`), so a
+    // small, highly-compressed archive can't exhaust memory decompressing it.
+    const MAX_TOTAL_UNCOMPRESSED_BYTES: u64 = 64 * 1024 * 1024;
+
+    let field = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("invalid multipart upload: {e}")))?
+        .ok_or_else(|| AppError::BadRequest("multipart upload has no file field".to_string()))?;
+    let bytes = field
+        .bytes()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("failed to read uploaded file: {e}")))?;
+
+    let mut archive = ZipArchive::new(std::io::Cursor::new(bytes))
+        .map_err(|e| AppError::BadRequest(format!("not a valid zip archive: {e}")))?;
+
+    let mut summary = ZipImportSummary::default();
+    let mut total_uncompressed: u64 = 0;
+    let mut store = state.labels.write().await;
+
+    for i in 0..archive.len() {
+        let mut entry = match archive.by_index(i) {
+            Ok(entry) => entry,
+            Err(e) => {
+                summary.results.push(ZipImportEntryResult {
+                    entry: format!("#{i}"),
+                    status: ZipImportEntryStatus::Rejected {
+                        message: e.to_string(),
+                    },
+                });
+                continue;
+            }
+        };
+        let entry_name = entry.name().to_string();
+
+        if entry.is_dir() {
+            continue;
+        }
+        if !is_safe_zip_entry_name(&entry_name) {
+            summary.results.push(ZipImportEntryResult {
+                entry: entry_name,
+                status: ZipImportEntryStatus::Rejected {
+                    message: "entry path escapes the archive (zip-slip)".to_string(),
+                },
+            });
+            continue;
+        }
+        if !entry_name.ends_with(".jsonl") {
+            summary.results.push(ZipImportEntryResult {
+                entry: entry_name,
+                status: ZipImportEntryStatus::SkippedNotJsonl,
+            });
+            continue;
+        }
+
+        // `entry.size()` is the zip entry's self-reported, attacker-
+        // controlled uncompressed-size header, not a bound on how many
+        // bytes decompression actually produces — a crafted entry can
+        // understate it and still inflate far past the cap. Read through a
+        // `Take` sized to the *remaining* budget (plus one, to detect
+        // overshoot) instead, so the cap is enforced against real bytes.
+        let remaining_budget = MAX_TOTAL_UNCOMPRESSED_BYTES - total_uncompressed;
+        let mut raw = Vec::new();
+        let read_result = (&mut entry)
+            .take(remaining_budget + 1)
+            .read_to_end(&mut raw);
+        let bytes_read = match read_result {
+            Ok(n) => n as u64,
+            Err(e) => {
+                summary.results.push(ZipImportEntryResult {
+                    entry: entry_name,
+                    status: ZipImportEntryStatus::Rejected {
+                        message: format!("failed to read entry: {e}"),
+                    },
+                });
+                continue;
+            }
+        };
+        total_uncompressed += bytes_read;
+        if bytes_read > remaining_budget {
+            summary.results.push(ZipImportEntryResult {
+                entry: entry_name,
+                status: ZipImportEntryStatus::Rejected {
+                    message: format!(
+                        "archive exceeds the {MAX_TOTAL_UNCOMPRESSED_BYTES}-byte total \
+                         uncompressed size limit"
+                    ),
+                },
+            });
+            break;
+        }
+
+        let content = match String::from_utf8(raw) {
+            Ok(content) => content,
+            Err(e) => {
+                summary.results.push(ZipImportEntryResult {
+                    entry: entry_name,
+                    status: ZipImportEntryStatus::Rejected {
+                        message: format!("failed to read entry: {e}"),
+                    },
+                });
+                continue;
+            }
+        };
+
+        let stem = std::path::Path::new(&entry_name)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| entry_name.clone());
+
+        let status = match store.import_browser_file(&stem, &content) {
+            Ok(id) => ZipImportEntryStatus::Imported { id },
+            Err(LabelStoreError::DuplicateFileId(id)) => {
+                ZipImportEntryStatus::SkippedDuplicate { id }
+            }
+            Err(LabelStoreError::Core(CoreError::LabelParse { line, message })) => {
+                ZipImportEntryStatus::ParseError {
+                    message: format!("line {line}: {message}"),
+                }
+            }
+            Err(err) => ZipImportEntryStatus::Rejected {
+                message: err.to_string(),
+            },
+        };
+        summary.results.push(ZipImportEntryResult {
+            entry: entry_name,
+            status,
+        });
+    }
+    drop(store);
+
+    state.metrics.label_ops_total.inc("import_zip");
+    Ok(Json(summary))
+}
+
+/// Rejects an absolute zip entry path or one containing a `..` component,
+/// guarding against a zip-slip archive that would otherwise write outside
+/// the browser-file namespace `import_browser_file` confines entries to.
+fn is_safe_zip_entry_name(name: &str) -> bool {
+    let path = std::path::Path::new(name);
+    path.is_relative()
+        && !path
+            .components()
+            .any(|c| matches!(c, std::path::Component::ParentDir))
 }
 
 // ==============================================================================
@@ -291,6 +810,30 @@ fn unique_zip_entry_name(base_name: &str, used_names: &mut HashSet<String>) -> S
     unreachable!("unbounded suffix loop must eventually find an unused name");
 }
 
+/// Picks a name that doesn't collide with an existing file id, for
+/// [`ImportConflictMode::Rename`]: `"Wallet"` stays as-is if free, otherwise
+/// becomes `"Wallet (2)"`, `"Wallet (3)"`, etc.
+fn unique_import_name(base_name: &str, store: &LabelStore) -> String {
+    if store
+        .get_file(&normalize_label_file_id(base_name))
+        .is_none()
+    {
+        return base_name.to_string();
+    }
+
+    for suffix in 2.. {
+        let candidate = format!("{base_name} ({suffix})");
+        if store
+            .get_file(&normalize_label_file_id(&candidate))
+            .is_none()
+        {
+            return candidate;
+        }
+    }
+
+    unreachable!("unbounded suffix loop must eventually find an unused name");
+}
+
 fn build_zip(entries: Vec<(String, Vec<u8>)>) -> Result<Vec<u8>, AppError> {
     let cursor = std::io::Cursor::new(Vec::new());
     let mut writer = ZipWriter::new(cursor);
@@ -1,6 +1,8 @@
 use axum::extract::State;
 use axum::Json;
+use cory_core::rpc::EstimateMode;
 use serde::Serialize;
+use tracing::warn;
 
 use super::SharedState;
 
@@ -14,6 +16,10 @@ pub(crate) const HARD_MAX_DEPTH: usize = 1000;
 pub(crate) const HARD_MAX_NODES: usize = 50_000;
 pub(crate) const HARD_MAX_EDGES: usize = 200_000;
 
+/// Confirmation target, in blocks, used to contextualize historical
+/// transaction fees against the current fee market.
+const CURRENT_FEERATE_CONF_TARGET: u16 = 6;
+
 #[derive(Serialize)]
 pub(super) struct LimitsResponse {
     hard_max_depth: usize,
@@ -25,6 +31,11 @@ pub(super) struct LimitsResponse {
     hard_max_edges: usize,
     configured_default_edges: usize,
     effective_default_edges: usize,
+    /// Current estimated feerate to confirm within
+    /// [`CURRENT_FEERATE_CONF_TARGET`] blocks, so the UI can show "this tx
+    /// paid X sat/vB vs ~Y sat/vB now". `None` if the backend couldn't
+    /// produce an estimate.
+    current_feerate_sat_vb: Option<f64>,
 }
 
 pub(super) async fn get_limits(State(state): State<SharedState>) -> Json<LimitsResponse> {
@@ -32,6 +43,18 @@ pub(super) async fn get_limits(State(state): State<SharedState>) -> Json<LimitsR
     let configured_default_nodes = state.default_limits.max_nodes;
     let configured_default_edges = state.default_limits.max_edges;
 
+    let current_feerate_sat_vb = match state
+        .rpc
+        .estimate_smart_fee(CURRENT_FEERATE_CONF_TARGET, EstimateMode::Economical)
+        .await
+    {
+        Ok(feerate) => feerate,
+        Err(err) => {
+            warn!(error = %err, "estimate_smart_fee failed; omitting current feerate from limits");
+            None
+        }
+    };
+
     Json(LimitsResponse {
         hard_max_depth: HARD_MAX_DEPTH,
         configured_default_depth,
@@ -42,5 +65,6 @@ pub(super) async fn get_limits(State(state): State<SharedState>) -> Json<LimitsR
         hard_max_edges: HARD_MAX_EDGES,
         configured_default_edges,
         effective_default_edges: configured_default_edges.min(HARD_MAX_EDGES),
+        current_feerate_sat_vb,
     })
 }
@@ -0,0 +1,372 @@
+//! Process-wide Prometheus metrics, exposed in text exposition format by
+//! [`get_metrics`] at `/api/v1/metrics` alongside the existing
+//! `/api/v1/health` liveness check.
+//!
+//! Counters and histograms live on [`Metrics`], a field of
+//! [`super::AppState`], and are incremented directly inside the graph,
+//! label, and auth handlers that own the events being measured. The
+//! `cory_history_size` gauge is the one exception: it's derived from the
+//! live [`cory_core::history::HistoryStore`] at scrape time rather than
+//! tracked separately, since the store is already the source of truth and
+//! duplicating its length as a counter would just invite drift.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+use axum::extract::State;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+
+use super::SharedState;
+
+/// Bucket upper bounds (seconds) for [`Metrics::graph_build_duration_seconds`].
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Bucket upper bounds for [`Metrics::graph_traversal_nodes`] and
+/// [`Metrics::graph_traversal_edges`].
+const COUNT_BUCKETS: &[f64] = &[
+    1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0,
+];
+
+// ==============================================================================
+// Counter
+// ==============================================================================
+
+/// A counter partitioned by a single label value (e.g. `status`, `op`),
+/// rendered as one exposition line per distinct value observed so far.
+#[derive(Default)]
+pub struct LabeledCounter {
+    values: RwLock<HashMap<String, AtomicU64>>,
+}
+
+impl LabeledCounter {
+    pub fn inc(&self, label: &str) {
+        if let Some(counter) = self
+            .values
+            .read()
+            .expect("metrics lock poisoned")
+            .get(label)
+        {
+            counter.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        self.values
+            .write()
+            .expect("metrics lock poisoned")
+            .entry(label.to_string())
+            .or_insert_with(|| AtomicU64::new(0))
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Vec<(String, u64)> {
+        let mut entries: Vec<(String, u64)> = self
+            .values
+            .read()
+            .expect("metrics lock poisoned")
+            .iter()
+            .map(|(label, count)| (label.clone(), count.load(Ordering::Relaxed)))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+}
+
+// ==============================================================================
+// Histogram
+// ==============================================================================
+
+/// A cumulative histogram with fixed bucket bounds, matching the
+/// Prometheus exposition format: each `le` bucket counts every observation
+/// less than or equal to its bound, plus a final `+Inf` bucket equal to
+/// [`Self::count`].
+pub struct Histogram {
+    bounds: &'static [f64],
+    buckets: Vec<AtomicU64>,
+    sum: RwLock<f64>,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            buckets: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum: RwLock::new(0.0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, value: f64) {
+        for (bucket, bound) in self.buckets.iter().zip(self.bounds.iter()) {
+            if value <= *bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        // The final bucket is `+Inf` and always includes every observation.
+        self.buckets[self.bounds.len()].fetch_add(1, Ordering::Relaxed);
+        *self.sum.write().expect("metrics lock poisoned") += value;
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn render(&self, name: &str, out: &mut String) {
+        for (bucket, bound) in self.buckets.iter().zip(self.bounds.iter()) {
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{le=\"{bound}\"}} {}",
+                bucket.load(Ordering::Relaxed)
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{name}_bucket{{le=\"+Inf\"}} {}",
+            self.buckets[self.bounds.len()].load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "{name}_sum {}",
+            *self.sum.read().expect("metrics lock poisoned")
+        );
+        let _ = writeln!(out, "{name}_count {}", self.count.load(Ordering::Relaxed));
+    }
+}
+
+// ==============================================================================
+// Registry
+// ==============================================================================
+
+/// Process-wide counters and histograms for traversal cost and error rates,
+/// scraped via [`get_metrics`].
+pub struct Metrics {
+    /// Ancestry graph build attempts, keyed by `status` (`ok`/`error`).
+    pub graph_requests_total: LabeledCounter,
+    /// Label store mutations, keyed by `op`.
+    pub label_ops_total: LabeledCounter,
+    /// Rejected `X-API-Token` checks (see [`super::auth::check_auth`]).
+    pub auth_failures_total: AtomicU64,
+    /// Ancestry graph build latency, in seconds.
+    pub graph_build_duration_seconds: Histogram,
+    /// Node count of each successfully built ancestry graph.
+    pub graph_traversal_nodes: Histogram,
+    /// Edge count of each successfully built ancestry graph.
+    pub graph_traversal_edges: Histogram,
+    /// RPC calls issued (via [`cory_core::rpc::CountingRpc`]) per ancestry
+    /// graph build, successful or not.
+    pub graph_rpc_calls: Histogram,
+    /// `GET .../preview` lookups against [`super::PreviewStore`], keyed by
+    /// `hit`/`miss`.
+    pub preview_cache_results_total: LabeledCounter,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            graph_requests_total: LabeledCounter::default(),
+            label_ops_total: LabeledCounter::default(),
+            auth_failures_total: AtomicU64::new(0),
+            graph_build_duration_seconds: Histogram::new(LATENCY_BUCKETS),
+            graph_traversal_nodes: Histogram::new(COUNT_BUCKETS),
+            graph_traversal_edges: Histogram::new(COUNT_BUCKETS),
+            graph_rpc_calls: Histogram::new(COUNT_BUCKETS),
+            preview_cache_results_total: LabeledCounter::default(),
+        }
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ==============================================================================
+// Exposition
+// ==============================================================================
+
+/// `GET /api/v1/metrics`: renders [`Metrics`] and the live history-store
+/// size in Prometheus text exposition format.
+pub(super) async fn get_metrics(State(state): State<SharedState>) -> Response {
+    let mut out = String::new();
+    let metrics = &state.metrics;
+
+    let _ = writeln!(
+        out,
+        "# HELP cory_graph_requests_total Ancestry graph build attempts by outcome."
+    );
+    let _ = writeln!(out, "# TYPE cory_graph_requests_total counter");
+    for (status, count) in metrics.graph_requests_total.snapshot() {
+        let _ = writeln!(
+            out,
+            "cory_graph_requests_total{{status=\"{status}\"}} {count}"
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP cory_label_ops_total Label store mutations by operation."
+    );
+    let _ = writeln!(out, "# TYPE cory_label_ops_total counter");
+    for (op, count) in metrics.label_ops_total.snapshot() {
+        let _ = writeln!(out, "cory_label_ops_total{{op=\"{op}\"}} {count}");
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP cory_auth_failures_total Rejected X-API-Token checks."
+    );
+    let _ = writeln!(out, "# TYPE cory_auth_failures_total counter");
+    let _ = writeln!(
+        out,
+        "cory_auth_failures_total {}",
+        metrics.auth_failures_total.load(Ordering::Relaxed)
+    );
+
+    let _ = writeln!(
+        out,
+        "# HELP cory_graph_build_duration_seconds Ancestry graph build latency."
+    );
+    let _ = writeln!(out, "# TYPE cory_graph_build_duration_seconds histogram");
+    metrics
+        .graph_build_duration_seconds
+        .render("cory_graph_build_duration_seconds", &mut out);
+
+    let _ = writeln!(
+        out,
+        "# HELP cory_graph_traversal_nodes Node count per completed ancestry traversal."
+    );
+    let _ = writeln!(out, "# TYPE cory_graph_traversal_nodes histogram");
+    metrics
+        .graph_traversal_nodes
+        .render("cory_graph_traversal_nodes", &mut out);
+
+    let _ = writeln!(
+        out,
+        "# HELP cory_graph_traversal_edges Edge count per completed ancestry traversal."
+    );
+    let _ = writeln!(out, "# TYPE cory_graph_traversal_edges histogram");
+    metrics
+        .graph_traversal_edges
+        .render("cory_graph_traversal_edges", &mut out);
+
+    let _ = writeln!(
+        out,
+        "# HELP cory_graph_rpc_calls RPC calls issued per ancestry graph build."
+    );
+    let _ = writeln!(out, "# TYPE cory_graph_rpc_calls histogram");
+    metrics
+        .graph_rpc_calls
+        .render("cory_graph_rpc_calls", &mut out);
+
+    let tx_cache_stats = state.cache.tx_stats();
+    let prevout_cache_stats = state.cache.prevout_stats();
+    let _ = writeln!(
+        out,
+        "# HELP cory_cache_hits_total Cache lookups that found an entry, by cache."
+    );
+    let _ = writeln!(out, "# TYPE cory_cache_hits_total counter");
+    let _ = writeln!(
+        out,
+        "cory_cache_hits_total{{cache=\"tx\"}} {}",
+        tx_cache_stats.hits
+    );
+    let _ = writeln!(
+        out,
+        "cory_cache_hits_total{{cache=\"prevout\"}} {}",
+        prevout_cache_stats.hits
+    );
+
+    let _ = writeln!(
+        out,
+        "# HELP cory_cache_misses_total Cache lookups that found nothing, by cache."
+    );
+    let _ = writeln!(out, "# TYPE cory_cache_misses_total counter");
+    let _ = writeln!(
+        out,
+        "cory_cache_misses_total{{cache=\"tx\"}} {}",
+        tx_cache_stats.misses
+    );
+    let _ = writeln!(
+        out,
+        "cory_cache_misses_total{{cache=\"prevout\"}} {}",
+        prevout_cache_stats.misses
+    );
+
+    let _ = writeln!(
+        out,
+        "# HELP cory_cache_evictions_total Entries evicted to stay within capacity, by cache."
+    );
+    let _ = writeln!(out, "# TYPE cory_cache_evictions_total counter");
+    let _ = writeln!(
+        out,
+        "cory_cache_evictions_total{{cache=\"tx\"}} {}",
+        tx_cache_stats.evictions
+    );
+    let _ = writeln!(
+        out,
+        "cory_cache_evictions_total{{cache=\"prevout\"}} {}",
+        prevout_cache_stats.evictions
+    );
+
+    let _ = writeln!(
+        out,
+        "# HELP cory_preview_cache_results_total Graph preview cache lookups by outcome."
+    );
+    let _ = writeln!(out, "# TYPE cory_preview_cache_results_total counter");
+    for (outcome, count) in metrics.preview_cache_results_total.snapshot() {
+        let _ = writeln!(
+            out,
+            "cory_preview_cache_results_total{{outcome=\"{outcome}\"}} {count}"
+        );
+    }
+
+    let history_size = state.history.read().await.list().len();
+    let _ = writeln!(
+        out,
+        "# HELP cory_history_size Number of entries currently stored in the search history."
+    );
+    let _ = writeln!(out, "# TYPE cory_history_size gauge");
+    let _ = writeln!(out, "cory_history_size {history_size}");
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], out).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labeled_counter_tracks_distinct_labels() {
+        let counter = LabeledCounter::default();
+        counter.inc("ok");
+        counter.inc("ok");
+        counter.inc("error");
+
+        let snapshot = counter.snapshot();
+        assert_eq!(
+            snapshot,
+            vec![("error".to_string(), 1), ("ok".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn histogram_accumulates_cumulative_buckets_and_sum() {
+        let histogram = Histogram::new(&[1.0, 5.0, 10.0]);
+        histogram.observe(0.5);
+        histogram.observe(3.0);
+        histogram.observe(20.0);
+
+        let mut out = String::new();
+        histogram.render("test_metric", &mut out);
+
+        assert!(out.contains("test_metric_bucket{le=\"1\"} 1"));
+        assert!(out.contains("test_metric_bucket{le=\"5\"} 2"));
+        assert!(out.contains("test_metric_bucket{le=\"10\"} 2"));
+        assert!(out.contains("test_metric_bucket{le=\"+Inf\"} 3"));
+        assert!(out.contains("test_metric_sum 23.5"));
+        assert!(out.contains("test_metric_count 3"));
+    }
+}
@@ -2,23 +2,42 @@ mod auth;
 mod error;
 mod graph;
 mod history;
+mod jobs;
 mod labels;
 mod limits;
+mod metrics;
+mod openapi;
+mod preview;
+mod proof;
+mod range;
+mod rescan;
+mod share;
 mod static_files;
+mod watch;
+
+pub use jobs::JobStore;
+pub use metrics::Metrics;
+pub use preview::PreviewStore;
+pub use rescan::RescanJobStore;
+pub use watch::spawn_label_watchers;
 
-use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::extract::DefaultBodyLimit;
+use axum::response::{IntoResponse, Response};
 use axum::routing::{any, delete, get, post};
 use axum::{Json, Router};
 use tokio::sync::RwLock;
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 
 use cory_core::cache::Cache;
-use cory_core::labels::LabelStore;
+use cory_core::history::HistoryStore;
+use cory_core::labels::{LabelChangeEvent, LabelStore};
 use cory_core::rpc::BitcoinRpc;
-use cory_core::types::GraphLimits;
+use cory_core::types::{GraphLimits, GraphStrategy};
+use cory_core::wallet::WalletRegistry;
 
 // ==============================================================================
 // Application State
@@ -32,25 +51,353 @@ pub struct AppState {
     pub default_limits: GraphLimits,
     pub rpc_concurrency: usize,
     pub network: bitcoin::Network,
-    pub history: Arc<RwLock<HashMap<String, String>>>,
+    /// Durable record of prior ancestry searches, including the limits used
+    /// and the resulting node/edge counts.
+    pub history: Arc<RwLock<HistoryStore>>,
+    /// Registered wallet descriptors/xpubs, used to flag self-owned
+    /// addresses in ancestry graph responses.
+    pub wallet_registry: Arc<WalletRegistry>,
+    /// Issues and validates the JWT access/refresh pair used by
+    /// `/api/v1/auth/refresh`.
+    pub jwt_manager: Arc<crate::auth::JwtManager>,
+    /// Counters and histograms scraped via `/api/v1/metrics`.
+    pub metrics: Arc<Metrics>,
+    /// Minimum response body size, in bytes, before gzip/deflate
+    /// compression is applied.
+    pub compression_min_bytes: u16,
+    /// Background ancestry-graph build jobs queued via
+    /// `POST /api/v1/graph/tx/{txid}/job`.
+    pub jobs: Arc<JobStore>,
+    /// Rendered-preview cache backing
+    /// `GET /api/v1/graph/tx/{txid}/preview`.
+    pub previews: Arc<PreviewStore>,
+    /// Background label-directory rescans queued via
+    /// `POST /api/v1/label/jobs/rescan`.
+    pub rescan_jobs: Arc<RescanJobStore>,
+    /// `--labels-rw` directories this server was started with, so a
+    /// rescan request can be checked against them instead of rescanning
+    /// an arbitrary filesystem path.
+    pub labels_rw_dirs: Vec<std::path::PathBuf>,
+    /// `--labels-ro` directories this server was started with; see
+    /// `labels_rw_dirs`.
+    pub labels_ro_dirs: Vec<std::path::PathBuf>,
+    /// Publishes every change a `--watch-labels` filesystem watcher
+    /// applies to `labels`, fanned out to `GET /api/v1/label/events`
+    /// subscribers. Exists (and can be subscribed to) even when
+    /// `--watch-labels` is unset; it just never receives anything.
+    pub label_changes: tokio::sync::broadcast::Sender<LabelChangeEvent>,
+    /// Background filesystem watchers started by `--watch-labels`, one per
+    /// `labels_rw_dirs`/`labels_ro_dirs` entry. Kept alive for the life of
+    /// the server; empty when `--watch-labels` is unset.
+    pub label_watchers: Vec<cory_core::labels::LabelWatcher>,
 }
 
 type SharedState = Arc<AppState>;
 
+// ==============================================================================
+// CORS Configuration
+// ==============================================================================
+
+/// Cross-origin allowlist, on top of the server's own `origin` passed to
+/// [`build_router`].
+pub struct CorsConfig {
+    /// Which cross-origin requests are allowed.
+    pub origins: AllowedOrigins,
+    /// Emit `Access-Control-Allow-Credentials: true`, letting cross-origin
+    /// requests carry cookies/auth headers. [`build_router`] refuses to
+    /// start if this is combined with [`AllowedOrigins::All`].
+    pub allow_credentials: bool,
+    /// How long, in seconds, a browser may cache a preflight response
+    /// before re-checking it, emitted as `Access-Control-Max-Age`. `None`
+    /// omits the header entirely, forcing a preflight on every request.
+    pub max_age_secs: Option<u64>,
+    /// Response headers exposed to browser JavaScript via
+    /// `Access-Control-Expose-Headers` on actual (non-preflight)
+    /// responses, e.g. `etag` for callers reading
+    /// `/api/v1/label/{file_id}/export`'s caching header.
+    pub exposed_headers: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            origins: AllowedOrigins::default(),
+            allow_credentials: false,
+            max_age_secs: Some(3600),
+            exposed_headers: vec!["etag".to_string()],
+        }
+    }
+}
+
+/// Which cross-origin `Origin` values are allowed, modeled as an explicit
+/// three-state enum (the common `AllOrSome` CORS shape) rather than an
+/// implicit "empty list plus optional suffix", so [`Self::All`] is a state
+/// [`build_router`]'s credential-safety check can name and reject outright
+/// instead of having to infer "reflects everything" from an
+/// always-true predicate.
+pub enum AllowedOrigins {
+    /// Reflect any `Origin` header value. Must not be paired with
+    /// [`CorsConfig::allow_credentials`]: the CORS spec forbids
+    /// `Access-Control-Allow-Credentials: true` alongside a wildcard or
+    /// reflect-all origin, since that would let any site read
+    /// credentialed responses.
+    All,
+    /// The server's own origin, plus these extra origins (exact or host
+    /// glob, e.g. `https://*.example.com`) and/or this suffix.
+    Some {
+        extra_origins: Vec<String>,
+        allowed_origin_suffix: Option<String>,
+    },
+    /// Only the server's own origin; no cross-origin requests allowed.
+    None,
+}
+
+impl Default for AllowedOrigins {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// A configured `--cors-allowed-origin` entry, split into its scheme, host
+/// (possibly containing `*` glob segments), and port so a wildcard only
+/// ever matches the host component — `https://*.example.com` must never
+/// accidentally also allow `http://anything.example.com` or a mismatched
+/// port, since an attacker who controls either still can't forge the
+/// scheme/port a browser sends in its `Origin` header.
+struct OriginPattern {
+    scheme: String,
+    host_pattern: String,
+    port: Option<String>,
+}
+
+impl OriginPattern {
+    fn compile(pattern: &str) -> Option<Self> {
+        let (scheme, host, port) = split_origin(pattern)?;
+        Some(Self {
+            scheme: scheme.to_string(),
+            host_pattern: host.to_string(),
+            port: port.map(str::to_string),
+        })
+    }
+
+    fn matches(
+        &self,
+        candidate_scheme: &str,
+        candidate_host: &str,
+        candidate_port: Option<&str>,
+    ) -> bool {
+        self.scheme == candidate_scheme
+            && self.port.as_deref() == candidate_port
+            && glob_match(&self.host_pattern, candidate_host)
+    }
+}
+
+/// Splits an origin (or origin pattern) of the form `scheme://host[:port]`
+/// into its three parts. Returns `None` for anything that isn't even
+/// shaped like an origin, so a malformed `Origin` header never matches.
+fn split_origin(value: &str) -> Option<(&str, &str, Option<&str>)> {
+    let (scheme, rest) = value.split_once("://")?;
+    if rest.is_empty() {
+        return None;
+    }
+    match rest.rsplit_once(':') {
+        Some((host, port))
+            if !host.is_empty() && !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            Some((scheme, host, Some(port)))
+        }
+        _ => Some((scheme, rest, None)),
+    }
+}
+
+/// Matches `candidate` against `pattern`, where `*` in `pattern` matches
+/// any run of characters (including none). There's no dedicated glob
+/// dependency in this tree, so this is the standard two-pointer wildcard
+/// matching algorithm instead of pulling one in just for host globs.
+fn glob_match(pattern: &str, candidate: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let c: Vec<char> = candidate.chars().collect();
+    let (mut pi, mut ci) = (0usize, 0usize);
+    let mut star: Option<usize> = None;
+    let mut resume = 0usize;
+
+    while ci < c.len() {
+        if pi < p.len() && (p[pi] == '*' || p[pi] == c[ci]) {
+            if p[pi] == '*' {
+                star = Some(pi);
+                resume = ci;
+                pi += 1;
+            } else {
+                pi += 1;
+                ci += 1;
+            }
+        } else if let Some(star_idx) = star {
+            pi = star_idx + 1;
+            resume += 1;
+            ci = resume;
+        } else {
+            return false;
+        }
+    }
+    while pi < p.len() && p[pi] == '*' {
+        pi += 1;
+    }
+    pi == p.len()
+}
+
+/// Only reflects the allowed origin when the request's `Origin` header
+/// actually matches the server's own origin, the configured allowlist
+/// (exact or glob), or the configured suffix. Disallowed origins get no
+/// CORS headers at all, rather than a reflected or mismatched value.
+fn is_allowed_origin(
+    candidate: &axum::http::HeaderValue,
+    self_origin: &axum::http::HeaderValue,
+    extra_origins: &[OriginPattern],
+    allowed_origin_suffix: Option<&str>,
+) -> bool {
+    if candidate == self_origin {
+        return true;
+    }
+
+    let Ok(candidate_str) = candidate.to_str() else {
+        return false;
+    };
+
+    if let Some((scheme, host, port)) = split_origin(candidate_str) {
+        if extra_origins
+            .iter()
+            .any(|pattern| pattern.matches(scheme, host, port))
+        {
+            return true;
+        }
+    }
+
+    match allowed_origin_suffix {
+        Some(suffix) => candidate_str.ends_with(suffix),
+        None => false,
+    }
+}
+
+// ==============================================================================
+// Host Header Allowlist
+// ==============================================================================
+
+/// `Host` header allowlist, guarding against DNS-rebinding: a page on an
+/// attacker-controlled domain that resolves to `127.0.0.1` and drives the
+/// local API with a `Host` header of its own choosing. CORS alone doesn't
+/// stop this, since a same-origin request from the rebound page never
+/// triggers a CORS preflight at all.
+#[derive(Default)]
+pub struct HostConfig {
+    /// Allowed `Host` header values (exact or glob, e.g. `localhost:*` or
+    /// `127.0.0.1:*`); `*` allows any host. Empty defaults to just the
+    /// server's own bound `host:port`.
+    pub allowed_hosts: Vec<String>,
+}
+
+/// A configured `--allowed-host` entry, matched against the raw `Host`
+/// header value (e.g. `127.0.0.1:3080`) via [`glob_match`]. Unlike
+/// [`OriginPattern`], `Host` never carries a scheme, so the whole header
+/// value is globbed directly — a pattern like `localhost:*` covers any
+/// port without needing a separate port field.
+struct HostPattern(String);
+
+impl HostPattern {
+    fn matches(&self, candidate: &str) -> bool {
+        glob_match(&self.0, candidate)
+    }
+}
+
+/// Rejects any request whose `Host` header doesn't match `allowed_hosts`
+/// with `403 Forbidden`, before it reaches routing.
+async fn validate_host(
+    allowed_hosts: Arc<Vec<HostPattern>>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    let host = request
+        .headers()
+        .get(axum::http::header::HOST)
+        .and_then(|v| v.to_str().ok());
+
+    let allowed = match host {
+        Some(host) => allowed_hosts.iter().any(|pattern| pattern.matches(host)),
+        None => allowed_hosts.iter().any(|pattern| pattern.0 == "*"),
+    };
+
+    if allowed {
+        next.run(request).await
+    } else {
+        error::AppError::Forbidden("host header not in the configured allowlist".to_string())
+            .into_response()
+    }
+}
+
 // ==============================================================================
 // Router
 // ==============================================================================
 
-pub fn build_router(state: AppState, origin: &str) -> Router {
-    // Only reflect the allowed origin when the request's Origin header
-    // actually matches. Otherwise, omit the header entirely so browsers
-    // get a clean CORS rejection instead of a mismatched origin value.
-    let allowed: axum::http::HeaderValue = origin.parse().expect("valid origin header value");
-    let cors = CorsLayer::new()
-        .allow_origin(AllowOrigin::predicate({
-            let allowed = allowed.clone();
-            move |request_origin: &axum::http::HeaderValue, _| *request_origin == allowed
-        }))
+pub fn build_router(
+    state: AppState,
+    origin: &str,
+    cors_config: CorsConfig,
+    host_config: HostConfig,
+) -> eyre::Result<Router> {
+    if cors_config.allow_credentials && matches!(cors_config.origins, AllowedOrigins::All) {
+        return Err(eyre::eyre!(
+            "invalid CORS configuration: --cors-allow-credentials cannot be combined with \
+             --cors-allow-any-origin, since the CORS spec forbids \
+             Access-Control-Allow-Credentials: true alongside a wildcard or reflect-all origin"
+        ));
+    }
+
+    let self_origin: axum::http::HeaderValue = origin.parse().expect("valid origin header value");
+    let (extra_origins, allowed_origin_suffix) = match &cors_config.origins {
+        AllowedOrigins::All | AllowedOrigins::None => (Vec::new(), None),
+        AllowedOrigins::Some {
+            extra_origins,
+            allowed_origin_suffix,
+        } => {
+            let extra_origins: Vec<OriginPattern> = extra_origins
+                .iter()
+                .map(|origin| {
+                    OriginPattern::compile(origin)
+                        .unwrap_or_else(|| panic!("invalid CORS allowed-origin pattern `{origin}`"))
+                })
+                .collect();
+            (extra_origins, allowed_origin_suffix.clone())
+        }
+    };
+    let reflect_all = matches!(cors_config.origins, AllowedOrigins::All);
+
+    let allowed_hosts: Arc<Vec<HostPattern>> = Arc::new(if host_config.allowed_hosts.is_empty() {
+        let (_, host, port) = split_origin(origin).expect("valid origin passed to build_router");
+        let default_host = match port {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        };
+        vec![HostPattern(default_host)]
+    } else {
+        host_config
+            .allowed_hosts
+            .iter()
+            .map(|pattern| HostPattern(pattern.clone()))
+            .collect()
+    });
+
+    let mut cors = CorsLayer::new()
+        .allow_origin(if reflect_all {
+            AllowOrigin::any()
+        } else {
+            AllowOrigin::predicate(move |request_origin: &axum::http::HeaderValue, _| {
+                is_allowed_origin(
+                    request_origin,
+                    &self_origin,
+                    &extra_origins,
+                    allowed_origin_suffix.as_deref(),
+                )
+            })
+        })
         .allow_methods([
             axum::http::Method::GET,
             axum::http::Method::POST,
@@ -59,14 +406,42 @@ pub fn build_router(state: AppState, origin: &str) -> Router {
         ])
         .allow_headers([
             axum::http::header::CONTENT_TYPE,
+            axum::http::header::AUTHORIZATION,
             axum::http::header::HeaderName::from_static("x-api-token"),
         ]);
+    if cors_config.allow_credentials {
+        cors = cors.allow_credentials(true);
+    }
+    if let Some(max_age_secs) = cors_config.max_age_secs {
+        cors = cors.max_age(std::time::Duration::from_secs(max_age_secs));
+    }
+    if !cors_config.exposed_headers.is_empty() {
+        let exposed_headers: Vec<axum::http::HeaderName> = cors_config
+            .exposed_headers
+            .iter()
+            .map(|name| {
+                axum::http::HeaderName::try_from(name)
+                    .unwrap_or_else(|_| panic!("invalid exposed CORS header name `{name}`"))
+            })
+            .collect();
+        cors = cors.expose_headers(exposed_headers);
+    }
+
+    // `labels.zip` is already compressed, so it's excluded even though it's
+    // served well above the size threshold.
+    let compression = CompressionLayer::new().compress_when(
+        SizeAbove::new(state.compression_min_bytes).and(NotForContentType::new("application/zip")),
+    );
 
     let shared = Arc::new(state);
 
     let public_api = Router::new()
         .route("/api/v1/health", get(health))
-        .route("/api/v1/limits", get(limits::get_limits));
+        .route("/api/v1/metrics", get(metrics::get_metrics))
+        .route("/api/v1/openapi.json", get(openapi::get_openapi))
+        .route("/api/v1/limits", get(limits::get_limits))
+        .route("/api/v1/auth/refresh", post(auth::refresh_token))
+        .route("/api/v1/graph/share/{token}", get(share::get_shared_graph));
 
     // Label mutation routes get a 2 MB body limit to prevent abuse via
     // oversized import payloads. Graph and other routes use Axum's default.
@@ -86,26 +461,81 @@ pub fn build_router(state: AppState, origin: &str) -> Router {
             "/api/v1/label/{file_id}/entry",
             delete(labels::delete_local_label_entry),
         )
+        .route(
+            "/api/v1/label/{file_id}/batch",
+            post(labels::batch_update_local_label_file),
+        )
         .route(
             "/api/v1/label/{file_id}/export",
             get(labels::export_local_label_file),
         )
+        .route(
+            "/api/v1/label/{file_id}/manifest",
+            get(labels::get_label_manifest),
+        )
+        .route(
+            "/api/v1/label/{file_id}/sync",
+            post(labels::sync_label_file),
+        )
+        .route(
+            "/api/v1/label/export-all",
+            get(labels::export_all_local_label_files),
+        )
+        .route(
+            "/api/v1/label/import-all",
+            post(labels::import_all_local_label_files),
+        )
+        .route(
+            "/api/v1/label/import-zip",
+            post(labels::import_zip_labels),
+        )
+        .route(
+            "/api/v1/label/jobs/rescan",
+            post(rescan::enqueue_rescan_job),
+        )
+        .route("/api/v1/label/jobs", get(rescan::list_rescan_jobs))
+        .route(
+            "/api/v1/label/jobs/{id}",
+            get(rescan::get_rescan_job).delete(rescan::cancel_rescan_job),
+        )
+        .route("/api/v1/label/events", get(watch::label_change_events))
         .layer(DefaultBodyLimit::max(LABEL_BODY_LIMIT));
 
     let protected_api = Router::new()
         .route("/api/v1/graph/tx/{txid}", get(graph::get_graph))
+        .route(
+            "/api/v1/graph/tx/{txid}/labels/export",
+            get(graph::export_graph_labels),
+        )
+        .route("/api/v1/graph/tx/{txid}/job", post(jobs::enqueue_graph_job))
+        .route(
+            "/api/v1/graph/tx/{txid}/preview",
+            get(preview::get_graph_preview),
+        )
+        .route(
+            "/api/v1/jobs/{id}",
+            get(jobs::get_job_status).delete(jobs::delete_job),
+        )
+        .route("/api/v1/jobs/{id}/result", get(jobs::get_job_result))
+        .route("/api/v1/tx/{txid}/proof", get(proof::get_tx_proof))
         .route("/api/v1/history", get(history::get_history))
         .route("/api/v1/labels.zip", get(labels::zip_browser_labels))
+        .route("/api/v1/share", post(share::create_share_link))
         .merge(label_routes);
 
-    Router::new()
+    Ok(Router::new()
         .merge(public_api)
         .merge(protected_api)
         .route("/api", any(api_not_found))
         .route("/api/{*path}", any(api_not_found))
         .fallback(static_files::static_files)
         .layer(cors)
-        .with_state(shared)
+        .layer(compression)
+        .layer(axum::middleware::from_fn(move |request, next| {
+            let allowed_hosts = Arc::clone(&allowed_hosts);
+            async move { validate_host(allowed_hosts, request, next).await }
+        }))
+        .with_state(shared))
 }
 
 async fn health() -> Json<serde_json::Value> {
@@ -124,7 +554,7 @@ mod tests {
     use axum::http::{Request, StatusCode};
     use bitcoin::hashes::Hash;
     use bitcoin::{Amount, ScriptBuf, Txid};
-    use cory_core::error::{CoreError, RpcError};
+    use cory_core::error::{BitcoinRpcErrorCode, CoreError, RpcError};
     use cory_core::types::{ScriptType, TxInput, TxNode, TxOutput};
     use tower::ServiceExt;
 
@@ -150,7 +580,7 @@ mod tests {
                     Err(CoreError::InvalidTxData("invalid tx fixture".to_string()))
                 }
                 FakeRpcMode::RpcFailure => Err(CoreError::Rpc(RpcError::ServerError {
-                    code: -28,
+                    code: BitcoinRpcErrorCode::InWarmup,
                     message: "Loading block index...".to_string(),
                 })),
             }
@@ -160,7 +590,8 @@ mod tests {
             &self,
             _txid: &Txid,
             _vout: u32,
-        ) -> Result<Option<TxOutput>, CoreError> {
+            _include_mempool: bool,
+        ) -> Result<Option<cory_core::rpc::TxOutInfo>, CoreError> {
             Ok(None)
         }
 
@@ -172,6 +603,40 @@ mod tests {
                 pruned: false,
             })
         }
+
+        async fn get_txout_proof(&self, _txids: &[Txid]) -> Result<Option<String>, CoreError> {
+            Ok(None)
+        }
+
+        async fn get_block_header(
+            &self,
+            id: cory_core::rpc::BlockId,
+        ) -> Result<cory_core::rpc::BlockHeaderInfo, CoreError> {
+            let hash = self.get_block_hash(id).await?;
+            Ok(cory_core::rpc::BlockHeaderInfo {
+                hash,
+                height: 1,
+                merkle_root: bitcoin::TxMerkleNode::all_zeros(),
+            })
+        }
+
+        async fn estimate_smart_fee(
+            &self,
+            _conf_target: u16,
+            _mode: cory_core::rpc::EstimateMode,
+        ) -> Result<Option<f64>, CoreError> {
+            Ok(Some(5.0))
+        }
+
+        async fn get_block_hash(
+            &self,
+            id: cory_core::rpc::BlockId,
+        ) -> Result<bitcoin::BlockHash, CoreError> {
+            match id {
+                cory_core::rpc::BlockId::Hash(hash) => Ok(hash),
+                _ => Ok(bitcoin::BlockHash::all_zeros()),
+            }
+        }
     }
 
     fn sample_tx(txid: Txid) -> TxNode {
@@ -189,11 +654,14 @@ mod tests {
                 sequence: 0xFFFF_FFFF,
                 value: None,
                 script_type: None,
+                address: None,
+                unresolved_reason: None,
             }],
             outputs: vec![TxOutput {
                 value: Amount::from_sat(1_000),
                 script_pub_key: ScriptBuf::new(),
                 script_type: ScriptType::Unknown,
+                address: None,
             }],
         }
     }
@@ -211,9 +679,75 @@ mod tests {
             default_limits,
             rpc_concurrency: 4,
             network: bitcoin::Network::Regtest,
-            history: Arc::new(RwLock::new(HashMap::new())),
+            history: Arc::new(RwLock::new(HistoryStore::new(1000))),
+            wallet_registry: Arc::new(WalletRegistry::new(bitcoin::Network::Regtest, 0)),
+            jwt_manager: Arc::new(crate::auth::JwtManager::new(
+                crate::auth::generate_jwt_secret(),
+            )),
+            metrics: Arc::new(Metrics::new()),
+            compression_min_bytes: 1024,
+            jobs: Arc::new(JobStore::new(4, std::time::Duration::from_secs(300))),
+            previews: Arc::new(PreviewStore::new()),
+            rescan_jobs: Arc::new(RescanJobStore::new(2, std::time::Duration::from_secs(300))),
+            labels_rw_dirs: Vec::new(),
+            labels_ro_dirs: Vec::new(),
+            label_changes: tokio::sync::broadcast::channel(16).0,
+            label_watchers: Vec::new(),
         };
-        build_router(state, "http://127.0.0.1:3080")
+        build_router(
+            state,
+            "http://127.0.0.1:3080",
+            CorsConfig::default(),
+            test_host_config(),
+        )
+        .expect("default CORS config must be valid")
+    }
+
+    /// Test requests built via `Request::builder()` don't set a `Host`
+    /// header, so the default allowlist (just the bound `host:port`) would
+    /// reject every one of them. Host-allowlist enforcement itself is
+    /// covered by the dedicated `host_header_*` tests below.
+    fn test_host_config() -> HostConfig {
+        HostConfig {
+            allowed_hosts: vec!["*".to_string()],
+        }
+    }
+
+    fn test_router_with_jwt_manager(mode: FakeRpcMode) -> (Router, Arc<crate::auth::JwtManager>) {
+        let jwt_manager = Arc::new(crate::auth::JwtManager::new(
+            crate::auth::generate_jwt_secret(),
+        ));
+        let state = AppState {
+            rpc: Arc::new(FakeRpc { mode }),
+            cache: Arc::new(Cache::with_capacity(100, 100)),
+            labels: Arc::new(RwLock::new(LabelStore::new())),
+            api_token: "test-token".to_string(),
+            default_limits: GraphLimits::default(),
+            rpc_concurrency: 4,
+            network: bitcoin::Network::Regtest,
+            history: Arc::new(RwLock::new(HistoryStore::new(1000))),
+            wallet_registry: Arc::new(WalletRegistry::new(bitcoin::Network::Regtest, 0)),
+            jwt_manager: jwt_manager.clone(),
+            metrics: Arc::new(Metrics::new()),
+            compression_min_bytes: 1024,
+            jobs: Arc::new(JobStore::new(4, std::time::Duration::from_secs(300))),
+            previews: Arc::new(PreviewStore::new()),
+            rescan_jobs: Arc::new(RescanJobStore::new(2, std::time::Duration::from_secs(300))),
+            labels_rw_dirs: Vec::new(),
+            labels_ro_dirs: Vec::new(),
+            label_changes: tokio::sync::broadcast::channel(16).0,
+            label_watchers: Vec::new(),
+        };
+        (
+            build_router(
+                state,
+                "http://127.0.0.1:3080",
+                CorsConfig::default(),
+                test_host_config(),
+            )
+            .expect("default CORS config must be valid"),
+            jwt_manager,
+        )
     }
 
     fn txid_str(byte: u8) -> String {
@@ -258,6 +792,7 @@ mod tests {
                 max_depth: 5_000,
                 max_nodes: 80_000,
                 max_edges: 300_000,
+                strategy: GraphStrategy::BreadthFirst,
             },
         );
         let response = router
@@ -353,6 +888,7 @@ mod tests {
                 max_depth: 2,
                 max_nodes: 500,
                 max_edges: 2000,
+                strategy: GraphStrategy::BreadthFirst,
             },
         );
         let url = format!("/api/v1/graph/tx/{}?max_depth={}", txid_str(1), 3);
@@ -425,4 +961,442 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
     }
+
+    #[tokio::test]
+    async fn auth_refresh_rotates_tokens_and_revokes_the_old_pair() {
+        let (router, jwt_manager) = test_router_with_jwt_manager(FakeRpcMode::Ok);
+        let (_access_token, refresh_token) = jwt_manager
+            .issue_token_pair("session-1".to_string(), Vec::new())
+            .await
+            .expect("token pair must be issued");
+
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/auth/refresh")
+                    .header("cookie", format!("cory_refresh_token={refresh_token}"))
+                    .body(Body::empty())
+                    .expect("request must build"),
+            )
+            .await
+            .expect("router should serve request");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("set-cookie").is_some());
+        let json = response_body_json(response).await;
+        assert!(json
+            .get("access_token")
+            .and_then(serde_json::Value::as_str)
+            .is_some());
+
+        // The old refresh token's session has been revoked, so replaying it
+        // must fail even though the JWT itself hasn't expired.
+        let replay = router
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/auth/refresh")
+                    .header("cookie", format!("cory_refresh_token={refresh_token}"))
+                    .body(Body::empty())
+                    .expect("request must build"),
+            )
+            .await
+            .expect("router should serve request");
+
+        assert_eq!(replay.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn auth_refresh_rejects_access_token() {
+        let (router, jwt_manager) = test_router_with_jwt_manager(FakeRpcMode::Ok);
+        let (access_token, _refresh_token) = jwt_manager
+            .issue_token_pair("session-2".to_string(), Vec::new())
+            .await
+            .expect("token pair must be issued");
+
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/auth/refresh")
+                    .header("cookie", format!("cory_refresh_token={access_token}"))
+                    .body(Body::empty())
+                    .expect("request must build"),
+            )
+            .await
+            .expect("router should serve request");
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn glob_match_supports_leading_and_embedded_wildcards() {
+        assert!(glob_match("*.example.com", "app.example.com"));
+        assert!(glob_match("app-*.internal", "app-42.internal"));
+        assert!(!glob_match("app-*.internal", "other-42.internal"));
+        assert!(glob_match("exact.example.com", "exact.example.com"));
+        assert!(!glob_match("exact.example.com", "other.example.com"));
+    }
+
+    #[test]
+    fn origin_pattern_wildcard_only_applies_to_host() {
+        let pattern = OriginPattern::compile("https://*.example.com").expect("valid pattern");
+        assert!(pattern.matches("https", "wallet.example.com", None));
+        // Same host match, but a mismatched scheme or port must not pass.
+        assert!(!pattern.matches("http", "wallet.example.com", None));
+        assert!(!pattern.matches("https", "wallet.example.com", Some("8443")));
+    }
+
+    #[test]
+    fn is_allowed_origin_matches_self_wildcard_and_suffix_but_not_others() {
+        let self_origin: axum::http::HeaderValue =
+            "http://127.0.0.1:3080".parse().expect("valid header value");
+        let extra = vec![
+            OriginPattern::compile("https://*.example.com").expect("valid pattern"),
+            OriginPattern::compile("https://app-*.internal").expect("valid pattern"),
+        ];
+
+        let allowed_self: axum::http::HeaderValue =
+            "http://127.0.0.1:3080".parse().expect("valid header value");
+        assert!(is_allowed_origin(&allowed_self, &self_origin, &extra, None));
+
+        let allowed_glob: axum::http::HeaderValue = "https://wallet.example.com"
+            .parse()
+            .expect("valid header value");
+        assert!(is_allowed_origin(&allowed_glob, &self_origin, &extra, None));
+
+        let wrong_scheme: axum::http::HeaderValue = "http://wallet.example.com"
+            .parse()
+            .expect("valid header value");
+        assert!(!is_allowed_origin(
+            &wrong_scheme,
+            &self_origin,
+            &extra,
+            None
+        ));
+
+        let allowed_suffix: axum::http::HeaderValue = "https://wallet.other.com"
+            .parse()
+            .expect("valid header value");
+        assert!(is_allowed_origin(
+            &allowed_suffix,
+            &self_origin,
+            &extra,
+            Some(".other.com")
+        ));
+
+        let disallowed: axum::http::HeaderValue =
+            "https://evil.com".parse().expect("valid header value");
+        assert!(!is_allowed_origin(&disallowed, &self_origin, &extra, None));
+    }
+
+    #[test]
+    fn host_pattern_supports_glob_and_wildcard_all() {
+        assert!(HostPattern("localhost:*".to_string()).matches("localhost:3080"));
+        assert!(HostPattern("127.0.0.1:*".to_string()).matches("127.0.0.1:3080"));
+        assert!(!HostPattern("localhost:*".to_string()).matches("evil.com:3080"));
+        assert!(HostPattern("*".to_string()).matches("anything-at-all"));
+    }
+
+    fn router_with_host_config(mode: FakeRpcMode, host_config: HostConfig) -> Router {
+        let state = AppState {
+            rpc: Arc::new(FakeRpc { mode }),
+            cache: Arc::new(Cache::with_capacity(100, 100)),
+            labels: Arc::new(RwLock::new(LabelStore::new())),
+            api_token: "test-token".to_string(),
+            default_limits: GraphLimits::default(),
+            rpc_concurrency: 4,
+            network: bitcoin::Network::Regtest,
+            history: Arc::new(RwLock::new(HistoryStore::new(1000))),
+            wallet_registry: Arc::new(WalletRegistry::new(bitcoin::Network::Regtest, 0)),
+            jwt_manager: Arc::new(crate::auth::JwtManager::new(
+                crate::auth::generate_jwt_secret(),
+            )),
+            metrics: Arc::new(Metrics::new()),
+            compression_min_bytes: 1024,
+            jobs: Arc::new(JobStore::new(4, std::time::Duration::from_secs(300))),
+            previews: Arc::new(PreviewStore::new()),
+            rescan_jobs: Arc::new(RescanJobStore::new(2, std::time::Duration::from_secs(300))),
+            labels_rw_dirs: Vec::new(),
+            labels_ro_dirs: Vec::new(),
+            label_changes: tokio::sync::broadcast::channel(16).0,
+            label_watchers: Vec::new(),
+        };
+        build_router(
+            state,
+            "http://127.0.0.1:3080",
+            CorsConfig::default(),
+            host_config,
+        )
+        .expect("default CORS config must be valid")
+    }
+
+    #[tokio::test]
+    async fn allowed_host_header_returns_200() {
+        let router = router_with_host_config(
+            FakeRpcMode::Ok,
+            HostConfig {
+                allowed_hosts: vec!["127.0.0.1:3080".to_string()],
+            },
+        );
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/health")
+                    .header("host", "127.0.0.1:3080")
+                    .body(Body::empty())
+                    .expect("request must build"),
+            )
+            .await
+            .expect("router should serve request");
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn spoofed_host_header_is_rejected() {
+        let router = router_with_host_config(
+            FakeRpcMode::Ok,
+            HostConfig {
+                allowed_hosts: vec!["127.0.0.1:3080".to_string()],
+            },
+        );
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/health")
+                    .header("host", "evil.example.com")
+                    .body(Body::empty())
+                    .expect("request must build"),
+            )
+            .await
+            .expect("router should serve request");
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn missing_host_header_is_rejected_unless_wildcard_allowed() {
+        let router = router_with_host_config(
+            FakeRpcMode::Ok,
+            HostConfig {
+                allowed_hosts: vec!["127.0.0.1:3080".to_string()],
+            },
+        );
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/health")
+                    .body(Body::empty())
+                    .expect("request must build"),
+            )
+            .await
+            .expect("router should serve request");
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    fn router_with_cors_config(mode: FakeRpcMode, cors_config: CorsConfig) -> eyre::Result<Router> {
+        let state = AppState {
+            rpc: Arc::new(FakeRpc { mode }),
+            cache: Arc::new(Cache::with_capacity(100, 100)),
+            labels: Arc::new(RwLock::new(LabelStore::new())),
+            api_token: "test-token".to_string(),
+            default_limits: GraphLimits::default(),
+            rpc_concurrency: 4,
+            network: bitcoin::Network::Regtest,
+            history: Arc::new(RwLock::new(HistoryStore::new(1000))),
+            wallet_registry: Arc::new(WalletRegistry::new(bitcoin::Network::Regtest, 0)),
+            jwt_manager: Arc::new(crate::auth::JwtManager::new(
+                crate::auth::generate_jwt_secret(),
+            )),
+            metrics: Arc::new(Metrics::new()),
+            compression_min_bytes: 1024,
+            jobs: Arc::new(JobStore::new(4, std::time::Duration::from_secs(300))),
+            previews: Arc::new(PreviewStore::new()),
+            rescan_jobs: Arc::new(RescanJobStore::new(2, std::time::Duration::from_secs(300))),
+            labels_rw_dirs: Vec::new(),
+            labels_ro_dirs: Vec::new(),
+            label_changes: tokio::sync::broadcast::channel(16).0,
+            label_watchers: Vec::new(),
+        };
+        build_router(
+            state,
+            "http://127.0.0.1:3080",
+            cors_config,
+            test_host_config(),
+        )
+    }
+
+    #[tokio::test]
+    async fn preflight_response_includes_numeric_max_age() {
+        let router = router_with_cors_config(FakeRpcMode::Ok, CorsConfig::default())
+            .expect("default CORS config must be valid");
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .method("OPTIONS")
+                    .uri("/api/v1/health")
+                    .header("origin", "http://127.0.0.1:3080")
+                    .header("access-control-request-method", "GET")
+                    .body(Body::empty())
+                    .expect("request must build"),
+            )
+            .await
+            .expect("router should serve request");
+
+        assert!(response
+            .headers()
+            .get("access-control-allow-methods")
+            .is_some());
+        assert!(response
+            .headers()
+            .get("access-control-allow-headers")
+            .is_some());
+        let max_age = response
+            .headers()
+            .get("access-control-max-age")
+            .expect("max-age header must be present by default")
+            .to_str()
+            .expect("header value must be a valid string");
+        assert!(max_age.parse::<u64>().is_ok());
+    }
+
+    #[tokio::test]
+    async fn preflight_response_omits_max_age_when_disabled() {
+        let router = router_with_cors_config(
+            FakeRpcMode::Ok,
+            CorsConfig {
+                max_age_secs: None,
+                ..CorsConfig::default()
+            },
+        )
+        .expect("default CORS config must be valid");
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .method("OPTIONS")
+                    .uri("/api/v1/health")
+                    .header("origin", "http://127.0.0.1:3080")
+                    .header("access-control-request-method", "GET")
+                    .body(Body::empty())
+                    .expect("request must build"),
+            )
+            .await
+            .expect("router should serve request");
+
+        assert!(response.headers().get("access-control-max-age").is_none());
+    }
+
+    #[tokio::test]
+    async fn health_response_exposes_configured_headers() {
+        let router = router_with_cors_config(
+            FakeRpcMode::Ok,
+            CorsConfig {
+                exposed_headers: vec!["etag".to_string(), "x-request-id".to_string()],
+                ..CorsConfig::default()
+            },
+        )
+        .expect("default CORS config must be valid");
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/health")
+                    .header("origin", "http://127.0.0.1:3080")
+                    .body(Body::empty())
+                    .expect("request must build"),
+            )
+            .await
+            .expect("router should serve request");
+
+        let exposed = response
+            .headers()
+            .get("access-control-expose-headers")
+            .expect("expose-headers must be present")
+            .to_str()
+            .expect("header value must be a valid string");
+        assert_eq!(exposed, "etag,x-request-id");
+    }
+
+    #[test]
+    fn credentials_with_reflect_all_origin_is_rejected() {
+        let result = router_with_cors_config(
+            FakeRpcMode::Ok,
+            CorsConfig {
+                origins: AllowedOrigins::All,
+                allow_credentials: true,
+                ..CorsConfig::default()
+            },
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn credentials_with_allowlisted_origin_are_reflected() {
+        let router = router_with_cors_config(
+            FakeRpcMode::Ok,
+            CorsConfig {
+                origins: AllowedOrigins::Some {
+                    extra_origins: vec!["https://wallet.example.com".to_string()],
+                    allowed_origin_suffix: None,
+                },
+                allow_credentials: true,
+                ..CorsConfig::default()
+            },
+        )
+        .expect("concrete allowlist with credentials must be valid");
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .method("OPTIONS")
+                    .uri("/api/v1/health")
+                    .header("origin", "https://wallet.example.com")
+                    .header("access-control-request-method", "GET")
+                    .body(Body::empty())
+                    .expect("request must build"),
+            )
+            .await
+            .expect("router should serve request");
+
+        let allowed_origin = response
+            .headers()
+            .get("access-control-allow-origin")
+            .expect("allow-origin header must be present")
+            .to_str()
+            .expect("header value must be a valid string");
+        assert_eq!(allowed_origin, "https://wallet.example.com");
+
+        let allow_credentials = response
+            .headers()
+            .get("access-control-allow-credentials")
+            .expect("allow-credentials header must be present")
+            .to_str()
+            .expect("header value must be a valid string");
+        assert_eq!(allow_credentials, "true");
+    }
+
+    #[tokio::test]
+    async fn wildcard_host_config_allows_any_host() {
+        let router = router_with_host_config(
+            FakeRpcMode::Ok,
+            HostConfig {
+                allowed_hosts: vec!["*".to_string()],
+            },
+        );
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/health")
+                    .header("host", "anything.example.com")
+                    .body(Body::empty())
+                    .expect("request must build"),
+            )
+            .await
+            .expect("router should serve request");
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
 }
@@ -0,0 +1,347 @@
+//! Hand-written OpenAPI 3 description of the HTTP API, served at
+//! `/api/v1/openapi.json` so generated clients and tooling can consume the
+//! same surface the e2e test exercises by hand.
+//!
+//! Kept as a single `serde_json::json!` literal rather than derived from the
+//! handlers: the route set is small and changes rarely enough that a
+//! derive-macro dependency isn't worth it, and a literal is easy to keep in
+//! sync by eye when a route is added.
+
+use axum::Json;
+
+pub(super) async fn get_openapi() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Cory API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Bitcoin transaction ancestry explorer: graph traversal, BIP-329 labels, search history, and inclusion proofs."
+        },
+        "components": {
+            "securitySchemes": {
+                "apiKeyHeader": {
+                    "type": "apiKey",
+                    "in": "header",
+                    "name": "X-API-Token"
+                },
+                "bearerAuth": {
+                    "type": "http",
+                    "scheme": "bearer"
+                }
+            },
+            "schemas": {
+                "Error": {
+                    "type": "object",
+                    "required": ["error"],
+                    "properties": {
+                        "error": { "type": "string" }
+                    }
+                },
+                "LabelRecord": {
+                    "type": "object",
+                    "required": ["type", "ref", "label"],
+                    "properties": {
+                        "type": { "type": "string", "enum": ["tx", "input", "output", "addr"] },
+                        "ref": { "type": "string" },
+                        "label": { "type": "string" }
+                    }
+                },
+                "LabelFileSummary": {
+                    "type": "object",
+                    "required": ["id", "name", "kind", "editable", "record_count"],
+                    "properties": {
+                        "id": { "type": "string" },
+                        "name": { "type": "string" },
+                        "kind": { "type": "string" },
+                        "editable": { "type": "boolean" },
+                        "record_count": { "type": "integer", "minimum": 0 }
+                    }
+                },
+                "HistoryEntry": {
+                    "type": "object",
+                    "required": ["txid", "searched_at", "network", "limits", "node_count", "edge_count"],
+                    "properties": {
+                        "txid": { "type": "string" },
+                        "searched_at": { "type": "string", "format": "date-time" },
+                        "network": { "type": "string" },
+                        "limits": { "type": "object" },
+                        "node_count": { "type": "integer", "minimum": 0 },
+                        "edge_count": { "type": "integer", "minimum": 0 }
+                    }
+                },
+                "GraphResponse": {
+                    "type": "object",
+                    "description": "Ancestry graph plus fee/RBF/locktime enrichments, merged BIP-329 labels, and address clustering.",
+                    "required": ["nodes", "edges", "stats", "enrichments", "labels_by_type"],
+                    "properties": {
+                        "nodes": { "type": "object" },
+                        "edges": { "type": "array", "items": { "type": "object" } },
+                        "stats": { "type": "object" },
+                        "enrichments": { "type": "object" },
+                        "labels_by_type": { "type": "object" },
+                        "input_address_refs": { "type": "object" },
+                        "output_address_refs": { "type": "object" },
+                        "address_occurrences": { "type": "object" },
+                        "clusters": { "type": "object" }
+                    }
+                }
+            }
+        },
+        "security": [
+            { "apiKeyHeader": [] },
+            { "bearerAuth": [] }
+        ],
+        "paths": {
+            "/api/v1/health": {
+                "get": {
+                    "summary": "Liveness check",
+                    "security": [],
+                    "responses": {
+                        "200": { "description": "Server is up" }
+                    }
+                }
+            },
+            "/api/v1/metrics": {
+                "get": {
+                    "summary": "Prometheus metrics in text exposition format",
+                    "security": [],
+                    "responses": {
+                        "200": { "description": "Metrics", "content": { "text/plain; version=0.0.4": {} } }
+                    }
+                }
+            },
+            "/api/v1/limits": {
+                "get": {
+                    "summary": "Server-configured and hard-ceiling graph traversal limits",
+                    "security": [],
+                    "responses": {
+                        "200": { "description": "Limits" }
+                    }
+                }
+            },
+            "/api/v1/openapi.json": {
+                "get": {
+                    "summary": "This document",
+                    "security": [],
+                    "responses": {
+                        "200": { "description": "OpenAPI 3 description" }
+                    }
+                }
+            },
+            "/api/v1/auth/refresh": {
+                "post": {
+                    "summary": "Exchange a refresh-token cookie for a new access/refresh pair",
+                    "security": [],
+                    "responses": {
+                        "200": { "description": "New access token issued" },
+                        "401": { "description": "Missing, expired, revoked, or wrong-type token", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+                    }
+                }
+            },
+            "/api/v1/graph/tx/{txid}": {
+                "get": {
+                    "summary": "Build the ancestry graph for a transaction",
+                    "parameters": [
+                        { "name": "txid", "in": "path", "required": true, "schema": { "type": "string" } },
+                        { "name": "max_depth", "in": "query", "schema": { "type": "integer" } },
+                        { "name": "max_nodes", "in": "query", "schema": { "type": "integer" } },
+                        { "name": "max_edges", "in": "query", "schema": { "type": "integer" } },
+                        { "name": "strategy", "in": "query", "schema": { "type": "string" } },
+                        { "name": "cluster", "in": "query", "schema": { "type": "boolean" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "Graph", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/GraphResponse" } } } },
+                        "400": { "description": "Invalid txid or limits", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "404": { "description": "Transaction not found", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+                    }
+                }
+            },
+            "/api/v1/graph/tx/{txid}/labels/export": {
+                "get": {
+                    "summary": "Export the graph's merged BIP-329 labels as newline-delimited JSON",
+                    "parameters": [
+                        { "name": "txid", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "JSONL export", "content": { "text/plain; charset=utf-8": {} } },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+                    }
+                }
+            },
+            "/api/v1/graph/tx/{txid}/preview": {
+                "get": {
+                    "summary": "Render the ancestry graph as an SVG or PNG preview image",
+                    "description": "Supports `Range` and `If-None-Match` conditional requests. Results are cached keyed by txid, limits, format, and label-store revision.",
+                    "parameters": [
+                        { "name": "txid", "in": "path", "required": true, "schema": { "type": "string" } },
+                        { "name": "format", "in": "query", "schema": { "type": "string", "enum": ["svg", "png"] } },
+                        { "name": "max_depth", "in": "query", "schema": { "type": "integer" } },
+                        { "name": "max_nodes", "in": "query", "schema": { "type": "integer" } },
+                        { "name": "max_edges", "in": "query", "schema": { "type": "integer" } },
+                        { "name": "strategy", "in": "query", "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "Rendered preview", "content": { "image/svg+xml": {}, "image/png": {} } },
+                        "206": { "description": "Partial preview" },
+                        "304": { "description": "Not modified" },
+                        "400": { "description": "Invalid txid, limits, or format", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "404": { "description": "Transaction not found", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "416": { "description": "Unsatisfiable range" }
+                    }
+                }
+            },
+            "/api/v1/tx/{txid}/proof": {
+                "get": {
+                    "summary": "Fetch and verify a confirmed transaction's Merkle inclusion proof",
+                    "parameters": [
+                        { "name": "txid", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "Inclusion proof" },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "404": { "description": "Unconfirmed or not found", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+                    }
+                }
+            },
+            "/api/v1/history": {
+                "get": {
+                    "summary": "Paginated, newest-first search history",
+                    "parameters": [
+                        { "name": "offset", "in": "query", "schema": { "type": "integer" } },
+                        { "name": "limit", "in": "query", "schema": { "type": "integer" } }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "History page",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "entries": { "type": "array", "items": { "$ref": "#/components/schemas/HistoryEntry" } },
+                                            "offset": { "type": "integer" },
+                                            "total": { "type": "integer" }
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+                    }
+                }
+            },
+            "/api/v1/label": {
+                "get": {
+                    "summary": "List label files",
+                    "responses": {
+                        "200": { "description": "Label files", "content": { "application/json": { "schema": { "type": "array", "items": { "$ref": "#/components/schemas/LabelFileSummary" } } } } },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+                    }
+                },
+                "post": {
+                    "summary": "Create or import a browser-editable label file",
+                    "responses": {
+                        "200": { "description": "Created file", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/LabelFileSummary" } } } },
+                        "400": { "description": "Invalid request body", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+                    }
+                }
+            },
+            "/api/v1/label/{file_id}": {
+                "post": {
+                    "summary": "Upsert a label or replace a browser file's full content",
+                    "parameters": [
+                        { "name": "file_id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "Updated file", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/LabelFileSummary" } } } },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+                    }
+                },
+                "delete": {
+                    "summary": "Delete a browser-editable label file",
+                    "parameters": [
+                        { "name": "file_id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "Deleted" },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+                    }
+                }
+            },
+            "/api/v1/label/{file_id}/entry": {
+                "delete": {
+                    "summary": "Delete one label entry from a file",
+                    "parameters": [
+                        { "name": "file_id", "in": "path", "required": true, "schema": { "type": "string" } },
+                        { "name": "type", "in": "query", "required": true, "schema": { "type": "string" } },
+                        { "name": "ref", "in": "query", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "Updated file", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/LabelFileSummary" } } } },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+                    }
+                }
+            },
+            "/api/v1/label/{file_id}/export": {
+                "get": {
+                    "summary": "Export one label file verbatim",
+                    "description": "Supports `Range` and `If-None-Match` conditional requests.",
+                    "parameters": [
+                        { "name": "file_id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "Full export", "content": { "text/plain; charset=utf-8": {} } },
+                        "206": { "description": "Partial export" },
+                        "304": { "description": "Not modified" },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "416": { "description": "Unsatisfiable range" }
+                    }
+                }
+            },
+            "/api/v1/label/export-all": {
+                "get": {
+                    "summary": "Export every editable label file as a single JSON manifest",
+                    "description": "Supports `Range` and `If-None-Match` conditional requests.",
+                    "responses": {
+                        "200": { "description": "Manifest", "content": { "application/json": {} } },
+                        "206": { "description": "Partial manifest" },
+                        "304": { "description": "Not modified" },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "404": { "description": "No editable label files to export", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "416": { "description": "Unsatisfiable range" }
+                    }
+                }
+            },
+            "/api/v1/label/import-all": {
+                "post": {
+                    "summary": "Restore a JSON manifest produced by export-all, recreating each file",
+                    "parameters": [
+                        { "name": "conflict", "in": "query", "schema": { "type": "string", "enum": ["skip", "rename", "overwrite"] } }
+                    ],
+                    "responses": {
+                        "200": { "description": "Summary of created/skipped/renamed/overwritten files", "content": { "application/json": {} } },
+                        "400": { "description": "Invalid request body", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } }
+                    }
+                }
+            },
+            "/api/v1/labels.zip": {
+                "get": {
+                    "summary": "Export all browser-editable label files as a zip archive",
+                    "description": "Supports `Range` and `If-None-Match` conditional requests.",
+                    "responses": {
+                        "200": { "description": "Archive", "content": { "application/zip": {} } },
+                        "206": { "description": "Partial archive" },
+                        "304": { "description": "Not modified" },
+                        "401": { "description": "Missing or invalid credential", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "404": { "description": "No browser label files to export", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } } },
+                        "416": { "description": "Unsatisfiable range" }
+                    }
+                }
+            }
+        }
+    }))
+}
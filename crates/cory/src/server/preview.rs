@@ -0,0 +1,413 @@
+//! Server-side rendering of an ancestry graph to SVG/PNG, for embedding in
+//! reports, READMEs, or link previews — places that can't run the embedded
+//! SPA that normally renders `GET /api/v1/graph/tx/{txid}`'s JSON.
+//!
+//! [`render_svg`] lays nodes out in depth columns from the root, colors
+//! each by its [`TxEnrichment`](super::graph::TxEnrichment) (self-owned,
+//! RBF-signaling, locktime-active) and captions it with its first `tx`
+//! label or, failing that, its fee. [`render_png`] rasterizes that SVG via
+//! `resvg`.
+//!
+//! Rendering a deep graph isn't free, so results are cached in
+//! [`PreviewStore`] keyed by `(txid, limits, format, label-store
+//! revision)` — the revision (see
+//! [`cory_core::labels::LabelStore::revision`]) changes whenever any label
+//! mutates, which naturally invalidates every cached preview that might
+//! have rendered a now-stale label, without needing to track which
+//! preview depends on which label file.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::HeaderMap;
+use axum::response::Response;
+use bitcoin::Txid;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+
+use cory_core::enrich::LocktimeKind;
+use cory_core::types::GraphStrategy;
+use cory_core::AncestryGraph;
+
+use super::auth::check_auth;
+use super::error::AppError;
+use super::graph::{
+    build_graph_response, resolve_graph_request, run_graph_build, GraphQuery, GraphResponse,
+};
+use super::range::serve_with_range_and_etag;
+use super::SharedState;
+
+// ==============================================================================
+// Query
+// ==============================================================================
+
+#[derive(Deserialize)]
+pub(super) struct PreviewQuery {
+    format: Option<String>,
+    max_depth: Option<usize>,
+    max_nodes: Option<usize>,
+    max_edges: Option<usize>,
+    strategy: Option<GraphStrategy>,
+}
+
+// ==============================================================================
+// Preview store
+// ==============================================================================
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PreviewFormat {
+    Svg,
+    Png,
+}
+
+impl PreviewFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Svg => "image/svg+xml",
+            Self::Png => "image/png",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Png => "png",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct PreviewKey {
+    txid: Txid,
+    max_depth: usize,
+    max_nodes: usize,
+    max_edges: usize,
+    strategy: GraphStrategy,
+    format: PreviewFormat,
+    label_revision: u64,
+}
+
+impl std::hash::Hash for PreviewKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.txid.hash(state);
+        self.max_depth.hash(state);
+        self.max_nodes.hash(state);
+        self.max_edges.hash(state);
+        // `GraphStrategy` doesn't derive `Hash` (it's a small, fixed enum
+        // used mostly for equality checks elsewhere), so hash it by
+        // discriminant instead of adding the derive just for this one use.
+        (self.strategy == GraphStrategy::ValueWeighted).hash(state);
+        (self.format == PreviewFormat::Png).hash(state);
+        self.label_revision.hash(state);
+    }
+}
+
+/// Caches rendered previews keyed by `(txid, limits, format, label-store
+/// revision)`. Entries aren't evicted on a timer — a label mutation bumps
+/// the store-wide revision, so every entry keyed on an older revision for
+/// the same `(txid, limits, format)` becomes unreachable, and
+/// [`Self::insert`] sweeps those out opportunistically (same pattern as
+/// [`super::jobs::JobStore`]'s TTL sweep).
+pub struct PreviewStore {
+    entries: RwLock<HashMap<PreviewKey, Arc<Vec<u8>>>>,
+}
+
+impl PreviewStore {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    async fn get(&self, key: &PreviewKey) -> Option<Arc<Vec<u8>>> {
+        self.entries.read().await.get(key).cloned()
+    }
+
+    async fn insert(&self, key: PreviewKey, rendered: Vec<u8>) -> Arc<Vec<u8>> {
+        let rendered = Arc::new(rendered);
+        let mut entries = self.entries.write().await;
+        entries.retain(|existing, _| {
+            existing.label_revision == key.label_revision
+                || existing.txid != key.txid
+                || existing.format != key.format
+                || existing.max_depth != key.max_depth
+                || existing.max_nodes != key.max_nodes
+                || existing.max_edges != key.max_edges
+                || existing.strategy != key.strategy
+        });
+        entries.insert(key, Arc::clone(&rendered));
+        rendered
+    }
+}
+
+impl Default for PreviewStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ==============================================================================
+// Handler
+// ==============================================================================
+
+pub(super) async fn get_graph_preview(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(txid_str): Path<String>,
+    Query(query): Query<PreviewQuery>,
+) -> Result<Response, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+
+    let format = match query.format.as_deref() {
+        None | Some("svg") => PreviewFormat::Svg,
+        Some("png") => PreviewFormat::Png,
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "unsupported preview format `{other}`, expected `svg` or `png`"
+            )))
+        }
+    };
+
+    let graph_query = GraphQuery {
+        max_depth: query.max_depth,
+        max_nodes: query.max_nodes,
+        max_edges: query.max_edges,
+        strategy: query.strategy,
+        cluster: None,
+    };
+    let (txid, limits) = resolve_graph_request(&state, &txid_str, &graph_query)?;
+    let label_revision = state.labels.read().await.revision();
+
+    let key = PreviewKey {
+        txid,
+        max_depth: limits.max_depth,
+        max_nodes: limits.max_nodes,
+        max_edges: limits.max_edges,
+        strategy: limits.strategy,
+        format,
+        label_revision,
+    };
+
+    let bytes = match state.previews.get(&key).await {
+        Some(cached) => {
+            state.metrics.preview_cache_results_total.inc("hit");
+            cached
+        }
+        None => {
+            state.metrics.preview_cache_results_total.inc("miss");
+            let graph = run_graph_build(&state, txid, &limits, None).await?;
+            let response = build_graph_response(&state, graph, true).await;
+            let rendered = match format {
+                PreviewFormat::Svg => render_svg(&response).into_bytes(),
+                PreviewFormat::Png => render_png(&response)
+                    .map_err(|e| AppError::Internal(format!("failed to render preview: {e}")))?,
+            };
+            state.previews.insert(key, rendered).await
+        }
+    };
+
+    let content_type = format.content_type();
+    let disposition = format!(
+        "attachment; filename=\"{txid}-preview.{}\"",
+        format.extension()
+    );
+    serve_with_range_and_etag(
+        &headers,
+        (*bytes).clone(),
+        content_type,
+        Some(disposition),
+        None,
+    )
+}
+
+// ==============================================================================
+// Rendering
+// ==============================================================================
+
+const COLUMN_WIDTH: f64 = 180.0;
+const ROW_HEIGHT: f64 = 70.0;
+const NODE_WIDTH: f64 = 160.0;
+const NODE_HEIGHT: f64 = 48.0;
+const MARGIN: f64 = 20.0;
+
+#[derive(Debug, thiserror::Error)]
+pub(super) enum PreviewError {
+    #[error("failed to parse rendered SVG: {0}")]
+    Svg(String),
+    #[error("failed to rasterize preview: {0}")]
+    Raster(String),
+}
+
+/// Renders `response`'s graph into a depth-columned SVG: the root at
+/// column 0, each ancestor one column further right, colored by its
+/// [`TxEnrichment`](super::graph::TxEnrichment) and annotated with its
+/// first `tx`-type label, if any.
+fn render_svg(response: &GraphResponse) -> String {
+    let graph = &response.graph;
+    let depths = node_depths(graph);
+    let max_depth = depths.values().copied().max().unwrap_or(0);
+
+    let mut columns: Vec<Vec<Txid>> = vec![Vec::new(); max_depth + 1];
+    for (txid, depth) in &depths {
+        columns[*depth].push(*txid);
+    }
+    for column in &mut columns {
+        column.sort();
+    }
+
+    let width = MARGIN * 2.0 + (max_depth as f64 + 1.0) * COLUMN_WIDTH;
+    let max_rows = columns.iter().map(Vec::len).max().unwrap_or(1).max(1);
+    let height = MARGIN * 2.0 + max_rows as f64 * ROW_HEIGHT;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    );
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="#ffffff"/>"#);
+
+    for edge in &graph.edges {
+        if let (Some(&spending_depth), Some(&funding_depth)) = (
+            depths.get(&edge.spending_txid),
+            depths.get(&edge.funding_txid),
+        ) {
+            let (x1, y1) = node_center(&columns, spending_depth, edge.spending_txid);
+            let (x2, y2) = node_center(&columns, funding_depth, edge.funding_txid);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="#94a3b8" stroke-width="1.5"/>"#
+            );
+        }
+    }
+
+    for (depth, column) in columns.iter().enumerate() {
+        for txid in column {
+            let (cx, cy) = node_center(&columns, depth, *txid);
+            let x = cx - NODE_WIDTH / 2.0;
+            let y = cy - NODE_HEIGHT / 2.0;
+            let enrichment = response.enrichments.get(&txid.to_string());
+
+            let fill = match enrichment {
+                Some(e) if e.self_owned => "#dbeafe",
+                Some(e) if e.rbf_signaling => "#ffedd5",
+                _ => "#f1f5f9",
+            };
+            let stroke = match enrichment {
+                Some(e) if e.locktime.kind != LocktimeKind::Disabled && e.locktime.active => {
+                    "#dc2626"
+                }
+                _ => "#475569",
+            };
+
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{x}" y="{y}" width="{NODE_WIDTH}" height="{NODE_HEIGHT}" rx="6" fill="{fill}" stroke="{stroke}" stroke-width="1.5"/>"#
+            );
+
+            let short_txid = format!("{}…", &txid.to_string()[..10]);
+            let _ = writeln!(
+                svg,
+                r#"<text x="{cx}" y="{}" text-anchor="middle" font-family="monospace" font-size="12" fill="#0f172a">{}</text>"#,
+                y + 18.0,
+                escape_xml(&short_txid)
+            );
+
+            let subtitle = enrichment
+                .and_then(|e| e.fee_sats)
+                .map(|sats| format!("fee {sats} sat"))
+                .unwrap_or_default();
+            let label = response
+                .labels_by_type
+                .tx
+                .get(&txid.to_string())
+                .and_then(|entries| entries.first())
+                .map(|entry| entry.label.clone());
+            let caption = label.unwrap_or(subtitle);
+            if !caption.is_empty() {
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{cx}" y="{}" text-anchor="middle" font-family="sans-serif" font-size="11" fill="#64748b">{}</text>"#,
+                    y + 34.0,
+                    escape_xml(&caption)
+                );
+            }
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn render_png(response: &GraphResponse) -> Result<Vec<u8>, PreviewError> {
+    let svg = render_svg(response);
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default())
+        .map_err(|e| PreviewError::Svg(e.to_string()))?;
+    let size = tree.size();
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width().round() as u32, size.height().round() as u32)
+            .ok_or_else(|| PreviewError::Raster("preview canvas has zero size".to_string()))?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::identity(),
+        &mut pixmap.as_mut(),
+    );
+    pixmap
+        .encode_png()
+        .map_err(|e| PreviewError::Raster(e.to_string()))
+}
+
+/// BFS depth of every node from the graph root, following edges from
+/// spending transaction to funding transaction (i.e. "how many hops back
+/// in ancestry"). Matches the depth `build_ancestry` itself assigns during
+/// traversal, just recomputed from the edge list since `TxNode` doesn't
+/// carry its depth.
+fn node_depths(graph: &AncestryGraph) -> HashMap<Txid, usize> {
+    let mut children: HashMap<Txid, Vec<Txid>> = HashMap::new();
+    for edge in &graph.edges {
+        children
+            .entry(edge.spending_txid)
+            .or_default()
+            .push(edge.funding_txid);
+    }
+
+    let mut depths = HashMap::new();
+    depths.insert(graph.root_txid, 0usize);
+    let mut queue = std::collections::VecDeque::new();
+    queue.push_back(graph.root_txid);
+
+    while let Some(txid) = queue.pop_front() {
+        let depth = depths[&txid];
+        if let Some(funding) = children.get(&txid) {
+            for &next in funding {
+                if !depths.contains_key(&next) {
+                    depths.insert(next, depth + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    depths
+}
+
+fn node_center(columns: &[Vec<Txid>], depth: usize, txid: Txid) -> (f64, f64) {
+    let column = &columns[depth];
+    let row = column.iter().position(|t| *t == txid).unwrap_or(0);
+    let x = MARGIN + depth as f64 * COLUMN_WIDTH + NODE_WIDTH / 2.0;
+    let y = MARGIN + row as f64 * ROW_HEIGHT + NODE_HEIGHT / 2.0;
+    (x, y)
+}
+
+fn escape_xml(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            _ => c.to_string(),
+        })
+        .collect()
+}
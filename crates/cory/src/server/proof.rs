@@ -0,0 +1,87 @@
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::Json;
+use serde::Serialize;
+
+use cory_core::CoreError;
+
+use super::auth::check_auth;
+use super::error::AppError;
+use super::SharedState;
+
+// ==============================================================================
+// DTOs
+// ==============================================================================
+
+#[derive(Serialize)]
+pub(super) struct ProofResponse {
+    txid: String,
+    block_hash: String,
+    block_height: u32,
+    merkle_root: String,
+}
+
+// ==============================================================================
+// Handler
+// ==============================================================================
+
+/// Fetch and verify a confirmed transaction's Merkle-inclusion proof, so
+/// the UI can show proof of inclusion without trusting the node's
+/// self-reported `confirmations` count. Returns `404` for unconfirmed
+/// transactions, since no proof exists for them yet.
+pub(super) async fn get_tx_proof(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(txid_str): Path<String>,
+) -> Result<Json<ProofResponse>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+
+    let txid: bitcoin::Txid = txid_str
+        .parse()
+        .map_err(|e| AppError::BadRequest(format!("invalid txid: {e}")))?;
+
+    let node = state
+        .rpc
+        .get_transaction(&txid)
+        .await
+        .map_err(|e| map_proof_error(txid, e))?;
+
+    let Some(block_hash) = node.block_hash else {
+        return Err(AppError::NotFound(format!(
+            "transaction {txid} is unconfirmed; no inclusion proof exists yet"
+        )));
+    };
+
+    let proof = state
+        .rpc
+        .get_tx_inclusion_proof(&txid, &block_hash)
+        .await
+        .map_err(|e| map_proof_error(txid, e))?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "transaction {txid} is unconfirmed; no inclusion proof exists yet"
+            ))
+        })?;
+
+    Ok(Json(ProofResponse {
+        txid: proof.txid.to_string(),
+        block_hash: proof.block_hash.to_string(),
+        block_height: proof.block_height,
+        merkle_root: proof.merkle_root.to_string(),
+    }))
+}
+
+fn map_proof_error(txid: bitcoin::Txid, err: CoreError) -> AppError {
+    match err {
+        CoreError::TxNotFound(_) => AppError::NotFound(format!("transaction not found: {txid}")),
+        CoreError::PrunedBlockData(message) => AppError::BadGateway(format!(
+            "cannot build inclusion proof for {txid}: {message}"
+        )),
+        CoreError::TxNotIncluded(_) | CoreError::InvalidProof(_) => AppError::BadGateway(format!(
+            "node returned an inconsistent inclusion proof for {txid}: {err}"
+        )),
+        CoreError::InvalidTxData(message) => AppError::BadRequest(message),
+        CoreError::Rpc(rpc) => AppError::BadGateway(format!("bitcoin rpc error: {rpc}")),
+        other => AppError::Internal(format!("build inclusion proof for {txid}: {other}")),
+    }
+}
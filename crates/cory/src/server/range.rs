@@ -0,0 +1,405 @@
+//! Shared HTTP caching and partial-content support for export endpoints that
+//! serve an in-memory byte buffer: computes a strong `ETag` from the
+//! content, honors `If-None-Match`/`If-Modified-Since` with
+//! `304 Not Modified`, and serves a single `Range: bytes=start-end` request
+//! as `206 Partial Content`, rejecting unsatisfiable ranges with `416`.
+
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use bitcoin::hashes::{sha256, Hash};
+
+use super::error::AppError;
+
+/// Wraps `body` as a `200`/`206`/`304` response honoring `If-None-Match`,
+/// `If-Modified-Since`, and a single-range `Range` request, with
+/// `content_type` and an optional `Content-Disposition` value applied
+/// whenever a body is actually sent. `last_modified_unix_secs`, if known
+/// (e.g. [`cory_core::labels::LabelFile::last_modified_unix_secs`]), is
+/// sent as `Last-Modified` and compared against an incoming
+/// `If-Modified-Since`; omitted entirely when `None`, since a flat file
+/// with no recorded mutation time has nothing honest to report.
+pub(super) fn serve_with_range_and_etag(
+    headers: &HeaderMap,
+    body: Vec<u8>,
+    content_type: &'static str,
+    content_disposition: Option<String>,
+    last_modified_unix_secs: Option<u64>,
+) -> Result<Response, AppError> {
+    let etag = format!("\"{}\"", sha256::Hash::hash(&body));
+    let total = body.len();
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        == Some(etag.as_str())
+        || last_modified_unix_secs.is_some_and(|modified| {
+            headers
+                .get(header::IF_MODIFIED_SINCE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_http_date)
+                .is_some_and(|since| modified <= since)
+        });
+
+    if not_modified {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        insert_common_headers(&mut response, &etag, last_modified_unix_secs)?;
+        return Ok(response);
+    }
+
+    if let Some(range) = headers.get(header::RANGE) {
+        let range = range
+            .to_str()
+            .map_err(|_| AppError::BadRequest("invalid Range header".to_string()))?;
+        return serve_range(
+            range,
+            body,
+            total,
+            content_type,
+            content_disposition,
+            &etag,
+            last_modified_unix_secs,
+        );
+    }
+
+    let mut response = (StatusCode::OK, body).into_response();
+    set_content_headers(&mut response, content_type, content_disposition)?;
+    insert_common_headers(&mut response, &etag, last_modified_unix_secs)?;
+    Ok(response)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn serve_range(
+    range: &str,
+    body: Vec<u8>,
+    total: usize,
+    content_type: &'static str,
+    content_disposition: Option<String>,
+    etag: &str,
+    last_modified_unix_secs: Option<u64>,
+) -> Result<Response, AppError> {
+    let Some((start, end)) = parse_byte_range(range, total) else {
+        let mut response = StatusCode::RANGE_NOT_SATISFIABLE.into_response();
+        response.headers_mut().insert(
+            header::CONTENT_RANGE,
+            HeaderValue::from_str(&format!("bytes */{total}"))
+                .map_err(|e| AppError::Internal(format!("invalid content-range header: {e}")))?,
+        );
+        insert_common_headers(&mut response, etag, last_modified_unix_secs)?;
+        return Ok(response);
+    };
+
+    let slice = body[start..=end].to_vec();
+    let mut response = (StatusCode::PARTIAL_CONTENT, slice).into_response();
+    set_content_headers(&mut response, content_type, content_disposition)?;
+    insert_common_headers(&mut response, etag, last_modified_unix_secs)?;
+    response.headers_mut().insert(
+        header::CONTENT_RANGE,
+        HeaderValue::from_str(&format!("bytes {start}-{end}/{total}"))
+            .map_err(|e| AppError::Internal(format!("invalid content-range header: {e}")))?,
+    );
+    Ok(response)
+}
+
+/// Parses a single `bytes=start-end` range, including the suffix
+/// (`bytes=-N`) and open-ended (`bytes=N-`) forms. Returns `None` for
+/// anything malformed, multi-range, or out of bounds, signalling `416`.
+fn parse_byte_range(range: &str, total: usize) -> Option<(usize, usize)> {
+    if total == 0 {
+        return None;
+    }
+
+    let spec = range.strip_prefix("bytes=")?;
+    // Multi-range requests aren't supported; they get a 416 like any other
+    // unsatisfiable range.
+    if spec.contains(',') {
+        return None;
+    }
+    let (start_str, end_str) = spec.split_once('-')?;
+
+    let (start, end) = if start_str.is_empty() {
+        let suffix_len: usize = end_str.parse().ok()?;
+        if suffix_len == 0 {
+            return None;
+        }
+        (total.saturating_sub(suffix_len), total - 1)
+    } else {
+        let start: usize = start_str.parse().ok()?;
+        let end = match end_str {
+            "" => total - 1,
+            _ => end_str.parse().ok()?,
+        };
+        (start, end)
+    };
+
+    if start > end || end >= total {
+        return None;
+    }
+    Some((start, end))
+}
+
+fn set_content_headers(
+    response: &mut Response,
+    content_type: &'static str,
+    content_disposition: Option<String>,
+) -> Result<(), AppError> {
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    if let Some(disposition) = content_disposition {
+        response.headers_mut().insert(
+            header::CONTENT_DISPOSITION,
+            HeaderValue::from_str(&disposition).map_err(|e| {
+                AppError::Internal(format!("invalid content disposition header: {e}"))
+            })?,
+        );
+    }
+    Ok(())
+}
+
+fn insert_common_headers(
+    response: &mut Response,
+    etag: &str,
+    last_modified_unix_secs: Option<u64>,
+) -> Result<(), AppError> {
+    response.headers_mut().insert(
+        header::ETAG,
+        HeaderValue::from_str(etag)
+            .map_err(|e| AppError::Internal(format!("invalid etag header: {e}")))?,
+    );
+    response
+        .headers_mut()
+        .insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    // Label data can be edited between requests, so a caching proxy must
+    // always revalidate rather than serve a stale copy past any max-age.
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-cache"),
+    );
+    if let Some(unix_secs) = last_modified_unix_secs {
+        response.headers_mut().insert(
+            header::LAST_MODIFIED,
+            HeaderValue::from_str(&format_http_date(unix_secs))
+                .map_err(|e| AppError::Internal(format!("invalid last-modified header: {e}")))?,
+        );
+    }
+    Ok(())
+}
+
+/// Formats a Unix timestamp as an RFC 7231 IMF-fixdate, e.g.
+/// `Sun, 06 Nov 1994 08:49:37 GMT` — the only form [`parse_http_date`]
+/// accepts back, which covers every `If-Modified-Since` header sent by
+/// current browsers and HTTP clients.
+fn format_http_date(unix_secs: u64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let days = (unix_secs / 86_400) as i64;
+    let secs_of_day = unix_secs % 86_400;
+    let weekday = WEEKDAYS[(days.rem_euclid(7)) as usize];
+    let (year, month, day) = civil_from_days(days);
+    let (h, m, s) = (secs_of_day / 3600, (secs_of_day % 3600) / 60, secs_of_day % 60);
+
+    format!(
+        "{weekday}, {day:02} {} {year:04} {h:02}:{m:02}:{s:02} GMT",
+        MONTHS[(month - 1) as usize]
+    )
+}
+
+/// Inverse of [`format_http_date`]. Returns `None` for anything not in the
+/// exact IMF-fixdate shape, including the legacy RFC 850/asctime forms
+/// `If-Modified-Since` is technically allowed to use — callers treat a
+/// `None` as "can't tell, so don't short-circuit with a 304".
+fn parse_http_date(value: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let rest = value.split_once(", ").map(|(_, rest)| rest).unwrap_or(value);
+    let rest = rest.strip_suffix(" GMT")?;
+    let (date, time) = rest.split_once(' ')?;
+    let mut date_parts = date.split(' ');
+    let day: i64 = date_parts.next()?.parse().ok()?;
+    let month = date_parts.next()?;
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month = (MONTHS.iter().position(|m| *m == month)? + 1) as u32;
+
+    let mut time_parts = time.split(':');
+    let hour: u64 = time_parts.next()?.parse().ok()?;
+    let minute: u64 = time_parts.next()?.parse().ok()?;
+    let second: u64 = time_parts.next()?.parse().ok()?;
+
+    let days = days_from_civil(year, month, day as u32);
+    Some((days * 86_400) as u64 + hour * 3600 + minute * 60 + second)
+}
+
+/// Howard Hinnant's `civil_from_days` algorithm: day count since the Unix
+/// epoch to a (year, month, day) civil calendar date. Duplicated from
+/// [`cory_core::labels::S3Transport`]'s own copy (same algorithm, different
+/// crate) rather than sharing it across the `cory-core`/`cory` boundary for
+/// one small, self-contained function.
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = (z - era * 146_097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = if m <= 2 { y + 1 } else { y };
+    (y, m, d)
+}
+
+/// Inverse of [`civil_from_days`], also Howard Hinnant's algorithm.
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = (y - era * 400) as u64;
+    let mp = (if m > 2 { m - 3 } else { m + 9 }) as u64;
+    let doy = (153 * mp + 2) / 5 + d as u64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe as i64 - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header_map(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn full_response_includes_etag_and_accept_ranges() {
+        let headers = HeaderMap::new();
+        let response =
+            serve_with_range_and_etag(&headers, b"hello world".to_vec(), "text/plain", None, None)
+                .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key(header::ETAG));
+        assert_eq!(
+            response.headers().get(header::ACCEPT_RANGES).unwrap(),
+            "bytes"
+        );
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-cache"
+        );
+        assert!(!response.headers().contains_key(header::LAST_MODIFIED));
+    }
+
+    #[test]
+    fn if_none_match_returns_304() {
+        let body = b"hello world".to_vec();
+        let etag = format!("\"{}\"", sha256::Hash::hash(&body));
+        let headers = header_map(&[(header::IF_NONE_MATCH, &etag)]);
+        let response =
+            serve_with_range_and_etag(&headers, body, "text/plain", None, None).unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[test]
+    fn range_request_returns_206_with_content_range() {
+        let headers = header_map(&[(header::RANGE, "bytes=0-4")]);
+        let response =
+            serve_with_range_and_etag(&headers, b"hello world".to_vec(), "text/plain", None, None)
+                .unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 0-4/11"
+        );
+    }
+
+    #[test]
+    fn unsatisfiable_range_returns_416() {
+        let headers = header_map(&[(header::RANGE, "bytes=100-200")]);
+        let response =
+            serve_with_range_and_etag(&headers, b"hello world".to_vec(), "text/plain", None, None)
+                .unwrap();
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            response.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes */11"
+        );
+    }
+
+    #[test]
+    fn suffix_range_returns_last_n_bytes() {
+        let headers = header_map(&[(header::RANGE, "bytes=-5")]);
+        let response =
+            serve_with_range_and_etag(&headers, b"hello world".to_vec(), "text/plain", None, None)
+                .unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 6-10/11"
+        );
+    }
+
+    #[test]
+    fn last_modified_round_trips_through_http_date_formatting() {
+        // 2023-06-15T12:34:56Z.
+        let unix_secs = 1_686_832_496;
+        let headers = HeaderMap::new();
+        let response = serve_with_range_and_etag(
+            &headers,
+            b"hello world".to_vec(),
+            "text/plain",
+            None,
+            Some(unix_secs),
+        )
+        .unwrap();
+        let last_modified = response
+            .headers()
+            .get(header::LAST_MODIFIED)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert_eq!(last_modified, "Thu, 15 Jun 2023 12:34:56 GMT");
+        assert_eq!(parse_http_date(last_modified), Some(unix_secs));
+    }
+
+    #[test]
+    fn if_modified_since_at_or_after_last_modified_returns_304() {
+        let unix_secs = 1_686_832_496;
+        let headers = header_map(&[(
+            header::IF_MODIFIED_SINCE,
+            "Thu, 15 Jun 2023 12:34:56 GMT",
+        )]);
+        let response = serve_with_range_and_etag(
+            &headers,
+            b"hello world".to_vec(),
+            "text/plain",
+            None,
+            Some(unix_secs),
+        )
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[test]
+    fn if_modified_since_before_last_modified_returns_full_body() {
+        let unix_secs = 1_686_832_496;
+        let headers = header_map(&[(
+            header::IF_MODIFIED_SINCE,
+            "Wed, 14 Jun 2023 00:00:00 GMT",
+        )]);
+        let response = serve_with_range_and_etag(
+            &headers,
+            b"hello world".to_vec(),
+            "text/plain",
+            None,
+            Some(unix_secs),
+        )
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,399 @@
+//! Background label-directory rescan jobs.
+//!
+//! Label directories are only ever walked at startup (see
+//! [`cory_core::labels::LabelStore::load_rw_dir`]/`load_ro_dir`) — there's
+//! no way to pick up files added to a live `--labels-rw`/`--labels-ro`
+//! tree without restarting the process. `POST /api/v1/label/jobs/rescan`
+//! instead enqueues a rescan on a background blocking task (directory
+//! walking is synchronous filesystem I/O, so it runs via `spawn_blocking`
+//! rather than tying up an async worker), bounded by [`RescanJobStore`]'s
+//! own semaphore so a flood of rescan requests can't spawn unbounded
+//! walks; `GET /api/v1/label/jobs/{id}` polls its [`RescanJobStatus`]
+//! (backed by the `tokio::sync::watch` channel
+//! [`cory_core::jobs::rescan_dir`] reports progress over), and
+//! `GET /api/v1/label/jobs` lists every tracked job. `DELETE
+//! .../jobs/{id}` requests cancellation, which
+//! [`cory_core::jobs::rescan_dir`] checkpoints between files rather than
+//! mid-file. Finished jobs are swept out after this store's TTL, same as
+//! the graph build queue in `super::jobs`.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{watch, RwLock, Semaphore};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use cory_core::jobs::{rescan_dir, JobCancelToken, JobProgress};
+use cory_core::labels::{LabelFileKind, LabelStore};
+use cory_core::CoreError;
+
+use super::auth::check_auth;
+use super::error::AppError;
+use super::SharedState;
+
+pub(super) type JobId = Uuid;
+
+// ==============================================================================
+// Job Status
+// ==============================================================================
+
+/// Which directory set a rescan targets; maps 1:1 to the two directory
+/// kinds [`cory_core::labels::LabelStore`] walks from disk.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum RescanKind {
+    Rw,
+    Ro,
+}
+
+impl RescanKind {
+    fn as_label_file_kind(self) -> LabelFileKind {
+        match self {
+            Self::Rw => LabelFileKind::PersistentRw,
+            Self::Ro => LabelFileKind::PersistentRo,
+        }
+    }
+}
+
+/// Current state of one enqueued rescan.
+#[derive(Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub(super) enum RescanJobStatus {
+    Queued,
+    Running {
+        files_scanned: usize,
+        records_parsed: usize,
+        current_path: Option<String>,
+    },
+    Done {
+        files_loaded: usize,
+    },
+    Cancelled,
+    Failed {
+        msg: String,
+    },
+}
+
+/// One tracked job, as returned by `GET /api/v1/label/jobs`.
+#[derive(Serialize)]
+pub(super) struct RescanJobSummary {
+    job_id: JobId,
+    kind: RescanKind,
+    dir: PathBuf,
+    #[serde(flatten)]
+    status: RescanJobStatus,
+}
+
+enum JobOutcome {
+    Done { files_loaded: usize },
+    Cancelled,
+    Failed(String),
+}
+
+struct JobEntry {
+    kind: RescanKind,
+    dir: PathBuf,
+    cancel: JobCancelToken,
+    progress: watch::Receiver<JobProgress>,
+    /// `true` once the task has cleared `rescan_permits` and actually
+    /// started walking; until then the job reports [`RescanJobStatus::Queued`].
+    started: bool,
+    /// `None` while queued or running; set once by the spawned task.
+    outcome: Option<JobOutcome>,
+    /// Set alongside `outcome`, so [`RescanJobStore::sweep_expired`] can
+    /// evict it once its TTL elapses.
+    finished_at: Option<Instant>,
+    /// Cooperative cancellation via `cancel` — checkpointed between files
+    /// by [`cory_core::jobs::rescan_dir`] — is what actually stops the
+    /// walk cleanly; this handle is only aborted on [`Drop`] as a backstop
+    /// so an evicted entry never leaks its background task.
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for JobEntry {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+// ==============================================================================
+// Job Store
+// ==============================================================================
+
+/// Holds every in-flight and recently-finished rescan job.
+pub struct RescanJobStore {
+    jobs: RwLock<HashMap<JobId, JobEntry>>,
+    rescan_permits: Arc<Semaphore>,
+    ttl: Duration,
+}
+
+impl RescanJobStore {
+    pub fn new(max_concurrent_rescans: usize, ttl: Duration) -> Self {
+        Self {
+            jobs: RwLock::new(HashMap::new()),
+            rescan_permits: Arc::new(Semaphore::new(max_concurrent_rescans)),
+            ttl,
+        }
+    }
+
+    /// Enqueues a rescan of `dir` (as `kind`) against `labels`, spawning it
+    /// on a background blocking task that waits for a free
+    /// `rescan_permits` permit before actually walking. Returns the new
+    /// job's id immediately.
+    async fn spawn(
+        self: &Arc<Self>,
+        labels: Arc<RwLock<LabelStore>>,
+        kind: RescanKind,
+        dir: PathBuf,
+    ) -> JobId {
+        let job_id = Uuid::new_v4();
+        let cancel = JobCancelToken::new();
+        let (sender, receiver) = watch::channel(JobProgress::default());
+
+        self.jobs.write().await.insert(
+            job_id,
+            JobEntry {
+                kind,
+                dir: dir.clone(),
+                cancel: cancel.clone(),
+                progress: receiver,
+                started: false,
+                outcome: None,
+                finished_at: None,
+                handle: None,
+            },
+        );
+
+        let store = Arc::clone(self);
+        let permits = Arc::clone(&self.rescan_permits);
+        let walk_dir = dir.clone();
+        let handle = tokio::spawn(async move {
+            let _permit = permits
+                .acquire_owned()
+                .await
+                .expect("rescan_permits semaphore is never closed");
+            store.mark_started(job_id).await;
+
+            let label_kind = kind.as_label_file_kind();
+            let result = tokio::task::spawn_blocking(move || {
+                let mut label_store = labels.blocking_write();
+                rescan_dir(&mut label_store, &walk_dir, label_kind, &sender, &cancel)
+                    .map(|()| label_store.list_files().len())
+            })
+            .await;
+
+            match result {
+                Ok(Ok(files_loaded)) => store.finish(job_id, files_loaded).await,
+                Ok(Err(CoreError::Cancelled)) => store.mark_cancelled(job_id).await,
+                Ok(Err(err)) => store.fail(job_id, err.to_string()).await,
+                Err(join_err) => {
+                    store
+                        .fail(job_id, format!("rescan task panicked: {join_err}"))
+                        .await
+                }
+            }
+        });
+
+        if let Some(entry) = self.jobs.write().await.get_mut(&job_id) {
+            entry.handle = Some(handle);
+        }
+        job_id
+    }
+
+    async fn mark_started(&self, job_id: JobId) {
+        if let Some(entry) = self.jobs.write().await.get_mut(&job_id) {
+            entry.started = true;
+        }
+    }
+
+    async fn finish(&self, job_id: JobId, files_loaded: usize) {
+        let mut jobs = self.jobs.write().await;
+        if let Some(entry) = jobs.get_mut(&job_id) {
+            entry.outcome = Some(JobOutcome::Done { files_loaded });
+            entry.finished_at = Some(Instant::now());
+        }
+    }
+
+    async fn mark_cancelled(&self, job_id: JobId) {
+        let mut jobs = self.jobs.write().await;
+        if let Some(entry) = jobs.get_mut(&job_id) {
+            entry.outcome = Some(JobOutcome::Cancelled);
+            entry.finished_at = Some(Instant::now());
+        }
+    }
+
+    async fn fail(&self, job_id: JobId, msg: String) {
+        let mut jobs = self.jobs.write().await;
+        if let Some(entry) = jobs.get_mut(&job_id) {
+            entry.outcome = Some(JobOutcome::Failed(msg));
+            entry.finished_at = Some(Instant::now());
+        }
+    }
+
+    async fn status(&self, job_id: JobId) -> Option<RescanJobStatus> {
+        let mut jobs = self.jobs.write().await;
+        Self::sweep_expired(&mut jobs, self.ttl);
+        jobs.get(&job_id).map(Self::status_of)
+    }
+
+    async fn list(&self) -> Vec<RescanJobSummary> {
+        let mut jobs = self.jobs.write().await;
+        Self::sweep_expired(&mut jobs, self.ttl);
+        jobs.iter()
+            .map(|(job_id, entry)| RescanJobSummary {
+                job_id: *job_id,
+                kind: entry.kind,
+                dir: entry.dir.clone(),
+                status: Self::status_of(entry),
+            })
+            .collect()
+    }
+
+    /// Requests cancellation, leaving the job's background task to stop at
+    /// its next checkpoint; the job itself stays in the store (reporting [`RescanJobStatus::Cancelled`]
+    /// once the task notices) until it's swept out by its TTL, so a poller
+    /// still sees the final outcome instead of a sudden 404.
+    async fn cancel(&self, job_id: JobId) -> bool {
+        let jobs = self.jobs.read().await;
+        match jobs.get(&job_id) {
+            Some(entry) => {
+                entry.cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn status_of(entry: &JobEntry) -> RescanJobStatus {
+        match &entry.outcome {
+            Some(JobOutcome::Done { files_loaded }) => RescanJobStatus::Done {
+                files_loaded: *files_loaded,
+            },
+            Some(JobOutcome::Cancelled) => RescanJobStatus::Cancelled,
+            Some(JobOutcome::Failed(msg)) => RescanJobStatus::Failed { msg: msg.clone() },
+            None if !entry.started => RescanJobStatus::Queued,
+            None => {
+                let progress = entry.progress.borrow();
+                RescanJobStatus::Running {
+                    files_scanned: progress.files_scanned,
+                    records_parsed: progress.records_parsed,
+                    current_path: progress
+                        .current_path
+                        .as_ref()
+                        .map(|p| p.display().to_string()),
+                }
+            }
+        }
+    }
+
+    fn sweep_expired(jobs: &mut HashMap<JobId, JobEntry>, ttl: Duration) {
+        jobs.retain(|_, entry| match entry.finished_at {
+            Some(finished_at) => finished_at.elapsed() < ttl,
+            None => true,
+        });
+    }
+}
+
+// ==============================================================================
+// Handlers
+// ==============================================================================
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct RescanRequest {
+    kind: RescanKind,
+    /// Directory to rescan. Must be exactly one of, or a subtree of, the
+    /// directories this server was started with for `kind` via
+    /// `--labels-rw`/`--labels-ro` — an arbitrary filesystem path is
+    /// rejected so this endpoint can't be used to probe the host's
+    /// directory tree.
+    dir: PathBuf,
+}
+
+#[derive(Serialize)]
+pub(super) struct EnqueueRescanJobResponse {
+    job_id: JobId,
+}
+
+/// Rejects a rescan request for a directory outside every directory this
+/// server was actually configured with for `kind`.
+fn check_dir_is_configured(
+    state: &SharedState,
+    kind: RescanKind,
+    dir: &std::path::Path,
+) -> Result<(), AppError> {
+    let configured = match kind {
+        RescanKind::Rw => &state.labels_rw_dirs,
+        RescanKind::Ro => &state.labels_ro_dirs,
+    };
+    if configured.iter().any(|base| dir.starts_with(base)) {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(format!(
+            "`{}` is not one of this server's configured labels-{} directories",
+            dir.display(),
+            if kind == RescanKind::Rw { "rw" } else { "ro" },
+        )))
+    }
+}
+
+pub(super) async fn enqueue_rescan_job(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    req: Result<Json<RescanRequest>, JsonRejection>,
+) -> Result<Json<EnqueueRescanJobResponse>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    let Json(req) = req.map_err(|e| AppError::BadRequest(e.to_string()))?;
+    check_dir_is_configured(&state, req.kind, &req.dir)?;
+
+    let job_id = state
+        .rescan_jobs
+        .spawn(Arc::clone(&state.labels), req.kind, req.dir)
+        .await;
+    Ok(Json(EnqueueRescanJobResponse { job_id }))
+}
+
+pub(super) async fn list_rescan_jobs(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<RescanJobSummary>>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    Ok(Json(state.rescan_jobs.list().await))
+}
+
+pub(super) async fn get_rescan_job(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(job_id): Path<JobId>,
+) -> Result<Json<RescanJobStatus>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    state
+        .rescan_jobs
+        .status(job_id)
+        .await
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("rescan job not found: {job_id}")))
+}
+
+pub(super) async fn cancel_rescan_job(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(job_id): Path<JobId>,
+) -> Result<StatusCode, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    if state.rescan_jobs.cancel(job_id).await {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NotFound(format!("rescan job not found: {job_id}")))
+    }
+}
@@ -0,0 +1,300 @@
+//! Time-limited signed share links: a way to hand someone read-only access
+//! to one ancestry graph without handing out the full-access `api_token`.
+//!
+//! `POST /api/v1/share` (authenticated) signs `{txid, limits, expiry}` into
+//! an opaque token with a key derived from `api_token`. The companion
+//! public route, `GET /api/v1/graph/share/{token}`, verifies the signature
+//! and expiry and serves the same [`GraphResponse`](super::graph::GraphResponse)
+//! `get_graph` would, bypassing [`check_auth`]. Signing the limits as part
+//! of the token means a recipient can't edit the URL to request a bigger
+//! graph than was shared — any change to `txid`/limits/expiry breaks the
+//! signature, and the hard ceilings are re-applied on top regardless, in
+//! case they've been lowered since the link was issued.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::Json;
+use bitcoin::hashes::{sha256, Hash};
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use cory_core::types::{GraphLimits, GraphStrategy};
+
+use super::auth::check_auth;
+use super::error::AppError;
+use super::graph::{
+    build_graph_response, resolve_graph_request, run_graph_build, GraphQuery, GraphResponse,
+};
+use super::limits::{HARD_MAX_DEPTH, HARD_MAX_EDGES, HARD_MAX_NODES};
+use super::SharedState;
+
+/// Share links are valid for one hour unless `ttl_secs` says otherwise.
+const DEFAULT_TTL_SECS: u64 = 60 * 60;
+/// A share link can't outlive a week, regardless of the requested `ttl_secs`.
+const MAX_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+// ==============================================================================
+// DTOs
+// ==============================================================================
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct CreateShareLinkRequest {
+    txid: String,
+    max_depth: Option<usize>,
+    max_nodes: Option<usize>,
+    max_edges: Option<usize>,
+    strategy: Option<GraphStrategy>,
+    /// How long the link stays valid, in seconds. Defaults to
+    /// [`DEFAULT_TTL_SECS`], clamped to [`MAX_TTL_SECS`].
+    ttl_secs: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub(super) struct CreateShareLinkResponse {
+    token: String,
+    expires_at: String,
+}
+
+/// The signed payload, serialized to JSON and HMAC'd as a whole — so
+/// tampering with any field (not just the signature) invalidates the link.
+#[derive(Serialize, Deserialize)]
+struct SharePayload {
+    txid: String,
+    max_depth: usize,
+    max_nodes: usize,
+    max_edges: usize,
+    strategy: GraphStrategy,
+    /// Unix timestamp (seconds) the link stops being accepted.
+    expiry: u64,
+}
+
+// ==============================================================================
+// Handlers
+// ==============================================================================
+
+pub(super) async fn create_share_link(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    req: Result<Json<CreateShareLinkRequest>, JsonRejection>,
+) -> Result<Json<CreateShareLinkResponse>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+    let Json(req) = req.map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let query = GraphQuery {
+        max_depth: req.max_depth,
+        max_nodes: req.max_nodes,
+        max_edges: req.max_edges,
+        strategy: req.strategy,
+        cluster: None,
+    };
+    let (txid, limits) = resolve_graph_request(&state, &req.txid, &query)?;
+
+    let ttl_secs = req.ttl_secs.unwrap_or(DEFAULT_TTL_SECS).min(MAX_TTL_SECS);
+    if ttl_secs == 0 {
+        return Err(AppError::BadRequest(
+            "ttl_secs must be at least 1".to_string(),
+        ));
+    }
+    let expiry = unix_now().saturating_add(ttl_secs);
+
+    let payload = SharePayload {
+        txid: txid.to_string(),
+        max_depth: limits.max_depth,
+        max_nodes: limits.max_nodes,
+        max_edges: limits.max_edges,
+        strategy: limits.strategy,
+        expiry,
+    };
+    let token = sign_payload(&state, &payload)
+        .map_err(|e| AppError::Internal(format!("failed to sign share link: {e}")))?;
+
+    let expires_at = OffsetDateTime::from_unix_timestamp(expiry as i64)
+        .map_err(|e| AppError::Internal(format!("format share link expiry: {e}")))?
+        .format(&Rfc3339)
+        .map_err(|e| AppError::Internal(format!("format share link expiry: {e}")))?;
+
+    state.metrics.label_ops_total.inc("share_create");
+    Ok(Json(CreateShareLinkResponse { token, expires_at }))
+}
+
+pub(super) async fn get_shared_graph(
+    State(state): State<SharedState>,
+    Path(token): Path<String>,
+) -> Result<Json<GraphResponse>, AppError> {
+    let payload = verify_token(&state, &token)
+        .ok_or_else(|| AppError::Unauthorized("invalid or expired share link".to_string()))?;
+
+    if payload.expiry < unix_now() {
+        return Err(AppError::Unauthorized(
+            "invalid or expired share link".to_string(),
+        ));
+    }
+
+    let txid: bitcoin::Txid = payload
+        .txid
+        .parse()
+        .map_err(|e| AppError::Internal(format!("share link carries an invalid txid: {e}")))?;
+
+    // Re-clamp to the server's *current* hard ceilings, not whatever they
+    // were when the link was signed — if they've since been lowered, a
+    // still-validly-signed old link shouldn't bypass the new limit.
+    let limits = GraphLimits {
+        max_depth: payload.max_depth.min(HARD_MAX_DEPTH),
+        max_nodes: payload.max_nodes.min(HARD_MAX_NODES),
+        max_edges: payload.max_edges.min(HARD_MAX_EDGES),
+        strategy: payload.strategy,
+    };
+
+    let graph = run_graph_build(&state, txid, &limits, None).await?;
+    let response = build_graph_response(&state, graph, true).await;
+    Ok(Json(response))
+}
+
+// ==============================================================================
+// Signing
+// ==============================================================================
+
+fn sign_payload(state: &SharedState, payload: &SharePayload) -> Result<String, serde_json::Error> {
+    let payload_bytes = serde_json::to_vec(payload)?;
+    let mac = hmac_sha256(&share_signing_key(state), &payload_bytes);
+    Ok(format!(
+        "{}.{}",
+        hex_encode(&payload_bytes),
+        hex_encode(&mac)
+    ))
+}
+
+/// Parses and verifies `token`, returning its payload if the signature
+/// checks out. Expiry is checked separately by the caller so an expired
+/// (but otherwise validly-signed) token gets a distinct error path to
+/// reason about, even though both currently map to the same response.
+fn verify_token(state: &SharedState, token: &str) -> Option<SharePayload> {
+    let (payload_hex, mac_hex) = token.split_once('.')?;
+    let payload_bytes = hex_decode(payload_hex)?;
+    let presented_mac = hex_decode(mac_hex)?;
+
+    let expected_mac = hmac_sha256(&share_signing_key(state), &payload_bytes);
+    if !constant_time_eq(&presented_mac, &expected_mac) {
+        return None;
+    }
+
+    serde_json::from_slice(&payload_bytes).ok()
+}
+
+/// Derives the HMAC key from `api_token` rather than using it directly, so
+/// a share link's signing key isn't literally the same secret that grants
+/// full label-editing access.
+fn share_signing_key(state: &SharedState) -> [u8; 32] {
+    *sha256::Hash::hash(format!("cory-share-link-v1:{}", state.api_token).as_bytes())
+        .as_byte_array()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock is after the Unix epoch")
+        .as_secs()
+}
+
+// ==============================================================================
+// HMAC-SHA256 and Hex Helpers
+// ==============================================================================
+
+const SHA256_BLOCK_SIZE: usize = 64;
+
+/// HMAC-SHA256, built from [`sha256::Hash`] per RFC 2104 since this crate
+/// has no `hmac` dependency to reach for.
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block_key = [0u8; SHA256_BLOCK_SIZE];
+    if key.len() > SHA256_BLOCK_SIZE {
+        let hashed = sha256::Hash::hash(key);
+        block_key[..32].copy_from_slice(hashed.as_byte_array());
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut ipad = [0x36u8; SHA256_BLOCK_SIZE];
+    let mut opad = [0x5cu8; SHA256_BLOCK_SIZE];
+    for i in 0..SHA256_BLOCK_SIZE {
+        ipad[i] ^= block_key[i];
+        opad[i] ^= block_key[i];
+    }
+
+    let mut inner = ipad.to_vec();
+    inner.extend_from_slice(message);
+    let inner_hash = sha256::Hash::hash(&inner);
+
+    let mut outer = opad.to_vec();
+    outer.extend_from_slice(inner_hash.as_byte_array());
+    *sha256::Hash::hash(&outer).as_byte_array()
+}
+
+/// Constant-time byte-slice equality, for comparing a presented MAC against
+/// the expected one without leaking how many leading bytes matched through
+/// timing — unlike `==`, which can short-circuit on the first mismatch.
+/// Unequal lengths are rejected up front since there's no secret-dependent
+/// length to hide here.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hmac_sha256_matches_rfc4231_test_case_1() {
+        let key = [0x0bu8; 20];
+        let data = b"Hi There";
+        let expected = "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7";
+        assert_eq!(hex_encode(&hmac_sha256(&key, data)), expected);
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        let bytes = [0x00, 0x01, 0x7f, 0xff, 0xab];
+        assert_eq!(hex_decode(&hex_encode(&bytes)).unwrap(), bytes);
+    }
+
+    #[test]
+    fn hex_decode_rejects_odd_length() {
+        assert!(hex_decode("abc").is_none());
+    }
+
+    #[test]
+    fn constant_time_eq_matches_equal_slices() {
+        assert!(constant_time_eq(b"same-bytes", b"same-bytes"));
+    }
+
+    #[test]
+    fn constant_time_eq_rejects_different_bytes() {
+        assert!(!constant_time_eq(b"same-bytes", b"diff-bytes"));
+    }
+
+    #[test]
+    fn constant_time_eq_rejects_different_lengths() {
+        assert!(!constant_time_eq(b"short", b"a-longer-slice"));
+    }
+}
@@ -1,6 +1,7 @@
-use axum::http::StatusCode;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
-use rust_embed::Embed;
+use bitcoin::hashes::{sha256, Hash};
+use rust_embed::{Embed, EmbeddedFile};
 
 // ==============================================================================
 // Static File Serving
@@ -10,28 +11,38 @@ use rust_embed::Embed;
 #[folder = "ui/dist/"]
 struct Assets;
 
+/// Applied to exact asset matches: the build hashes each asset's filename
+/// from its content, so a given URL's bytes never change and the browser
+/// can cache it indefinitely without ever revalidating.
+const CACHE_CONTROL_IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// Applied to `index.html`: its URL never changes even though a new deploy
+/// rewrites its content (it references the freshly hashed asset URLs), so
+/// every request must revalidate against the current `ETag`.
+const CACHE_CONTROL_NO_CACHE: &str = "no-cache";
+
 /// Serves the embedded SPA. Exact file matches are returned with the correct
-/// MIME type; everything else falls back to `index.html` for client-side routing.
-pub(super) async fn static_files(uri: axum::http::Uri) -> Response {
+/// MIME type; everything else falls back to `index.html` for client-side
+/// routing. Both cases honor `If-None-Match` with `304 Not Modified` and, if
+/// the client's `Accept-Encoding` allows it, prefer a precompressed `.br` or
+/// `.gz` sibling embedded alongside the raw asset.
+pub(super) async fn static_files(uri: axum::http::Uri, headers: HeaderMap) -> Response {
     let path = uri.path().trim_start_matches('/');
-    // Serve exact file if it exists
     if !path.is_empty() {
-        if let Some(content) = Assets::get(path) {
-            let mime = mime_guess::from_path(path).first_or_octet_stream();
-            return (
-                [(axum::http::header::CONTENT_TYPE, mime.as_ref())],
-                content.data,
-            )
-                .into_response();
+        let mime = mime_guess::from_path(path).first_or_octet_stream();
+        if let Some(response) = serve_asset(&headers, path, mime.as_ref(), CACHE_CONTROL_IMMUTABLE)
+        {
+            return response;
         }
     }
-    // SPA fallback: serve index.html for all unmatched routes
-    match Assets::get("index.html") {
-        Some(content) => (
-            [(axum::http::header::CONTENT_TYPE, "text/html; charset=utf-8")],
-            content.data,
-        )
-            .into_response(),
+    // SPA fallback: serve index.html for all unmatched routes.
+    match serve_asset(
+        &headers,
+        "index.html",
+        "text/html; charset=utf-8",
+        CACHE_CONTROL_NO_CACHE,
+    ) {
+        Some(response) => response,
         None => (
             StatusCode::NOT_FOUND,
             "UI not built. Run: cd ui && npm run build",
@@ -39,3 +50,79 @@ pub(super) async fn static_files(uri: axum::http::Uri) -> Response {
             .into_response(),
     }
 }
+
+/// Looks up `path`, preferring a precompressed `.br`/`.gz` sibling over the
+/// raw asset when `headers` advertises support for it (checked in that
+/// order, since brotli typically compresses smaller). Returns `None` when
+/// neither the compressed siblings nor the raw asset exist.
+fn negotiate_asset(
+    path: &str,
+    headers: &HeaderMap,
+) -> Option<(EmbeddedFile, Option<&'static str>)> {
+    let accept_encoding = headers
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    if accept_encoding.contains("br") {
+        if let Some(file) = Assets::get(&format!("{path}.br")) {
+            return Some((file, Some("br")));
+        }
+    }
+    if accept_encoding.contains("gzip") {
+        if let Some(file) = Assets::get(&format!("{path}.gz")) {
+            return Some((file, Some("gzip")));
+        }
+    }
+    Assets::get(path).map(|file| (file, None))
+}
+
+/// Resolves `path` (honoring compression negotiation, `If-None-Match`, and
+/// `cache_control`) into a full response, or `None` if no embedded asset
+/// matches `path` at all (compressed or not).
+fn serve_asset(
+    headers: &HeaderMap,
+    path: &str,
+    content_type: &str,
+    cache_control: &'static str,
+) -> Option<Response> {
+    let (file, encoding) = negotiate_asset(path, headers)?;
+    let etag = format!("\"{}\"", sha256::Hash::hash(&file.data));
+
+    let if_none_match = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok());
+    if if_none_match == Some(etag.as_str()) {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        insert_asset_headers(&mut response, &etag, cache_control, encoding);
+        return Some(response);
+    }
+
+    let mut response = (StatusCode::OK, file.data).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(content_type).unwrap_or(HeaderValue::from_static("text/plain")),
+    );
+    insert_asset_headers(&mut response, &etag, cache_control, encoding);
+    Some(response)
+}
+
+fn insert_asset_headers(
+    response: &mut Response,
+    etag: &str,
+    cache_control: &'static str,
+    encoding: Option<&'static str>,
+) {
+    let headers = response.headers_mut();
+    headers.insert(
+        header::ETAG,
+        HeaderValue::from_str(etag).expect("hex-encoded sha256 digest is a valid header value"),
+    );
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+    // A cache keying solely on URL could otherwise serve a brotli body to a
+    // client that never claimed to accept it.
+    headers.insert(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+    if let Some(encoding) = encoding {
+        headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding));
+    }
+}
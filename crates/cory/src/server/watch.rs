@@ -0,0 +1,102 @@
+//! `GET /api/v1/label/events`: server-sent events for live label directory
+//! watching. Requires axum's `sse` feature.
+//!
+//! When the server is started with `--watch-labels`, [`spawn_label_watchers`]
+//! starts one [`LabelWatcher`] per `--labels-rw`/`--labels-ro` directory,
+//! all publishing onto a single [`super::AppState::label_changes`]
+//! broadcast channel. This endpoint adapts that channel into an SSE
+//! stream so the web UI can refresh label views as changes land instead
+//! of polling `GET /api/v1/label`. With `--watch-labels` unset, no
+//! watchers are spawned but the channel and endpoint still exist — the
+//! stream just never emits anything.
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use serde::Serialize;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tracing::warn;
+
+use cory_core::labels::{LabelChangeEvent, LabelFileKind, LabelStore, LabelWatcher};
+
+use super::auth::check_auth;
+use super::error::AppError;
+use super::SharedState;
+
+/// Start one [`LabelWatcher`] per directory in `labels_rw_dirs`/`labels_ro_dirs`,
+/// all reporting into `changes`. Returns the handles; dropping them stops
+/// watching, so the caller (`main.rs`) must keep them alive (e.g. in
+/// [`super::AppState`]) for as long as the server runs.
+pub fn spawn_label_watchers(
+    labels: std::sync::Arc<tokio::sync::RwLock<LabelStore>>,
+    labels_rw_dirs: &[std::path::PathBuf],
+    labels_ro_dirs: &[std::path::PathBuf],
+    changes: tokio::sync::broadcast::Sender<LabelChangeEvent>,
+) -> Vec<LabelWatcher> {
+    let dirs = labels_rw_dirs
+        .iter()
+        .map(|dir| (dir.clone(), LabelFileKind::PersistentRw))
+        .chain(
+            labels_ro_dirs
+                .iter()
+                .map(|dir| (dir.clone(), LabelFileKind::PersistentRo)),
+        );
+
+    dirs.filter_map(|(dir, kind)| {
+        match LabelWatcher::watch(dir.clone(), kind, labels.clone(), changes.clone()) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                warn!(dir = %dir.display(), error = %e, "failed to start label directory watcher");
+                None
+            }
+        }
+    })
+    .collect()
+}
+
+#[derive(Serialize)]
+struct LabelChangePayload {
+    kind: LabelFileKind,
+    file_id: String,
+    removed: bool,
+}
+
+impl From<LabelChangeEvent> for LabelChangePayload {
+    fn from(event: LabelChangeEvent) -> Self {
+        Self {
+            kind: event.kind,
+            file_id: event.file_id,
+            removed: event.removed,
+        }
+    }
+}
+
+/// `GET /api/v1/label/events`: one `label-changed` SSE event per applied
+/// watch event, for as long as the connection stays open. A lagged
+/// subscriber (more than the broadcast channel's capacity behind) sees a
+/// `label-watch-lagged` event instead of a silent gap, so the UI knows to
+/// fall back to a full reload.
+pub(super) async fn label_change_events(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    check_auth(&state.api_token, &headers, &state.metrics)?;
+
+    let stream = BroadcastStream::new(state.label_changes.subscribe()).map(|result| {
+        let event = match result {
+            Ok(change) => Event::default()
+                .event("label-changed")
+                .json_data(LabelChangePayload::from(change))
+                .unwrap_or_else(|_| Event::default().event("label-watch-error")),
+            Err(BroadcastStreamRecvError::Lagged(_)) => Event::default().event("label-watch-lagged"),
+        };
+        Ok(event)
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15))))
+}
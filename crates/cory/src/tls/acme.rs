@@ -0,0 +1,187 @@
+//! Orders a certificate from an ACME directory (Let's Encrypt by default)
+//! via HTTP-01 validation, using [`instant_acme`] for account/order/JOSE
+//! handling and [`rcgen`] to generate the leaf key and CSR.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use instant_acme::{
+    Account, AccountCredentials, AuthorizationStatus, ChallengeType, Identifier, NewAccount,
+    NewOrder, OrderStatus,
+};
+use rcgen::{CertificateParams, KeyPair};
+use tokio::sync::RwLock;
+
+use super::cache::CertMaterial;
+
+/// How long a fulfilled challenge is given to propagate to the ACME
+/// server's validation workers before we give up polling the order.
+const POLL_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, thiserror::Error)]
+pub enum AcmeError {
+    #[error("ACME protocol error: {0}")]
+    Acme(#[from] instant_acme::Error),
+    #[error("certificate generation error: {0}")]
+    Rcgen(#[from] rcgen::Error),
+    #[error("cached ACME account credentials are invalid: {0}")]
+    InvalidCredentials(serde_json::Error),
+    #[error("failed to serialize ACME account credentials: {0}")]
+    SerializeCredentials(serde_json::Error),
+    #[error("ACME order for {0} has no HTTP-01 challenge on its authorization")]
+    NoHttp01Challenge(String),
+    #[error("ACME order for {0} did not become ready in time (last status: {1:?})")]
+    OrderNotReady(String, OrderStatus),
+    #[error("ACME order for {0} was marked invalid by the server")]
+    OrderInvalid(String),
+    #[error("certificate file error: {0}")]
+    Cert(String),
+    #[error("cache I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Token -> key authorization for HTTP-01 challenges currently in flight,
+/// shared between [`order_certificate`] (which populates it while an order
+/// is pending) and `super::challenge_router` (which serves it at
+/// `/.well-known/acme-challenge/{token}`).
+#[derive(Clone, Default)]
+pub struct PendingChallenges(Arc<RwLock<std::collections::HashMap<String, String>>>);
+
+impl PendingChallenges {
+    pub async fn insert(&self, token: String, key_authorization: String) {
+        self.0.write().await.insert(token, key_authorization);
+    }
+
+    pub async fn remove(&self, token: &str) {
+        self.0.write().await.remove(token);
+    }
+
+    pub(super) async fn get(&self, token: &str) -> Option<String> {
+        self.0.read().await.get(token).cloned()
+    }
+}
+
+/// Loads (or creates, caching the result) the ACME account used to place
+/// orders against `directory_url`.
+async fn load_or_create_account(
+    directory_url: &str,
+    contact_email: &str,
+    cached_credentials: Option<String>,
+) -> Result<(Account, String), AcmeError> {
+    if let Some(credentials_json) = cached_credentials {
+        let credentials: AccountCredentials =
+            serde_json::from_str(&credentials_json).map_err(AcmeError::InvalidCredentials)?;
+        let account = Account::from_credentials(credentials).await?;
+        return Ok((account, credentials_json));
+    }
+
+    let (account, credentials) = Account::create(
+        &NewAccount {
+            contact: &[&format!("mailto:{contact_email}")],
+            terms_of_service_agreed: true,
+            only_return_existing: false,
+        },
+        directory_url,
+        None,
+    )
+    .await?;
+    let credentials_json =
+        serde_json::to_string(&credentials).map_err(AcmeError::SerializeCredentials)?;
+    Ok((account, credentials_json))
+}
+
+/// Orders a certificate for `domain`, fulfilling its HTTP-01 challenge via
+/// `pending` (the caller is expected to have `super::challenge_router`
+/// mounted and reachable at `http://{domain}/.well-known/acme-challenge/`
+/// before calling this). Returns the issued certificate/key alongside the
+/// account credentials JSON, so the caller can cache both for next time.
+pub async fn order_certificate(
+    domain: &str,
+    contact_email: &str,
+    directory_url: &str,
+    cached_credentials: Option<String>,
+    pending: &PendingChallenges,
+) -> Result<(CertMaterial, String), AcmeError> {
+    let (account, credentials_json) =
+        load_or_create_account(directory_url, contact_email, cached_credentials).await?;
+
+    let identifier = Identifier::Dns(domain.to_string());
+    let mut order = account
+        .new_order(&NewOrder {
+            identifiers: &[identifier],
+        })
+        .await?;
+
+    let authorizations = order.authorizations().await?;
+    let mut challenge_urls = Vec::with_capacity(authorizations.len());
+    let mut challenge_tokens = Vec::with_capacity(authorizations.len());
+    for authz in &authorizations {
+        match authz.status {
+            AuthorizationStatus::Valid => continue,
+            AuthorizationStatus::Pending => {}
+            _ => return Err(AcmeError::OrderInvalid(domain.to_string())),
+        }
+
+        let challenge = authz
+            .challenges
+            .iter()
+            .find(|c| c.r#type == ChallengeType::Http01)
+            .ok_or_else(|| AcmeError::NoHttp01Challenge(domain.to_string()))?;
+
+        let key_authorization = order.key_authorization(challenge).as_str().to_string();
+        pending
+            .insert(challenge.token.clone(), key_authorization)
+            .await;
+        challenge_tokens.push(challenge.token.clone());
+        challenge_urls.push(challenge.url.clone());
+    }
+
+    for url in &challenge_urls {
+        order.set_challenge_ready(url).await?;
+    }
+
+    let result = poll_until_ready(&mut order, domain).await;
+    for token in &challenge_tokens {
+        pending.remove(token).await;
+    }
+    result?;
+
+    let key_pair = KeyPair::generate()?;
+    let mut params = CertificateParams::new(vec![domain.to_string()])?;
+    params.distinguished_name = rcgen::DistinguishedName::new();
+    let csr = params.serialize_request(&key_pair)?;
+    order.finalize(csr.der()).await?;
+
+    let cert_chain_pem = loop {
+        match order.certificate().await? {
+            Some(cert_chain_pem) => break cert_chain_pem,
+            None => tokio::time::sleep(POLL_INTERVAL).await,
+        }
+    };
+
+    let not_after = super::x509::not_after(cert_chain_pem.as_bytes())?;
+    Ok((
+        CertMaterial {
+            cert_pem: cert_chain_pem,
+            key_pem: key_pair.serialize_pem(),
+            not_after,
+        },
+        credentials_json,
+    ))
+}
+
+async fn poll_until_ready(order: &mut instant_acme::Order, domain: &str) -> Result<(), AcmeError> {
+    let deadline = tokio::time::Instant::now() + POLL_TIMEOUT;
+    loop {
+        let state = order.refresh().await?;
+        match state.status {
+            OrderStatus::Ready => return Ok(()),
+            OrderStatus::Invalid => return Err(AcmeError::OrderInvalid(domain.to_string())),
+            _ if tokio::time::Instant::now() >= deadline => {
+                return Err(AcmeError::OrderNotReady(domain.to_string(), state.status));
+            }
+            _ => tokio::time::sleep(POLL_INTERVAL).await,
+        }
+    }
+}
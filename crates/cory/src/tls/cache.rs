@@ -0,0 +1,86 @@
+//! On-disk cache for the ACME account key and issued certificate/key,
+//! keyed by domain, so a restart doesn't re-create the account or
+//! re-order a still-valid certificate.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::AcmeError;
+
+/// A certificate and its private key, plus the certificate's `notAfter`
+/// so callers can decide when it needs renewing.
+pub struct CertMaterial {
+    pub cert_pem: String,
+    pub key_pem: String,
+    pub not_after: SystemTime,
+}
+
+/// Reads and writes `{cache_dir}/account-key.pem` (shared by every domain)
+/// and `{cache_dir}/{domain}.{cert,key}.pem`.
+pub struct CertCache {
+    dir: PathBuf,
+}
+
+impl CertCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn account_key_path(&self) -> PathBuf {
+        self.dir.join("account-key.pem")
+    }
+
+    fn cert_path(&self, domain: &str) -> PathBuf {
+        self.dir.join(format!("{domain}.cert.pem"))
+    }
+
+    fn key_path(&self, domain: &str) -> PathBuf {
+        self.dir.join(format!("{domain}.key.pem"))
+    }
+
+    /// Loads the cached ACME account key, if one was created by a prior
+    /// run. The account key is shared across domains, unlike the
+    /// certificate/key pair.
+    pub fn load_account_key(&self) -> Result<Option<String>, AcmeError> {
+        read_optional(&self.account_key_path())
+    }
+
+    pub fn store_account_key(&self, key_pem: &str) -> Result<(), AcmeError> {
+        std::fs::create_dir_all(&self.dir).map_err(AcmeError::Io)?;
+        std::fs::write(self.account_key_path(), key_pem).map_err(AcmeError::Io)
+    }
+
+    /// Loads the cached certificate/key for `domain`, if one was issued by
+    /// a prior run. Returns `None` if either half is missing so a partial
+    /// write (e.g. from a crash between the two `fs::write` calls) is
+    /// treated as "nothing cached" rather than served half-broken.
+    pub fn load(&self, domain: &str) -> Result<Option<CertMaterial>, AcmeError> {
+        let (Some(cert_pem), Some(key_pem)) = (
+            read_optional(&self.cert_path(domain))?,
+            read_optional(&self.key_path(domain))?,
+        ) else {
+            return Ok(None);
+        };
+
+        let not_after = super::x509::not_after(cert_pem.as_bytes())?;
+        Ok(Some(CertMaterial {
+            cert_pem,
+            key_pem,
+            not_after,
+        }))
+    }
+
+    pub fn store(&self, domain: &str, material: &CertMaterial) -> Result<(), AcmeError> {
+        std::fs::create_dir_all(&self.dir).map_err(AcmeError::Io)?;
+        std::fs::write(self.cert_path(domain), &material.cert_pem).map_err(AcmeError::Io)?;
+        std::fs::write(self.key_path(domain), &material.key_pem).map_err(AcmeError::Io)
+    }
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>, AcmeError> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(AcmeError::Io(e)),
+    }
+}
@@ -0,0 +1,161 @@
+//! Optional built-in ACME/Let's Encrypt TLS termination.
+//!
+//! When `--tls-domain` is set, [`provision`] orders a certificate from the
+//! configured ACME directory via HTTP-01 validation (see [`acme`]),
+//! caching the account credentials and issued certificate/key to disk
+//! (see [`cache`]) so a restart reuses both instead of re-ordering.
+//! [`spawn_renewal_task`] then keeps the cached cert renewed in the
+//! background. When `--tls-domain` is unset none of this is touched and
+//! `main` serves plaintext HTTP exactly as before.
+
+mod acme;
+mod cache;
+mod x509;
+
+pub use acme::{AcmeError, PendingChallenges};
+
+use std::time::SystemTime;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+
+use cache::CertCache;
+
+/// How long before expiry a certificate is renewed. Let's Encrypt certs
+/// are valid 90 days; renewing with 30 days left leaves ample room for a
+/// failed attempt to be retried before the cert actually lapses.
+const RENEW_BEFORE_EXPIRY: std::time::Duration = std::time::Duration::from_secs(30 * 24 * 60 * 60);
+
+/// How often the background task wakes up to check whether the cached
+/// cert is due for renewal.
+const RENEWAL_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+pub struct TlsConfig {
+    pub domain: String,
+    pub contact_email: String,
+    pub acme_directory_url: String,
+    pub cache_dir: std::path::PathBuf,
+}
+
+/// Mounts the HTTP-01 challenge responder at
+/// `/.well-known/acme-challenge/{token}`. Merged into the app's router
+/// (see `main`) so the ACME validation server can reach it over the same
+/// plaintext listener used during provisioning/renewal, without standing
+/// up a second router just for this.
+pub fn challenge_router(pending: PendingChallenges) -> Router {
+    Router::new()
+        .route("/.well-known/acme-challenge/{token}", get(serve_challenge))
+        .with_state(pending)
+}
+
+async fn serve_challenge(
+    State(pending): State<PendingChallenges>,
+    Path(token): Path<String>,
+) -> impl IntoResponse {
+    match pending.get(&token).await {
+        Some(key_authorization) => (StatusCode::OK, key_authorization),
+        None => (StatusCode::NOT_FOUND, String::new()),
+    }
+}
+
+/// Loads a still-valid cached certificate, or orders a fresh one if none
+/// is cached or the cached one is within [`RENEW_BEFORE_EXPIRY`] of
+/// expiring. Returns a [`RustlsConfig`] ready to serve.
+pub async fn provision(
+    config: &TlsConfig,
+    pending: &PendingChallenges,
+) -> eyre::Result<RustlsConfig> {
+    let cache = CertCache::new(&config.cache_dir);
+    let material = match cache.load(&config.domain)? {
+        Some(material) if !due_for_renewal(&material.not_after) => material,
+        _ => order_and_cache(config, &cache, pending).await?,
+    };
+
+    RustlsConfig::from_pem(
+        material.cert_pem.into_bytes(),
+        material.key_pem.into_bytes(),
+    )
+    .await
+    .map_err(|e| eyre::eyre!("failed to load issued certificate into rustls: {e}"))
+}
+
+/// Spawns a background task that checks every [`RENEWAL_CHECK_INTERVAL`]
+/// whether the cached cert needs renewing and, if so, orders a new one and
+/// hot-reloads `rustls_config` in place — existing connections keep using
+/// the old cert, new handshakes pick up the new one.
+pub fn spawn_renewal_task(
+    config: TlsConfig,
+    rustls_config: RustlsConfig,
+    pending: PendingChallenges,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let cache = CertCache::new(&config.cache_dir);
+        loop {
+            tokio::time::sleep(RENEWAL_CHECK_INTERVAL).await;
+
+            let needs_renewal = match cache.load(&config.domain) {
+                Ok(Some(material)) => due_for_renewal(&material.not_after),
+                Ok(None) => true,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to read cached cert while checking renewal due date");
+                    continue;
+                }
+            };
+            if !needs_renewal {
+                continue;
+            }
+
+            tracing::info!(domain = %config.domain, "renewing ACME certificate");
+            match order_and_cache(&config, &cache, &pending).await {
+                Ok(material) => {
+                    if let Err(e) = rustls_config
+                        .reload_from_pem(
+                            material.cert_pem.into_bytes(),
+                            material.key_pem.into_bytes(),
+                        )
+                        .await
+                    {
+                        tracing::error!(error = %e, "failed to reload renewed certificate into rustls");
+                    } else {
+                        tracing::info!(domain = %config.domain, "reloaded renewed certificate");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, domain = %config.domain, "certificate renewal failed, will retry next interval");
+                }
+            }
+        }
+    })
+}
+
+fn due_for_renewal(not_after: &SystemTime) -> bool {
+    match not_after.duration_since(SystemTime::now()) {
+        Ok(remaining) => remaining < RENEW_BEFORE_EXPIRY,
+        Err(_) => true, // already expired
+    }
+}
+
+async fn order_and_cache(
+    config: &TlsConfig,
+    cache: &CertCache,
+    pending: &PendingChallenges,
+) -> eyre::Result<cache::CertMaterial> {
+    let cached_credentials = cache.load_account_key()?;
+    let (material, credentials_json) = acme::order_certificate(
+        &config.domain,
+        &config.contact_email,
+        &config.acme_directory_url,
+        cached_credentials,
+        pending,
+    )
+    .await
+    .map_err(|e| eyre::eyre!("ACME certificate order for {} failed: {e}", config.domain))?;
+
+    cache.store_account_key(&credentials_json)?;
+    cache.store(&config.domain, &material)?;
+    Ok(material)
+}
@@ -0,0 +1,19 @@
+//! Just enough X.509 parsing to read a certificate's expiry, so the
+//! renewal task (see [`super::spawn_renewal_task`]) knows when a cached
+//! cert needs replacing without having to track that separately from the
+//! PEM files themselves.
+
+use std::time::SystemTime;
+
+use super::AcmeError;
+
+/// Returns the `notAfter` field of the leaf certificate in a PEM chain
+/// (the first `CERTIFICATE` block), as issued certs list the leaf first.
+pub fn not_after(chain_pem: &[u8]) -> Result<SystemTime, AcmeError> {
+    let (_, pem) =
+        x509_parser::pem::parse_x509_pem(chain_pem).map_err(|e| AcmeError::Cert(e.to_string()))?;
+    let cert = pem
+        .parse_x509()
+        .map_err(|e| AcmeError::Cert(e.to_string()))?;
+    Ok(cert.validity().not_after.to_system_time())
+}